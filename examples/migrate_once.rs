@@ -0,0 +1,41 @@
+//! Submit a single `continue_migrate` and wait for it to finalize
+//!
+//! The seed is a hex seed, mnemonic or SURI in `SIGNER_SEED`:
+//!
+//! ```sh
+//! SIGNER_SEED="//Alice" cargo run --example migrate_once -- ws://127.0.0.1:9944
+//! ```
+
+use anyhow::Context;
+use std::str::FromStr;
+use subxt_signer::{sr25519::Keypair, SecretUri};
+use westend_migration_bot::client::MigrationClient;
+use westend_migration_bot::limits::MigrationLimits;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "ws://127.0.0.1:9944".to_string());
+    let seed = std::env::var("SIGNER_SEED").context("SIGNER_SEED is not set")?;
+    let uri = SecretUri::from_str(&seed).context("SIGNER_SEED is not a valid seed or SURI")?;
+    let signer = Keypair::from_uri(&uri).context("Failed to derive the signer")?;
+
+    let client = MigrationClient::connect(&url).await?;
+    let before = client.status().await?;
+    if before.is_complete() {
+        println!("Migration already complete");
+        return Ok(());
+    }
+
+    // Half the chain maximum, as the bot picks by default
+    let limits = MigrationLimits::working(MigrationLimits::new(0, 0), client.max_limits().await?);
+    let block = client.submit_one(&signer, limits).await?;
+
+    let after = client.status().await?;
+    println!(
+        "Finalized in {:?}: {} -> {} top items",
+        block, before.top_items, after.top_items
+    );
+    Ok(())
+}
@@ -0,0 +1,21 @@
+#!/usr/bin/env python3
+"""Print the migration status and a rough progress estimate.
+
+Build the bindings first: `maturin develop --release`
+"""
+
+import sys
+
+from westend_migration_bot import MigrationClient
+
+url = sys.argv[1] if len(sys.argv) > 1 else "wss://westend-rpc.polkadot.io:443"
+client = MigrationClient(url)
+
+status = client.status()
+print(f"top items migrated: {status['top_items']} ({status['size']} bytes)")
+print(f"~{client.estimate():.1f}% of the top trie migrated")
+max_limits = client.max_limits()
+if max_limits is None:
+    print("Signed migrations are disabled (no SignedMigrationMaxLimits)")
+else:
+    print(f"Signed max limits: {max_limits[1]} items / {max_limits[0]} bytes")
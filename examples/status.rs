@@ -0,0 +1,24 @@
+//! Print the migration status and a rough progress estimate
+//!
+//! ```sh
+//! cargo run --example status -- wss://westend-rpc.polkadot.io:443
+//! ```
+
+use westend_migration_bot::client::MigrationClient;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "wss://westend-rpc.polkadot.io:443".to_string());
+    let client = MigrationClient::connect(&url).await?;
+
+    let status = client.status().await?;
+    println!("{}", serde_json::to_string_pretty(&status)?);
+    println!("~{:.1}% of the top trie migrated", client.estimate().await?);
+    match client.max_limits().await? {
+        Some(max) => println!("Signed max limits: {}", max),
+        None => println!("Signed migrations are disabled (no SignedMigrationMaxLimits)"),
+    }
+    Ok(())
+}
@@ -8,7 +8,7 @@
 # Features:
 #   - Auto-reconnects on SSH connection loss
 #   - Desktop notifications for progress/errors
-#   - Dad joke heartbeat notifications
+#   - Heartbeat notifications (chain stats, or dad jokes with --heartbeat joke)
 
 set -o pipefail
 
@@ -94,10 +94,10 @@ export -f check_node_status
 
 # Function to run migration with output processing
 run_migration() {
-    # Kill any orphaned bot processes before starting (prevents lockfile conflicts)
-    ssh "$SERVER" "pkill -9 westend-migrate 2>/dev/null; rm -f /tmp/westend-migrate.lock" 2>/dev/null || true
+    # Kill any orphaned bot processes before starting (the bot takes over their lock)
+    ssh "$SERVER" "pkill -9 westend-migrate 2>/dev/null" 2>/dev/null || true
 
-    ssh "$SERVER" "export SIGNER_SEED='$SEED'; ~/westend-migrate --rpc-url ws://127.0.0.1:9944 --no-notify $RUNS_FLAG" 2>&1 | while read -r line; do
+    ssh "$SERVER" "export SIGNER_SEED='$SEED'; ~/westend-migrate --rpc-url ws://127.0.0.1:9944 --no-notify run $RUNS_FLAG" 2>&1 | while read -r line; do
         # Log to file
         echo "$line" >> migration.log
 
@@ -146,10 +146,10 @@ run_migration() {
         elif [[ "$line" == *"Westend State-Trie Migration Bot"* ]]; then
             notify-send "🚀 Bot Started" "Connected to $SERVER" -t 5000
         elif [[ "$line" == *"💓"* ]]; then
-            # Dad joke heartbeat - keep it!
-            joke="${line#*💓 }"
-            joke=$(strip_ansi "$joke")
-            notify-send -u low "🤣 Dad Joke" "$joke" -t 8000
+            # Heartbeat (chain stats or dad joke, depending on --heartbeat)
+            beat="${line#*💓 }"
+            beat=$(strip_ansi "$beat")
+            notify-send -u low "💓 Heartbeat" "$beat" -t 8000
         fi
     done
     return ${PIPESTATUS[0]}  # Return SSH exit code, not while loop
@@ -167,13 +167,27 @@ while true; do
     exit_code=$?
     run_duration=$(($(date +%s) - run_start))
 
-    # Check exit status
-    if [[ $exit_code -eq 0 ]]; then
-        # Clean exit (--runs completed or migration finished)
-        echo "[$(date '+%Y-%m-%d %H:%M:%S')] Migration completed successfully" >> migration.log
-        notify-send "Migration" "Completed successfully!" -t 10000
-        break
-    fi
+    # Check exit status (codes are listed in README.md, "Exit Codes")
+    case $exit_code in
+        0|2)
+            # Migration finished or --runs completed
+            echo "[$(date '+%Y-%m-%d %H:%M:%S')] Migration completed successfully (exit code: $exit_code)" >> migration.log
+            notify-send "Migration" "Completed successfully!" -t 10000
+            break
+            ;;
+        3|4|5|6)
+            # --once, deadline, campaign cap or shutdown: stopped on purpose
+            echo "[$(date '+%Y-%m-%d %H:%M:%S')] Bot stopped (exit code: $exit_code)" >> migration.log
+            notify-send "Migration" "Bot stopped (exit code $exit_code)" -t 10000
+            break
+            ;;
+        11|13|14)
+            # Balance decreased, account or configuration problem: retrying won't help
+            echo "[$(date '+%Y-%m-%d %H:%M:%S')] Bot stopped with fatal exit code $exit_code, not retrying" >> migration.log
+            notify-send -u critical "Migration Stopped" "Fatal exit code $exit_code - check migration.log" -t 0
+            exit "$exit_code"
+            ;;
+    esac
 
     # Connection lost or error - reset Fibonacci if bot ran for >60s (was stable)
     if [[ $run_duration -gt 60 ]]; then
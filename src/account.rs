@@ -0,0 +1,158 @@
+//! `System::Account` balance decoding across FRAME versions
+//!
+//! The account value is decoded dynamically from runtime metadata, but the
+//! shape of its `data` field (`pallet_balances::AccountData`) changed over
+//! time: older runtimes have `misc_frozen`/`fee_frozen`, newer ones a single
+//! `frozen` plus `flags`. [`AccountBalance::decode`] reads either layout, so
+//! transferable balance and existential-deposit checks stay correct across
+//! runtime upgrades instead of a missing field silently reading as zero.
+
+use std::fmt;
+use subxt::dynamic::{At, Value};
+
+/// `AccountData` layout expected in `System::Account`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum AccountLayout {
+    /// Detect from the fields present
+    #[default]
+    Auto,
+    /// `free`, `reserved`, `frozen`, `flags` (FRAME with fungible holds/freezes)
+    Current,
+    /// `free`, `reserved`, `misc_frozen`, `fee_frozen`
+    Legacy,
+}
+
+/// Balances of an account, in units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AccountBalance {
+    pub free: u128,
+    pub reserved: u128,
+    /// Amount that may not be transferred (max of the legacy freezes)
+    pub frozen: u128,
+    pub layout: AccountLayout,
+}
+
+impl AccountBalance {
+    /// Decode the `data` field of a `System::Account` value.
+    /// Fails if it doesn't match `layout` (or any known layout for `Auto`).
+    pub fn decode<T>(account: &Value<T>, layout: AccountLayout) -> Result<Self, String> {
+        let data = account
+            .at("data")
+            .ok_or_else(|| "account info has no `data` field".to_string())?;
+        let field = |name: &str| data.at(name).and_then(|v| v.as_u128());
+
+        let layout = match layout {
+            AccountLayout::Auto if field("frozen").is_some() => AccountLayout::Current,
+            AccountLayout::Auto if field("misc_frozen").is_some() => AccountLayout::Legacy,
+            AccountLayout::Auto => {
+                return Err("unrecognized AccountData layout (no frozen/misc_frozen)".to_string())
+            }
+            explicit => explicit,
+        };
+
+        let require = |name: &str| {
+            field(name).ok_or_else(|| format!("AccountData has no numeric `{}` field", name))
+        };
+        let frozen = match layout {
+            AccountLayout::Legacy => require("misc_frozen")?.max(require("fee_frozen")?),
+            _ => require("frozen")?,
+        };
+        Ok(Self {
+            free: require("free")?,
+            reserved: require("reserved")?,
+            frozen,
+            layout,
+        })
+    }
+
+    /// Free balance that can be moved without going below the frozen amount
+    /// or (for the current layout) the existential deposit.
+    ///
+    /// With fungible holds, reserved funds count towards the frozen amount,
+    /// so only the part of `frozen` not covered by `reserved` locks free balance.
+    pub fn transferable(&self, existential_deposit: u128) -> u128 {
+        let untouchable = match self.layout {
+            AccountLayout::Legacy => self.frozen,
+            _ => self
+                .frozen
+                .saturating_sub(self.reserved)
+                .max(existential_deposit),
+        };
+        self.free.saturating_sub(untouchable)
+    }
+}
+
+impl fmt::Display for AccountLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountLayout::Auto => write!(f, "auto"),
+            AccountLayout::Current => write!(f, "current"),
+            AccountLayout::Legacy => write!(f, "legacy"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(data: Vec<(&str, u128)>) -> Value<()> {
+        Value::named_composite([
+            ("nonce", Value::u128(3)),
+            (
+                "data",
+                Value::named_composite(data.into_iter().map(|(k, v)| (k, Value::u128(v)))),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_decode_current_layout() {
+        let value = account(vec![
+            ("free", 1_000),
+            ("reserved", 100),
+            ("frozen", 300),
+            ("flags", 1 << 127),
+        ]);
+        let balance = AccountBalance::decode(&value, AccountLayout::Auto).unwrap();
+        assert_eq!(balance.layout, AccountLayout::Current);
+        assert_eq!(
+            (balance.free, balance.reserved, balance.frozen),
+            (1_000, 100, 300)
+        );
+        // 300 frozen, 100 of it covered by reserved; ED 50 is lower
+        assert_eq!(balance.transferable(50), 800);
+        // ED dominates
+        assert_eq!(balance.transferable(500), 500);
+    }
+
+    #[test]
+    fn test_decode_legacy_layout() {
+        let value = account(vec![
+            ("free", 1_000),
+            ("reserved", 0),
+            ("misc_frozen", 200),
+            ("fee_frozen", 400),
+        ]);
+        let balance = AccountBalance::decode(&value, AccountLayout::Auto).unwrap();
+        assert_eq!(balance.layout, AccountLayout::Legacy);
+        assert_eq!(balance.frozen, 400);
+        assert_eq!(balance.transferable(50), 600);
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_layout() {
+        let value = account(vec![("free", 1_000)]);
+        assert!(AccountBalance::decode(&value, AccountLayout::Auto).is_err());
+
+        let value = Value::named_composite([("nonce", Value::u128(0))]);
+        assert!(AccountBalance::decode(&value, AccountLayout::Auto).is_err());
+    }
+
+    #[test]
+    fn test_explicit_layout_must_match() {
+        let value = account(vec![("free", 1_000), ("reserved", 0), ("frozen", 0)]);
+        assert!(AccountBalance::decode(&value, AccountLayout::Legacy).is_err());
+        assert!(AccountBalance::decode(&value, AccountLayout::Current).is_ok());
+    }
+}
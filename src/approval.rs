@@ -0,0 +1,293 @@
+//! Two-person control over submissions (`--approval-queue`)
+//!
+//! Every transaction the bot is about to sign is first written to the queue
+//! directory as `<id>.json` (call data, decoded call, limits, nonce and a
+//! one-time token) and held until an approver signs off, either
+//!
+//! - over HTTP: `GET /approve/<token>` or `/reject/<token>` on the health
+//!   server (the link is sent through the usual notification channels), or
+//! - on disk: the token written to `<id>.approved` or `<id>.rejected` next to
+//!   the request, for approvers working from a shared directory.
+//!
+//! The token only appears in the request and its notification, so a sign-off
+//! shows the approver has seen the call. The signature is made after the
+//! approval, over exactly the approved call data. A request left unanswered
+//! for `--approval-timeout-mins` expires and its tx is not submitted. Each
+//! request file is updated with its decision, leaving an audit trail.
+
+use anyhow::{Context, Result};
+use blake2::{Blake2s256, Digest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+/// Decisions received over HTTP, by token (`None` = still open)
+static HTTP_DECISIONS: LazyLock<Mutex<HashMap<String, Option<bool>>>> =
+    LazyLock::new(Default::default);
+
+/// A transaction awaiting sign-off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+    pub account: String,
+    pub nonce: u32,
+    pub limits: String,
+    /// SCALE-encoded call, hex
+    pub call_data: String,
+    pub decoded: String,
+    /// `approved`, `rejected` or `expired` once decided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl ApprovalRequest {
+    pub fn new(
+        account: &str,
+        nonce: u32,
+        limits: String,
+        call_data: &[u8],
+        decoded: String,
+    ) -> Self {
+        let created_at = Utc::now();
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let seed = format!("{}:{}:{}:{}", std::process::id(), nanos, account, nonce);
+        Self {
+            id: format!("{}-n{}", created_at.format("%Y%m%dT%H%M%SZ"), nonce),
+            token: hex::encode(&Blake2s256::digest(seed.as_bytes())[..16]),
+            created_at,
+            account: account.to_string(),
+            nonce,
+            limits,
+            call_data: format!("0x{}", hex::encode(call_data)),
+            decoded,
+            decision: None,
+            decided_at: None,
+        }
+    }
+}
+
+/// How an approval request ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approved,
+    Rejected,
+    /// Unanswered within the timeout
+    Expired,
+    /// The bot is shutting down
+    Cancelled,
+}
+
+impl Decision {
+    fn as_str(self) -> &'static str {
+        match self {
+            Decision::Approved => "approved",
+            Decision::Rejected => "rejected",
+            Decision::Expired => "expired",
+            Decision::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Record an HTTP decision for `token`; `false` if no open request has it
+pub fn decide(token: &str, approve: bool) -> bool {
+    let mut decisions = HTTP_DECISIONS.lock().unwrap_or_else(|e| e.into_inner());
+    match decisions.get_mut(token) {
+        Some(decision @ None) => {
+            *decision = Some(approve);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Directory of approval requests
+pub struct ApprovalQueue {
+    dir: PathBuf,
+}
+
+impl ApprovalQueue {
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create approval queue {}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn path(&self, request: &ApprovalRequest, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", request.id, extension))
+    }
+
+    /// Queue `request` and start accepting decisions for its token
+    pub fn submit(&self, request: &ApprovalRequest) -> Result<PathBuf> {
+        let path = self.path(request, "json");
+        self.write(request)?;
+        HTTP_DECISIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request.token.clone(), None);
+        Ok(path)
+    }
+
+    fn write(&self, request: &ApprovalRequest) -> Result<()> {
+        let path = self.path(request, "json");
+        std::fs::write(&path, serde_json::to_string_pretty(request)?)
+            .with_context(|| format!("Failed to write approval request {}", path.display()))
+    }
+
+    /// Decision so far: HTTP first, then `<id>.approved` / `<id>.rejected`
+    /// files holding the token
+    pub fn poll(&self, request: &ApprovalRequest) -> Option<Decision> {
+        let http = HTTP_DECISIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&request.token)
+            .copied()
+            .flatten();
+        match http {
+            Some(true) => return Some(Decision::Approved),
+            Some(false) => return Some(Decision::Rejected),
+            None => {}
+        }
+        let signed_off = |extension| {
+            std::fs::read_to_string(self.path(request, extension))
+                .is_ok_and(|content| content.lines().next().map(str::trim) == Some(&request.token))
+        };
+        if signed_off("rejected") {
+            Some(Decision::Rejected)
+        } else if signed_off("approved") {
+            Some(Decision::Approved)
+        } else {
+            None
+        }
+    }
+
+    /// Close `request` with `decision`: the token stops being accepted and
+    /// the request file records the outcome
+    pub fn close(&self, request: &mut ApprovalRequest, decision: Decision) -> Result<()> {
+        HTTP_DECISIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request.token);
+        request.decision = Some(decision.as_str().to_string());
+        request.decided_at = Some(Utc::now());
+        self.write(request)
+    }
+
+    /// Poll every `interval` until `request` is decided, `timeout` passes or
+    /// `shutdown` is cancelled; the request is closed either way
+    pub async fn wait(
+        &self,
+        request: &mut ApprovalRequest,
+        interval: Duration,
+        timeout: Duration,
+        shutdown: &CancellationToken,
+    ) -> Result<Decision> {
+        let start = Instant::now();
+        let decision = loop {
+            if let Some(decision) = self.poll(request) {
+                break decision;
+            }
+            if start.elapsed() >= timeout {
+                break Decision::Expired;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.cancelled() => break Decision::Cancelled,
+            }
+        };
+        self.close(request, decision)?;
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(name: &str) -> (ApprovalQueue, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "westend-migrate-approvals-{}-{}",
+            name,
+            std::process::id()
+        ));
+        (ApprovalQueue::open(&dir).unwrap(), dir)
+    }
+
+    fn request(nonce: u32) -> ApprovalRequest {
+        ApprovalRequest::new(
+            "5Grwva",
+            nonce,
+            "10 items / 1000 bytes".to_string(),
+            &[1, 2],
+            "continue_migrate".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_http_decision() {
+        let (queue, dir) = queue("http");
+        let mut req = request(7);
+        queue.submit(&req).unwrap();
+        assert_eq!(queue.poll(&req), None);
+        assert!(!decide("not-a-token", true));
+        assert!(decide(&req.token, false));
+        // A token decides once
+        assert!(!decide(&req.token, true));
+        assert_eq!(queue.poll(&req), Some(Decision::Rejected));
+
+        queue.close(&mut req, Decision::Rejected).unwrap();
+        assert!(!decide(&req.token, true));
+        let saved: ApprovalRequest = serde_json::from_str(
+            &std::fs::read_to_string(dir.join(format!("{}.json", req.id))).unwrap(),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(saved.decision.as_deref(), Some("rejected"));
+        assert_eq!(saved.call_data, "0x0102");
+    }
+
+    #[test]
+    fn test_file_decision_needs_token() {
+        let (queue, dir) = queue("file");
+        let req = request(8);
+        queue.submit(&req).unwrap();
+        let approved = dir.join(format!("{}.approved", req.id));
+        std::fs::write(&approved, "wrong token\n").unwrap();
+        assert_eq!(queue.poll(&req), None);
+        std::fs::write(&approved, format!("{}\nalice\n", req.token)).unwrap();
+        let decision = queue.poll(&req);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(decision, Some(Decision::Approved));
+    }
+
+    #[tokio::test]
+    async fn test_wait_expires() {
+        let (queue, dir) = queue("expire");
+        let mut req = request(9);
+        queue.submit(&req).unwrap();
+        let decision = queue
+            .wait(
+                &mut req,
+                Duration::from_millis(1),
+                Duration::from_millis(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(decision, Decision::Expired);
+        assert_eq!(req.decision.as_deref(), Some("expired"));
+    }
+}
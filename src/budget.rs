@@ -0,0 +1,300 @@
+//! RPC provider budget (`--rpc-quota`)
+//!
+//! Hosted RPC plans (OnFinality, Dwellir, ...) cap the requests an API key
+//! may make per day. Every request and subscription the bot makes goes
+//! through [`CountingRpc`], which counts it against its endpoint for the
+//! current UTC day. For an endpoint with a quota, a warning goes out once the
+//! day's count passes `--rpc-quota-warn-percent` of it and a critical
+//! notification once the quota is used up; with `--rpc-quota-failover` the
+//! bot then moves to the next endpoint with budget left. With
+//! `--rpc-usage-file` the counts survive restarts within the day.
+
+use crate::notify::{send_notification, Severity};
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RawValue, RpcClient, RpcClientT};
+use tracing::{debug, warn};
+
+/// Usage is written to `--rpc-usage-file` every this many requests (and on exit)
+const SAVE_EVERY: u64 = 100;
+
+static BUDGET: LazyLock<Mutex<Budget>> = LazyLock::new(Default::default);
+
+/// Daily request quota of one endpoint (`url=requests`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quota {
+    pub endpoint: String,
+    pub per_day: u64,
+}
+
+impl FromStr for Quota {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the last '=': API keys often sit in the URL's query string
+        let (endpoint, per_day) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("invalid quota '{}' (expected url=requests)", s))?;
+        let per_day = per_day
+            .trim()
+            .replace('_', "")
+            .parse::<u64>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("invalid request count in quota '{}'", s))?;
+        Ok(Quota {
+            endpoint: endpoint.trim().to_string(),
+            per_day,
+        })
+    }
+}
+
+/// Request counts of one UTC day, by endpoint
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Usage {
+    day: Option<NaiveDate>,
+    requests: HashMap<String, u64>,
+}
+
+/// Alert level reached by an endpoint today
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Warned,
+    Exhausted,
+}
+
+#[derive(Default)]
+struct Budget {
+    quotas: HashMap<String, u64>,
+    warn_percent: u64,
+    usage: Usage,
+    alerted: HashMap<String, Level>,
+    usage_file: Option<PathBuf>,
+    unsaved: u64,
+}
+
+impl Budget {
+    /// Start a new day's counts when the UTC date changes
+    fn roll(&mut self, today: NaiveDate) {
+        if self.usage.day != Some(today) {
+            self.usage = Usage {
+                day: Some(today),
+                requests: HashMap::new(),
+            };
+            self.alerted.clear();
+        }
+    }
+
+    /// Count one request to `endpoint`; the alert to send if it crossed a threshold
+    fn record(&mut self, endpoint: &str, today: NaiveDate) -> Option<(Severity, String)> {
+        self.roll(today);
+        let count = self.usage.requests.entry(endpoint.to_string()).or_default();
+        *count += 1;
+        let count = *count;
+        self.unsaved += 1;
+
+        let quota = *self.quotas.get(endpoint)?;
+        let level = if count >= quota {
+            Level::Exhausted
+        } else if count * 100 >= quota * self.warn_percent {
+            Level::Warned
+        } else {
+            return None;
+        };
+        if self.alerted.get(endpoint).is_some_and(|l| *l >= level) {
+            return None;
+        }
+        self.alerted.insert(endpoint.to_string(), level);
+        Some(match level {
+            Level::Warned => (
+                Severity::Warning,
+                format!(
+                    "{} has used {} of its {} requests for today ({}%)",
+                    endpoint,
+                    count,
+                    quota,
+                    count * 100 / quota
+                ),
+            ),
+            Level::Exhausted => (
+                Severity::Critical,
+                format!(
+                    "{} has used its daily quota of {} requests; the provider may start \
+                     rejecting or billing further requests until 00:00 UTC",
+                    endpoint, quota
+                ),
+            ),
+        })
+    }
+
+    fn exhausted(&mut self, endpoint: &str, today: NaiveDate) -> bool {
+        self.roll(today);
+        match self.quotas.get(endpoint) {
+            Some(quota) => self.usage.requests.get(endpoint).copied().unwrap_or(0) >= *quota,
+            None => false,
+        }
+    }
+}
+
+/// Set the quotas and load today's usage from `usage_file` (set once at startup)
+pub fn configure(quotas: &[Quota], warn_percent: u8, usage_file: Option<&Path>) -> Result<()> {
+    let usage = match usage_file {
+        Some(path) => load(path)?,
+        None => Usage::default(),
+    };
+    let mut budget = BUDGET.lock().unwrap_or_else(|e| e.into_inner());
+    budget.quotas = quotas
+        .iter()
+        .map(|q| (q.endpoint.clone(), q.per_day))
+        .collect();
+    budget.warn_percent = u64::from(warn_percent);
+    budget.usage = usage;
+    budget.usage_file = usage_file.map(Path::to_path_buf);
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Usage> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse RPC usage file {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Usage::default()),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to read RPC usage file {}", path.display()))
+        }
+    }
+}
+
+/// Write the counts to `--rpc-usage-file`, if set
+pub fn save() {
+    let mut budget = BUDGET.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(path) = budget.usage_file.clone() else {
+        return;
+    };
+    budget.unsaved = 0;
+    let result = serde_json::to_string_pretty(&budget.usage)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(std::fs::write(&path, json)?));
+    if let Err(e) = result {
+        debug!("Could not save RPC usage to {}: {:#}", path.display(), e);
+    }
+}
+
+/// Count one request to `endpoint` and alert on crossing its quota thresholds
+pub fn record(endpoint: &str) {
+    let (alert, save_due) = {
+        let mut budget = BUDGET.lock().unwrap_or_else(|e| e.into_inner());
+        let alert = budget.record(endpoint, Utc::now().date_naive());
+        (alert, budget.unsaved >= SAVE_EVERY)
+    };
+    if save_due {
+        save();
+    }
+    if let Some((severity, msg)) = alert {
+        warn!("{}", msg);
+        send_notification(severity, "RPC Budget", &msg);
+    }
+}
+
+/// Whether `endpoint` has used up today's quota
+pub fn exhausted(endpoint: &str) -> bool {
+    BUDGET
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .exhausted(endpoint, Utc::now().date_naive())
+}
+
+/// An RPC client that counts every call against its endpoint
+pub struct CountingRpc {
+    inner: RpcClient,
+    endpoint: String,
+}
+
+impl CountingRpc {
+    /// Wrap `inner`, connected to `endpoint`
+    pub fn wrap(inner: RpcClient, endpoint: &str) -> RpcClient {
+        RpcClient::new(Self {
+            inner,
+            endpoint: endpoint.to_string(),
+        })
+    }
+}
+
+impl RpcClientT for CountingRpc {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RawRpcFuture<'a, Box<RawValue>> {
+        record(&self.endpoint);
+        self.inner.request_raw(method, params)
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RawRpcFuture<'a, RawRpcSubscription> {
+        record(&self.endpoint);
+        self.inner.subscribe_raw(sub, params, unsub)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "wss://westend.api.onfinality.io/ws?apikey=abc";
+
+    fn budget(quota: u64) -> Budget {
+        Budget {
+            quotas: HashMap::from([(URL.to_string(), quota)]),
+            warn_percent: 80,
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_quota() {
+        let quota: Quota = format!("{}=500_000", URL).parse().unwrap();
+        assert_eq!(quota.endpoint, URL);
+        assert_eq!(quota.per_day, 500_000);
+        assert!("wss://a".parse::<Quota>().is_err());
+        assert!("wss://a=0".parse::<Quota>().is_err());
+        assert!("wss://a=many".parse::<Quota>().is_err());
+    }
+
+    #[test]
+    fn test_alerts_once_per_threshold() {
+        let mut b = budget(10);
+        let alerts: Vec<_> = (0..12)
+            .filter_map(|_| b.record(URL, day(1)).map(|(severity, _)| severity))
+            .collect();
+        assert_eq!(alerts, vec![Severity::Warning, Severity::Critical]);
+        assert!(b.exhausted(URL, day(1)));
+        // Endpoints without a quota are counted but never alert
+        assert_eq!(b.record("ws://127.0.0.1:9944", day(1)), None);
+        assert_eq!(b.usage.requests["ws://127.0.0.1:9944"], 1);
+    }
+
+    #[test]
+    fn test_new_day_resets() {
+        let mut b = budget(1);
+        b.record(URL, day(1));
+        assert!(b.exhausted(URL, day(1)));
+        assert!(!b.exhausted(URL, day(2)));
+        assert_eq!(b.usage.day, Some(day(2)));
+        assert!(b.usage.requests.is_empty());
+        // Alerts fire again on the new day
+        assert!(b.record(URL, day(2)).is_some());
+    }
+}
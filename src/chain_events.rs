@@ -0,0 +1,197 @@
+//! `StateTrieMigration` events
+//!
+//! Classified from an event's decoded fields, so the bot's block follower
+//! and a dashboard reading `System.Events` (see [`crate::runtime`]) agree on
+//! what each event means.
+
+use crate::process::collect_bytes;
+use scale_value::{At, Composite, Value, ValueDef};
+use serde::Serialize;
+
+/// An event of the state-trie-migration pallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum MigrationEvent {
+    /// Items migrated by one step; `compute` is `Signed` or `Auto`
+    Migrated {
+        top: u64,
+        child: u64,
+        compute: String,
+    },
+    /// A signed migration exceeded its limits and the deposit was slashed
+    Slashed {
+        who: String,
+        amount: u128,
+    },
+    AutoMigrationFinished,
+    /// Automatic migration stopped on an error
+    Halted {
+        error: String,
+    },
+}
+
+impl MigrationEvent {
+    /// Classify event `variant` of the pallet from its fields
+    pub fn decode<T>(variant: &str, fields: &Composite<T>) -> Option<Self> {
+        let number = |name: &str| fields.at(name).and_then(|v| v.as_u128());
+        let name = |name: &str| {
+            fields.at(name).map(|v| match &v.value {
+                ValueDef::Variant(variant) => variant.name.clone(),
+                _ => v.to_string(),
+            })
+        };
+        match variant {
+            "Migrated" => Some(MigrationEvent::Migrated {
+                top: number("top").unwrap_or(0) as u64,
+                child: number("child").unwrap_or(0) as u64,
+                compute: name("compute").unwrap_or_default(),
+            }),
+            "Slashed" => {
+                let mut who = Vec::new();
+                collect_bytes(fields.at("who")?, &mut who).ok()?;
+                Some(MigrationEvent::Slashed {
+                    who: format!("0x{}", hex::encode(who)),
+                    amount: number("amount").unwrap_or(0),
+                })
+            }
+            "AutoMigrationFinished" => Some(MigrationEvent::AutoMigrationFinished),
+            "Halted" => Some(MigrationEvent::Halted {
+                error: name("error").unwrap_or_default(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A [`MigrationEvent`] of a block, with the extrinsic that emitted it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockEvent {
+    /// Index of the extrinsic (`None` outside `ApplyExtrinsic`)
+    pub extrinsic: Option<u32>,
+    #[serde(flatten)]
+    pub event: MigrationEvent,
+}
+
+/// The migration events in a decoded `System.Events` value
+pub fn migration_events<T>(events: &Value<T>) -> Vec<BlockEvent> {
+    let ValueDef::Composite(records) = &events.value else {
+        return Vec::new();
+    };
+    records
+        .values()
+        .filter_map(|record| {
+            let ValueDef::Variant(pallet) = &record.at("event")?.value else {
+                return None;
+            };
+            if pallet.name != "StateTrieMigration" {
+                return None;
+            }
+            let ValueDef::Variant(event) = &pallet.values.values().next()?.value else {
+                return None;
+            };
+            let extrinsic = match &record.at("phase")?.value {
+                ValueDef::Variant(phase) if phase.name == "ApplyExtrinsic" => phase
+                    .values
+                    .values()
+                    .next()
+                    .and_then(|index| index.as_u128())
+                    .map(|index| index as u32),
+                _ => None,
+            };
+            Some(BlockEvent {
+                extrinsic,
+                event: MigrationEvent::decode(&event.name, &event.values)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated(top: u128) -> Value {
+        Value::named_variant(
+            "Migrated",
+            [
+                ("top", Value::u128(top)),
+                ("child", Value::u128(2)),
+                ("compute", Value::unnamed_variant("Signed", [])),
+            ],
+        )
+    }
+
+    fn record(phase: Value, pallet: &str, event: Value) -> Value {
+        Value::named_composite([
+            ("phase", phase),
+            ("event", Value::unnamed_variant(pallet, [event])),
+            ("topics", Value::unnamed_composite(Vec::<Value>::new())),
+        ])
+    }
+
+    #[test]
+    fn test_decode() {
+        let ValueDef::Variant(event) = migrated(1024).value else {
+            unreachable!()
+        };
+        assert_eq!(
+            MigrationEvent::decode(&event.name, &event.values),
+            Some(MigrationEvent::Migrated {
+                top: 1024,
+                child: 2,
+                compute: "Signed".to_string()
+            })
+        );
+
+        let slashed = Composite::named([
+            ("who", Value::from_bytes([0xab; 32])),
+            ("amount", Value::u128(5)),
+        ]);
+        assert_eq!(
+            MigrationEvent::decode("Slashed", &slashed),
+            Some(MigrationEvent::Slashed {
+                who: format!("0x{}", "ab".repeat(32)),
+                amount: 5
+            })
+        );
+        assert_eq!(
+            MigrationEvent::decode("Unknown", &Composite::named(Vec::<(String, Value)>::new())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_migration_events() {
+        let events = Value::unnamed_composite([
+            record(
+                Value::unnamed_variant("ApplyExtrinsic", [Value::u128(3)]),
+                "StateTrieMigration",
+                migrated(1024),
+            ),
+            record(
+                Value::unnamed_variant("ApplyExtrinsic", [Value::u128(4)]),
+                "Balances",
+                Value::unnamed_variant("Deposit", []),
+            ),
+            record(
+                Value::unnamed_variant("Finalization", []),
+                "StateTrieMigration",
+                Value::unnamed_variant("AutoMigrationFinished", []),
+            ),
+        ]);
+        let found = migration_events(&events);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].extrinsic, Some(3));
+        assert!(matches!(
+            found[0].event,
+            MigrationEvent::Migrated { top: 1024, .. }
+        ));
+        assert_eq!(
+            found[1],
+            BlockEvent {
+                extrinsic: None,
+                event: MigrationEvent::AutoMigrationFinished
+            }
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! High-level client for scripting the migration
+//!
+//! [`MigrationClient`] wraps a chain connection with the operations the bot
+//! is built from — read the status, estimate how far along the top trie is,
+//! submit one `continue_migrate`, set `SignedMigrationMaxLimits` — so a short
+//! Rust script (or a binding) can compose them in ways the CLI does not
+//! anticipate. It is deliberately thin: no retries, pacing, notifications,
+//! dry runs or balance checks; those are what the `westend-migrate` binary
+//! adds on top. [`MigrationClient::client`] and [`MigrationClient::rpc`]
+//! give access to the underlying subxt clients, e.g. for [`crate::pool`].
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), westend_migration_bot::client::ClientError> {
+//! use westend_migration_bot::client::MigrationClient;
+//! use westend_migration_bot::limits::MigrationLimits;
+//!
+//! let client = MigrationClient::connect("wss://westend-rpc.polkadot.io:443").await?;
+//! let status = client.status().await?;
+//! println!("{} top items migrated", status.top_items);
+//!
+//! let signer = subxt_signer::sr25519::dev::alice();
+//! let block = client
+//!     .submit_one(&signer, MigrationLimits::new(51_200, 1024))
+//!     .await?;
+//! println!("finalized in {:?}", block);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::limits::MigrationLimits;
+use crate::process::{parse_migration_status, position_percent};
+use crate::status::MigrationStatus;
+use subxt::backend::rpc::RpcClient;
+use subxt::dynamic::Value;
+use subxt::tx::{DynamicPayload, Signer};
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Errors from [`MigrationClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Failed to connect to {url}: {source}")]
+    Connect {
+        url: String,
+        #[source]
+        source: subxt::Error,
+    },
+    #[error(transparent)]
+    Subxt(#[from] subxt::Error),
+    #[error("Failed to decode storage value: {0}")]
+    Decode(#[from] subxt::error::DecodeError),
+    #[error("No MigrationProcess found - migration may not be active")]
+    NoMigration,
+    #[error("Invalid migration limits: {0}")]
+    InvalidLimits(String),
+}
+
+/// `StateTrieMigration.continue_migrate(limits, real_size_upper, witness_task)`
+pub fn continue_migrate(limits: MigrationLimits, witness_task: Value<()>) -> DynamicPayload {
+    subxt::dynamic::tx(
+        "StateTrieMigration",
+        "continue_migrate",
+        vec![
+            limits.to_value(),
+            Value::u128(limits.real_size_upper() as u128),
+            witness_task,
+        ],
+    )
+}
+
+/// A connection to a chain running the state-trie-migration pallet
+pub struct MigrationClient {
+    client: OnlineClient<PolkadotConfig>,
+    rpc: RpcClient,
+}
+
+impl MigrationClient {
+    pub async fn connect(url: &str) -> Result<Self, ClientError> {
+        let connect_error = |source| ClientError::Connect {
+            url: url.to_string(),
+            source,
+        };
+        let rpc = RpcClient::from_url(url).await.map_err(connect_error)?;
+        let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone())
+            .await
+            .map_err(connect_error)?;
+        Ok(Self { client, rpc })
+    }
+
+    pub fn client(&self) -> &OnlineClient<PolkadotConfig> {
+        &self.client
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// `MigrationProcess` at the best block: the status, and the witness
+    /// `continue_migrate` has to pass back
+    pub async fn task(&self) -> Result<Value<()>, ClientError> {
+        let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+        let thunk = self
+            .client
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+            .ok_or(ClientError::NoMigration)?;
+        Ok(thunk.to_value()?.map_context(|_| ()))
+    }
+
+    pub async fn status(&self) -> Result<MigrationStatus, ClientError> {
+        Ok(parse_migration_status(&self.task().await?))
+    }
+
+    /// Rough percent of the top trie migrated, from where the last migrated
+    /// key sits in the keyspace (free: one storage read). The `remaining`
+    /// command samples the keys left for a better figure.
+    pub async fn estimate(&self) -> Result<f64, ClientError> {
+        let task = self.task().await?;
+        if parse_migration_status(&task).is_complete() {
+            return Ok(100.0);
+        }
+        Ok(position_percent(&task).unwrap_or(0.0))
+    }
+
+    /// `SignedMigrationMaxLimits`, if set
+    pub async fn max_limits(&self) -> Result<Option<MigrationLimits>, ClientError> {
+        let query =
+            subxt::dynamic::storage("StateTrieMigration", "SignedMigrationMaxLimits", vec![]);
+        match self
+            .client
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+        {
+            Some(thunk) => MigrationLimits::from_value(&thunk.to_value()?)
+                .map(Some)
+                .map_err(ClientError::InvalidLimits),
+            None => Ok(None),
+        }
+    }
+
+    /// Submit one `continue_migrate` with the current witness and wait for
+    /// it to finalize; returns the block it landed in. Limits above
+    /// `SignedMigrationMaxLimits` are refused before anything is signed.
+    pub async fn submit_one<S: Signer<PolkadotConfig>>(
+        &self,
+        signer: &S,
+        limits: MigrationLimits,
+    ) -> Result<H256, ClientError> {
+        limits
+            .validate(self.max_limits().await?)
+            .map_err(ClientError::InvalidLimits)?;
+        let call = continue_migrate(limits, self.task().await?);
+        self.submit(&call, signer).await
+    }
+
+    /// Set `SignedMigrationMaxLimits` (the signer must be the pallet's
+    /// controller); returns the block it landed in
+    pub async fn set_limits<S: Signer<PolkadotConfig>>(
+        &self,
+        signer: &S,
+        limits: MigrationLimits,
+    ) -> Result<H256, ClientError> {
+        limits.validate(None).map_err(ClientError::InvalidLimits)?;
+        let call = subxt::dynamic::tx(
+            "StateTrieMigration",
+            "set_signed_max_limits",
+            vec![limits.to_value()],
+        );
+        self.submit(&call, signer).await
+    }
+
+    async fn submit<S: Signer<PolkadotConfig>>(
+        &self,
+        call: &DynamicPayload,
+        signer: &S,
+    ) -> Result<H256, ClientError> {
+        let block = self
+            .client
+            .tx()
+            .sign_and_submit_then_watch_default(call, signer)
+            .await?
+            .wait_for_finalized()
+            .await?;
+        block.wait_for_success().await?;
+        Ok(block.block_hash())
+    }
+}
@@ -0,0 +1,146 @@
+//! Backing off when other accounts are migrating (`--compete-backoff`)
+//!
+//! Every signed migration rewrites `MigrationProcess`, so while another
+//! account is submitting `continue_migrate` our witness is stale more often
+//! than not and dry runs and submissions are wasted. The tracker counts
+//! `Migrated` events signed by other accounts over a trailing window and turns
+//! the count into an extra delay, or tells the bot to yield entirely while
+//! someone else is clearly driving the migration.
+
+use crate::follower::BlockMigrations;
+use crate::policy::BLOCK_TIME_SECS;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Trailing window over which foreign migrations are counted
+pub const COMPETE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Upper bound for the extra delay while competing
+const MAX_COMPETE_DELAY_SECS: u64 = 300;
+
+/// What to do about foreign migrations before the next submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compete {
+    /// Nobody else is migrating
+    Proceed,
+    /// Others are migrating; wait this much longer than usual
+    Delay(Duration),
+    /// Others are migrating steadily; stand aside for this long
+    Yield(Duration),
+}
+
+/// Counts migrations signed by other accounts
+#[derive(Debug)]
+pub struct CompetitionTracker {
+    /// Our signer accounts (more than one with --rotate-seed-file)
+    our_accounts: Vec<String>,
+    window: Duration,
+    /// Foreign migrations at or above which we yield instead of delaying
+    yield_after: usize,
+    foreign: VecDeque<Instant>,
+}
+
+impl CompetitionTracker {
+    pub fn new(our_accounts: Vec<String>, yield_after: usize) -> Self {
+        Self {
+            our_accounts,
+            window: COMPETE_WINDOW,
+            yield_after,
+            foreign: VecDeque::new(),
+        }
+    }
+
+    /// Record the `Migrated` events of a finalized block (automatic
+    /// migrations do not touch our witness path and are ignored)
+    pub fn record(&mut self, block: &BlockMigrations, now: Instant) {
+        for event in &block.migrated {
+            match &event.signer {
+                Some(signer) if !self.our_accounts.contains(signer) => self.foreign.push_back(now),
+                _ => {}
+            }
+        }
+    }
+
+    /// Foreign migrations within the window
+    pub fn foreign_count(&mut self, now: Instant) -> usize {
+        while let Some(at) = self.foreign.front() {
+            if now.duration_since(*at) <= self.window {
+                break;
+            }
+            self.foreign.pop_front();
+        }
+        self.foreign.len()
+    }
+
+    /// Extra delay or yield, doubling from one block per foreign migration seen
+    pub fn advice(&mut self, now: Instant) -> Compete {
+        let count = self.foreign_count(now);
+        if count == 0 {
+            return Compete::Proceed;
+        }
+        if self.yield_after > 0 && count >= self.yield_after {
+            return Compete::Yield(self.window);
+        }
+        let exp = (count as u32 - 1).min(6);
+        let secs = (BLOCK_TIME_SECS << exp).min(MAX_COMPETE_DELAY_SECS);
+        Compete::Delay(Duration::from_secs(secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::follower::MigratedEvent;
+
+    const OURS: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn block(signers: &[Option<&str>]) -> BlockMigrations {
+        BlockMigrations {
+            number: 1,
+            migrated: signers
+                .iter()
+                .map(|signer| MigratedEvent {
+                    top: 1024,
+                    child: 0,
+                    signer: signer.map(str::to_string),
+                })
+                .collect(),
+            size: None,
+        }
+    }
+
+    #[test]
+    fn test_ignores_our_and_automatic_migrations() {
+        let now = Instant::now();
+        // Migrations of our other rotation accounts aren't competition either
+        let mut tracker = CompetitionTracker::new(vec![OURS.to_string(), "ours2".to_string()], 5);
+        tracker.record(&block(&[Some(OURS), None, Some("ours2")]), now);
+        assert_eq!(tracker.advice(now), Compete::Proceed);
+    }
+
+    #[test]
+    fn test_delay_grows_then_yields() {
+        let now = Instant::now();
+        let mut tracker = CompetitionTracker::new(vec![OURS.to_string()], 4);
+        tracker.record(&block(&[Some("other")]), now);
+        assert_eq!(tracker.advice(now), Compete::Delay(Duration::from_secs(6)));
+        tracker.record(&block(&[Some("other"), Some("another")]), now);
+        assert_eq!(tracker.advice(now), Compete::Delay(Duration::from_secs(24)));
+        tracker.record(&block(&[Some("other")]), now);
+        assert_eq!(tracker.advice(now), Compete::Yield(COMPETE_WINDOW));
+    }
+
+    #[test]
+    fn test_old_migrations_expire() {
+        let now = Instant::now();
+        let mut tracker = CompetitionTracker::new(vec![OURS.to_string()], 0);
+        tracker.record(&block(&[Some("other"); 20]), now);
+        // yield_after 0 never yields; the delay is capped
+        assert_eq!(
+            tracker.advice(now),
+            Compete::Delay(Duration::from_secs(MAX_COMPETE_DELAY_SECS))
+        );
+        let later = now + COMPETE_WINDOW + Duration::from_secs(1);
+        assert_eq!(tracker.advice(later), Compete::Proceed);
+    }
+}
@@ -0,0 +1,180 @@
+//! Runtime constants, cached per spec version
+//!
+//! Deposits, `MaxKeyLen`, block weights and the existential deposit come from
+//! the runtime metadata. [`ConstantsCache`] decodes them once and re-reads
+//! only when the runtime's `spec_version` changes, so the preflight checks,
+//! risk scoring and status report all see the same values instead of each
+//! querying ad hoc. A constant missing from the runtime is `None` rather than
+//! an error: not every feature needs every constant.
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use subxt::dynamic::{At, Value};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::debug;
+
+/// Constants the bot relies on, from one runtime version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainConstants {
+    pub spec_version: u32,
+    /// `StateTrieMigration::SignedDepositBase`
+    pub signed_deposit_base: Option<u128>,
+    /// `StateTrieMigration::SignedDepositPerItem`
+    pub signed_deposit_per_item: Option<u128>,
+    /// `StateTrieMigration::MaxKeyLen`
+    pub max_key_len: Option<u32>,
+    /// `Balances::ExistentialDeposit`
+    pub existential_deposit: Option<u128>,
+    /// `System::BlockWeights.max_block` (ref_time, proof_size)
+    pub max_block_weight: Option<(u64, u64)>,
+}
+
+impl ChainConstants {
+    /// Decode from a `(pallet, constant) -> value` lookup
+    pub fn decode<T>(spec_version: u32, lookup: impl Fn(&str, &str) -> Option<Value<T>>) -> Self {
+        let number = |pallet: &str, name: &str| {
+            let value = lookup(pallet, name)?.as_u128();
+            if value.is_none() {
+                debug!("{}::{} is not an unsigned integer", pallet, name);
+            }
+            value
+        };
+        let max_block_weight = lookup("System", "BlockWeights").and_then(|weights| {
+            let max = weights.at("max_block")?;
+            let part = |name| max.at(name).and_then(|v| v.as_u128()).map(|v| v as u64);
+            Some((part("ref_time")?, part("proof_size")?))
+        });
+        Self {
+            spec_version,
+            signed_deposit_base: number("StateTrieMigration", "SignedDepositBase"),
+            signed_deposit_per_item: number("StateTrieMigration", "SignedDepositPerItem"),
+            max_key_len: number("StateTrieMigration", "MaxKeyLen").map(|v| v as u32),
+            existential_deposit: number("Balances", "ExistentialDeposit"),
+            max_block_weight,
+        }
+    }
+
+    /// Read from the client's current metadata
+    pub fn read(client: &OnlineClient<PolkadotConfig>) -> Self {
+        let constants = client.constants();
+        Self::decode(client.runtime_version().spec_version, |pallet, name| {
+            let query = subxt::dynamic::constant(pallet, name);
+            match constants.at(&query).and_then(|thunk| Ok(thunk.to_value()?)) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    debug!("Constant {}::{} unavailable: {}", pallet, name, e);
+                    None
+                }
+            }
+        })
+    }
+
+    /// Deposit held for a signed migration of `items` keys
+    pub fn signed_deposit(&self, items: u32) -> Option<u128> {
+        let base = self.signed_deposit_base?;
+        let per_item = self.signed_deposit_per_item?;
+        Some(base.saturating_add(per_item.saturating_mul(items as u128)))
+    }
+}
+
+impl fmt::Display for ChainConstants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn show<T: fmt::Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map_or_else(|| "n/a".to_string(), T::to_string)
+        }
+        write!(
+            f,
+            "spec {}: deposit base {}, per item {}, ED {}, MaxKeyLen {}",
+            self.spec_version,
+            show(&self.signed_deposit_base),
+            show(&self.signed_deposit_per_item),
+            show(&self.existential_deposit),
+            show(&self.max_key_len)
+        )?;
+        if let Some((ref_time, proof_size)) = self.max_block_weight {
+            write!(f, ", max block weight {}/{}", ref_time, proof_size)?;
+        }
+        Ok(())
+    }
+}
+
+/// [`ChainConstants`] of the latest runtime seen, re-read on upgrades
+#[derive(Default)]
+pub struct ConstantsCache {
+    cached: Mutex<Option<Arc<ChainConstants>>>,
+}
+
+impl ConstantsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constants of the client's current runtime
+    pub fn get(&self, client: &OnlineClient<PolkadotConfig>) -> Arc<ChainConstants> {
+        let spec_version = client.runtime_version().spec_version;
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.as_ref() {
+            Some(constants) if constants.spec_version == spec_version => constants.clone(),
+            _ => {
+                let constants = Arc::new(ChainConstants::read(client));
+                debug!("Read chain constants for spec {}", spec_version);
+                *cached = Some(constants.clone());
+                constants
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(pallet: &str, name: &str) -> Option<Value<()>> {
+        match (pallet, name) {
+            ("StateTrieMigration", "SignedDepositBase") => Some(Value::u128(1_000)),
+            ("StateTrieMigration", "SignedDepositPerItem") => Some(Value::u128(10)),
+            ("StateTrieMigration", "MaxKeyLen") => Some(Value::u128(512)),
+            ("Balances", "ExistentialDeposit") => Some(Value::u128(1_000_000)),
+            ("System", "BlockWeights") => Some(Value::named_composite([
+                ("base_block", Value::u128(1)),
+                (
+                    "max_block",
+                    Value::named_composite([
+                        ("ref_time", Value::u128(2_000_000_000_000)),
+                        ("proof_size", Value::u128(5_242_880)),
+                    ]),
+                ),
+            ])),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let constants = ChainConstants::decode(1_016_000, lookup);
+        assert_eq!(constants.max_key_len, Some(512));
+        assert_eq!(constants.existential_deposit, Some(1_000_000));
+        assert_eq!(
+            constants.max_block_weight,
+            Some((2_000_000_000_000, 5_242_880))
+        );
+        assert_eq!(constants.signed_deposit(1024), Some(11_240));
+    }
+
+    #[test]
+    fn test_missing_constants() {
+        let constants = ChainConstants::decode(1, |_, _| None::<Value<()>>);
+        assert_eq!(constants.signed_deposit(1024), None);
+        assert_eq!(constants.max_block_weight, None);
+        assert_eq!(
+            constants.to_string(),
+            "spec 1: deposit base n/a, per item n/a, ED n/a, MaxKeyLen n/a"
+        );
+
+        let wrong_type = ChainConstants::decode(1, |_, _| Some(Value::string("x")));
+        assert_eq!(wrong_type.existential_deposit, None);
+    }
+}
@@ -0,0 +1,143 @@
+//! Run counters persisted across restarts (`--state-file`)
+//!
+//! Successful and failed runs and the migrated items/bytes are written to a
+//! small JSON file after every change and reloaded on startup, so `--runs`,
+//! the campaign caps and the exit summary count from the first start rather
+//! than from the last crash or redeploy. The file is tied to the instance ID
+//! (account and chain): loading one written by another instance is an error
+//! rather than a silent merge.
+
+use crate::stats::SessionStats;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Contents of the state file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedCounters {
+    pub instance_id: String,
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub items_migrated: u64,
+    pub bytes_migrated: u64,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SavedCounters {
+    /// Session stats continuing from these counters (elapsed time restarts)
+    pub fn restore(&self) -> SessionStats {
+        SessionStats::restored(
+            self.successful_runs,
+            self.failed_runs,
+            self.items_migrated,
+            self.bytes_migrated,
+        )
+    }
+}
+
+/// State file of one bot instance
+pub struct CounterFile {
+    path: PathBuf,
+    instance_id: String,
+}
+
+impl CounterFile {
+    pub fn new(path: &Path, instance_id: &str) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            instance_id: instance_id.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saved counters, or `None` on first start
+    pub fn load(&self) -> Result<Option<SavedCounters>> {
+        let json = match std::fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read state file {}", self.path.display()))
+            }
+        };
+        let saved: SavedCounters = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid state file {}", self.path.display()))?;
+        if saved.instance_id != self.instance_id {
+            anyhow::bail!(
+                "State file {} belongs to instance {}, not {} (use another --state-file)",
+                self.path.display(),
+                saved.instance_id,
+                self.instance_id
+            );
+        }
+        Ok(Some(saved))
+    }
+
+    pub fn save(&self, stats: &SessionStats) -> Result<()> {
+        let saved = SavedCounters {
+            instance_id: self.instance_id.clone(),
+            successful_runs: stats.successful_runs,
+            failed_runs: stats.failed_runs,
+            items_migrated: stats.items_migrated,
+            bytes_migrated: stats.bytes_migrated,
+            updated_at: chrono::Utc::now(),
+        };
+        // Write then rename so a crash mid-write never loses the counters
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        std::fs::write(&tmp, serde_json::to_string_pretty(&saved)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write state file {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "westend-migrate-counters-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = temp_path("roundtrip");
+        let file = CounterFile::new(&path, "5Grwva@0xe143f238");
+        assert_eq!(file.load().unwrap(), None);
+
+        let mut stats = SessionStats::new();
+        stats.successful_runs = 12;
+        stats.failed_runs = 1;
+        stats.items_migrated = 12_288;
+        stats.bytes_migrated = 600_000;
+        file.save(&stats).unwrap();
+
+        let restored = file.load().unwrap().unwrap().restore();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.successful_runs, 12);
+        assert_eq!(restored.failed_runs, 1);
+        assert_eq!(restored.items_migrated, 12_288);
+        assert_eq!(restored.bytes_migrated, 600_000);
+    }
+
+    #[test]
+    fn test_other_instance_is_rejected() {
+        let path = temp_path("instance");
+        CounterFile::new(&path, "5Grwva@0xe143f238")
+            .save(&SessionStats::new())
+            .unwrap();
+        let err = CounterFile::new(&path, "5FHneW@0xe143f238").load();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.unwrap_err().to_string().contains("belongs to instance"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Second-endpoint consistency check (`--verify-rpc-url`)
+//!
+//! The witness passed to `continue_migrate` comes from a single RPC node. A
+//! broken or malicious node can serve a stale or forged `MigrationProcess`,
+//! and a tx built from it is wasted at best and slashed at worst. With a
+//! second, independent endpoint configured, the task state is re-read there
+//! at the same block before each submission and compared; on divergence the
+//! submission is held.
+
+use crate::task::Task;
+use anyhow::{Context, Result};
+use std::time::Duration;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::debug;
+
+/// Outcome of comparing our state with the second endpoint's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossCheck {
+    /// Both endpoints return the same state at the block
+    Match,
+    /// The endpoints disagree; values are rendered for the alert
+    Diverged { ours: String, theirs: String },
+    /// The second endpoint does not know the block (yet)
+    UnknownBlock,
+}
+
+/// Compare two states, rendering them with `Debug` on divergence
+pub fn compare<S: PartialEq + std::fmt::Debug>(ours: &S, theirs: Option<&S>) -> CrossCheck {
+    match theirs {
+        Some(theirs) if theirs == ours => CrossCheck::Match,
+        Some(theirs) => CrossCheck::Diverged {
+            ours: format!("{:?}", ours),
+            theirs: format!("{:?}", theirs),
+        },
+        None => CrossCheck::Diverged {
+            ours: format!("{:?}", ours),
+            theirs: "none".to_string(),
+        },
+    }
+}
+
+/// Read-only connection to the second endpoint
+pub struct Verifier {
+    url: String,
+    rpc: LegacyRpcMethods<PolkadotConfig>,
+    client: OnlineClient<PolkadotConfig>,
+}
+
+impl Verifier {
+    /// Wrap a connection to `url`, refusing one to another chain than `genesis`
+    pub fn new(
+        url: &str,
+        rpc: LegacyRpcMethods<PolkadotConfig>,
+        client: OnlineClient<PolkadotConfig>,
+        genesis: H256,
+    ) -> Result<Self> {
+        if client.genesis_hash() != genesis {
+            anyhow::bail!(
+                "--verify-rpc-url {} is on another chain (genesis {:?}, expected {:?})",
+                url,
+                client.genesis_hash(),
+                genesis
+            );
+        }
+        Ok(Self {
+            url: url.to_string(),
+            rpc,
+            client,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Re-read `task`'s state at block `at` and compare it with `ours`;
+    /// a block the endpoint has not imported yet gets one block time's grace
+    pub async fn check<T>(
+        &self,
+        task: &T,
+        at: H256,
+        ours: &T::State,
+        block_time: Duration,
+    ) -> Result<CrossCheck>
+    where
+        T: Task,
+        T::State: PartialEq + std::fmt::Debug,
+    {
+        if !self.knows_block(at).await? {
+            debug!(
+                "{} has not imported {:?} yet, waiting a block",
+                self.url, at
+            );
+            tokio::time::sleep(block_time).await;
+            if !self.knows_block(at).await? {
+                return Ok(CrossCheck::UnknownBlock);
+            }
+        }
+        let theirs = task
+            .fetch(&self.client, at)
+            .await
+            .with_context(|| format!("Failed to read {} state from {}", task.name(), self.url))?;
+        Ok(compare(ours, theirs.as_ref()))
+    }
+
+    async fn knows_block(&self, at: H256) -> Result<bool> {
+        Ok(self.rpc.chain_get_header(Some(at)).await?.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(compare(&7u32, Some(&7)), CrossCheck::Match);
+        assert_eq!(
+            compare(&7u32, Some(&5)),
+            CrossCheck::Diverged {
+                ours: "7".to_string(),
+                theirs: "5".to_string()
+            }
+        );
+        assert_eq!(
+            compare(&7u32, None),
+            CrossCheck::Diverged {
+                ours: "7".to_string(),
+                theirs: "none".to_string()
+            }
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! for different failure modes, enabling better error recovery and logging.
 
 use crate::utils::ValidityError;
+use subxt::utils::AccountId32;
 use thiserror::Error;
 
 /// Main error type for the migration bot
@@ -35,10 +36,31 @@ pub enum MigrationError {
     #[error("SizeUpperBoundExceeded - reduce item_limit to avoid slashing")]
     SizeExceeded,
 
+    /// No way to validate the tx before submission, and --require-validation is set
+    #[error("Pre-submission validation unavailable: {0} (--require-validation)")]
+    ValidationUnavailable(String),
+
     /// Transaction validity error from dry run
     #[error("Transaction validity error: {0}")]
     ValidityError(ValidityError),
 
+    /// `MigrationProcess` moved on between the dry run and signing
+    #[error("Witness changed since the dry run: {0}")]
+    WitnessChanged(String),
+
+    /// The call failed with `BadWitness`: another migration advanced
+    /// `MigrationProcess` first
+    #[error("Stale witness: {0}")]
+    BadWitness(String),
+
+    /// An approver rejected the tx, or its approval request expired
+    #[error("Not approved: {0}")]
+    NotApproved(String),
+
+    /// The signing device rejected the tx, or nobody confirmed it in time
+    #[error("Not signed: {0}")]
+    NotSigned(String),
+
     // === Balance/Safety Errors ===
     /// Balance decreased after transaction - possible slashing
     #[error("Balance decreased by {lost_wnd:.6} WND - possible slashing detected!")]
@@ -48,6 +70,14 @@ pub enum MigrationError {
     #[error("Account has zero balance - transactions will fail")]
     ZeroBalance,
 
+    /// Signer account has never been funded (no `System::Account` entry)
+    #[error("Account {0} does not exist on chain - fund it before migrating")]
+    AccountNotFound(String),
+
+    /// `System::Account` exists but its balance data could not be decoded
+    #[error("Failed to decode System::Account: {0}")]
+    AccountDecode(String),
+
     // === Network Errors ===
     /// Failed to connect to RPC endpoint
     #[error("Failed to connect to RPC: {0}")]
@@ -65,6 +95,16 @@ pub enum MigrationError {
     #[error("Transaction dropped: {0}")]
     TxDropped(String),
 
+    /// The node kept broadcasting the transaction to no peers; the signed
+    /// bytes, signer and nonce are kept to rebroadcast it elsewhere
+    #[error("Transaction broadcast to 0 peers {broadcasts} times - node is not connected")]
+    NoPeers {
+        broadcasts: u32,
+        tx_bytes: Vec<u8>,
+        account: AccountId32,
+        nonce_before: u32,
+    },
+
     // === State Errors ===
     /// Migration is already complete (reserved for future use)
     #[allow(dead_code)]
@@ -76,11 +116,19 @@ pub enum MigrationError {
     #[error("Could not fetch migration progress from chain")]
     NoMigrationProgress,
 
+    /// Shutdown was requested before the tx was signed
+    #[error("Shutdown requested before signing")]
+    ShuttingDown,
+
     // === Configuration Errors ===
     /// Invalid seed/mnemonic
     #[error("Invalid seed: {0}")]
     InvalidSeed(String),
 
+    /// Item/size limits are zero, malformed on chain, or above the chain maximum
+    #[error("Invalid migration limits: {0}")]
+    InvalidLimits(String),
+
     /// Too many consecutive errors
     #[error("Stopped after {count} consecutive errors. Last: {last_error}")]
     TooManyErrors { count: u32, last_error: String },
@@ -101,9 +149,25 @@ impl MigrationError {
                 | MigrationError::NonceFuture
                 | MigrationError::TxBanned
                 | MigrationError::RpcError(_)
+                | MigrationError::NoPeers { .. }
+                | MigrationError::WitnessChanged(_)
+                | MigrationError::BadWitness(_)
         )
     }
 
+    /// Check if the witness was stale, so a fresh read fixes it
+    pub fn is_stale_witness(&self) -> bool {
+        matches!(
+            self,
+            MigrationError::WitnessChanged(_) | MigrationError::BadWitness(_)
+        )
+    }
+
+    /// Check if this error must stop the bot at once, without retries
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, MigrationError::ValidationUnavailable(_))
+    }
+
     /// Check if this error indicates a pool conflict that requires waiting
     pub fn requires_pool_wait(&self) -> bool {
         matches!(
@@ -151,12 +215,68 @@ mod tests {
         assert!(MigrationError::TxBanned.is_recoverable());
     }
 
+    #[test]
+    fn test_no_peers_is_recoverable() {
+        let err = MigrationError::NoPeers {
+            broadcasts: 3,
+            tx_bytes: vec![0u8; 4],
+            account: AccountId32([0; 32]),
+            nonce_before: 7,
+        };
+        assert!(err.is_recoverable());
+        assert!(!err.requires_pool_wait());
+        assert!(err.to_string().contains("0 peers 3 times"));
+    }
+
+    #[test]
+    fn test_witness_changed_is_recoverable() {
+        let err = MigrationError::WitnessChanged("top_items 1024 -> 2048".to_string());
+        assert!(err.is_recoverable());
+        assert!(!err.requires_pool_wait());
+        assert!(err.to_string().contains("since the dry run"));
+        assert!(err.is_stale_witness());
+        assert!(MigrationError::BadWitness("BadWitness".to_string()).is_stale_witness());
+        assert!(!MigrationError::SizeExceeded.is_stale_witness());
+    }
+
     #[test]
     fn test_balance_decrease_not_recoverable() {
         let err = MigrationError::BalanceDecreased { lost_wnd: 0.5 };
         assert!(!err.is_recoverable());
     }
 
+    #[test]
+    fn test_account_errors_not_recoverable() {
+        let err = MigrationError::AccountNotFound("5Grwva".to_string());
+        assert!(!err.is_recoverable());
+        assert!(err.to_string().contains("does not exist"));
+
+        let err = MigrationError::AccountDecode("no `frozen` field".to_string());
+        assert!(!err.is_recoverable());
+        assert!(err.to_string().contains("no `frozen` field"));
+    }
+
+    #[test]
+    fn test_validation_unavailable_is_fatal() {
+        let err = MigrationError::ValidationUnavailable("no system_dryRun".to_string());
+        assert!(err.is_fatal());
+        assert!(!err.is_recoverable());
+        assert!(!MigrationError::SizeExceeded.is_fatal());
+        assert!(!MigrationError::PoolConflict.is_fatal());
+    }
+
+    #[test]
+    fn test_not_approved_counts_as_error() {
+        let err = MigrationError::NotApproved("tx 20261016T120000Z-n7 rejected".to_string());
+        assert!(!err.is_recoverable());
+        assert!(!err.is_fatal());
+        assert!(err.to_string().contains("rejected"));
+
+        let err = MigrationError::NotSigned("not confirmed on the Ledger within 120s".to_string());
+        assert!(!err.is_recoverable());
+        assert!(!err.is_fatal());
+    }
+
     #[test]
     fn test_from_rpc_error_parsing() {
         assert!(matches!(
@@ -0,0 +1,45 @@
+//! Progress arithmetic shared by the bot and dashboards
+//!
+//! Percent complete from the items `MigrationProcess` counts and the keys
+//! still to migrate (from `state_trieMigrationStatus` or a key scan), and
+//! the ETA at a given throughput.
+
+use std::time::Duration;
+
+/// Share of the top trie migrated, given `remaining` keys after the
+/// `migrated` items `MigrationProcess` counts
+pub fn percent_complete(migrated: u64, remaining: u64) -> f64 {
+    let total = migrated + remaining;
+    if total == 0 {
+        return 100.0;
+    }
+    migrated as f64 / total as f64 * 100.0
+}
+
+/// Time to migrate `remaining` keys at `items_per_min`
+pub fn eta(remaining: u64, items_per_min: f64) -> Option<Duration> {
+    if items_per_min <= 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(
+        remaining as f64 / items_per_min * 60.0,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_complete() {
+        assert!((percent_complete(250, 750) - 25.0).abs() < 1e-9);
+        assert_eq!(percent_complete(0, 0), 100.0);
+        assert_eq!(percent_complete(0, 10), 0.0);
+    }
+
+    #[test]
+    fn test_eta() {
+        assert_eq!(eta(1000, 100.0), Some(Duration::from_secs(600)));
+        assert_eq!(eta(1000, 0.0), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Migration ETA from recent throughput
+//!
+//! The run loop feeds [`RateTracker`] the items/bytes attributed to each
+//! finalized tx; rates are computed over a sliding window so the estimate
+//! follows phase changes (bulk vs stragglers) instead of the session average.
+//! Remaining work comes from the node's `state_trieMigrationStatus` RPC,
+//! which scans the whole trie (~30s), so it is refreshed in the background
+//! every [`REMAINING_REFRESH`] rather than per tx.
+
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subxt::backend::rpc::{RpcClient, RpcParams};
+use tracing::debug;
+
+pub use westend_migration_bot::estimate::eta;
+
+/// Throughput window
+pub const RATE_WINDOW: Duration = Duration::from_secs(30 * 60);
+
+/// How often the remaining key count is re-read from the node
+pub const REMAINING_REFRESH: Duration = Duration::from_secs(30 * 60);
+
+/// Items and bytes migrated per minute over the last [`RATE_WINDOW`]
+#[derive(Debug)]
+pub struct RateTracker {
+    window: Duration,
+    /// (finalized at, items, bytes) per tx, oldest first
+    samples: VecDeque<(Instant, u64, u64)>,
+}
+
+impl RateTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, at: Instant, items: u64, bytes: u64) {
+        self.samples.push_back((at, items, bytes));
+        while let Some(&(first, _, _)) = self.samples.front() {
+            if at.duration_since(first) <= self.window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// (items/min, bytes/min), once the window spans at least two txs
+    pub fn per_minute(&self) -> Option<(f64, f64)> {
+        let (first, _, _) = *self.samples.front()?;
+        let (last, _, _) = *self.samples.back()?;
+        let minutes = last.duration_since(first).as_secs_f64() / 60.0;
+        if self.samples.len() < 2 || minutes <= 0.0 {
+            return None;
+        }
+        // The first sample's work happened before the window started
+        let (items, bytes) = self
+            .samples
+            .iter()
+            .skip(1)
+            .fold((0, 0), |(i, b), &(_, items, bytes)| (i + items, b + bytes));
+        Some((items as f64 / minutes, bytes as f64 / minutes))
+    }
+}
+
+/// Remaining top + child keys from `state_trieMigrationStatus`
+pub async fn remaining_keys(rpc: &RpcClient) -> Result<u64> {
+    let status: serde_json::Value = rpc
+        .request("state_trieMigrationStatus", RpcParams::new())
+        .await
+        .context("state_trieMigrationStatus failed")?;
+    let field = |name: &str| status[name].as_u64();
+    let top = field("topRemainingToMigrate").context("no topRemainingToMigrate in response")?;
+    Ok(top + field("childRemainingToMigrate").unwrap_or(0))
+}
+
+/// Latest remaining key count, refreshed in the background
+#[derive(Clone, Default)]
+pub struct RemainingKeys(Arc<Mutex<Option<u64>>>);
+
+impl RemainingKeys {
+    pub fn get(&self) -> Option<u64> {
+        self.0.lock().ok().and_then(|r| *r)
+    }
+
+    /// Start a refresh unless the last one is younger than [`REMAINING_REFRESH`]
+    pub fn refresh(&self, rpc: &RpcClient, last_refresh: &mut Option<Instant>) {
+        if last_refresh.is_some_and(|at| at.elapsed() < REMAINING_REFRESH) {
+            return;
+        }
+        *last_refresh = Some(Instant::now());
+
+        let rpc = rpc.clone();
+        let slot = self.0.clone();
+        tokio::spawn(async move {
+            match remaining_keys(&rpc).await {
+                Ok(remaining) => {
+                    if let Ok(mut slot) = slot.lock() {
+                        *slot = Some(remaining);
+                    }
+                }
+                Err(e) => debug!("Could not read remaining keys for ETA: {:#}", e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_rate_until_two_samples() {
+        let mut rates = RateTracker::new(RATE_WINDOW);
+        assert_eq!(rates.per_minute(), None);
+        rates.record(Instant::now(), 1024, 4096);
+        assert_eq!(rates.per_minute(), None);
+    }
+
+    #[test]
+    fn test_rate_over_window() {
+        let start = Instant::now();
+        let mut rates = RateTracker::new(RATE_WINDOW);
+        rates.record(start, 1000, 10_000);
+        rates.record(start + Duration::from_secs(60), 1000, 10_000);
+        rates.record(start + Duration::from_secs(120), 1000, 10_000);
+        let (items, bytes) = rates.per_minute().unwrap();
+        assert!((items - 1000.0).abs() < 1e-9);
+        assert!((bytes - 10_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_old_samples_leave_window() {
+        let start = Instant::now();
+        let mut rates = RateTracker::new(Duration::from_secs(300));
+        rates.record(start, 5000, 0);
+        rates.record(start + Duration::from_secs(60), 5000, 0);
+        // Slow phase: the fast samples age out
+        rates.record(start + Duration::from_secs(600), 10, 0);
+        rates.record(start + Duration::from_secs(660), 10, 0);
+        let (items, _) = rates.per_minute().unwrap();
+        assert!((items - 10.0).abs() < 1e-9);
+    }
+}
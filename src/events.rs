@@ -0,0 +1,441 @@
+//! Persistent bot event log and replay
+//!
+//! The run loop emits a [`BotEvent`] for every state change (status fetched,
+//! tx submitted/finalized/failed, stop). With `--event-log` they are appended
+//! to a JSON-lines file (`--events ndjson` streams the same lines to stdout
+//! for jq or a supervisor), and `replay` rebuilds session statistics and the
+//! last known state from it, so "why did the bot stop at 03:12" can be
+//! answered long after the logs have rotated away.
+//!
+//! The most recent events are also kept in memory ([`Timeline`]) so critical
+//! notifications can carry a short incident timeline.
+
+use crate::utils::MigrationStatus;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A state change of the bot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BotEvent {
+    Started {
+        account: String,
+        chain: String,
+        item_limit: u32,
+        size_limit: u32,
+    },
+    Status(MigrationStatus),
+    TxSubmitted {
+        item_limit: u32,
+    },
+    TxInBlock {
+        tx_hash: String,
+        block_hash: String,
+    },
+    TxFinalized {
+        tx_hash: Option<String>,
+        elapsed_secs: f64,
+    },
+    /// Progress of the last finalized tx, attributed on the next status fetch
+    Progress {
+        items: u64,
+        bytes: u64,
+    },
+    TxFailed {
+        error: String,
+        recoverable: bool,
+    },
+    BalanceDecreased {
+        lost_wnd: f64,
+    },
+    Stopped {
+        reason: String,
+    },
+}
+
+impl BotEvent {
+    /// Short human-readable description for incident timelines
+    pub fn describe(&self) -> String {
+        match self {
+            BotEvent::Started {
+                item_limit,
+                size_limit,
+                ..
+            } => format!(
+                "started (limits {} items / {} bytes)",
+                item_limit, size_limit
+            ),
+            BotEvent::Status(status) => format!(
+                "status top={} child={} size={}",
+                status.top_items, status.child_items, status.size
+            ),
+            BotEvent::TxSubmitted { item_limit } => format!("submitted ({} items)", item_limit),
+            BotEvent::TxInBlock { block_hash, .. } => format!("included in {}", block_hash),
+            BotEvent::TxFinalized {
+                tx_hash,
+                elapsed_secs,
+            } => format!(
+                "finalized {} after {:.0}s",
+                tx_hash.as_deref().unwrap_or("(dry run)"),
+                elapsed_secs
+            ),
+            BotEvent::Progress { items, bytes } => {
+                format!("migrated {} items / {} bytes", items, bytes)
+            }
+            BotEvent::TxFailed { error, recoverable } => format!(
+                "{} error: {}",
+                if *recoverable { "recoverable" } else { "FATAL" },
+                error
+            ),
+            BotEvent::BalanceDecreased { lost_wnd } => {
+                format!("BALANCE DECREASED by {:.6} WND", lost_wnd)
+            }
+            BotEvent::Stopped { reason } => format!("stopped: {}", reason),
+        }
+    }
+}
+
+/// An event with the time it was emitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: BotEvent,
+}
+
+/// Append-only JSON-lines event log
+pub struct EventLog {
+    file: Mutex<File>,
+}
+
+impl EventLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open event log {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn append(&self, record: &EventRecord) -> Result<()> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("event log lock poisoned"))?;
+        write_record(&mut *file, record)
+    }
+}
+
+/// Write `record` as one JSON line and flush, so a reader sees it at once
+/// (the --event-log file, or stdout with `--events ndjson`)
+pub fn write_record(out: &mut impl Write, record: &EventRecord) -> Result<()> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    out.write_all(line.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The last `capacity` events, kept in memory for incident timelines
+pub struct Timeline {
+    capacity: usize,
+    records: Mutex<VecDeque<EventRecord>>,
+}
+
+impl Timeline {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, record: EventRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut records) = self.records.lock() {
+            if records.len() == self.capacity {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+
+    /// One line per event, oldest first (empty if no events)
+    pub fn render(&self) -> String {
+        let Ok(records) = self.records.lock() else {
+            return String::new();
+        };
+        records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}  {}",
+                    r.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                    r.event.describe()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Read every event from a log; unparseable lines (e.g. a torn final write) are skipped
+pub fn read_events(path: &Path) -> Result<Vec<EventRecord>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open event log {}", path.display()))?;
+
+    let mut events = Vec::new();
+    for (n, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => events.push(record),
+            Err(e) => tracing::warn!("Skipping event log line {}: {}", n + 1, e),
+        }
+    }
+    Ok(events)
+}
+
+/// State of one bot session reconstructed from its events
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionReplay {
+    pub started: Option<DateTime<Utc>>,
+    pub last_event: Option<DateTime<Utc>>,
+    pub account: Option<String>,
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub items_migrated: u64,
+    pub bytes_migrated: u64,
+    pub last_status: Option<MigrationStatus>,
+    pub last_error: Option<String>,
+    /// Stop reason; `None` if the log ends without one (crash or kill)
+    pub stopped: Option<String>,
+}
+
+impl SessionReplay {
+    fn apply(&mut self, record: &EventRecord) {
+        self.last_event = Some(record.timestamp);
+        match &record.event {
+            BotEvent::Started { account, .. } => {
+                self.started = Some(record.timestamp);
+                self.account = Some(account.clone());
+            }
+            BotEvent::Status(status) => self.last_status = Some(status.clone()),
+            BotEvent::TxSubmitted { .. } | BotEvent::TxInBlock { .. } => {}
+            BotEvent::TxFinalized { .. } => self.successful_runs += 1,
+            BotEvent::Progress { items, bytes } => {
+                self.items_migrated += items;
+                self.bytes_migrated += bytes;
+            }
+            BotEvent::TxFailed { error, .. } => {
+                self.failed_runs += 1;
+                self.last_error = Some(error.clone());
+            }
+            BotEvent::BalanceDecreased { lost_wnd } => {
+                self.last_error = Some(format!("balance decreased by {:.6} WND", lost_wnd));
+            }
+            BotEvent::Stopped { reason } => self.stopped = Some(reason.clone()),
+        }
+    }
+}
+
+/// Replay `events` (up to and including `until`, if set) into per-session state.
+/// A `Started` event begins a new session.
+pub fn replay(events: &[EventRecord], until: Option<DateTime<Utc>>) -> Vec<SessionReplay> {
+    let mut sessions: Vec<SessionReplay> = Vec::new();
+    for record in events {
+        if until.is_some_and(|until| record.timestamp > until) {
+            break;
+        }
+        if matches!(record.event, BotEvent::Started { .. }) || sessions.is_empty() {
+            sessions.push(SessionReplay::default());
+        }
+        if let Some(session) = sessions.last_mut() {
+            session.apply(record);
+        }
+    }
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64, event: BotEvent) -> EventRecord {
+        EventRecord {
+            timestamp: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            event,
+        }
+    }
+
+    fn started() -> BotEvent {
+        BotEvent::Started {
+            account: "5Grwva".to_string(),
+            chain: "0xe143".to_string(),
+            item_limit: 1024,
+            size_limit: 51200,
+        }
+    }
+
+    fn status(top_items: u64) -> BotEvent {
+        BotEvent::Status(MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size: top_items * 10,
+            top_items,
+            child_items: 0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = at(
+            0,
+            BotEvent::TxFailed {
+                error: "Pool conflict".to_string(),
+                recoverable: true,
+            },
+        );
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""event":"tx_failed""#));
+        assert_eq!(serde_json::from_str::<EventRecord>(&json).unwrap(), record);
+
+        let record = at(0, status(7));
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<EventRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_write_record_is_one_line() {
+        let mut out = Vec::new();
+        write_record(&mut out, &at(0, started())).unwrap();
+        write_record(&mut out, &at(6, status(7))).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with('{') && lines[0].contains(r#""event":"started""#));
+        assert_eq!(
+            serde_json::from_str::<EventRecord>(lines[1]).unwrap(),
+            at(6, status(7))
+        );
+    }
+
+    #[test]
+    fn test_timeline_keeps_last_events() {
+        let timeline = Timeline::new(2);
+        assert_eq!(timeline.render(), "");
+
+        timeline.push(at(0, started()));
+        timeline.push(at(1, BotEvent::TxSubmitted { item_limit: 512 }));
+        timeline.push(at(
+            2,
+            BotEvent::TxFailed {
+                error: "BadProof".to_string(),
+                recoverable: false,
+            },
+        ));
+
+        let rendered = timeline.render();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("submitted (512 items)"));
+        assert!(lines[1].ends_with("FATAL error: BadProof"));
+    }
+
+    #[test]
+    fn test_timeline_disabled() {
+        let timeline = Timeline::new(0);
+        timeline.push(at(0, started()));
+        assert_eq!(timeline.render(), "");
+    }
+
+    #[test]
+    fn test_replay_reconstructs_session() {
+        let events = vec![
+            at(0, started()),
+            at(1, status(100)),
+            at(2, BotEvent::TxSubmitted { item_limit: 1024 }),
+            at(
+                14,
+                BotEvent::TxFinalized {
+                    tx_hash: Some("0xab".to_string()),
+                    elapsed_secs: 12.0,
+                },
+            ),
+            at(20, status(1124)),
+            at(
+                20,
+                BotEvent::Progress {
+                    items: 1024,
+                    bytes: 10240,
+                },
+            ),
+            at(
+                30,
+                BotEvent::TxFailed {
+                    error: "BadProof".to_string(),
+                    recoverable: false,
+                },
+            ),
+            at(
+                31,
+                BotEvent::Stopped {
+                    reason: "error: too many errors".to_string(),
+                },
+            ),
+        ];
+
+        let sessions = replay(&events, None);
+        assert_eq!(sessions.len(), 1);
+        let s = &sessions[0];
+        assert_eq!(s.successful_runs, 1);
+        assert_eq!(s.failed_runs, 1);
+        assert_eq!(s.items_migrated, 1024);
+        assert_eq!(s.bytes_migrated, 10240);
+        assert_eq!(s.last_status.as_ref().unwrap().top_items, 1124);
+        assert_eq!(s.last_error.as_deref(), Some("BadProof"));
+        assert_eq!(s.stopped.as_deref(), Some("error: too many errors"));
+
+        // State as of just after the first tx finalized
+        let until = events[3].timestamp;
+        let s = &replay(&events, Some(until))[0];
+        assert_eq!(s.successful_runs, 1);
+        assert_eq!(s.last_status.as_ref().unwrap().top_items, 100);
+        assert!(s.stopped.is_none());
+    }
+
+    #[test]
+    fn test_replay_splits_sessions() {
+        let events = vec![
+            at(0, started()),
+            at(5, BotEvent::TxSubmitted { item_limit: 1024 }),
+            // Killed without a Stopped event, then restarted
+            at(100, started()),
+            at(
+                110,
+                BotEvent::Stopped {
+                    reason: "deadline reached".to_string(),
+                },
+            ),
+        ];
+        let sessions = replay(&events, None);
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions[0].stopped.is_none());
+        assert_eq!(sessions[0].last_event, Some(events[1].timestamp));
+        assert_eq!(sessions[1].stopped.as_deref(), Some("deadline reached"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Process exit codes
+//!
+//! `run()` ends with a [`Stop`] saying why the bot stopped, and `main` maps
+//! it, or the error that ended the run, to a distinct exit code, so scripts
+//! and supervisors can tell "migration finished" from "bot crashed":
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Migration complete, or a one-shot command finished |
+//! | 1 | Unclassified error |
+//! | 2 | `--runs` reached |
+//! | 3 | `--once` finished its run |
+//! | 4 | `--deadline` reached |
+//! | 5 | Campaign cap reached (`--max-items-total` / `--max-bytes-total`) |
+//! | 6 | Shut down by SIGINT/SIGTERM |
+//! | 7 | `--once` stopped without submitting (progress unavailable, risk, endpoints disagree) |
+//! | 9 | Another instance holds the lock |
+//! | 10 | Too many consecutive errors |
+//! | 11 | Balance decreased (possible slashing) |
+//! | 12 | Connection lost |
+//! | 13 | Signer account missing, empty or undecodable |
+//! | 14 | Configuration rejected (seed, limits, `--require-validation`) |
+//! | 130 | Second shutdown signal (aborted) |
+
+use crate::error::MigrationError;
+use crate::notify::Severity;
+use std::fmt;
+use std::process::ExitCode;
+use westend_migration_bot::MigrationStatus;
+
+/// Another instance holds the lockfile
+pub const LOCKED: u8 = 9;
+
+/// Why the run loop stopped without an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// The migration is complete
+    Complete,
+    /// A one-shot command (status, limits, account, pool clear, remaining, ...) finished
+    Command,
+    /// `--runs` successful migrations submitted
+    RunsReached(u32),
+    /// `--once` submitted its run
+    Once,
+    Deadline,
+    /// A campaign cap was reached, with the cap's reason
+    CapReached(String),
+    /// SIGINT/SIGTERM; `drained` is false if the in-flight tx was abandoned
+    Shutdown {
+        drained: bool,
+    },
+    /// `--once` stopped before submitting, with the reason
+    Held(String),
+}
+
+impl Stop {
+    pub fn code(&self) -> u8 {
+        match self {
+            Stop::Complete | Stop::Command => 0,
+            Stop::RunsReached(_) => 2,
+            Stop::Once => 3,
+            Stop::Deadline => 4,
+            Stop::CapReached(_) => 5,
+            Stop::Shutdown { .. } => 6,
+            Stop::Held(_) => 7,
+        }
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Complete => write!(f, "migration complete"),
+            Stop::Command => write!(f, "command finished"),
+            Stop::RunsReached(runs) => write!(f, "completed {} runs", runs),
+            Stop::Once => write!(f, "single run (--once)"),
+            Stop::Deadline => write!(f, "deadline reached"),
+            Stop::CapReached(reason) => write!(f, "{}", reason),
+            Stop::Shutdown { drained: true } => write!(f, "shutdown requested"),
+            Stop::Shutdown { drained: false } => {
+                write!(f, "shutdown requested (in-flight tx not finalized)")
+            }
+            Stop::Held(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl From<Stop> for ExitCode {
+    fn from(stop: Stop) -> Self {
+        ExitCode::from(stop.code())
+    }
+}
+
+/// Exit code for the error that ended the run
+pub fn error_code(err: &anyhow::Error) -> u8 {
+    if err
+        .downcast_ref::<subxt::Error>()
+        .is_some_and(|e| matches!(e, subxt::Error::Rpc(_) | subxt::Error::Io(_)))
+    {
+        return 12;
+    }
+    match err.downcast_ref::<MigrationError>() {
+        Some(MigrationError::TooManyErrors { .. }) => 10,
+        Some(MigrationError::BalanceDecreased { .. }) => 11,
+        Some(MigrationError::ConnectionFailed(_) | MigrationError::RpcError(_)) => 12,
+        Some(
+            MigrationError::AccountNotFound(_)
+            | MigrationError::ZeroBalance
+            | MigrationError::AccountDecode(_),
+        ) => 13,
+        Some(
+            MigrationError::InvalidSeed(_)
+            | MigrationError::InvalidLimits(_)
+            | MigrationError::ValidationUnavailable(_),
+        ) => 14,
+        _ => 1,
+    }
+}
+
+/// Final notification for a run that ended with `result`: outcome, exit
+/// code and what is left to migrate. `None` for one-shot commands.
+pub fn shutdown_report(
+    result: Result<&Stop, &anyhow::Error>,
+    session: Option<&str>,
+    status: Option<&MigrationStatus>,
+    remaining_keys: Option<u64>,
+) -> Option<(Severity, String)> {
+    let (severity, outcome, code) = match result {
+        Ok(Stop::Command) => return None,
+        Ok(stop @ Stop::Complete) => (Severity::Success, stop.to_string(), stop.code()),
+        Ok(stop @ Stop::Held(_)) => (Severity::Warning, stop.to_string(), stop.code()),
+        Ok(stop) => (Severity::Info, stop.to_string(), stop.code()),
+        Err(e) => (Severity::Critical, format!("error: {:#}", e), error_code(e)),
+    };
+    let mut lines = vec![
+        format!("Outcome: {}", outcome),
+        format!("Exit code: {}", code),
+    ];
+    if let Some(session) = session {
+        lines.push(format!("Session: {}", session));
+    }
+    match status {
+        Some(status) if status.is_complete() => lines.push("Remaining: none".to_string()),
+        Some(status) => lines.push(format!(
+            "Remaining: {} keys left, {} top / {} child items migrated so far (last key {})",
+            remaining_keys.map_or("unknown number of".to_string(), |keys| keys.to_string()),
+            status.top_items,
+            status.child_items,
+            status.top_last_key.as_deref().unwrap_or("none")
+        )),
+        None => lines.push("Remaining: unknown (migration status never read)".to_string()),
+    }
+    Some((severity, lines.join("\n")))
+}
+
+/// Print `err` the way a `main` returning `Err` would, and pick its exit code
+pub fn report(err: &anyhow::Error) -> ExitCode {
+    eprintln!("Error: {:?}", err);
+    ExitCode::from(error_code(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_codes_are_distinct() {
+        let stops = [
+            Stop::Complete,
+            Stop::RunsReached(5),
+            Stop::Once,
+            Stop::Deadline,
+            Stop::CapReached("item cap of 10 reached".to_string()),
+            Stop::Shutdown { drained: true },
+            Stop::Held("risk above --max-risk".to_string()),
+        ];
+        let mut codes: Vec<u8> = stops.iter().map(Stop::code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), stops.len());
+        assert_eq!(Stop::Complete.code(), 0);
+        assert_eq!(Stop::RunsReached(5).to_string(), "completed 5 runs");
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let code = |e: MigrationError| error_code(&e.into());
+        assert_eq!(
+            code(MigrationError::TooManyErrors {
+                count: 5,
+                last_error: "x".to_string()
+            }),
+            10
+        );
+        assert_eq!(code(MigrationError::BalanceDecreased { lost_wnd: 0.1 }), 11);
+        assert_eq!(code(MigrationError::ConnectionFailed("x".to_string())), 12);
+        assert_eq!(code(MigrationError::ZeroBalance), 13);
+        assert_eq!(code(MigrationError::InvalidSeed("x".to_string())), 14);
+        assert_eq!(code(MigrationError::SizeExceeded), 1);
+        assert_eq!(error_code(&anyhow::anyhow!("boom")), 1);
+    }
+
+    #[test]
+    fn test_shutdown_report() {
+        assert_eq!(shutdown_report(Ok(&Stop::Command), None, None, None), None);
+
+        let status = MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size: 0,
+            top_items: 120,
+            child_items: 4,
+            top_last_key: Some("0x26aa".to_string()),
+            child_last_key: None,
+        };
+        let (severity, body) = shutdown_report(
+            Ok(&Stop::Shutdown { drained: true }),
+            Some("3 successful, 0 failed"),
+            Some(&status),
+            Some(5000),
+        )
+        .unwrap();
+        assert_eq!(severity, Severity::Info);
+        assert_eq!(
+            body,
+            "Outcome: shutdown requested\nExit code: 6\nSession: 3 successful, 0 failed\n\
+             Remaining: 5000 keys left, 120 top / 4 child items migrated so far (last key 0x26aa)"
+        );
+
+        let failed = MigrationError::ZeroBalance.into();
+        let (severity, body) = shutdown_report(Err(&failed), None, None, None).unwrap();
+        assert_eq!(severity, Severity::Critical);
+        assert!(body.contains("Exit code: 13"));
+        assert!(body.ends_with("Remaining: unknown (migration status never read)"));
+    }
+}
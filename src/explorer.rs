@@ -0,0 +1,163 @@
+//! Block explorer API client (Subscan) for enriching history entries
+//!
+//! The bot records what it can observe locally; the explorer fills in the
+//! block number and timestamp, the fee charged and the events emitted, for
+//! rows recorded before this existed or when the bot missed the finalized
+//! block (e.g. finalization detected by nonce only). Requires an API key.
+
+use anyhow::{Context, Result};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+
+/// Subscan API for Westend Asset Hub
+pub const DEFAULT_API_URL: &str = "https://assethub-westend.api.subscan.io";
+
+/// Subscan "Record Not Found" response code
+const NOT_FOUND: i64 = 10004;
+
+/// Explorer data about one extrinsic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enrichment {
+    pub tx_hash: String,
+    pub block_number: u64,
+    /// Unix timestamp (seconds) of the block
+    pub block_timestamp: i64,
+    /// Fee charged, in planck
+    pub fee: u128,
+    pub success: bool,
+    /// Emitted events as `Pallet.Event`
+    pub events: Vec<String>,
+}
+
+pub struct ExplorerClient {
+    api_url: String,
+    api_key: SecretString,
+    http: reqwest::Client,
+}
+
+impl ExplorerClient {
+    pub fn new(api_url: &str, api_key: SecretString) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Look up an extrinsic by hash; `None` if the explorer doesn't know it (yet)
+    pub async fn extrinsic(&self, tx_hash: &str) -> Result<Option<Enrichment>> {
+        let response: Value = self
+            .http
+            .post(format!("{}/api/scan/extrinsic", self.api_url))
+            .header("X-API-Key", self.api_key.expose_secret())
+            .json(&json!({ "hash": tx_hash }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_extrinsic(tx_hash, &response)
+    }
+}
+
+/// Parse a `/api/scan/extrinsic` response
+fn parse_extrinsic(tx_hash: &str, response: &Value) -> Result<Option<Enrichment>> {
+    match response["code"].as_i64() {
+        Some(0) => {}
+        Some(NOT_FOUND) => return Ok(None),
+        _ => anyhow::bail!(
+            "Explorer API error {}: {}",
+            response["code"],
+            response["message"].as_str().unwrap_or("unknown")
+        ),
+    }
+    let data = &response["data"];
+    if data.is_null() {
+        return Ok(None);
+    }
+
+    let fee = match &data["fee"] {
+        Value::String(fee) => fee.parse().context("Invalid fee in explorer response")?,
+        fee => fee.as_u64().unwrap_or(0) as u128,
+    };
+    let events = data["event"]
+        .as_array()
+        .map(|events| {
+            events
+                .iter()
+                .filter_map(|e| {
+                    Some(format!(
+                        "{}.{}",
+                        e["module_id"].as_str()?,
+                        e["event_id"].as_str()?
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(Enrichment {
+        tx_hash: tx_hash.to_string(),
+        block_number: data["block_num"].as_u64().context("Missing block_num")?,
+        block_timestamp: data["block_timestamp"]
+            .as_i64()
+            .context("Missing block_timestamp")?,
+        fee,
+        success: data["success"].as_bool().unwrap_or(false),
+        events,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0x6c5f1b3e1cfa0ad7a3c9a0b5d1c6b8e0e9cb2f29a5a1e1f0b4f4c6c5d3b2a1f0";
+
+    #[test]
+    fn test_parse_extrinsic() {
+        let response = json!({
+            "code": 0,
+            "message": "Success",
+            "data": {
+                "block_num": 7654321,
+                "block_timestamp": 1700000000,
+                "extrinsic_index": "7654321-2",
+                "call_module": "statetriemigration",
+                "call_module_function": "continue_migrate",
+                "fee": "0",
+                "success": true,
+                "event": [
+                    { "module_id": "statetriemigration", "event_id": "Migrated" },
+                    { "module_id": "system", "event_id": "ExtrinsicSuccess" }
+                ]
+            }
+        });
+        assert_eq!(
+            parse_extrinsic(HASH, &response).unwrap(),
+            Some(Enrichment {
+                tx_hash: HASH.to_string(),
+                block_number: 7654321,
+                block_timestamp: 1700000000,
+                fee: 0,
+                success: true,
+                events: vec![
+                    "statetriemigration.Migrated".to_string(),
+                    "system.ExtrinsicSuccess".to_string()
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_not_found_and_errors() {
+        let not_found = json!({ "code": 10004, "message": "Record Not Found", "data": null });
+        assert_eq!(parse_extrinsic(HASH, &not_found).unwrap(), None);
+        let empty = json!({ "code": 0, "message": "Success", "data": null });
+        assert_eq!(parse_extrinsic(HASH, &empty).unwrap(), None);
+
+        let unauthorized = json!({ "code": 401, "message": "Invalid API Key" });
+        let err = parse_extrinsic(HASH, &unauthorized).unwrap_err();
+        assert!(err.to_string().contains("Invalid API Key"));
+    }
+}
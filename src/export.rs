@@ -0,0 +1,337 @@
+//! Export of the tx history for offline analysis
+//!
+//! Parquet files (`parquet` feature) hold one row per finalized tx and can be
+//! queried directly from DuckDB or Spark, e.g.
+//! `SELECT sponsor, sum(items) FROM 'history.parquet' GROUP BY sponsor`.
+//!
+//! CSV and JSON exports dump the submission journal instead (every broadcast
+//! tx, including failed ones), with the limits of each call and its
+//! finalization latency, for the reports posted to the migration
+//! coordination thread.
+
+use crate::history::HistoryEntry;
+use crate::journal::JournalEntry;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+/// Output format of `history export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Finalized txs from --history-db
+    Parquet,
+    /// Submission journal (--journal-db), one row per tx
+    Csv,
+    /// Submission journal (--journal-db), as an array of objects
+    Json,
+}
+
+impl ExportFormat {
+    /// Whether this format exports the submission journal rather than the history
+    pub fn is_journal(self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::Json)
+    }
+}
+
+/// Write `entries` to `path` in `format`
+pub fn export_history(entries: &[HistoryEntry], format: ExportFormat, path: &Path) -> Result<()> {
+    match format {
+        ExportFormat::Parquet => write_parquet(entries, path),
+        ExportFormat::Csv | ExportFormat::Json => {
+            anyhow::bail!("CSV and JSON export the submission journal, not the history")
+        }
+    }
+}
+
+/// One journaled tx as exported
+#[derive(Debug, Serialize)]
+struct JournalRow<'a> {
+    tx_hash: &'a str,
+    /// RFC 3339 time of the first broadcast
+    submitted_at: String,
+    nonce: u32,
+    item_limit: Option<u32>,
+    size_limit: Option<u32>,
+    attempts: u32,
+    outcome: &'a str,
+    block_hash: Option<&'a str>,
+    events: &'a [String],
+    error: Option<&'a str>,
+    /// Planck, as a decimal string (u128 doesn't survive JSON number parsers)
+    fee: Option<String>,
+    finalization_secs: Option<i64>,
+    /// Signing account (SS58)
+    account: Option<&'a str>,
+}
+
+impl<'a> From<&'a JournalEntry> for JournalRow<'a> {
+    fn from(entry: &'a JournalEntry) -> Self {
+        Self {
+            tx_hash: &entry.tx_hash,
+            submitted_at: chrono::DateTime::from_timestamp(entry.submitted_at, 0)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_else(|| entry.submitted_at.to_string()),
+            nonce: entry.nonce,
+            item_limit: entry.limits.map(|l| l.item),
+            size_limit: entry.limits.map(|l| l.size),
+            attempts: entry.attempts,
+            outcome: &entry.outcome,
+            block_hash: entry.block_hash.as_deref(),
+            events: &entry.events,
+            error: entry.error.as_deref(),
+            fee: entry.fee.map(|fee| fee.to_string()),
+            finalization_secs: entry.finalization_latency(),
+            account: entry.account.as_deref(),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "tx_hash,submitted_at,nonce,item_limit,size_limit,attempts,outcome,\
+    block_hash,events,error,fee,finalization_secs,account";
+
+/// Quote a CSV field if it needs it (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv(rows: &[JournalRow<'_>], out: &mut impl Write) -> Result<()> {
+    fn opt<T: ToString>(value: Option<T>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+    writeln!(out, "{}", CSV_HEADER)?;
+    for row in rows {
+        let fields = [
+            row.tx_hash.to_string(),
+            row.submitted_at.clone(),
+            row.nonce.to_string(),
+            opt(row.item_limit),
+            opt(row.size_limit),
+            row.attempts.to_string(),
+            row.outcome.to_string(),
+            opt(row.block_hash),
+            row.events.join(";"),
+            opt(row.error),
+            opt(row.fee.as_deref()),
+            opt(row.finalization_secs),
+            opt(row.account),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        writeln!(out, "{}", line.join(","))?;
+    }
+    Ok(())
+}
+
+/// Write journal `entries` to `path` as CSV or JSON
+pub fn export_journal(entries: &[JournalEntry], format: ExportFormat, path: &Path) -> Result<()> {
+    let rows: Vec<JournalRow<'_>> = entries.iter().map(JournalRow::from).collect();
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+    match format {
+        ExportFormat::Csv => write_csv(&rows, &mut out)?,
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &rows)?;
+            writeln!(out)?;
+        }
+        ExportFormat::Parquet => {
+            anyhow::bail!("Parquet exports the history, not the submission journal")
+        }
+    }
+    out.flush()
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_entries: &[HistoryEntry], _path: &Path) -> Result<()> {
+    anyhow::bail!("Parquet export requires building with `--features parquet`")
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(entries: &[HistoryEntry], path: &Path) -> Result<()> {
+    use arrow_array::{
+        ArrayRef, Decimal128Array, RecordBatch, StringArray, TimestampSecondArray, UInt64Array,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            false,
+        ),
+        Field::new("instance_id", DataType::Utf8, false),
+        Field::new("chain", DataType::Utf8, false),
+        Field::new("account", DataType::Utf8, false),
+        Field::new("sponsor", DataType::Utf8, true),
+        Field::new("tx_hash", DataType::Utf8, false),
+        Field::new("items", DataType::UInt64, false),
+        Field::new("bytes", DataType::UInt64, false),
+        // Planck; DECIMAL(38, 0) covers any realistic fee
+        Field::new("fee", DataType::Decimal128(38, 0), false),
+    ]));
+
+    let strings = |f: fn(&HistoryEntry) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(entries.iter().map(f)))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            TimestampSecondArray::from_iter_values(entries.iter().map(|e| e.timestamp))
+                .with_timezone("UTC"),
+        ),
+        strings(|e| &e.instance_id),
+        strings(|e| &e.chain),
+        strings(|e| &e.account),
+        Arc::new(StringArray::from_iter(
+            entries.iter().map(|e| e.sponsor.as_deref()),
+        )),
+        strings(|e| &e.tx_hash),
+        Arc::new(UInt64Array::from_iter_values(
+            entries.iter().map(|e| e.items),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            entries.iter().map(|e| e.bytes),
+        )),
+        Arc::new(
+            Decimal128Array::from_iter_values(
+                entries
+                    .iter()
+                    .map(|e| i128::try_from(e.fee).unwrap_or(i128::MAX)),
+            )
+            .with_precision_and_scale(38, 0)?,
+        ),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close().context("Failed to write Parquet file")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::MigrationLimits;
+
+    const ACCOUNT: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn journal_entry(outcome: &str, error: Option<&str>) -> JournalEntry {
+        JournalEntry {
+            tx_hash: "0xabcd".to_string(),
+            submitted_at: 1_700_000_000,
+            updated_at: 1_700_000_030,
+            account: Some(ACCOUNT.to_string()),
+            nonce: 7,
+            limits: Some(MigrationLimits::new(51_200, 1024)),
+            attempts: 1,
+            outcome: outcome.to_string(),
+            finished_at: Some(1_700_000_024),
+            block_hash: (outcome == "finalized").then(|| "0x01".to_string()),
+            events: vec!["Migrated".to_string(), "Halted".to_string()],
+            error: error.map(str::to_string),
+            fee: Some(1_500),
+        }
+    }
+
+    #[test]
+    fn test_csv() {
+        let entries = [
+            journal_entry("finalized", None),
+            journal_entry("failed", Some("Invalid, \"stale\"")),
+        ];
+        let rows: Vec<_> = entries.iter().map(JournalRow::from).collect();
+        let mut out = Vec::new();
+        write_csv(&rows, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].split(',').count(), 13);
+        assert_eq!(
+            lines[1],
+            format!(
+                "0xabcd,2023-11-14T22:13:20+00:00,7,1024,51200,1,finalized,0x01,Migrated;Halted,,1500,24,{}",
+                ACCOUNT
+            )
+        );
+        // Failed txs have no latency; fields with commas or quotes are quoted
+        assert!(lines[2].ends_with(&format!(
+            ",failed,,Migrated;Halted,\"Invalid, \"\"stale\"\"\",1500,,{}",
+            ACCOUNT
+        )));
+    }
+
+    #[test]
+    fn test_json() {
+        let entry = journal_entry("finalized", None);
+        let json = serde_json::to_value(JournalRow::from(&entry)).unwrap();
+        assert_eq!(json["item_limit"], 1024);
+        assert_eq!(json["events"][1], "Halted");
+        assert_eq!(json["fee"], "1500");
+        assert_eq!(json["finalization_secs"], 24);
+        assert!(json["error"].is_null());
+        assert_eq!(json["account"], ACCOUNT);
+    }
+
+    #[cfg(feature = "parquet")]
+    fn entry(sponsor: Option<&str>, items: u64, fee: u128) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 1_700_000_000,
+            instance_id: "5Grwva@0xe143f238".to_string(),
+            chain: "0xe143f238".to_string(),
+            account: "5GrwvaEF".to_string(),
+            sponsor: sponsor.map(str::to_string),
+            tx_hash: "0xabcd".to_string(),
+            items,
+            bytes: items * 32,
+            fee,
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_roundtrip() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let path = std::env::temp_dir().join(format!(
+            "westend-migrate-export-{}.parquet",
+            std::process::id()
+        ));
+        let entries = vec![entry(Some("parity"), 1024, 0), entry(None, 512, 1_500)];
+        export_history(&entries, ExportFormat::Parquet, &path).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 2);
+        let batch = &batches[0];
+        assert_eq!(batch.num_columns(), 9);
+        assert_eq!(batch.column_by_name("sponsor").unwrap().null_count(), 1);
+        let fee = batch
+            .column_by_name("fee")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow_array::Decimal128Array>()
+            .unwrap();
+        assert_eq!(fee.value(1), 1_500);
+    }
+}
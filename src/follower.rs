@@ -0,0 +1,207 @@
+//! Finalized block follower for migration events
+//!
+//! Subscribes to finalized blocks once and publishes, per block, every
+//! `StateTrieMigration.Migrated` event (from any account) with the signer of
+//! the `continue_migrate` call that caused it, plus the pallet's cumulative
+//! migrated size after the block. Consumers such as the network statistics
+//! subscribe to the same stream instead of each following the chain.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use subxt::blocks::Block;
+use subxt::dynamic::At;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+use westend_migration_bot::chain_events::MigrationEvent;
+
+/// Blocks buffered per subscriber before a slow one starts missing blocks
+const CHANNEL_CAPACITY: usize = 64;
+
+/// One `StateTrieMigration.Migrated` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedEvent {
+    pub top: u64,
+    pub child: u64,
+    /// Signer of the migrating extrinsic (`None` for automatic migration)
+    pub signer: Option<String>,
+}
+
+impl MigratedEvent {
+    pub fn items(&self) -> u64 {
+        self.top + self.child
+    }
+}
+
+/// Migration activity of one finalized block
+#[derive(Debug, Clone)]
+pub struct BlockMigrations {
+    pub number: u32,
+    pub migrated: Vec<MigratedEvent>,
+    /// `MigrationProcess.size` after the block (only read when something migrated)
+    pub size: Option<u64>,
+}
+
+/// Signer of an extrinsic from its `MultiAddress` bytes (`Id` variant only)
+pub fn signer(address: &[u8]) -> Option<String> {
+    match address {
+        [0, id @ ..] if id.len() == 32 => {
+            let id: [u8; 32] = id.try_into().ok()?;
+            Some(AccountId32::from(id).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Every `Migrated` event of `block`, attributed to its extrinsic's signer
+pub async fn migrated_events(
+    block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<Vec<MigratedEvent>> {
+    let mut migrated = Vec::new();
+    let events = block.events().await?;
+    let mut indices = Vec::new();
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() != "StateTrieMigration" {
+            continue;
+        }
+        let Some(MigrationEvent::Migrated { top, child, .. }) =
+            MigrationEvent::decode(event.variant_name(), &event.field_values()?)
+        else {
+            continue;
+        };
+        indices.push(match event.phase() {
+            Phase::ApplyExtrinsic(index) => Some(index),
+            _ => None,
+        });
+        migrated.push(MigratedEvent {
+            top,
+            child,
+            signer: None,
+        });
+    }
+    if migrated.is_empty() {
+        return Ok(migrated);
+    }
+
+    // Signers of the block's migration extrinsics, by extrinsic index
+    let mut signers = HashMap::new();
+    for ext in block.extrinsics().await?.iter() {
+        let ext = ext?;
+        if ext.pallet_name()? != "StateTrieMigration" {
+            continue;
+        }
+        if let Some(account) = ext.address_bytes().and_then(signer) {
+            signers.insert(ext.index(), account);
+        }
+    }
+    for (event, index) in migrated.iter_mut().zip(indices) {
+        event.signer = index.and_then(|i| signers.get(&i).cloned());
+    }
+    Ok(migrated)
+}
+
+/// Shared stream of [`BlockMigrations`]
+#[derive(Clone)]
+pub struct Follower {
+    tx: broadcast::Sender<Arc<BlockMigrations>>,
+}
+
+impl Follower {
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BlockMigrations>> {
+        self.tx.subscribe()
+    }
+}
+
+async fn follow(
+    client: &OnlineClient<PolkadotConfig>,
+    tx: &broadcast::Sender<Arc<BlockMigrations>>,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+    let mut blocks = client
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .context("Failed to subscribe to finalized blocks")?;
+    loop {
+        let block = tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            next = blocks.next() => match next {
+                Some(block) => block?,
+                None => anyhow::bail!("Finalized block subscription ended"),
+            },
+        };
+        let migrated = migrated_events(&block).await?;
+        let size = if migrated.is_empty() {
+            None
+        } else {
+            client
+                .storage()
+                .at(block.hash())
+                .fetch(&query)
+                .await?
+                .map(|thunk| thunk.to_value())
+                .transpose()?
+                .and_then(|task| task.at("size").and_then(|v| v.as_u128()))
+                .map(|size| size as u64)
+        };
+        // No subscribers is fine; they may come and go
+        let _ = tx.send(Arc::new(BlockMigrations {
+            number: block.number(),
+            migrated,
+            size,
+        }));
+    }
+}
+
+/// Start following finalized blocks; restarts the subscription after errors
+pub fn spawn(client: OnlineClient<PolkadotConfig>, shutdown: CancellationToken) -> Follower {
+    let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+    let follower = Follower { tx: tx.clone() };
+    tokio::spawn(async move {
+        while !shutdown.is_cancelled() {
+            if let Err(e) = follow(&client, &tx, &shutdown).await {
+                warn!("Block follower: {:#}, resubscribing", e);
+                tokio::select! {
+                    _ = shutdown.cancelled() => {}
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {}
+                }
+            }
+        }
+        debug!("Block follower shutting down");
+    });
+    follower
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signer_from_address() {
+        let mut address = vec![0u8];
+        address.extend([1u8; 32]);
+        assert_eq!(
+            signer(&address),
+            Some(AccountId32::from([1u8; 32]).to_string())
+        );
+        // Non-`Id` MultiAddress variants are not attributed
+        assert_eq!(signer(&[2u8; 33]), None);
+        assert_eq!(signer(&[0u8; 20]), None);
+    }
+
+    #[test]
+    fn test_event_items() {
+        let event = MigratedEvent {
+            top: 1000,
+            child: 24,
+            signer: None,
+        };
+        assert_eq!(event.items(), 1024);
+    }
+}
@@ -0,0 +1,131 @@
+//! Fork detection and the chain-read basis
+//!
+//! By default storage reads and nonces follow the best block, like the
+//! TypeScript bot. Each loop iteration the best head is sampled and the block
+//! seen at the previous sample's height is compared with the canonical one:
+//! if it was replaced, the best chain diverged from what became final. When
+//! that happens in a large enough share of recent samples the bot switches
+//! all reads and nonce lookups to the finalized head for the rest of the
+//! session, trading a few blocks of latency for witnesses and nonces that
+//! cannot be reorged away.
+
+use std::collections::VecDeque;
+use subxt::utils::H256;
+
+pub use westend_migration_bot::ReadBasis;
+
+/// Samples considered when judging whether forks are persistent
+const FORK_WINDOW: usize = 10;
+
+/// Diverged samples in the window that trigger the switch to finalized reads
+const FORK_SWITCH_COUNT: usize = 3;
+
+/// Tracks best-head divergence over a sliding window of samples
+#[derive(Debug, Default)]
+pub struct ForkMonitor {
+    /// Whether each recent sample saw the previous best block replaced
+    samples: VecDeque<bool>,
+    /// Best head (number, hash) at the last sample
+    last_best: Option<(u32, H256)>,
+    basis: ReadBasis,
+}
+
+impl ForkMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Best head seen at the last sample, to be checked against the canonical chain
+    pub fn last_best(&self) -> Option<(u32, H256)> {
+        self.last_best
+    }
+
+    pub fn basis(&self) -> ReadBasis {
+        self.basis
+    }
+
+    /// Share of recent samples in which the best chain diverged
+    pub fn divergence_ratio(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let diverged = self.samples.iter().filter(|&&d| d).count();
+        diverged as f64 / self.samples.len() as f64
+    }
+
+    /// Record a sample of the best head and whether the previous best block
+    /// was reorged out. Returns true when this sample switches the basis to
+    /// finalized (the switch is one-way for the session).
+    pub fn record(&mut self, best: (u32, H256), diverged: bool) -> bool {
+        self.last_best = Some(best);
+        if self.samples.len() == FORK_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(diverged);
+
+        let persistent = self.samples.iter().filter(|&&d| d).count() >= FORK_SWITCH_COUNT;
+        if persistent && self.basis == ReadBasis::Best {
+            self.basis = ReadBasis::Finalized;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(n: u32) -> (u32, H256) {
+        (n, H256::repeat_byte(n as u8))
+    }
+
+    #[test]
+    fn test_starts_on_best_basis() {
+        let monitor = ForkMonitor::new();
+        assert_eq!(monitor.basis(), ReadBasis::Best);
+        assert_eq!(monitor.last_best(), None);
+        assert_eq!(monitor.divergence_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_isolated_forks_do_not_switch() {
+        let mut monitor = ForkMonitor::new();
+        for n in 0..30 {
+            // One reorg every 5 samples stays below the threshold
+            assert!(!monitor.record(head(n), n % 5 == 0));
+        }
+        assert_eq!(monitor.basis(), ReadBasis::Best);
+        assert!((monitor.divergence_ratio() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_persistent_forks_switch_once() {
+        let mut monitor = ForkMonitor::new();
+        assert!(!monitor.record(head(1), true));
+        assert!(!monitor.record(head(2), false));
+        assert!(!monitor.record(head(3), true));
+        assert!(monitor.record(head(4), true));
+        assert_eq!(monitor.basis(), ReadBasis::Finalized);
+
+        // Further forks don't report another switch, and calm doesn't switch back
+        assert!(!monitor.record(head(5), true));
+        for n in 6..20 {
+            assert!(!monitor.record(head(n), false));
+        }
+        assert_eq!(monitor.basis(), ReadBasis::Finalized);
+        assert_eq!(monitor.last_best(), Some(head(19)));
+    }
+
+    #[test]
+    fn test_old_forks_leave_the_window() {
+        let mut monitor = ForkMonitor::new();
+        monitor.record(head(1), true);
+        monitor.record(head(2), true);
+        for n in 3..(3 + FORK_WINDOW as u32) {
+            monitor.record(head(n), false);
+        }
+        assert!(!monitor.record(head(100), true));
+        assert_eq!(monitor.basis(), ReadBasis::Best);
+    }
+}
@@ -0,0 +1,308 @@
+//! HTTP health endpoints for container orchestration
+//!
+//! `GET /healthz` (liveness) fails once the run loop has made no progress for
+//! the stale threshold, so Kubernetes/docker-compose restarts a stuck bot.
+//! `GET /readyz` (readiness) succeeds once the bot is connected and has read
+//! the migration status. Both return the current [`BotSnapshot`] as JSON.
+//! `GET /status` returns it unconditionally, so `ctl status` can ask a running
+//! bot what it is doing without a second, read-only invocation.
+//! `GET /ack/<token>` acknowledges a critical notification (see `notify`).
+//! `GET /approve/<token>` and `/reject/<token>` decide a tx held in the
+//! approval queue (see `approval`).
+//!
+//! The server is a minimal HTTP/1.1 responder on a tokio listener; it only
+//! answers these paths and closes every connection.
+
+use crate::{approval, notify};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+use westend_migration_bot::{BotSnapshot, BotState, ReadBasis};
+
+/// Largest request head read before answering
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Shared, updatable health state
+pub struct Health {
+    snapshot: RwLock<BotSnapshot>,
+    stale_after: Duration,
+}
+
+impl Health {
+    pub fn new(stale_after: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            snapshot: RwLock::new(BotSnapshot {
+                connected: false,
+                started_at: now,
+                last_activity: now,
+                last_success: None,
+                consecutive_errors: 0,
+                paused: false,
+                read_basis: ReadBasis::Best,
+                status: None,
+                state: BotState::Idle,
+                state_since: None,
+            }),
+            stale_after,
+        }
+    }
+
+    /// Apply `f` to the snapshot and mark the loop as active
+    pub fn update(&self, f: impl FnOnce(&mut BotSnapshot)) {
+        if let Ok(mut snapshot) = self.snapshot.write() {
+            f(&mut snapshot);
+            snapshot.last_activity = Utc::now();
+        }
+    }
+
+    pub fn snapshot(&self) -> Option<BotSnapshot> {
+        self.snapshot.read().ok().map(|s| s.clone())
+    }
+}
+
+/// Status code and JSON body for a request path
+fn respond(
+    path: &str,
+    snapshot: &BotSnapshot,
+    stale_after: Duration,
+    now: DateTime<Utc>,
+) -> (u16, String) {
+    let idle = (now - snapshot.last_activity).to_std().unwrap_or_default();
+    let (ok, reason) = match path {
+        "/healthz" if idle > stale_after => {
+            (false, Some(format!("no activity for {}s", idle.as_secs())))
+        }
+        "/healthz" => (true, None),
+        "/readyz" if !snapshot.connected => (false, Some("not connected".to_string())),
+        "/readyz" if snapshot.status.is_none() => {
+            (false, Some("migration status not read yet".to_string()))
+        }
+        "/readyz" => (true, None),
+        // The running bot's state for `ctl status`, whatever its health
+        "/status" => (true, None),
+        _ => return (404, r#"{"error":"not found"}"#.to_string()),
+    };
+
+    let mut body = serde_json::to_value(snapshot).unwrap_or_default();
+    body["ok"] = ok.into();
+    if let Some(reason) = reason {
+        body["reason"] = reason.into();
+    }
+    (if ok { 200 } else { 503 }, body.to_string())
+}
+
+/// Acknowledge a critical notification by its token
+fn acknowledge(token: &str) -> (u16, String) {
+    match notify::acknowledge(token) {
+        Some(summary) => (
+            200,
+            serde_json::json!({ "ok": true, "acknowledged": summary }).to_string(),
+        ),
+        None => (
+            404,
+            r#"{"error":"unknown or already acknowledged token"}"#.to_string(),
+        ),
+    }
+}
+
+/// Approve or reject the queued tx holding `token`
+fn decide(token: &str, approve: bool) -> (u16, String) {
+    if approval::decide(token, approve) {
+        let decision = if approve { "approved" } else { "rejected" };
+        (
+            200,
+            serde_json::json!({ "ok": true, "decision": decision }).to_string(),
+        )
+    } else {
+        (
+            404,
+            r#"{"error":"unknown or already decided token"}"#.to_string(),
+        )
+    }
+}
+
+/// Read a running bot's snapshot from its health server (`ctl status`)
+pub async fn fetch_status(addr: SocketAddr) -> Result<BotSnapshot> {
+    let url = format!("http://{}/status", addr);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "No bot answering on {} (is it running with --health-addr?)",
+                addr
+            )
+        })?;
+    response
+        .error_for_status()?
+        .json()
+        .await
+        .with_context(|| format!("Unexpected response from {}", url))
+}
+
+/// Bind `addr` and serve the health endpoints until `shutdown` is cancelled
+pub async fn serve(
+    addr: SocketAddr,
+    health: Arc<Health>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind health endpoint on {}", addr))?;
+    info!("Health endpoints on http://{}/healthz and /readyz", addr);
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        debug!("Health endpoint accept failed: {}", e);
+                        continue;
+                    }
+                },
+            };
+
+            let health = health.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; MAX_REQUEST_BYTES];
+                let mut len = 0;
+                while len < buf.len() {
+                    match stream.read(&mut buf[len..]).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => len += n,
+                    }
+                    if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+
+                let head = String::from_utf8_lossy(&buf[..len]);
+                let path = head.split_whitespace().nth(1).unwrap_or("/");
+                let (code, body) = if let Some(token) = path.strip_prefix("/ack/") {
+                    acknowledge(token)
+                } else if let Some(token) = path.strip_prefix("/approve/") {
+                    decide(token, true)
+                } else if let Some(token) = path.strip_prefix("/reject/") {
+                    decide(token, false)
+                } else {
+                    match health.snapshot() {
+                        Some(snapshot) => respond(path, &snapshot, health.stale_after, Utc::now()),
+                        None => (500, r#"{"error":"health state unavailable"}"#.to_string()),
+                    }
+                };
+                debug!("Health {} {} -> {}", peer, path, code);
+
+                let reason = match code {
+                    200 => "OK",
+                    404 => "Not Found",
+                    503 => "Service Unavailable",
+                    _ => "Internal Server Error",
+                };
+                let response = format!(
+                    "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    code,
+                    reason,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::MigrationStatus;
+
+    fn snapshot(
+        connected: bool,
+        with_status: bool,
+        idle_secs: i64,
+    ) -> (BotSnapshot, DateTime<Utc>) {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let snapshot = BotSnapshot {
+            connected,
+            started_at: now - chrono::Duration::hours(1),
+            last_activity: now - chrono::Duration::seconds(idle_secs),
+            last_success: None,
+            consecutive_errors: 2,
+            paused: false,
+            read_basis: ReadBasis::Finalized,
+            status: with_status.then_some(MigrationStatus {
+                top_complete: false,
+                child_complete: false,
+                size: 100,
+                top_items: 10,
+                child_items: 0,
+                ..Default::default()
+            }),
+            state: BotState::AwaitingFinality,
+            state_since: Some(now - chrono::Duration::seconds(12)),
+        };
+        (snapshot, now)
+    }
+
+    #[test]
+    fn test_healthz_stale() {
+        let stale_after = Duration::from_secs(600);
+        let (s, now) = snapshot(true, true, 30);
+        let (code, body) = respond("/healthz", &s, stale_after, now);
+        assert_eq!(code, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["state"], "awaiting_finality");
+        assert_eq!(json["consecutive_errors"], 2);
+        assert_eq!(json["status"]["top_items"], 10);
+        assert_eq!(json["read_basis"], "finalized");
+
+        let (s, now) = snapshot(true, true, 601);
+        let (code, body) = respond("/healthz", &s, stale_after, now);
+        assert_eq!(code, 503);
+        assert!(body.contains("no activity for 601s"));
+    }
+
+    #[test]
+    fn test_readyz() {
+        let stale_after = Duration::from_secs(600);
+        let (s, now) = snapshot(false, false, 0);
+        assert_eq!(respond("/readyz", &s, stale_after, now).0, 503);
+        let (s, now) = snapshot(true, false, 0);
+        assert_eq!(respond("/readyz", &s, stale_after, now).0, 503);
+        let (s, now) = snapshot(true, true, 0);
+        assert_eq!(respond("/readyz", &s, stale_after, now).0, 200);
+    }
+
+    #[test]
+    fn test_status_always_answers() {
+        // Stale and not connected, yet /status still reports the snapshot
+        let (s, now) = snapshot(false, false, 3600);
+        let (code, body) = respond("/status", &s, Duration::from_secs(600), now);
+        assert_eq!(code, 200);
+        let parsed: BotSnapshot = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.state, BotState::AwaitingFinality);
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let (s, now) = snapshot(true, true, 0);
+        assert_eq!(
+            respond("/metrics", &s, Duration::from_secs(600), now).0,
+            404
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Periodic "bot is alive" log line
+//!
+//! A [`HeartbeatProvider`] produces the message logged every heartbeat
+//! interval (prefixed with 💓, which `run_remote.sh` turns into a desktop
+//! notification). The default reports chain and migration stats; `offline`
+//! reports uptime and session progress from in-process state only, with no
+//! network calls at all. The original dad-joke heartbeat is kept as an
+//! opt-in mode.
+
+use crate::stats::SessionStats;
+use crate::utils::{format_duration, parse_migration_status, MigrationStatus};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+/// Heartbeat message source selected with `--heartbeat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HeartbeatMode {
+    /// Finalized height, peers, nonce and migration progress
+    Stats,
+    /// Uptime and session progress, without any network calls
+    Offline,
+    /// A random dad joke
+    Joke,
+    /// No heartbeat
+    Off,
+}
+
+/// Produces the message for one heartbeat (`None` skips the beat)
+pub trait HeartbeatProvider: Send + 'static {
+    fn beat(&mut self) -> impl Future<Output = Option<String>> + Send;
+}
+
+/// Spawn the heartbeat task; it stops when `shutdown` is cancelled
+pub fn spawn<P: HeartbeatProvider>(
+    mut provider: P,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        interval.tick().await; // Skip first immediate tick
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    debug!("Heartbeat task shutting down");
+                    break;
+                }
+                _ = interval.tick() => {
+                    if let Some(message) = provider.beat().await {
+                        info!("💓 {}", message);
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Snapshot reported by the stats heartbeat (fields are `None` if their query failed)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChainStats {
+    pub finalized: Option<u32>,
+    pub peers: Option<usize>,
+    pub nonce: Option<u64>,
+    /// (top items, child items, bytes) migrated so far
+    pub progress: Option<(u64, u64, u64)>,
+}
+
+impl ChainStats {
+    pub fn summary(&self) -> String {
+        fn or_unknown<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "?".to_string(), |v| v.to_string())
+        }
+
+        let progress = match self.progress {
+            Some((top, child, bytes)) => {
+                format!(
+                    "migrated {} top / {} child items, {} bytes",
+                    top, child, bytes
+                )
+            }
+            None => "migration progress ?".to_string(),
+        };
+        format!(
+            "finalized #{} | {} peers | nonce {} | {}",
+            or_unknown(self.finalized),
+            or_unknown(self.peers),
+            or_unknown(self.nonce),
+            progress
+        )
+    }
+}
+
+/// Default heartbeat: chain health and migration progress
+pub struct StatsHeartbeat {
+    client: OnlineClient<PolkadotConfig>,
+    rpc: LegacyRpcMethods<PolkadotConfig>,
+    account_id: AccountId32,
+}
+
+impl StatsHeartbeat {
+    pub fn new(
+        client: OnlineClient<PolkadotConfig>,
+        rpc: LegacyRpcMethods<PolkadotConfig>,
+        account_id: AccountId32,
+    ) -> Self {
+        Self {
+            client,
+            rpc,
+            account_id,
+        }
+    }
+
+    async fn finalized(&self) -> Option<u32> {
+        let hash = self.rpc.chain_get_finalized_head().await.ok()?;
+        let header = self.rpc.chain_get_header(Some(hash)).await.ok()??;
+        Some(header.number)
+    }
+
+    async fn progress(&self) -> Option<(u64, u64, u64)> {
+        let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+        let thunk = self
+            .client
+            .storage()
+            .at_latest()
+            .await
+            .ok()?
+            .fetch(&query)
+            .await
+            .ok()??;
+        let status = parse_migration_status(&thunk.to_value().ok()?);
+        Some((status.top_items, status.child_items, status.size))
+    }
+}
+
+impl HeartbeatProvider for StatsHeartbeat {
+    async fn beat(&mut self) -> Option<String> {
+        let stats = ChainStats {
+            finalized: self.finalized().await,
+            peers: self.rpc.system_health().await.ok().map(|h| h.peers),
+            nonce: self
+                .rpc
+                .system_account_next_index(&self.account_id)
+                .await
+                .ok(),
+            progress: self.progress().await,
+        };
+        Some(stats.summary())
+    }
+}
+
+/// Session progress published by the run loop for the offline heartbeat
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionProgress {
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub items_migrated: u64,
+    pub bytes_migrated: u64,
+    pub last_status: Option<MigrationStatus>,
+}
+
+impl SessionProgress {
+    pub fn new(stats: &SessionStats, last_status: Option<&MigrationStatus>) -> Self {
+        Self {
+            successful_runs: stats.successful_runs,
+            failed_runs: stats.failed_runs,
+            items_migrated: stats.items_migrated,
+            bytes_migrated: stats.bytes_migrated,
+            last_status: last_status.cloned(),
+        }
+    }
+
+    pub fn summary(&self, uptime: Duration) -> String {
+        let mut summary = format!(
+            "up {} | {} ok / {} failed txs | session {} items, {} bytes",
+            format_duration(uptime),
+            self.successful_runs,
+            self.failed_runs,
+            self.items_migrated,
+            self.bytes_migrated
+        );
+        if let Some(status) = &self.last_status {
+            summary.push_str(&format!(
+                " | last status {} top / {} child items",
+                status.top_items, status.child_items
+            ));
+        }
+        summary
+    }
+}
+
+/// Heartbeat from in-process state only: no RPC or HTTP traffic
+pub struct OfflineHeartbeat {
+    started: Instant,
+    progress: watch::Receiver<SessionProgress>,
+}
+
+impl OfflineHeartbeat {
+    pub fn new(progress: watch::Receiver<SessionProgress>) -> Self {
+        Self {
+            started: Instant::now(),
+            progress,
+        }
+    }
+}
+
+impl HeartbeatProvider for OfflineHeartbeat {
+    async fn beat(&mut self) -> Option<String> {
+        Some(self.progress.borrow().summary(self.started.elapsed()))
+    }
+}
+
+/// Opt-in heartbeat: a random dad joke from icanhazdadjoke.com
+pub struct JokeHeartbeat;
+
+impl HeartbeatProvider for JokeHeartbeat {
+    async fn beat(&mut self) -> Option<String> {
+        fetch_dad_joke().await
+    }
+}
+
+/// Fetch a random dad joke from icanhazdadjoke.com
+async fn fetch_dad_joke() -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct JokeResponse {
+        joke: String,
+    }
+
+    let client = reqwest::Client::new();
+    match client
+        .get("https://icanhazdadjoke.com/")
+        .header("Accept", "application/json")
+        .header("User-Agent", "WestendMigrationBot/0.1")
+        .send()
+        .await
+    {
+        Ok(resp) => match resp.json::<JokeResponse>().await {
+            Ok(j) => Some(j.joke),
+            Err(e) => {
+                debug!("Failed to parse dad joke: {:?}", e);
+                None
+            }
+        },
+        Err(e) => {
+            debug!("Failed to fetch dad joke: {:?}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_summary() {
+        let stats = ChainStats {
+            finalized: Some(24_000_123),
+            peers: Some(12),
+            nonce: Some(42),
+            progress: Some((1_500_000, 3, 98_765_432)),
+        };
+        assert_eq!(
+            stats.summary(),
+            "finalized #24000123 | 12 peers | nonce 42 | migrated 1500000 top / 3 child items, 98765432 bytes"
+        );
+    }
+
+    #[test]
+    fn test_offline_summary() {
+        let mut progress = SessionProgress {
+            successful_runs: 3,
+            failed_runs: 1,
+            items_migrated: 3072,
+            bytes_migrated: 150_000,
+            last_status: None,
+        };
+        assert_eq!(
+            progress.summary(Duration::from_secs(3723)),
+            "up 1h 02m 03s | 3 ok / 1 failed txs | session 3072 items, 150000 bytes"
+        );
+
+        progress.last_status = Some(MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size: 0,
+            top_items: 1_500_000,
+            child_items: 3,
+            ..Default::default()
+        });
+        assert!(progress
+            .summary(Duration::from_secs(60))
+            .ends_with("| last status 1500000 top / 3 child items"));
+    }
+
+    #[test]
+    fn test_stats_summary_unknown_fields() {
+        let summary = ChainStats::default().summary();
+        assert_eq!(
+            summary,
+            "finalized #? | ? peers | nonce ? | migration progress ?"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Local history of finalized migration transactions
+//!
+//! Each finalized `continue_migrate` is stored with the items/bytes it
+//! migrated, any fee paid, and the sponsor label of the session, so
+//! contributions can be attributed when several organizations fund
+//! different accounts.
+//!
+//! The database may be shared by several bot processes (different accounts
+//! or chains): rows carry an instance ID and chain, the connection uses WAL
+//! with a busy timeout so concurrent writers wait instead of failing.
+//! Long-running multi-instance deployments can use PostgreSQL instead
+//! (`postgres` feature, `--history-db postgres://...`).
+//!
+//! Block explorer data for recorded txs (`history enrich`) lives in a
+//! separate `enrichment` table keyed by tx hash.
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+use crate::explorer::Enrichment;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a writer waits for another process holding the database lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A finalized migration transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Unix timestamp (seconds) of finalization
+    pub timestamp: i64,
+    /// Bot instance that submitted the tx (shared-database mode)
+    pub instance_id: String,
+    /// Genesis hash of the chain the tx was submitted to
+    pub chain: String,
+    pub account: String,
+    pub sponsor: Option<String>,
+    pub tx_hash: String,
+    pub items: u64,
+    pub bytes: u64,
+    /// Balance decrease attributed to the tx, in planck (0 for free migrations)
+    pub fee: u128,
+}
+
+/// Aggregated contribution of one sponsor
+#[derive(Debug, Clone, PartialEq)]
+pub struct SponsorTotals {
+    pub sponsor: String,
+    pub txs: u64,
+    pub items: u64,
+    pub bytes: u64,
+    pub fees: u128,
+}
+
+/// Aggregated activity of one bot instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceTotals {
+    pub instance_id: String,
+    pub chain: String,
+    pub account: String,
+    pub txs: u64,
+    pub items: u64,
+    pub bytes: u64,
+    pub fees: u128,
+    /// Unix timestamp of the instance's latest tx
+    pub last_seen: i64,
+}
+
+/// History store: SQLite file, or PostgreSQL with the `postgres` feature
+pub struct History {
+    backend: Backend,
+}
+
+enum Backend {
+    Sqlite(Connection),
+    #[cfg(feature = "postgres")]
+    Postgres(Box<postgres::PgHistory>),
+}
+
+impl History {
+    /// Open (or create) the history database at `location`:
+    /// a SQLite file path, or a `postgres://` URL (requires the `postgres` feature)
+    pub fn open(location: &str) -> Result<Self> {
+        if location.starts_with("postgres://") || location.starts_with("postgresql://") {
+            #[cfg(feature = "postgres")]
+            {
+                return Ok(Self {
+                    backend: Backend::Postgres(Box::new(postgres::PgHistory::connect(location)?)),
+                });
+            }
+            #[cfg(not(feature = "postgres"))]
+            anyhow::bail!("PostgreSQL history requires building with `--features postgres`");
+        }
+
+        let path = Path::new(location);
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history database {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// In-memory database (tests)
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets readers and a writer from other processes work concurrently
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS transactions (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp   INTEGER NOT NULL,
+                account     TEXT NOT NULL,
+                sponsor     TEXT,
+                tx_hash     TEXT NOT NULL,
+                items       INTEGER NOT NULL,
+                bytes       INTEGER NOT NULL,
+                fee         TEXT NOT NULL,
+                instance_id TEXT NOT NULL DEFAULT '',
+                chain       TEXT NOT NULL DEFAULT ''
+            );
+            CREATE INDEX IF NOT EXISTS idx_transactions_sponsor ON transactions (sponsor);
+            CREATE TABLE IF NOT EXISTS enrichment (
+                tx_hash         TEXT PRIMARY KEY,
+                block_number    INTEGER NOT NULL,
+                block_timestamp INTEGER NOT NULL,
+                fee             TEXT NOT NULL,
+                success         INTEGER NOT NULL,
+                events          TEXT NOT NULL
+            );",
+        )
+        .context("Failed to initialize history schema")?;
+        Self::add_missing_columns(&conn)?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_transactions_instance ON transactions (instance_id);",
+        )?;
+        Ok(Self {
+            backend: Backend::Sqlite(conn),
+        })
+    }
+
+    /// Upgrade databases created before shared mode existed
+    fn add_missing_columns(conn: &Connection) -> Result<()> {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('transactions')")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for column in ["instance_id", "chain"] {
+            if !columns.iter().any(|c| c == column) {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE transactions ADD COLUMN {} TEXT NOT NULL DEFAULT ''",
+                    column
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append a finalized transaction
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        match &self.backend {
+            Backend::Sqlite(conn) => {
+                // u128 fees don't fit SQLite integers - stored as decimal text
+                conn.execute(
+                    "INSERT INTO transactions
+                        (timestamp, account, sponsor, tx_hash, items, bytes, fee, instance_id, chain)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        entry.timestamp,
+                        entry.account,
+                        entry.sponsor,
+                        entry.tx_hash,
+                        entry.items as i64,
+                        entry.bytes as i64,
+                        entry.fee.to_string(),
+                        entry.instance_id,
+                        entry.chain,
+                    ],
+                )
+                .context("Failed to record history entry")?;
+                Ok(())
+            }
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(pg) => pg.record(entry),
+        }
+    }
+
+    /// All recorded transactions, oldest first
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        match &self.backend {
+            Backend::Sqlite(conn) => {
+                let mut stmt = conn.prepare(
+                    "SELECT timestamp, instance_id, chain, account, sponsor, tx_hash, items, bytes, fee
+                     FROM transactions ORDER BY timestamp, id",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok(HistoryEntry {
+                        timestamp: row.get(0)?,
+                        instance_id: row.get(1)?,
+                        chain: row.get(2)?,
+                        account: row.get(3)?,
+                        sponsor: row.get(4)?,
+                        tx_hash: row.get(5)?,
+                        items: row.get::<_, i64>(6)? as u64,
+                        bytes: row.get::<_, i64>(7)? as u64,
+                        fee: row.get::<_, String>(8)?.parse().unwrap_or(0),
+                    })
+                })?;
+                Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+            }
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(pg) => pg.entries(),
+        }
+    }
+
+    /// Hashes of recorded txs without explorer data yet, oldest first
+    pub fn unenriched(&self, limit: usize) -> Result<Vec<String>> {
+        match &self.backend {
+            Backend::Sqlite(conn) => {
+                let mut stmt = conn.prepare(
+                    "SELECT t.tx_hash FROM transactions t
+                     LEFT JOIN enrichment e ON e.tx_hash = t.tx_hash
+                     WHERE e.tx_hash IS NULL
+                     GROUP BY t.tx_hash ORDER BY MIN(t.timestamp) LIMIT ?1",
+                )?;
+                let rows = stmt.query_map([limit as i64], |row| row.get(0))?;
+                Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+            }
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(pg) => pg.unenriched(limit),
+        }
+    }
+
+    /// Store explorer data for a tx (replacing any earlier lookup)
+    pub fn enrich(&self, enrichment: &Enrichment) -> Result<()> {
+        match &self.backend {
+            Backend::Sqlite(conn) => {
+                conn.execute(
+                    "INSERT OR REPLACE INTO enrichment
+                        (tx_hash, block_number, block_timestamp, fee, success, events)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        enrichment.tx_hash,
+                        enrichment.block_number as i64,
+                        enrichment.block_timestamp,
+                        enrichment.fee.to_string(),
+                        enrichment.success,
+                        enrichment.events.join(","),
+                    ],
+                )
+                .context("Failed to store enrichment")?;
+                Ok(())
+            }
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(pg) => pg.enrich(enrichment),
+        }
+    }
+
+    /// Contribution totals per sponsor (unlabelled runs are grouped as "-")
+    pub fn sponsor_totals(&self) -> Result<Vec<SponsorTotals>> {
+        Ok(sponsor_totals(&self.entries()?))
+    }
+
+    /// Activity totals per instance, across every process sharing this database
+    pub fn instance_totals(&self) -> Result<Vec<InstanceTotals>> {
+        Ok(instance_totals(&self.entries()?))
+    }
+}
+
+/// Group entries by sponsor label
+pub fn sponsor_totals(entries: &[HistoryEntry]) -> Vec<SponsorTotals> {
+    let mut totals: Vec<SponsorTotals> = Vec::new();
+    for e in entries {
+        let sponsor = e.sponsor.clone().unwrap_or_else(|| "-".to_string());
+        match totals.iter_mut().find(|t| t.sponsor == sponsor) {
+            Some(t) => {
+                t.txs += 1;
+                t.items += e.items;
+                t.bytes += e.bytes;
+                t.fees += e.fee;
+            }
+            None => totals.push(SponsorTotals {
+                sponsor,
+                txs: 1,
+                items: e.items,
+                bytes: e.bytes,
+                fees: e.fee,
+            }),
+        }
+    }
+    totals.sort_by(|a, b| a.sponsor.cmp(&b.sponsor));
+    totals
+}
+
+/// Group entries by bot instance
+pub fn instance_totals(entries: &[HistoryEntry]) -> Vec<InstanceTotals> {
+    let mut totals: Vec<InstanceTotals> = Vec::new();
+    for e in entries {
+        match totals.iter_mut().find(|t| t.instance_id == e.instance_id) {
+            Some(t) => {
+                t.txs += 1;
+                t.items += e.items;
+                t.bytes += e.bytes;
+                t.fees += e.fee;
+                t.last_seen = t.last_seen.max(e.timestamp);
+            }
+            None => totals.push(InstanceTotals {
+                instance_id: e.instance_id.clone(),
+                chain: e.chain.clone(),
+                account: e.account.clone(),
+                txs: 1,
+                items: e.items,
+                bytes: e.bytes,
+                fees: e.fee,
+                last_seen: e.timestamp,
+            }),
+        }
+    }
+    totals.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sponsor: Option<&str>, items: u64, bytes: u64, fee: u128) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 1_700_000_000,
+            instance_id: "bot-a".to_string(),
+            chain: "0x67f9".to_string(),
+            account: "5Grw...".to_string(),
+            sponsor: sponsor.map(str::to_string),
+            tx_hash: "0xabc".to_string(),
+            items,
+            bytes,
+            fee,
+        }
+    }
+
+    #[test]
+    fn test_sponsor_totals_grouped() {
+        let history = History::open_in_memory().unwrap();
+        history
+            .record(&entry(Some("parity"), 1024, 50_000, 0))
+            .unwrap();
+        history
+            .record(&entry(Some("parity"), 512, 20_000, 0))
+            .unwrap();
+        history.record(&entry(Some("w3f"), 100, 1_000, 7)).unwrap();
+
+        let totals = history.sponsor_totals().unwrap();
+        assert_eq!(totals.len(), 2);
+        let parity = totals.iter().find(|t| t.sponsor == "parity").unwrap();
+        assert_eq!((parity.txs, parity.items, parity.bytes), (2, 1536, 70_000));
+        let w3f = totals.iter().find(|t| t.sponsor == "w3f").unwrap();
+        assert_eq!(w3f.fees, 7);
+    }
+
+    #[test]
+    fn test_unlabelled_runs_grouped() {
+        let history = History::open_in_memory().unwrap();
+        history.record(&entry(None, 10, 100, 0)).unwrap();
+        let totals = history.sponsor_totals().unwrap();
+        assert_eq!(totals[0].sponsor, "-");
+    }
+
+    #[test]
+    fn test_instance_totals_aggregate_across_instances() {
+        let history = History::open_in_memory().unwrap();
+        history.record(&entry(None, 100, 1_000, 0)).unwrap();
+        let mut other = entry(None, 50, 500, 0);
+        other.instance_id = "bot-b".to_string();
+        other.timestamp = 1_700_000_100;
+        history.record(&other).unwrap();
+        history.record(&other).unwrap();
+
+        let totals = history.instance_totals().unwrap();
+        assert_eq!(totals.len(), 2);
+        let b = totals.iter().find(|t| t.instance_id == "bot-b").unwrap();
+        assert_eq!((b.txs, b.items, b.bytes), (2, 100, 1_000));
+        assert_eq!(b.last_seen, 1_700_000_100);
+    }
+
+    #[test]
+    fn test_legacy_schema_upgraded() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE transactions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, timestamp INTEGER NOT NULL,
+                account TEXT NOT NULL, sponsor TEXT, tx_hash TEXT NOT NULL,
+                items INTEGER NOT NULL, bytes INTEGER NOT NULL, fee TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        let history = History::init(conn).unwrap();
+        history.record(&entry(None, 1, 1, 0)).unwrap();
+        assert_eq!(history.instance_totals().unwrap()[0].instance_id, "bot-a");
+    }
+
+    #[test]
+    fn test_enrichment_marks_tx_done() {
+        let history = History::open_in_memory().unwrap();
+        history.record(&entry(None, 1, 1, 0)).unwrap();
+        let mut later = entry(None, 1, 1, 0);
+        later.tx_hash = "0xdef".to_string();
+        later.timestamp += 60;
+        history.record(&later).unwrap();
+        assert_eq!(history.unenriched(10).unwrap(), vec!["0xabc", "0xdef"]);
+        assert_eq!(history.unenriched(1).unwrap(), vec!["0xabc"]);
+
+        history
+            .enrich(&Enrichment {
+                tx_hash: "0xabc".to_string(),
+                block_number: 42,
+                block_timestamp: 1_700_000_000,
+                fee: 0,
+                success: true,
+                events: vec!["statetriemigration.Migrated".to_string()],
+            })
+            .unwrap();
+        assert_eq!(history.unenriched(10).unwrap(), vec!["0xdef"]);
+    }
+
+    #[test]
+    fn test_large_fee_roundtrip() {
+        let history = History::open_in_memory().unwrap();
+        history.record(&entry(Some("x"), 1, 1, u128::MAX)).unwrap();
+        assert_eq!(history.sponsor_totals().unwrap()[0].fees, u128::MAX);
+    }
+}
@@ -0,0 +1,174 @@
+//! PostgreSQL history backend
+//!
+//! The `postgres` client runs its own small runtime, so calls made from
+//! inside the bot's tokio runtime are wrapped in `block_in_place`.
+//! Connections are unencrypted (`NoTls`): use a local socket, a trusted
+//! network, or a TLS-terminating proxy.
+
+use super::HistoryEntry;
+use crate::explorer::Enrichment;
+use anyhow::{Context, Result};
+use postgres::{Client, NoTls};
+use std::sync::Mutex;
+
+pub struct PgHistory {
+    client: Mutex<Client>,
+}
+
+/// Run blocking database work without stalling the async runtime
+fn blocking<R>(f: impl FnOnce() -> R) -> R {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::task::block_in_place(f)
+    } else {
+        f()
+    }
+}
+
+impl PgHistory {
+    pub fn connect(url: &str) -> Result<Self> {
+        let mut client = blocking(|| Client::connect(url, NoTls))
+            .context("Failed to connect to PostgreSQL history database")?;
+
+        blocking(|| {
+            client.batch_execute(
+                "CREATE TABLE IF NOT EXISTS transactions (
+                    id          BIGSERIAL PRIMARY KEY,
+                    timestamp   BIGINT NOT NULL,
+                    account     TEXT NOT NULL,
+                    sponsor     TEXT,
+                    tx_hash     TEXT NOT NULL,
+                    items       BIGINT NOT NULL,
+                    bytes       BIGINT NOT NULL,
+                    fee         NUMERIC(39, 0) NOT NULL,
+                    instance_id TEXT NOT NULL DEFAULT '',
+                    chain       TEXT NOT NULL DEFAULT ''
+                );
+                CREATE INDEX IF NOT EXISTS idx_transactions_sponsor ON transactions (sponsor);
+                CREATE INDEX IF NOT EXISTS idx_transactions_instance ON transactions (instance_id);
+                CREATE TABLE IF NOT EXISTS enrichment (
+                    tx_hash         TEXT PRIMARY KEY,
+                    block_number    BIGINT NOT NULL,
+                    block_timestamp BIGINT NOT NULL,
+                    fee             NUMERIC(39, 0) NOT NULL,
+                    success         BOOLEAN NOT NULL,
+                    events          TEXT NOT NULL
+                );",
+            )
+        })
+        .context("Failed to initialize PostgreSQL history schema")?;
+
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| anyhow::anyhow!("history client lock poisoned"))?;
+
+        // NUMERIC keeps u128 fees exact; passed as text and cast server-side
+        blocking(|| {
+            client.execute(
+                "INSERT INTO transactions
+                    (timestamp, account, sponsor, tx_hash, items, bytes, fee, instance_id, chain)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7::TEXT::NUMERIC, $8, $9)",
+                &[
+                    &entry.timestamp,
+                    &entry.account,
+                    &entry.sponsor,
+                    &entry.tx_hash,
+                    &(entry.items as i64),
+                    &(entry.bytes as i64),
+                    &entry.fee.to_string(),
+                    &entry.instance_id,
+                    &entry.chain,
+                ],
+            )
+        })
+        .context("Failed to record history entry")?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| anyhow::anyhow!("history client lock poisoned"))?;
+
+        let rows = blocking(|| {
+            client.query(
+                "SELECT timestamp, instance_id, chain, account, sponsor, tx_hash, items, bytes,
+                        fee::TEXT
+                 FROM transactions ORDER BY timestamp, id",
+                &[],
+            )
+        })
+        .context("Failed to read history")?;
+
+        Ok(rows
+            .iter()
+            .map(|row| HistoryEntry {
+                timestamp: row.get(0),
+                instance_id: row.get(1),
+                chain: row.get(2),
+                account: row.get(3),
+                sponsor: row.get(4),
+                tx_hash: row.get(5),
+                items: row.get::<_, i64>(6) as u64,
+                bytes: row.get::<_, i64>(7) as u64,
+                fee: row.get::<_, String>(8).parse().unwrap_or(0),
+            })
+            .collect())
+    }
+    pub fn unenriched(&self, limit: usize) -> Result<Vec<String>> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| anyhow::anyhow!("history client lock poisoned"))?;
+
+        let rows = blocking(|| {
+            client.query(
+                "SELECT t.tx_hash FROM transactions t
+                 LEFT JOIN enrichment e ON e.tx_hash = t.tx_hash
+                 WHERE e.tx_hash IS NULL
+                 GROUP BY t.tx_hash ORDER BY MIN(t.timestamp) LIMIT $1",
+                &[&(limit as i64)],
+            )
+        })
+        .context("Failed to read history")?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    pub fn enrich(&self, enrichment: &Enrichment) -> Result<()> {
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| anyhow::anyhow!("history client lock poisoned"))?;
+
+        blocking(|| {
+            client.execute(
+                "INSERT INTO enrichment
+                    (tx_hash, block_number, block_timestamp, fee, success, events)
+                 VALUES ($1, $2, $3, $4::TEXT::NUMERIC, $5, $6)
+                 ON CONFLICT (tx_hash) DO UPDATE SET
+                    block_number = EXCLUDED.block_number,
+                    block_timestamp = EXCLUDED.block_timestamp,
+                    fee = EXCLUDED.fee,
+                    success = EXCLUDED.success,
+                    events = EXCLUDED.events",
+                &[
+                    &enrichment.tx_hash,
+                    &(enrichment.block_number as i64),
+                    &enrichment.block_timestamp,
+                    &enrichment.fee.to_string(),
+                    &enrichment.success,
+                    &enrichment.events.join(","),
+                ],
+            )
+        })
+        .context("Failed to store enrichment")?;
+        Ok(())
+    }
+}
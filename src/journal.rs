@@ -0,0 +1,366 @@
+//! Journal of submitted transactions (`--journal-db`)
+//!
+//! Unlike the history DB, which only holds finalized migrations with their
+//! attributed progress, the journal has a row for every transaction the bot
+//! broadcast: signing account, nonce and limits at submission, then the finalized block and
+//! its `StateTrieMigration` events, or the error it failed with, and the fee
+//! once the balance check has run. Rows are keyed by tx hash, so a
+//! rebroadcast of the same signed bytes updates its row instead of adding one.
+//! `history export --format csv|json` dumps it for reporting.
+
+use crate::limits::MigrationLimits;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a writer waits for another process holding the database lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a journaled transaction ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Finalized {
+        block_hash: String,
+        /// `StateTrieMigration` event names emitted by the block
+        events: Vec<String>,
+    },
+    Failed(String),
+}
+
+/// One journaled transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub tx_hash: String,
+    /// Unix timestamp (seconds) of the first broadcast
+    pub submitted_at: i64,
+    /// Unix timestamp (seconds) of the last change to the row
+    pub updated_at: i64,
+    /// Signing account (SS58); `None` in rows journaled before it was recorded
+    pub account: Option<String>,
+    pub nonce: u32,
+    pub limits: Option<MigrationLimits>,
+    /// Broadcasts of these bytes (more than one after rebroadcasting)
+    pub attempts: u32,
+    /// `pending`, `finalized` or `failed`
+    pub outcome: String,
+    /// Unix timestamp (seconds) the outcome was recorded
+    pub finished_at: Option<i64>,
+    pub block_hash: Option<String>,
+    pub events: Vec<String>,
+    pub error: Option<String>,
+    /// Balance decrease attributed to the tx, in planck
+    pub fee: Option<u128>,
+}
+
+impl JournalEntry {
+    /// Seconds from first broadcast to finalization
+    pub fn finalization_latency(&self) -> Option<i64> {
+        match (self.outcome.as_str(), self.finished_at) {
+            ("finalized", Some(finished_at)) => Some(finished_at - self.submitted_at),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let submitted = chrono::DateTime::from_timestamp(self.submitted_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| self.submitted_at.to_string());
+        write!(f, "{}", self.tx_hash)?;
+        if let Some(account) = &self.account {
+            write!(f, " from {}", account)?;
+        }
+        write!(f, " nonce {}", self.nonce)?;
+        if let Some(limits) = self.limits {
+            write!(f, " ({})", limits)?;
+        }
+        write!(
+            f,
+            ": {} (submitted {}, {} broadcast(s), updated {}s later)",
+            self.outcome,
+            submitted,
+            self.attempts,
+            self.updated_at - self.submitted_at
+        )?;
+        if let Some(block) = &self.block_hash {
+            write!(f, " in {} [{}]", block, self.events.join(", "))?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, ": {}", error)?;
+        }
+        if let Some(fee) = self.fee {
+            write!(f, ", fee {}", fee)?;
+        }
+        Ok(())
+    }
+}
+
+/// SQLite journal
+pub struct Journal {
+    conn: Connection,
+}
+
+impl Journal {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open journal {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// In-memory journal (tests)
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS submissions (
+                tx_hash      TEXT PRIMARY KEY,
+                submitted_at INTEGER NOT NULL,
+                updated_at   INTEGER NOT NULL,
+                nonce        INTEGER NOT NULL,
+                item_limit   INTEGER,
+                size_limit   INTEGER,
+                attempts     INTEGER NOT NULL DEFAULT 1,
+                outcome      TEXT NOT NULL,
+                block_hash   TEXT,
+                events       TEXT NOT NULL DEFAULT '',
+                error        TEXT,
+                fee          TEXT,
+                finished_at  INTEGER,
+                account      TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_submissions_submitted ON submissions (submitted_at);",
+        )
+        .context("Failed to initialize journal schema")?;
+        Self::add_missing_columns(&conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Upgrade journals created before `finished_at` and `account` were recorded
+    fn add_missing_columns(conn: &Connection) -> Result<()> {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('submissions')")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if !columns.iter().any(|c| c == "finished_at") {
+            conn.execute_batch("ALTER TABLE submissions ADD COLUMN finished_at INTEGER")?;
+        }
+        if !columns.iter().any(|c| c == "account") {
+            conn.execute_batch("ALTER TABLE submissions ADD COLUMN account TEXT")?;
+        }
+        Ok(())
+    }
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    /// Record a broadcast (or a rebroadcast of the same tx)
+    pub fn submitted(
+        &self,
+        tx_hash: &str,
+        account: &str,
+        nonce: u32,
+        limits: Option<MigrationLimits>,
+    ) -> Result<()> {
+        let now = Self::now();
+        self.conn
+            .execute(
+                "INSERT INTO submissions
+                    (tx_hash, submitted_at, updated_at, account, nonce, item_limit, size_limit,
+                     outcome)
+                 VALUES (?1, ?2, ?2, ?3, ?4, ?5, ?6, 'pending')
+                 ON CONFLICT (tx_hash) DO UPDATE SET
+                    updated_at = excluded.updated_at,
+                    attempts = attempts + 1,
+                    outcome = 'pending',
+                    error = NULL,
+                    finished_at = NULL",
+                params![
+                    tx_hash,
+                    now,
+                    account,
+                    nonce,
+                    limits.map(|l| l.item),
+                    limits.map(|l| l.size),
+                ],
+            )
+            .context("Failed to journal submission")?;
+        Ok(())
+    }
+
+    /// Record how a journaled tx ended
+    pub fn finish(&self, tx_hash: &str, outcome: &Outcome) -> Result<()> {
+        let (status, block_hash, events, error) = match outcome {
+            Outcome::Finalized { block_hash, events } => (
+                "finalized",
+                Some(block_hash.as_str()),
+                events.join(","),
+                None,
+            ),
+            Outcome::Failed(error) => ("failed", None, String::new(), Some(error.as_str())),
+        };
+        self.conn
+            .execute(
+                "UPDATE submissions
+                 SET updated_at = ?2, finished_at = ?2, outcome = ?3, block_hash = ?4,
+                     events = ?5, error = ?6
+                 WHERE tx_hash = ?1",
+                params![tx_hash, Self::now(), status, block_hash, events, error],
+            )
+            .context("Failed to journal outcome")?;
+        Ok(())
+    }
+
+    /// Record the fee of a finalized tx
+    pub fn set_fee(&self, tx_hash: &str, fee: u128) -> Result<()> {
+        // u128 fees don't fit SQLite integers - stored as decimal text
+        self.conn
+            .execute(
+                "UPDATE submissions SET fee = ?2, updated_at = ?3 WHERE tx_hash = ?1",
+                params![tx_hash, fee.to_string(), Self::now()],
+            )
+            .context("Failed to journal fee")?;
+        Ok(())
+    }
+
+    fn row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JournalEntry> {
+        let item: Option<u32> = row.get(4)?;
+        let size: Option<u32> = row.get(5)?;
+        let events: String = row.get(9)?;
+        Ok(JournalEntry {
+            tx_hash: row.get(0)?,
+            submitted_at: row.get(1)?,
+            updated_at: row.get(2)?,
+            nonce: row.get(3)?,
+            limits: item
+                .zip(size)
+                .map(|(item, size)| MigrationLimits::new(size, item)),
+            attempts: row.get(6)?,
+            outcome: row.get(7)?,
+            block_hash: row.get(8)?,
+            events: events
+                .split(',')
+                .filter(|e| !e.is_empty())
+                .map(str::to_string)
+                .collect(),
+            error: row.get(10)?,
+            fee: row
+                .get::<_, Option<String>>(11)?
+                .and_then(|fee| fee.parse().ok()),
+            finished_at: row.get(12)?,
+            account: row.get(13)?,
+        })
+    }
+
+    const COLUMNS: &'static str = "tx_hash, submitted_at, updated_at, nonce, item_limit, \
+        size_limit, attempts, outcome, block_hash, events, error, fee, finished_at, account";
+
+    /// One journaled tx
+    #[cfg(test)]
+    pub fn entry(&self, tx_hash: &str) -> Result<Option<JournalEntry>> {
+        let sql = format!(
+            "SELECT {} FROM submissions WHERE tx_hash = ?1",
+            Self::COLUMNS
+        );
+        use rusqlite::OptionalExtension;
+        Ok(self.conn.query_row(&sql, [tx_hash], Self::row).optional()?)
+    }
+
+    /// All journaled txs, oldest first
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        let sql = format!(
+            "SELECT {} FROM submissions ORDER BY submitted_at, rowid",
+            Self::COLUMNS
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([], Self::row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0xabcd";
+    const ACCOUNT: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    #[test]
+    fn test_finalized_lifecycle() {
+        let journal = Journal::open_in_memory().unwrap();
+        let limits = MigrationLimits::new(51_200, 1024);
+        journal.submitted(HASH, ACCOUNT, 7, Some(limits)).unwrap();
+
+        let entry = journal.entry(HASH).unwrap().unwrap();
+        assert_eq!(entry.outcome, "pending");
+        assert_eq!(entry.nonce, 7);
+        assert_eq!(entry.account.as_deref(), Some(ACCOUNT));
+        assert_eq!(entry.limits, Some(limits));
+
+        journal
+            .finish(
+                HASH,
+                &Outcome::Finalized {
+                    block_hash: "0x01".to_string(),
+                    events: vec!["Migrated".to_string()],
+                },
+            )
+            .unwrap();
+        journal.set_fee(HASH, 0).unwrap();
+
+        let entry = journal.entry(HASH).unwrap().unwrap();
+        assert_eq!(entry.outcome, "finalized");
+        assert_eq!(entry.block_hash.as_deref(), Some("0x01"));
+        assert_eq!(entry.events, vec!["Migrated".to_string()]);
+        assert_eq!(entry.fee, Some(0));
+        assert_eq!(entry.error, None);
+        assert!(entry.finalization_latency().is_some());
+        let line = entry.to_string();
+        assert!(line.starts_with(&format!(
+            "0xabcd from {} nonce 7 (items=1024, size=51200): finalized",
+            ACCOUNT
+        )));
+        assert!(line.ends_with("in 0x01 [Migrated], fee 0"));
+    }
+
+    #[test]
+    fn test_rebroadcast_updates_the_same_row() {
+        let journal = Journal::open_in_memory().unwrap();
+        journal
+            .submitted(HASH, ACCOUNT, 7, Some(MigrationLimits::new(100, 10)))
+            .unwrap();
+        journal
+            .finish(HASH, &Outcome::Failed("0 peers".to_string()))
+            .unwrap();
+        let entry = journal.entry(HASH).unwrap().unwrap();
+        assert_eq!(entry.outcome, "failed");
+        assert_eq!(entry.finalization_latency(), None);
+
+        // Rebroadcasts don't know the limits; the original ones are kept
+        journal.submitted(HASH, ACCOUNT, 7, None).unwrap();
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].attempts, 2);
+        assert_eq!(entries[0].outcome, "pending");
+        assert_eq!(entries[0].error, None);
+        assert_eq!(entries[0].finished_at, None);
+        assert_eq!(entries[0].limits, Some(MigrationLimits::new(100, 10)));
+    }
+
+    #[test]
+    fn test_unknown_tx() {
+        let journal = Journal::open_in_memory().unwrap();
+        assert_eq!(journal.entry(HASH).unwrap(), None);
+        // Updating a tx that was never journaled is a no-op
+        journal.set_fee(HASH, 1).unwrap();
+        assert!(journal.entries().unwrap().is_empty());
+    }
+}
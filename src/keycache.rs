@@ -0,0 +1,319 @@
+//! Compressed cache of scanned keys (`remaining --key-cache`)
+//!
+//! A full `remaining --full` walk of a large trie takes hours. With
+//! `--key-cache DIR` every key it reads is kept in `DIR/<block hash>.keys.gz`
+//! together with its value size and the trie version it is stored under at
+//! that block (V1 up to the migration's `LastKey`, V0 after it). A later
+//! `remaining --key-cache DIR` without `--full` counts from the newest cached
+//! scan instead of walking the trie again: the cached keys after the current
+//! `LastKey` are the ones still to migrate, short of keys created since.
+//!
+//! Format, gzip-compressed: `WMKC`, a format version byte, the 32-byte block
+//! hash and the key the scan started after (u16 length + bytes, length
+//! `u16::MAX` for none), then per key its u16 length and bytes, the u32 value
+//! size (`u32::MAX`: unknown) and the u8 trie version. A cache is written
+//! under a `.partial` name and only renamed into place once the scan
+//! completes, so an interrupted scan never leaves a truncated cache behind.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use subxt::utils::H256;
+
+const MAGIC: &[u8; 4] = b"WMKC";
+const FORMAT_VERSION: u8 = 1;
+const EXTENSION: &str = "keys.gz";
+
+/// Length marking "no key" in the header
+const NO_KEY: u16 = u16::MAX;
+
+/// Value size marking "not known"
+const UNKNOWN_SIZE: u32 = u32::MAX;
+
+/// One cached key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRecord {
+    pub key: Vec<u8>,
+    /// Value size in bytes, if it was read
+    pub size: Option<u32>,
+    /// Trie version the value is stored under at the cached block
+    pub version: u8,
+}
+
+fn cache_path(dir: &Path, block: H256) -> PathBuf {
+    dir.join(format!("{}.{}", hex::encode(block.0), EXTENSION))
+}
+
+fn write_key(out: &mut impl Write, key: Option<&[u8]>) -> Result<()> {
+    match key {
+        Some(key) => {
+            let len = u16::try_from(key.len())
+                .ok()
+                .filter(|len| *len != NO_KEY)
+                .context("Key too long to cache")?;
+            out.write_all(&len.to_le_bytes())?;
+            out.write_all(key)?;
+        }
+        None => out.write_all(&NO_KEY.to_le_bytes())?,
+    }
+    Ok(())
+}
+
+/// Writes the keys of one scan
+pub struct KeyCacheWriter {
+    /// Taken by `finish`
+    out: Option<GzEncoder<BufWriter<File>>>,
+    partial: PathBuf,
+    path: PathBuf,
+    from: Option<Vec<u8>>,
+    count: u64,
+}
+
+impl KeyCacheWriter {
+    /// Start the cache of a scan at `block` of the keys after `from`
+    pub fn create(dir: &Path, block: H256, from: Option<&[u8]>) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create key cache {}", dir.display()))?;
+        let path = cache_path(dir, block);
+        let partial = path.with_extension("gz.partial");
+        let file = File::create(&partial)
+            .with_context(|| format!("Failed to create {}", partial.display()))?;
+        let mut out = GzEncoder::new(BufWriter::new(file), Compression::default());
+        out.write_all(MAGIC)?;
+        out.write_all(&[FORMAT_VERSION])?;
+        out.write_all(&block.0)?;
+        write_key(&mut out, from)?;
+        Ok(Self {
+            out: Some(out),
+            partial,
+            path,
+            from: from.map(<[u8]>::to_vec),
+            count: 0,
+        })
+    }
+
+    /// Add a key read by the scan
+    pub fn push(&mut self, key: &[u8], size: Option<u32>) -> Result<()> {
+        // The pallet migrates in key order: up to `from` is already V1
+        let version = match &self.from {
+            Some(from) if key <= from.as_slice() => 1,
+            _ => 0,
+        };
+        let out = self.out.as_mut().context("Key cache already finished")?;
+        write_key(out, Some(key))?;
+        out.write_all(&size.unwrap_or(UNKNOWN_SIZE).to_le_bytes())?;
+        out.write_all(&[version])?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// The scan completed: put the cache in place; the number of keys cached
+    pub fn finish(mut self) -> Result<u64> {
+        let out = self.out.take().context("Key cache already finished")?;
+        out.finish()?.flush()?;
+        std::fs::rename(&self.partial, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(self.count)
+    }
+}
+
+impl Drop for KeyCacheWriter {
+    fn drop(&mut self) {
+        // Unfinished scan (no-op once renamed)
+        let _ = std::fs::remove_file(&self.partial);
+    }
+}
+
+/// A completed scan read back from the cache; iterates its keys in scan order
+pub struct CachedScan {
+    pub block: H256,
+    /// Key the scan started after
+    pub from: Option<Vec<u8>>,
+    input: GzDecoder<BufReader<File>>,
+}
+
+impl CachedScan {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut input = GzDecoder::new(BufReader::new(file));
+        let mut header = [0u8; 5];
+        input
+            .read_exact(&mut header)
+            .with_context(|| format!("{} is not a key cache", path.display()))?;
+        anyhow::ensure!(
+            &header[..4] == MAGIC && header[4] == FORMAT_VERSION,
+            "{} is not a key cache (or of an unsupported version)",
+            path.display()
+        );
+        let mut block = [0u8; 32];
+        input.read_exact(&mut block)?;
+        let from = read_key(&mut input)?;
+        Ok(Self {
+            block: H256(block),
+            from,
+            input,
+        })
+    }
+
+    /// The most recently written scan in `dir`
+    pub fn latest(dir: &Path) -> Result<Option<Self>> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+        };
+        let mut newest = None;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if !path.to_string_lossy().ends_with(&format!(".{}", EXTENSION)) {
+                continue;
+            }
+            let modified = entry.metadata()?.modified()?;
+            if newest.as_ref().is_some_and(|(at, _)| modified <= *at) {
+                continue;
+            }
+            newest = Some((modified, path));
+        }
+        newest.map(|(_, path)| Self::open(&path)).transpose()
+    }
+
+    /// Number of cached keys after `last_key`, or `None` when the scan started
+    /// after it and so misses some of them
+    pub fn count_after(self, last_key: Option<&[u8]>) -> Result<Option<u64>> {
+        if self.from.as_deref() > last_key {
+            return Ok(None);
+        }
+        let mut count = 0;
+        for record in self {
+            if Some(record?.key.as_slice()) > last_key {
+                count += 1;
+            }
+        }
+        Ok(Some(count))
+    }
+
+    fn next_record(&mut self) -> Result<Option<KeyRecord>> {
+        let Some(key) = read_key(&mut self.input)? else {
+            return Ok(None);
+        };
+        let mut size = [0u8; 4];
+        let mut version = [0u8; 1];
+        self.input.read_exact(&mut size)?;
+        self.input.read_exact(&mut version)?;
+        let size = u32::from_le_bytes(size);
+        Ok(Some(KeyRecord {
+            key,
+            size: (size != UNKNOWN_SIZE).then_some(size),
+            version: version[0],
+        }))
+    }
+}
+
+/// A length-prefixed key; `None` at the end of the input or for the "no key" marker
+fn read_key(input: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 2];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u16::from_le_bytes(len);
+    if len == NO_KEY {
+        return Ok(None);
+    }
+    let mut key = vec![0u8; len as usize];
+    input.read_exact(&mut key)?;
+    Ok(Some(key))
+}
+
+impl Iterator for CachedScan {
+    type Item = Result<KeyRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "westend-migrate-keycache-{}-{}",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = cache_dir("round-trip");
+        let block = H256::repeat_byte(7);
+        let mut writer = KeyCacheWriter::create(&dir, block, Some(&[0x26, 0xaa])).unwrap();
+        writer.push(&[0x26, 0xab], Some(40)).unwrap();
+        writer.push(&[0x30; 48], None).unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let cached = CachedScan::latest(&dir).unwrap().unwrap();
+        assert_eq!(cached.block, block);
+        assert_eq!(cached.from, Some(vec![0x26, 0xaa]));
+        let records: Vec<_> = cached.map(Result::unwrap).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                KeyRecord {
+                    key: vec![0x26, 0xab],
+                    size: Some(40),
+                    version: 0,
+                },
+                KeyRecord {
+                    key: vec![0x30; 48],
+                    size: None,
+                    version: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_after() {
+        let dir = cache_dir("count");
+        let mut writer = KeyCacheWriter::create(&dir, H256::zero(), Some(&[0x10])).unwrap();
+        for key in [0x11, 0x20, 0x30] {
+            writer.push(&[key], Some(1)).unwrap();
+        }
+        writer.finish().unwrap();
+        let count = |last_key: Option<&[u8]>| {
+            CachedScan::latest(&dir)
+                .unwrap()
+                .unwrap()
+                .count_after(last_key)
+                .unwrap()
+        };
+        let counts = [count(Some(&[0x10])), count(Some(&[0x20])), count(None)];
+        std::fs::remove_dir_all(&dir).unwrap();
+        // Before the scan's start the cache cannot tell
+        assert_eq!(counts, [Some(3), Some(1), None]);
+    }
+
+    #[test]
+    fn test_unfinished_scan_is_not_cached() {
+        let dir = cache_dir("unfinished");
+        let mut writer = KeyCacheWriter::create(&dir, H256::zero(), None).unwrap();
+        writer.push(&[1], Some(1)).unwrap();
+        drop(writer);
+        let latest = CachedScan::latest(&dir).unwrap();
+        let left = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(latest.is_none());
+        assert_eq!(left, 0);
+    }
+}
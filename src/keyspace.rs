@@ -0,0 +1,745 @@
+//! Remaining top-trie keys from `state_getKeysPaged` sampling
+//!
+//! `MigrationProcess` only counts what has been migrated, and the node's
+//! `state_trieMigrationStatus` needs a full trie scan. Since the pallet walks
+//! the trie in key order, everything after `progress_top`'s `LastKey` is still
+//! unmigrated: this splits that part of the keyspace into equal strata, reads
+//! one page of keys at the start of each and extrapolates the key density
+//! over the stratum. A few dozen pages give a usable percentage in seconds;
+//! strata the page runs past are counted exactly. [`position_percent`] is the
+//! free, rougher figure from `LastKey` alone, and [`StorageNames`] says which
+//! storage item the key belongs to.
+//!
+//! Keys are read through [`KeyScanner`], in batches of at most [`MAX_BATCH`]
+//! that shrink when responses get large, and handed on one at a time (and
+//! optionally streamed to a file), so scanning millions of keys runs in
+//! constant memory. With a [`Checkpoint`] the scan's progress (strata done,
+//! or the last key counted) is saved as it goes, and an interrupted scan
+//! picks up there instead of at the first key.
+
+use crate::keycache::KeyCacheWriter;
+use anyhow::{Context, Result};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sp_crypto_hashing::twox_128;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use subxt::backend::rpc::RpcClient;
+use subxt::{rpc_params, utils::H256, Metadata};
+use tracing::{debug, info, warn};
+use westend_migration_bot::estimate;
+
+pub use westend_migration_bot::process::{key_position, last_top_key, position_percent};
+
+/// Most keys asked for in one `state_getKeysPaged` call (the node's own cap)
+pub const MAX_BATCH: u32 = 1000;
+
+/// Responses larger than this halve the batch size (hex key characters)
+const MAX_RESPONSE_BYTES: usize = 1 << 20;
+
+/// Full scans log their progress every this many keys
+const PROGRESS_EVERY: u64 = 100_000;
+
+/// Full counts save their checkpoint every this many keys (per range)
+const CHECKPOINT_EVERY: u64 = 10_000;
+
+/// Ranges a parallel full count is split into per worker, so a worker done
+/// with a sparse range picks up another
+const RANGES_PER_WORKER: usize = 4;
+
+/// Storage item names by key prefix: `twox128(pallet prefix) ++ twox128(item)`
+pub struct StorageNames {
+    items: HashMap<[u8; 32], String>,
+    pallets: HashMap<[u8; 16], String>,
+}
+
+impl StorageNames {
+    /// From (pallet storage prefix, item name) pairs
+    pub fn new<'a>(entries: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut items = HashMap::new();
+        let mut pallets = HashMap::new();
+        for (pallet, item) in entries {
+            let pallet_hash = twox_128(pallet.as_bytes());
+            let mut key = [0u8; 32];
+            key[..16].copy_from_slice(&pallet_hash);
+            key[16..].copy_from_slice(&twox_128(item.as_bytes()));
+            items.insert(key, format!("{}::{}", pallet, item));
+            pallets.insert(pallet_hash, pallet.to_string());
+        }
+        Self { items, pallets }
+    }
+
+    /// Every storage item declared in the runtime metadata
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        Self::new(metadata.pallets().flat_map(|pallet| {
+            pallet.storage().into_iter().flat_map(|storage| {
+                storage
+                    .entries()
+                    .iter()
+                    .map(move |entry| (storage.prefix(), entry.name()))
+            })
+        }))
+    }
+
+    /// "Pallet::Item" for a storage key, "Pallet::?" when only the pallet is
+    /// known, or the key itself for well-known `:`-prefixed keys like `:code`
+    pub fn resolve(&self, key: &[u8]) -> Option<String> {
+        if key.starts_with(b":") {
+            return Some(String::from_utf8_lossy(key).into_owned());
+        }
+        if let Some(name) = key.get(..32).and_then(|p| self.items.get(p)) {
+            return Some(name.clone());
+        }
+        key.get(..16)
+            .and_then(|p| self.pallets.get(p))
+            .map(|pallet| format!("{}::?", pallet))
+    }
+}
+
+/// Keys read from the start of the stratum `[start, end)`, as they stream in
+struct Stratum {
+    start: u128,
+    end: u128,
+    inside: u64,
+    last: Option<u64>,
+    /// A key past `end` was read: the stratum is counted exactly
+    past_end: bool,
+}
+
+impl Stratum {
+    fn new(start: u128, end: u128) -> Self {
+        Self {
+            start,
+            end,
+            inside: 0,
+            last: None,
+            past_end: false,
+        }
+    }
+
+    /// Take the next key's position; `false` once it lies past the stratum
+    fn push(&mut self, position: u64) -> bool {
+        if position as u128 >= self.end {
+            self.past_end = true;
+            return false;
+        }
+        self.inside += 1;
+        self.last = Some(position);
+        true
+    }
+
+    /// (keys, exact) once reading stopped; `full` = every key asked for was
+    /// read, so more follow
+    fn estimate(&self, full: bool) -> (f64, bool) {
+        // The read ran past the stratum or the end of the trie: nothing was missed
+        if self.past_end || !full {
+            return (self.inside as f64, true);
+        }
+        let Some(last) = self.last else {
+            return (0.0, true);
+        };
+        let covered = (last as u128 + 1).saturating_sub(self.start).max(1);
+        (
+            (self.inside as f64) * (self.end - self.start) as f64 / covered as f64,
+            false,
+        )
+    }
+}
+
+/// Reads keys in order through `state_getKeysPaged` at one block
+pub struct KeyScanner<'a> {
+    rpc: &'a RpcClient,
+    at: H256,
+    batch: u32,
+    /// Every key read is written here, one `0x` hex key per line
+    out: Option<&'a mut dyn Write>,
+    /// ... and cached here, with its value size
+    cache: Option<&'a mut KeyCacheWriter>,
+    read: u64,
+}
+
+impl<'a> KeyScanner<'a> {
+    pub fn new(rpc: &'a RpcClient, at: H256) -> Self {
+        Self {
+            rpc,
+            at,
+            batch: MAX_BATCH,
+            out: None,
+            cache: None,
+            read: 0,
+        }
+    }
+
+    /// Also stream every key read to `out`
+    pub fn with_output(mut self, out: &'a mut dyn Write) -> Self {
+        self.out = Some(out);
+        self
+    }
+
+    /// Also cache every key read, with its value size (one more RPC per batch)
+    pub fn with_cache(mut self, cache: &'a mut KeyCacheWriter) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Another scanner at the same block, without output
+    pub fn fork(&self) -> KeyScanner<'a> {
+        KeyScanner::new(self.rpc, self.at)
+    }
+
+    /// Keys read so far
+    pub fn read(&self) -> u64 {
+        self.read
+    }
+
+    /// Visit up to `limit` keys after `start` (from the first key when
+    /// `None`) until `visit` returns `false`; the number of keys visited
+    pub async fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        limit: u64,
+        mut visit: impl FnMut(&[u8]) -> bool,
+    ) -> Result<u64> {
+        let mut cursor = start.map(<[u8]>::to_vec);
+        let mut visited = 0;
+        while visited < limit {
+            let count = u64::from(self.batch).min(limit - visited) as u32;
+            let keys = self.fetch(cursor.as_deref(), count).await?;
+            let sizes = match self.cache {
+                Some(_) => self.value_sizes(&keys).await?,
+                None => HashMap::new(),
+            };
+            for key in &keys {
+                if let Some(out) = self.out.as_mut() {
+                    writeln!(out, "0x{}", hex::encode(key)).context("Failed to write key")?;
+                }
+                if let Some(cache) = self.cache.as_mut() {
+                    cache.push(key, sizes.get(key).copied())?;
+                }
+                visited += 1;
+                if !visit(key) {
+                    return Ok(visited);
+                }
+            }
+            if (keys.len() as u32) < count {
+                break;
+            }
+            cursor = keys.into_iter().last();
+        }
+        Ok(visited)
+    }
+
+    /// One bounded `state_getKeysPaged` call
+    async fn fetch(&mut self, start: Option<&[u8]>, count: u32) -> Result<Vec<Vec<u8>>> {
+        let start_key = start.map(|key| format!("0x{}", hex::encode(key)));
+        let keys: Vec<String> = self
+            .rpc
+            .request(
+                "state_getKeysPaged",
+                rpc_params!["0x", count, start_key, self.at],
+            )
+            .await
+            .context("state_getKeysPaged failed")?;
+        self.batch = next_batch(self.batch, keys.iter().map(String::len).sum());
+
+        let before = self.read;
+        self.read += keys.len() as u64;
+        if self.read / PROGRESS_EVERY > before / PROGRESS_EVERY {
+            info!("{} keys read...", self.read);
+        }
+        keys.iter()
+            .map(|k| hex::decode(k.trim_start_matches("0x")))
+            .collect::<Result<_, _>>()
+            .context("state_getKeysPaged returned a non-hex key")
+    }
+
+    /// Value sizes of `keys` at the scanned block
+    async fn value_sizes(&self, keys: &[Vec<u8>]) -> Result<HashMap<Vec<u8>, u32>> {
+        let hex_keys: Vec<String> = keys
+            .iter()
+            .map(|key| format!("0x{}", hex::encode(key)))
+            .collect();
+        let change_sets: serde_json::Value = self
+            .rpc
+            .request("state_queryStorageAt", rpc_params![hex_keys, self.at])
+            .await
+            .context("state_queryStorageAt failed")?;
+        let mut sizes = HashMap::new();
+        let changes = change_sets[0]["changes"].as_array().into_iter().flatten();
+        for change in changes {
+            let (Some(key), Some(value)) = (change[0].as_str(), change[1].as_str()) else {
+                continue;
+            };
+            if let Ok(key) = hex::decode(key.trim_start_matches("0x")) {
+                let size = value.trim_start_matches("0x").len() / 2;
+                sizes.insert(key, u32::try_from(size).unwrap_or(u32::MAX - 1));
+            }
+        }
+        Ok(sizes)
+    }
+}
+
+/// Batch size after a response of `bytes`: halved while responses are too
+/// large, grown back once they are small again
+fn next_batch(batch: u32, bytes: usize) -> u32 {
+    if bytes > MAX_RESPONSE_BYTES {
+        debug!("{} byte key page, halving the batch size", bytes);
+        (batch / 2).max(1)
+    } else if bytes < MAX_RESPONSE_BYTES / 4 {
+        (batch * 2).min(MAX_BATCH)
+    } else {
+        batch
+    }
+}
+
+/// `count` equal slices `[start, end)` of the keyspace after position `base`
+fn split(base: u128, count: usize) -> Vec<(u128, u128)> {
+    let count = count.max(1) as u128;
+    let width = ((1u128 << 64) - base).div_ceil(count);
+    (0..count)
+        .map(|i| (base + i * width, (base + (i + 1) * width).min(1 << 64)))
+        .filter(|(start, end)| start < end)
+        .collect()
+}
+
+/// Count every key after `last_key` (exact, but reads them all), resuming
+/// from `checkpoint`. With `parallel` above 1 the keyspace is split into
+/// ranges read that many at a time, each by its own fork of `scanner`.
+pub async fn count_remaining(
+    scanner: &mut KeyScanner<'_>,
+    last_key: Option<&[u8]>,
+    parallel: usize,
+    mut checkpoint: Option<&mut Checkpoint>,
+) -> Result<KeyEstimate> {
+    let base = last_key.map_or(0, key_position) as u128;
+    let saved = checkpoint
+        .as_deref()
+        .map(|c| c.progress.ranges.clone())
+        .unwrap_or_default();
+    // A resumed count keeps the ranges it was started with
+    let wanted = match (saved.len(), parallel) {
+        (0, 0 | 1) => 1,
+        (0, parallel) => parallel * RANGES_PER_WORKER,
+        (saved, _) => saved,
+    };
+    let ranges = split(base, wanted);
+    let saved = if saved.len() == ranges.len() {
+        saved
+    } else {
+        vec![RangeProgress::default(); ranges.len()]
+    };
+    if let Some(c) = checkpoint.as_deref_mut() {
+        c.progress.ranges = saved.clone();
+    }
+    let checkpoint = Mutex::new(checkpoint);
+
+    let mut forks: Vec<_> = (1..ranges.len()).map(|_| scanner.fork()).collect();
+    let counts: Vec<u64> = stream::iter(
+        std::iter::once(scanner)
+            .chain(forks.iter_mut())
+            .zip(ranges)
+            .enumerate(),
+    )
+    .map(|(index, (scanner, (start, end)))| {
+        let start_key = match index {
+            0 => last_key.map(<[u8]>::to_vec),
+            _ => Some((start as u64).to_be_bytes().to_vec()),
+        };
+        count_range(scanner, index, start_key, end, &saved[index], &checkpoint)
+    })
+    .buffer_unordered(parallel.max(1))
+    .try_collect()
+    .await?;
+
+    let remaining = counts.iter().sum();
+    Ok(KeyEstimate {
+        remaining,
+        sampled: remaining as usize,
+        exact: true,
+    })
+}
+
+/// Count the keys from `start` up to position `end`, continuing from `saved`
+async fn count_range(
+    scanner: &mut KeyScanner<'_>,
+    index: usize,
+    start: Option<Vec<u8>>,
+    end: u128,
+    saved: &RangeProgress,
+    checkpoint: &Mutex<Option<&mut Checkpoint>>,
+) -> Result<u64> {
+    if saved.done {
+        return Ok(saved.counted);
+    }
+    let start = match &saved.cursor {
+        Some(cursor) => decode_key(Some(cursor))?,
+        None => start,
+    };
+    let mut counted = saved.counted;
+    scanner
+        .scan(start.as_deref(), u64::MAX, |key| {
+            if key_position(key) as u128 >= end {
+                return false;
+            }
+            counted += 1;
+            if counted.is_multiple_of(CHECKPOINT_EVERY) {
+                let cursor = Some(format!("0x{}", hex::encode(key)));
+                save_range(checkpoint, index, cursor, counted, false);
+            }
+            true
+        })
+        .await?;
+    save_range(checkpoint, index, None, counted, true);
+    Ok(counted)
+}
+
+fn save_range(
+    checkpoint: &Mutex<Option<&mut Checkpoint>>,
+    index: usize,
+    cursor: Option<String>,
+    counted: u64,
+    done: bool,
+) {
+    let mut checkpoint = checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(checkpoint) = checkpoint.as_deref_mut() {
+        checkpoint.progress.ranges[index] = RangeProgress {
+            cursor,
+            counted,
+            done,
+        };
+        checkpoint.save();
+    }
+}
+
+/// Saved progress of one range of a full count
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RangeProgress {
+    /// Last key counted (`0x` hex)
+    pub cursor: Option<String>,
+    pub counted: u64,
+    pub done: bool,
+}
+
+/// Outcome of one sampled stratum
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StratumCount {
+    pub estimate: f64,
+    pub exact: bool,
+    pub read: u64,
+}
+
+/// Saved progress of a `remaining` scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanProgress {
+    /// Block the scan started at
+    pub block: H256,
+    /// Key the scan counts from (`0x` hex; `None`: the first key)
+    pub from: Option<String>,
+    /// `(samples, page_size)` of a sampled estimate, `None` for a full count
+    pub sampling: Option<(u32, u32)>,
+    /// Strata read so far (sampled estimate)
+    #[serde(default)]
+    pub strata: Vec<StratumCount>,
+    /// Ranges of a full count
+    #[serde(default)]
+    pub ranges: Vec<RangeProgress>,
+}
+
+impl ScanProgress {
+    pub fn new(block: H256, from: Option<&[u8]>, sampling: Option<(u32, u32)>) -> Self {
+        Self {
+            block,
+            from: from.map(|key| format!("0x{}", hex::encode(key))),
+            sampling,
+            strata: Vec::new(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Keys a full count has counted so far
+    pub fn counted(&self) -> u64 {
+        self.ranges.iter().map(|range| range.counted).sum()
+    }
+
+    /// The key the scan counts from
+    pub fn start_key(&self) -> Result<Option<Vec<u8>>> {
+        decode_key(self.from.as_deref())
+    }
+}
+
+fn decode_key(key: Option<&str>) -> Result<Option<Vec<u8>>> {
+    key.map(|key| hex::decode(key.trim_start_matches("0x")))
+        .transpose()
+        .context("Checkpoint holds a non-hex key")
+}
+
+/// A scan's progress, kept in a file while it runs
+pub struct Checkpoint {
+    path: PathBuf,
+    pub progress: ScanProgress,
+    /// `progress` was loaded from an interrupted scan
+    pub resumed: bool,
+}
+
+impl Checkpoint {
+    /// Resume the scan saved at `path` if it is the same kind of scan as
+    /// `fresh`, otherwise start `fresh`
+    pub fn open(path: &Path, fresh: ScanProgress) -> Result<Self> {
+        let saved = match std::fs::read_to_string(path) {
+            Ok(json) => Some(
+                serde_json::from_str::<ScanProgress>(&json)
+                    .with_context(|| format!("Invalid checkpoint {}", path.display()))?,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let (progress, resumed) = match saved {
+            Some(saved) if saved.sampling == fresh.sampling => (saved, true),
+            _ => (fresh, false),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            progress,
+            resumed,
+        })
+    }
+
+    fn save(&self) {
+        // Write-then-rename, so an interruption never leaves half a checkpoint
+        let tmp = self.path.with_extension("tmp");
+        let result = serde_json::to_string_pretty(&self.progress)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&tmp, json)?))
+            .and_then(|()| Ok(std::fs::rename(&tmp, &self.path)?));
+        if let Err(e) = result {
+            warn!("Could not save checkpoint {}: {:#}", self.path.display(), e);
+        }
+    }
+
+    /// The scan completed: drop the checkpoint
+    pub fn finish(self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Estimated remaining keys after the last migrated one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEstimate {
+    pub remaining: u64,
+    /// Keys read to produce the estimate
+    pub sampled: usize,
+    /// Every stratum was read to its end, so `remaining` is a count
+    pub exact: bool,
+}
+
+impl KeyEstimate {
+    /// Share of the top trie migrated, given the items `MigrationProcess` counts
+    pub fn percent_complete(&self, migrated: u64) -> f64 {
+        estimate::percent_complete(migrated, self.remaining)
+    }
+}
+
+/// Sample `samples` pages of `page_size` keys after `last_key` with
+/// `scanner`, skipping the strata `checkpoint` already has
+pub async fn estimate_remaining(
+    scanner: &mut KeyScanner<'_>,
+    last_key: Option<&[u8]>,
+    samples: u32,
+    page_size: u32,
+    mut checkpoint: Option<&mut Checkpoint>,
+) -> Result<KeyEstimate> {
+    let samples = samples.max(1) as u128;
+    let base = last_key.map_or(0, key_position) as u128;
+    let width = ((1u128 << 64) - base).div_ceil(samples);
+
+    let mut remaining = 0.0;
+    let mut sampled = 0;
+    let mut exact = true;
+    for i in 0..samples {
+        let start = base + i * width;
+        let end = (start + width).min(1 << 64);
+        if start >= end {
+            break;
+        }
+        let saved = checkpoint
+            .as_deref()
+            .and_then(|c| c.progress.strata.get(i as usize).copied());
+        if let Some(stratum) = saved {
+            debug!("Stratum {}/{}: from checkpoint", i + 1, samples);
+            remaining += stratum.estimate;
+            sampled += stratum.read as usize;
+            exact &= stratum.exact;
+            continue;
+        }
+        let start_key = match i {
+            0 => last_key.map(<[u8]>::to_vec),
+            _ => Some((start as u64).to_be_bytes().to_vec()),
+        };
+        let mut stratum = Stratum::new(start, end);
+        let read = scanner
+            .scan(start_key.as_deref(), page_size.into(), |key| {
+                stratum.push(key_position(key))
+            })
+            .await?;
+
+        let (count, counted) = stratum.estimate(read >= u64::from(page_size));
+        debug!(
+            "Stratum {}/{}: {} keys read, ~{:.0} estimated",
+            i + 1,
+            samples,
+            read,
+            count
+        );
+        remaining += count;
+        sampled += read as usize;
+        exact &= counted;
+        if let Some(checkpoint) = checkpoint.as_deref_mut() {
+            checkpoint.progress.strata.push(StratumCount {
+                estimate: count,
+                exact: counted,
+                read,
+            });
+            checkpoint.save();
+        }
+    }
+
+    Ok(KeyEstimate {
+        remaining: remaining.round() as u64,
+        sampled,
+        exact,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_names() {
+        let names = StorageNames::new([("System", "Account"), ("System", "Number")]);
+        let account =
+            hex::decode("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9")
+                .unwrap();
+        let mut key = account.clone();
+        key.extend_from_slice(&[0xde; 48]);
+        assert_eq!(names.resolve(&key), Some("System::Account".to_string()));
+        assert_eq!(names.resolve(&account), Some("System::Account".to_string()));
+
+        let mut unknown_item = account[..16].to_vec();
+        unknown_item.extend_from_slice(&[0; 16]);
+        assert_eq!(names.resolve(&unknown_item), Some("System::?".to_string()));
+
+        assert_eq!(names.resolve(b":code"), Some(":code".to_string()));
+        assert_eq!(names.resolve(&[0xff; 32]), None);
+        assert_eq!(names.resolve(&account[..8]), None);
+    }
+
+    /// Estimate of a stratum from the positions of the page read at its start
+    fn stratum_estimate(start: u128, end: u128, page: &[u64], full: bool) -> (f64, bool) {
+        let mut stratum = Stratum::new(start, end);
+        for &position in page {
+            if !stratum.push(position) {
+                break;
+            }
+        }
+        stratum.estimate(full)
+    }
+
+    #[test]
+    fn test_stratum_counted_exactly() {
+        // Page ran past the stratum end
+        assert_eq!(stratum_estimate(0, 100, &[10, 20, 150], true), (2.0, true));
+        // Trie ended inside the stratum
+        assert_eq!(stratum_estimate(0, 100, &[10, 20], false), (2.0, true));
+        assert_eq!(stratum_estimate(0, 100, &[], false), (0.0, true));
+    }
+
+    #[test]
+    fn test_stratum_extrapolated() {
+        // 4 keys in the first 10% of the stratum
+        let (count, exact) = stratum_estimate(0, 100, &[2, 4, 6, 9], true);
+        assert!(!exact);
+        assert!((count - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_batch_shrinks_on_large_responses() {
+        assert_eq!(next_batch(MAX_BATCH, MAX_RESPONSE_BYTES + 1), MAX_BATCH / 2);
+        assert_eq!(next_batch(1, MAX_RESPONSE_BYTES * 10), 1);
+        assert_eq!(next_batch(250, MAX_RESPONSE_BYTES / 2), 250);
+        // Grows back, but never past the cap
+        assert_eq!(next_batch(250, 100), 500);
+        assert_eq!(next_batch(MAX_BATCH, 100), MAX_BATCH);
+    }
+
+    #[test]
+    fn test_checkpoint_resumes_same_kind_of_scan() {
+        let path =
+            std::env::temp_dir().join(format!("westend-migrate-scan-{}.json", std::process::id()));
+        let block = H256::repeat_byte(1);
+        let mut checkpoint =
+            Checkpoint::open(&path, ScanProgress::new(block, Some(&[0x26]), None)).unwrap();
+        assert!(!checkpoint.resumed);
+        checkpoint.progress.ranges = vec![
+            RangeProgress {
+                cursor: Some("0x26aa".to_string()),
+                counted: 10_000,
+                done: false,
+            },
+            RangeProgress {
+                cursor: None,
+                counted: 500,
+                done: true,
+            },
+        ];
+        checkpoint.save();
+
+        let fresh = || ScanProgress::new(H256::repeat_byte(2), Some(&[0x30]), None);
+        let resumed = Checkpoint::open(&path, fresh()).unwrap();
+        assert!(resumed.resumed);
+        assert_eq!(resumed.progress.block, block);
+        assert_eq!(resumed.progress.start_key().unwrap(), Some(vec![0x26]));
+        assert_eq!(resumed.progress.counted(), 10_500);
+
+        // A sampled estimate does not pick up a full count's checkpoint
+        let sampled = ScanProgress::new(block, None, Some((32, 1000)));
+        assert!(!Checkpoint::open(&path, sampled).unwrap().resumed);
+
+        resumed.finish().unwrap();
+        assert!(!path.exists());
+        assert!(!Checkpoint::open(&path, fresh()).unwrap().resumed);
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(0, 1), vec![(0, 1 << 64)]);
+        assert_eq!(
+            split(1 << 63, 2),
+            vec![(1 << 63, 3 << 62), (3 << 62, 1 << 64)]
+        );
+        // Never more ranges than positions left
+        assert_eq!(split((1 << 64) - 2, 4).len(), 2);
+    }
+
+    #[test]
+    fn test_percent_complete() {
+        let estimate = KeyEstimate {
+            remaining: 750,
+            sampled: 0,
+            exact: false,
+        };
+        assert!((estimate.percent_complete(250) - 25.0).abs() < 1e-9);
+        let done = KeyEstimate {
+            remaining: 0,
+            ..estimate
+        };
+        assert_eq!(done.percent_complete(0), 100.0);
+    }
+}
@@ -0,0 +1,296 @@
+//! polkadot-js account exports (`--keystore`)
+//!
+//! The JSON file the polkadot-js extension and apps export: `encoded` is,
+//! base64, a scrypt salt and parameters, an xsalsa20-poly1305 nonce and the
+//! sealed PKCS#8 keypair. An export holds the expanded 64-byte secret key
+//! rather than a seed, which subxt-signer 0.37 has no constructor for, so
+//! [`KeystoreSigner`] signs with schnorrkel directly.
+
+use crate::error::MigrationError;
+use crate::signing::TxSigner;
+use anyhow::Result;
+use base64::Engine;
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::XSalsa20Poly1305;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::io::IsTerminal;
+use std::path::Path;
+use subxt::utils::{AccountId32, MultiSignature};
+use zeroize::Zeroizing;
+
+const SALT_LEN: usize = 32;
+/// Salt, then scrypt N, p and r as little-endian u32s
+const SCRYPT_LEN: usize = SALT_LEN + 12;
+const NONCE_LEN: usize = 24;
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+const SECRET_LEN: usize = 64;
+const PUBLIC_LEN: usize = 32;
+/// Largest scrypt cost accepted (polkadot-js uses 2^15)
+const MAX_SCRYPT_LOG_N: u8 = 20;
+/// Signing context of substrate sr25519 signatures
+const SIGNING_CTX: &[u8] = b"substrate";
+
+#[derive(Deserialize)]
+struct KeystoreJson {
+    encoded: String,
+    encoding: Encoding,
+}
+
+#[derive(Deserialize)]
+struct Encoding {
+    content: Vec<String>,
+    #[serde(rename = "type")]
+    kind: Vec<String>,
+}
+
+/// An sr25519 keypair decrypted from a polkadot-js export
+pub struct KeystoreSigner(schnorrkel::Keypair);
+
+impl TxSigner for KeystoreSigner {
+    fn account_id(&self) -> AccountId32 {
+        AccountId32(self.0.public.to_bytes())
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        let context = schnorrkel::signing_context(SIGNING_CTX);
+        let signature = self.0.sign(context.bytes(payload));
+        async move { Ok(MultiSignature::Sr25519(signature.to_bytes())) }.boxed()
+    }
+}
+
+/// Decrypt the export `json` with `password`
+pub fn decrypt_json(json: &str, password: &str) -> Result<KeystoreSigner, String> {
+    let json: KeystoreJson =
+        serde_json::from_str(json).map_err(|e| format!("not a polkadot-js export: {}", e))?;
+    if !json.encoding.content.iter().any(|c| c == "pkcs8")
+        || !json.encoding.content.iter().any(|c| c == "sr25519")
+    {
+        return Err(format!(
+            "unsupported key type {:?} (only sr25519 is)",
+            json.encoding.content
+        ));
+    }
+    if !json.encoding.kind.iter().any(|t| t == "scrypt")
+        || !json.encoding.kind.iter().any(|t| t == "xsalsa20-poly1305")
+    {
+        return Err(format!(
+            "unsupported encryption {:?} (expected scrypt and xsalsa20-poly1305)",
+            json.encoding.kind
+        ));
+    }
+    let encoded = base64::engine::general_purpose::STANDARD
+        .decode(json.encoded.trim())
+        .map_err(|e| format!("`encoded` is not base64: {}", e))?;
+    if encoded.len() < SCRYPT_LEN + NONCE_LEN {
+        return Err("`encoded` is too short".to_string());
+    }
+
+    let word = |at: usize| u32::from_le_bytes(encoded[at..at + 4].try_into().expect("4 bytes"));
+    let (n, p, r) = (word(SALT_LEN), word(SALT_LEN + 4), word(SALT_LEN + 8));
+    if !n.is_power_of_two() || n.trailing_zeros() > MAX_SCRYPT_LOG_N as u32 {
+        return Err(format!("unsupported scrypt cost N={}", n));
+    }
+    let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, 64)
+        .map_err(|e| format!("invalid scrypt parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 64]);
+    scrypt::scrypt(
+        password.as_bytes(),
+        &encoded[..SALT_LEN],
+        &params,
+        key.as_mut(),
+    )
+    .map_err(|e| format!("scrypt failed: {}", e))?;
+
+    let cipher = XSalsa20Poly1305::new_from_slice(&key[..32]).expect("32-byte key");
+    let nonce = &encoded[SCRYPT_LEN..SCRYPT_LEN + NONCE_LEN];
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(nonce.into(), &encoded[SCRYPT_LEN + NONCE_LEN..])
+            .map_err(|_| "wrong password (or corrupted file)".to_string())?,
+    );
+
+    let secret_at = PKCS8_HEADER.len();
+    let divider_at = secret_at + SECRET_LEN;
+    let public_at = divider_at + PKCS8_DIVIDER.len();
+    if plaintext.len() != public_at + PUBLIC_LEN
+        || plaintext[..secret_at] != PKCS8_HEADER
+        || plaintext[divider_at..public_at] != PKCS8_DIVIDER
+    {
+        return Err("decrypted key is not PKCS#8-encoded".to_string());
+    }
+    let secret = schnorrkel::SecretKey::from_ed25519_bytes(&plaintext[secret_at..divider_at])
+        .map_err(|e| format!("invalid secret key: {}", e))?;
+    let public = schnorrkel::PublicKey::from_bytes(&plaintext[public_at..])
+        .map_err(|e| format!("invalid public key: {}", e))?;
+    if secret.to_public() != public {
+        return Err("public key does not match the secret key".to_string());
+    }
+    Ok(KeystoreSigner(schnorrkel::Keypair { secret, public }))
+}
+
+/// Decrypt a polkadot-js account export (`--keystore`) with the password from
+/// `password_file`, or asked for on the terminal
+pub fn read_keystore(
+    path: &Path,
+    password_file: Option<&Path>,
+) -> Result<KeystoreSigner, MigrationError> {
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        MigrationError::InvalidSeed(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let password = match password_file {
+        Some(file) => {
+            let contents = Zeroizing::new(std::fs::read_to_string(file).map_err(|e| {
+                MigrationError::InvalidSeed(format!("Failed to read {}: {}", file.display(), e))
+            })?);
+            // Only the line ending: spaces may be part of the password
+            Zeroizing::new(contents.trim_end_matches(['\r', '\n']).to_string())
+        }
+        None if std::io::stdin().is_terminal() => Zeroizing::new(
+            rpassword::prompt_password(format!("Password for {}: ", path.display())).map_err(
+                |e| MigrationError::InvalidSeed(format!("Failed to read password: {}", e)),
+            )?,
+        ),
+        None => {
+            return Err(MigrationError::InvalidSeed(
+                "--keystore-password-file is required when not on a terminal".to_string(),
+            ))
+        }
+    };
+    decrypt_json(&json, &password).map_err(|e| {
+        MigrationError::InvalidSeed(format!("Failed to decrypt {}: {}", path.display(), e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PASSWORD: &str = "correct horse";
+
+    /// Seal a keypair the way polkadot-js does (with a cheap scrypt cost)
+    fn export(keypair: &schnorrkel::Keypair, password: &str) -> String {
+        let salt = [7u8; SALT_LEN];
+        let (log_n, p, r) = (10u8, 1u32, 8u32);
+        let mut key = [0u8; 64];
+        let params = scrypt::Params::new(log_n, r, p, 64).unwrap();
+        scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key).unwrap();
+        let nonce = [9u8; NONCE_LEN];
+
+        let mut plaintext = PKCS8_HEADER.to_vec();
+        plaintext.extend_from_slice(&keypair.secret.to_ed25519_bytes());
+        plaintext.extend_from_slice(&PKCS8_DIVIDER);
+        plaintext.extend_from_slice(&keypair.public.to_bytes());
+        let sealed = XSalsa20Poly1305::new_from_slice(&key[..32])
+            .unwrap()
+            .encrypt((&nonce).into(), plaintext.as_slice())
+            .unwrap();
+
+        let mut encoded = salt.to_vec();
+        for word in [1u32 << log_n, p, r] {
+            encoded.extend_from_slice(&word.to_le_bytes());
+        }
+        encoded.extend_from_slice(&nonce);
+        encoded.extend_from_slice(&sealed);
+        json!({
+            "address": "",
+            "encoded": base64::engine::general_purpose::STANDARD.encode(encoded),
+            "encoding": {
+                "content": ["pkcs8", "sr25519"],
+                "type": ["scrypt", "xsalsa20-poly1305"],
+                "version": "3"
+            },
+            "meta": { "name": "migration bot" }
+        })
+        .to_string()
+    }
+
+    fn keypair() -> schnorrkel::Keypair {
+        schnorrkel::MiniSecretKey::from_bytes(&[42u8; 32])
+            .unwrap()
+            .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519)
+    }
+
+    fn write(dir: &Path, name: &str, contents: &str) -> std::path::PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("keystore-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_read_keystore_with_good_password() {
+        let dir = temp_dir("good");
+        let keypair = keypair();
+        let keystore = write(&dir, "account.json", &export(&keypair, PASSWORD));
+        // Only the line ending is trimmed from the password file
+        let password = write(&dir, "password", &format!("{}\n", PASSWORD));
+
+        let signer = read_keystore(&keystore, Some(&password)).unwrap();
+        assert_eq!(signer.account_id(), AccountId32(keypair.public.to_bytes()));
+
+        let payload = b"continue_migrate";
+        let Ok(MultiSignature::Sr25519(signature)) =
+            futures_util::FutureExt::now_or_never(signer.sign(payload)).unwrap()
+        else {
+            panic!("expected an sr25519 signature");
+        };
+        let signature = schnorrkel::Signature::from_bytes(&signature).unwrap();
+        let context = schnorrkel::signing_context(SIGNING_CTX);
+        assert!(keypair
+            .public
+            .verify(context.bytes(payload), &signature)
+            .is_ok());
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_read_keystore_with_bad_password() {
+        let dir = temp_dir("bad");
+        let keystore = write(&dir, "account.json", &export(&keypair(), PASSWORD));
+        let password = write(&dir, "password", "correct horse ");
+
+        let Err(MigrationError::InvalidSeed(msg)) = read_keystore(&keystore, Some(&password))
+        else {
+            panic!("a wrong password must not decrypt");
+        };
+        assert!(msg.contains("wrong password"), "{}", msg);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_read_keystore_malformed() {
+        let dir = temp_dir("malformed");
+        let password = write(&dir, "password", PASSWORD);
+
+        let not_json = write(&dir, "not-json.json", "{ encoded: ");
+        assert!(read_keystore(&not_json, Some(&password)).is_err());
+
+        let mut truncated: serde_json::Value =
+            serde_json::from_str(&export(&keypair(), PASSWORD)).unwrap();
+        truncated["encoded"] = json!("AAAA");
+        let truncated = write(&dir, "truncated.json", &truncated.to_string());
+        let Err(MigrationError::InvalidSeed(msg)) = read_keystore(&truncated, Some(&password))
+        else {
+            panic!("a truncated export must not decrypt");
+        };
+        assert!(msg.contains("too short"), "{}", msg);
+
+        let mut ed25519: serde_json::Value =
+            serde_json::from_str(&export(&keypair(), PASSWORD)).unwrap();
+        ed25519["encoding"]["content"] = json!(["pkcs8", "ed25519"]);
+        let ed25519 = write(&dir, "ed25519.json", &ed25519.to_string());
+        assert!(read_keystore(&ed25519, Some(&password)).is_err());
+
+        let missing = dir.join("missing.json");
+        assert!(read_keystore(&missing, Some(&password)).is_err());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
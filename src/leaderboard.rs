@@ -0,0 +1,284 @@
+//! Migration throughput leaderboard
+//!
+//! Scans finalized blocks for `StateTrieMigration.Migrated` events and
+//! attributes the migrated items to the account that signed the
+//! `continue_migrate` call (automatic migrations are grouped as
+//! `(automatic)`). The report is rewritten periodically as markdown, or JSON
+//! when the path ends in `.json`, ready to paste into a forum progress update.
+
+use crate::follower::migrated_events;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Label for items migrated by the pallet's automatic (unsigned) migration
+const AUTOMATIC: &str = "(automatic)";
+
+/// Items migrated by one account
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Contribution {
+    pub account: String,
+    pub txs: u64,
+    pub top_items: u64,
+    pub child_items: u64,
+}
+
+impl Contribution {
+    pub fn items(&self) -> u64 {
+        self.top_items + self.child_items
+    }
+}
+
+/// Contributions over the scanned range of blocks
+#[derive(Debug, Clone, Serialize)]
+pub struct Leaderboard {
+    pub our_account: String,
+    pub from_block: Option<u32>,
+    pub to_block: Option<u32>,
+    pub generated_at: DateTime<Utc>,
+    /// Ranked by items migrated, most first
+    pub accounts: Vec<Contribution>,
+}
+
+impl Leaderboard {
+    pub fn new(our_account: String) -> Self {
+        Self {
+            our_account,
+            from_block: None,
+            to_block: None,
+            generated_at: Utc::now(),
+            accounts: Vec::new(),
+        }
+    }
+
+    /// Extend the scanned range to include block `number`
+    pub fn scanned(&mut self, number: u32) {
+        self.from_block = Some(self.from_block.map_or(number, |n| n.min(number)));
+        self.to_block = Some(self.to_block.map_or(number, |n| n.max(number)));
+    }
+
+    /// Attribute one `Migrated` event
+    pub fn record(&mut self, account: &str, top_items: u64, child_items: u64) {
+        let index = match self.accounts.iter().position(|c| c.account == account) {
+            Some(index) => index,
+            None => {
+                self.accounts.push(Contribution {
+                    account: account.to_string(),
+                    ..Default::default()
+                });
+                self.accounts.len() - 1
+            }
+        };
+        let entry = &mut self.accounts[index];
+        entry.txs += 1;
+        entry.top_items += top_items;
+        entry.child_items += child_items;
+        self.accounts.sort_by_key(|c| std::cmp::Reverse(c.items()));
+    }
+
+    fn total_items(&self) -> u64 {
+        self.accounts.iter().map(Contribution::items).sum()
+    }
+
+    pub fn markdown(&self) -> String {
+        let range = match (self.from_block, self.to_block) {
+            (Some(from), Some(to)) => format!("Blocks #{}–#{}", from, to),
+            _ => "No blocks scanned yet".to_string(),
+        };
+        let mut out = format!(
+            "## State trie migration leaderboard\n\n{} · generated {}\n\n",
+            range,
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        out.push_str("| # | Account | Txs | Top items | Child items | Share |\n");
+        out.push_str("|---|---------|-----|-----------|-------------|-------|\n");
+
+        let total = self.total_items();
+        for (rank, c) in self.accounts.iter().enumerate() {
+            let account = if c.account == self.our_account {
+                format!("**{} (us)**", c.account)
+            } else {
+                c.account.clone()
+            };
+            let share = if total > 0 {
+                c.items() as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.1}% |\n",
+                rank + 1,
+                account,
+                c.txs,
+                c.top_items,
+                c.child_items,
+                share
+            ));
+        }
+        out.push_str(&format!(
+            "\nTotal: {} items in {} txs by {} accounts\n",
+            total,
+            self.accounts.iter().map(|c| c.txs).sum::<u64>(),
+            self.accounts.len()
+        ));
+        out
+    }
+
+    /// Write the report, as JSON if `path` ends in `.json` and markdown otherwise
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.markdown()
+        };
+        // Write then rename so readers never see a half-written report
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        std::fs::write(&tmp, contents)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Record every `Migrated` event of block `number`
+async fn scan_block(
+    client: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    board: &mut Leaderboard,
+    number: u32,
+) -> Result<()> {
+    let hash = rpc
+        .chain_get_block_hash(Some(number.into()))
+        .await?
+        .with_context(|| format!("Block #{} not found", number))?;
+    let block = client.blocks().at(hash).await?;
+    for event in migrated_events(&block).await? {
+        let account = event.signer.as_deref().unwrap_or(AUTOMATIC);
+        board.record(account, event.top, event.child);
+    }
+    board.scanned(number);
+    Ok(())
+}
+
+async fn finalized_number(rpc: &LegacyRpcMethods<PolkadotConfig>) -> Result<u32> {
+    let hash = rpc.chain_get_finalized_head().await?;
+    let header = rpc
+        .chain_get_header(Some(hash))
+        .await?
+        .context("Finalized header not found")?;
+    Ok(header.number)
+}
+
+/// Spawn the leaderboard task: scan the last `lookback` finalized blocks, then
+/// every `interval` scan the blocks finalized since and rewrite the report
+pub fn spawn(
+    client: OnlineClient<PolkadotConfig>,
+    rpc: LegacyRpcMethods<PolkadotConfig>,
+    our_account: String,
+    path: PathBuf,
+    interval: Duration,
+    lookback: u32,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut board = Leaderboard::new(our_account);
+        let mut next_block: Option<u32> = None;
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    debug!("Leaderboard task shutting down");
+                    break;
+                }
+                _ = interval.tick() => {}
+            }
+
+            let finalized = match finalized_number(&rpc).await {
+                Ok(n) => n,
+                Err(e) => {
+                    warn!("Leaderboard: failed to get finalized head: {}", e);
+                    continue;
+                }
+            };
+            let from = next_block.unwrap_or_else(|| finalized.saturating_sub(lookback));
+            for number in from..=finalized {
+                if shutdown.is_cancelled() {
+                    return;
+                }
+                match scan_block(&client, &rpc, &mut board, number).await {
+                    Ok(()) => next_block = Some(number + 1),
+                    Err(e) => {
+                        warn!("Leaderboard: failed to scan block #{}: {}", number, e);
+                        break;
+                    }
+                }
+            }
+
+            board.generated_at = Utc::now();
+            match board.write(&path) {
+                Ok(()) => info!(
+                    "Leaderboard updated: {} ({} accounts)",
+                    path.display(),
+                    board.accounts.len()
+                ),
+                Err(e) => warn!("Leaderboard: {:#}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OURS: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn board() -> Leaderboard {
+        let mut board = Leaderboard::new(OURS.to_string());
+        board.record(OURS, 1000, 0);
+        board.record("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", 500, 20);
+        board.record(OURS, 1000, 0);
+        board.record(AUTOMATIC, 80, 0);
+        for number in 100..=103 {
+            board.scanned(number);
+        }
+        board
+    }
+
+    #[test]
+    fn test_ranked_contributions() {
+        let board = board();
+        assert_eq!(board.from_block, Some(100));
+        assert_eq!(board.to_block, Some(103));
+        let ranked: Vec<_> = board
+            .accounts
+            .iter()
+            .map(|c| (c.account.as_str(), c.txs, c.items()))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (OURS, 2, 2000),
+                ("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", 1, 520),
+                (AUTOMATIC, 1, 80),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = board().markdown();
+        assert!(markdown.contains("Blocks #100–#103"));
+        assert!(markdown.contains(&format!("| 1 | **{} (us)** | 2 | 2000 | 0 | 76.9% |", OURS)));
+        assert!(markdown.contains("Total: 2600 items in 4 txs by 3 accounts"));
+    }
+}
@@ -0,0 +1,375 @@
+//! Ledger hardware wallet signing (`--ledger`)
+//!
+//! Signs with the Polkadot Generic app over USB HID, so the controller key
+//! never leaves the device. Each signer payload goes to the app together
+//! with a metadata proof (fetched from `--ledger-proof-api`) that lets the
+//! device decode the call; the operator checks `continue_migrate` and its
+//! limits on screen and confirms. A tx nobody confirms within
+//! `--ledger-timeout-secs` fails with [`MigrationError::NotSigned`], which the
+//! run loop backs off from like any other failed tx; the stale prompt has to
+//! be rejected on the device before the next one shows.
+//!
+//! The app signs with ed25519 on `m/44'/354'/<account>'/0'/0'`: that account,
+//! not an sr25519 one, is the controller.
+
+use crate::error::MigrationError;
+use crate::signing::TxSigner;
+use anyhow::{Context, Result};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::utils::{AccountId32, MultiSignature};
+use tracing::info;
+
+/// Polkadot Generic app instructions
+const INS_GET_ADDRESS: u8 = 0x01;
+const INS_SIGN: u8 = 0x02;
+const SCHEME_ED25519: u8 = 0x00;
+/// GET_ADDRESS P1: show the address and wait for the operator
+const SHOW_ADDRESS: u8 = 0x01;
+
+/// Sign chunk kinds (P1)
+const CHUNK_INIT: u8 = 0x00;
+const CHUNK_ADD: u8 = 0x01;
+const CHUNK_LAST: u8 = 0x02;
+const CHUNK_SIZE: usize = 250;
+
+/// Polkadot's SLIP-44 coin type, used by the Generic app for every chain
+const COIN_TYPE: u32 = 354;
+const HARDENED: u32 = 0x8000_0000;
+
+/// Status words
+const SW_OK: u16 = 0x9000;
+const SW_REJECTED: u16 = 0x6986;
+const SW_LOCKED: u16 = 0x5515;
+const SW_WRONG_APP: [u16; 3] = [0x6d00, 0x6e00, 0x6e01];
+
+/// Westend's SS58 prefix, for the address the device shows
+const SS58_PREFIX: u16 = 42;
+
+/// Bound on fetching each metadata proof
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One APDU round trip: status word and response data
+trait Exchange: Send + Sync {
+    fn exchange(&self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<(u16, Vec<u8>)>;
+}
+
+/// Ledger device running the Polkadot Generic app
+pub struct LedgerSigner {
+    device: Arc<dyn Exchange>,
+    path: [u8; 20],
+    account: [u8; 32],
+    http: reqwest::Client,
+    proof_api: String,
+    chain: String,
+    timeout: Duration,
+}
+
+impl LedgerSigner {
+    /// Open the first Ledger found and show the address of `account` on it,
+    /// for the operator to check against the controller
+    pub async fn connect(
+        account: u32,
+        proof_api: &str,
+        chain: &str,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let device = open()?;
+        let path = bip44_path(account);
+        let mut data = path.to_vec();
+        data.extend_from_slice(&SS58_PREFIX.to_le_bytes());
+        info!(
+            "Confirm the address of Ledger account {} on the device",
+            account
+        );
+        let chunks = vec![(SHOW_ADDRESS, data)];
+        let response = exchange_all(&device, INS_GET_ADDRESS, chunks, timeout)
+            .await
+            .context("Ledger did not return the account")?;
+        let account = response
+            .get(..32)
+            .and_then(|key| key.try_into().ok())
+            .context("Ledger returned a short public key")?;
+        Ok(Self {
+            device,
+            path,
+            account,
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()?,
+            proof_api: proof_api.to_string(),
+            chain: chain.to_string(),
+            timeout,
+        })
+    }
+
+    /// Proof of the metadata the device needs to decode `payload`
+    async fn metadata_proof(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let response: serde_json::Value = self
+            .http
+            .post(&self.proof_api)
+            .json(&json!({
+                "chain": { "id": self.chain },
+                "txBlob": hex::encode(payload),
+            }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Metadata proof request to {} failed", self.proof_api))?
+            .json()
+            .await
+            .context("Metadata proof API returned invalid JSON")?;
+        let proof = response["txMetadata"]
+            .as_str()
+            .context("Metadata proof API returned no txMetadata")?;
+        hex::decode(proof.trim_start_matches("0x")).context("Metadata proof is not hex")
+    }
+}
+
+impl TxSigner for LedgerSigner {
+    fn account_id(&self) -> AccountId32 {
+        AccountId32(self.account)
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        async move {
+            let proof = self.metadata_proof(payload).await?;
+            info!(
+                "Confirm the tx on the Ledger (within {}s)",
+                self.timeout.as_secs()
+            );
+            let chunks = sign_chunks(&self.path, payload, &proof)?;
+            let response = exchange_all(&self.device, INS_SIGN, chunks, self.timeout).await?;
+            parse_signature(&response)
+        }
+        .boxed()
+    }
+
+    fn confirms_on_device(&self) -> bool {
+        true
+    }
+}
+
+/// `m/44'/354'/<account>'/0'/0'`, little-endian as the app expects
+fn bip44_path(account: u32) -> [u8; 20] {
+    let mut path = [0; 20];
+    for (i, index) in [44, COIN_TYPE, account, 0, 0].into_iter().enumerate() {
+        path[i * 4..i * 4 + 4].copy_from_slice(&(index | HARDENED).to_le_bytes());
+    }
+    path
+}
+
+/// The sign command as `(p1, data)` chunks: the derivation path, then the
+/// payload length, payload and metadata proof
+fn sign_chunks(path: &[u8; 20], payload: &[u8], proof: &[u8]) -> Result<Vec<(u8, Vec<u8>)>> {
+    let len = u16::try_from(payload.len()).context("Signer payload too long for the Ledger")?;
+    let mut blob = len.to_le_bytes().to_vec();
+    blob.extend_from_slice(payload);
+    blob.extend_from_slice(proof);
+
+    let mut chunks = vec![(CHUNK_INIT, path.to_vec())];
+    let count = blob.len().div_ceil(CHUNK_SIZE);
+    for (i, chunk) in blob.chunks(CHUNK_SIZE).enumerate() {
+        let kind = if i + 1 == count {
+            CHUNK_LAST
+        } else {
+            CHUNK_ADD
+        };
+        chunks.push((kind, chunk.to_vec()));
+    }
+    Ok(chunks)
+}
+
+/// Scheme byte and ed25519 signature
+fn parse_signature(response: &[u8]) -> Result<MultiSignature> {
+    match response {
+        [SCHEME_ED25519, signature @ ..] if signature.len() >= 64 => Ok(MultiSignature::Ed25519(
+            signature[..64].try_into().expect("64 bytes"),
+        )),
+        _ => anyhow::bail!(
+            "Ledger returned an unexpected signature: 0x{}",
+            hex::encode(response)
+        ),
+    }
+}
+
+/// Send `chunks` in order on a blocking thread and return the last response;
+/// gives up after `timeout`, which covers waiting for the operator
+async fn exchange_all(
+    device: &Arc<dyn Exchange>,
+    ins: u8,
+    chunks: Vec<(u8, Vec<u8>)>,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    let device = device.clone();
+    let exchange = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let mut response = Vec::new();
+        for (p1, data) in chunks {
+            let (status, data) = device.exchange(ins, p1, SCHEME_ED25519, &data)?;
+            match status {
+                SW_OK => response = data,
+                SW_REJECTED => {
+                    return Err(
+                        MigrationError::NotSigned("rejected on the Ledger".to_string()).into(),
+                    )
+                }
+                status => anyhow::bail!("Ledger error: {}", describe_status(status)),
+            }
+        }
+        Ok(response)
+    });
+    match tokio::time::timeout(timeout, exchange).await {
+        Ok(result) => result.context("Ledger exchange panicked")?,
+        Err(_) => Err(MigrationError::NotSigned(format!(
+            "not confirmed on the Ledger within {}s",
+            timeout.as_secs()
+        ))
+        .into()),
+    }
+}
+
+fn describe_status(status: u16) -> String {
+    match status {
+        SW_LOCKED => "device is locked".to_string(),
+        status if SW_WRONG_APP.contains(&status) => "Polkadot app is not open".to_string(),
+        status => format!("status 0x{:04x}", status),
+    }
+}
+
+#[cfg(feature = "ledger")]
+fn open() -> Result<Arc<dyn Exchange>> {
+    use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+
+    /// Polkadot Generic app
+    const CLA: u8 = 0xf9;
+
+    struct Hid(TransportNativeHID);
+
+    impl Exchange for Hid {
+        fn exchange(&self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<(u16, Vec<u8>)> {
+            let command = ledger_apdu::APDUCommand {
+                cla: CLA,
+                ins,
+                p1,
+                p2,
+                data,
+            };
+            let answer = self
+                .0
+                .exchange(&command)
+                .context("Ledger exchange failed")?;
+            Ok((answer.retcode(), answer.data().to_vec()))
+        }
+    }
+
+    let api = HidApi::new().context("Failed to open USB HID")?;
+    let transport = TransportNativeHID::new(&api).context("No Ledger found")?;
+    Ok(Arc::new(Hid(transport)))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn open() -> Result<Arc<dyn Exchange>> {
+    anyhow::bail!("--ledger requires building with `--features ledger`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records each command and answers with a fixed response
+    struct FakeDevice {
+        commands: Mutex<Vec<(u8, u8, usize)>>,
+        status: u16,
+        response: Vec<u8>,
+        delay: Duration,
+    }
+
+    impl Exchange for FakeDevice {
+        fn exchange(&self, ins: u8, p1: u8, _p2: u8, data: &[u8]) -> Result<(u16, Vec<u8>)> {
+            std::thread::sleep(self.delay);
+            self.commands.lock().unwrap().push((ins, p1, data.len()));
+            Ok((self.status, self.response.clone()))
+        }
+    }
+
+    fn device(status: u16, delay: Duration) -> Arc<FakeDevice> {
+        let mut response = vec![SCHEME_ED25519];
+        response.extend_from_slice(&[7; 64]);
+        Arc::new(FakeDevice {
+            commands: Mutex::new(Vec::new()),
+            status,
+            response,
+            delay,
+        })
+    }
+
+    #[test]
+    fn test_bip44_path() {
+        let path = bip44_path(2);
+        assert_eq!(path[..4], [44, 0, 0, 0x80]);
+        assert_eq!(path[4..8], [0x62, 0x01, 0, 0x80]);
+        assert_eq!(path[8..12], [2, 0, 0, 0x80]);
+        assert_eq!(path[16..], [0, 0, 0, 0x80]);
+    }
+
+    #[test]
+    fn test_sign_chunks() {
+        let path = bip44_path(0);
+        let chunks = sign_chunks(&path, &[1; 200], &[2; 100]).unwrap();
+        let kinds: Vec<(u8, usize)> = chunks.iter().map(|(p1, data)| (*p1, data.len())).collect();
+        assert_eq!(
+            kinds,
+            [(CHUNK_INIT, 20), (CHUNK_ADD, 250), (CHUNK_LAST, 52)]
+        );
+        assert_eq!(chunks[1].1[..3], [200, 0, 1]);
+        assert!(sign_chunks(&path, &vec![0; 70_000], &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exchange_all() {
+        let fake = device(SW_OK, Duration::ZERO);
+        let exchange: Arc<dyn Exchange> = fake.clone();
+        let chunks = sign_chunks(&bip44_path(0), &[1; 100], &[]).unwrap();
+        let response = exchange_all(&exchange, INS_SIGN, chunks, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            parse_signature(&response).unwrap(),
+            MultiSignature::Ed25519([7; 64])
+        );
+        assert_eq!(
+            *fake.commands.lock().unwrap(),
+            [(INS_SIGN, CHUNK_INIT, 20), (INS_SIGN, CHUNK_LAST, 102)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_not_signed() {
+        let chunks = || vec![(CHUNK_INIT, vec![0; 20])];
+
+        let rejected: Arc<dyn Exchange> = device(SW_REJECTED, Duration::ZERO);
+        let err = exchange_all(&rejected, INS_SIGN, chunks(), Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MigrationError>(),
+            Some(MigrationError::NotSigned(_))
+        ));
+
+        let unattended: Arc<dyn Exchange> = device(SW_OK, Duration::from_millis(500));
+        let err = exchange_all(&unattended, INS_SIGN, chunks(), Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not confirmed on the Ledger"));
+
+        let locked: Arc<dyn Exchange> = device(SW_LOCKED, Duration::ZERO);
+        let err = exchange_all(&locked, INS_SIGN, chunks(), Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("locked"));
+    }
+}
@@ -0,0 +1,35 @@
+//! Public types of the Westend state-trie migration bot
+//!
+//! The `westend-migrate` binary serializes these in its JSON outputs; tools
+//! that consume them (dashboards, monitors, replay tooling) can depend on this
+//! crate instead of re-declaring the schema.
+//!
+//! [`client`] is a thin high-level API over one chain connection (status,
+//! estimate, one `continue_migrate`, setting the signed limits) for scripts
+//! that want to drive the migration themselves, also available to Python as
+//! an extension module with `--features python`. [`pool`] holds the bot's
+//! transaction pool tooling (inspection, removal, rebroadcast, waiting out a
+//! pending tx), usable against any Substrate node.
+//!
+//! The monitoring core — [`status`], [`limits`], [`process`] (decoding
+//! `MigrationProcess`), [`estimate`], [`chain_events`] and [`runtime`]
+//! (decoding raw storage with the runtime metadata) — has no networking and
+//! also builds for `wasm32-unknown-unknown` (`--features wasm` exports it to
+//! JavaScript), so browser dashboards run the bot's own logic.
+
+pub mod chain_events;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+pub mod estimate;
+pub mod limits;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pool;
+pub mod process;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+mod python;
+pub mod runtime;
+pub mod status;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use status::{BotSnapshot, BotState, MigrationStatus, ReadBasis, ValidityError};
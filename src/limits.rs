@@ -0,0 +1,205 @@
+//! `MigrationLimits { size, item }`
+//!
+//! The same pair is passed to `continue_migrate`, stored in
+//! `SignedMigrationMaxLimits` and set with `set_signed_max_limits`; this type
+//! keeps its encoding, validation and display in one place instead of loose
+//! `(u32, u32)` tuples whose order is easy to swap.
+
+use scale_value::{At, Value, ValueDef};
+use std::fmt;
+
+/// Working limits when `SignedMigrationMaxLimits` is not set
+const DEFAULT_LIMITS: MigrationLimits = MigrationLimits {
+    size: 409_600,
+    item: 4096,
+};
+
+/// Item and byte limits of one migration step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationLimits {
+    /// Bytes of storage read
+    pub size: u32,
+    /// Keys migrated
+    pub item: u32,
+}
+
+impl MigrationLimits {
+    pub fn new(size: u32, item: u32) -> Self {
+        Self { size, item }
+    }
+
+    /// Encode as the pallet's `MigrationLimits` composite
+    pub fn to_value(self) -> Value<()> {
+        Value::named_composite([
+            ("size", Value::u128(self.size as u128)),
+            ("item", Value::u128(self.item as u128)),
+        ])
+    }
+
+    /// Decode a `MigrationLimits` value read from storage
+    pub fn from_value<T>(value: &Value<T>) -> Result<Self, String> {
+        let field = |name: &str| {
+            value
+                .at(name)
+                .and_then(|v| v.as_u128())
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| format!("MigrationLimits has no u32 `{}` field", name))
+        };
+        Ok(Self {
+            size: field("size")?,
+            item: field("item")?,
+        })
+    }
+
+    /// Decode an `Option<MigrationLimits>` value (`AutoLimits`)
+    pub fn from_option_value<T>(value: &Value<T>) -> Result<Option<Self>, String> {
+        let ValueDef::Variant(variant) = &value.value else {
+            return Err("expected an Option<MigrationLimits>".to_string());
+        };
+        match variant.name.as_str() {
+            "None" => Ok(None),
+            "Some" => {
+                let inner = value.at(0).ok_or("Some(..) without MigrationLimits")?;
+                Self::from_value(inner).map(Some)
+            }
+            other => Err(format!("unexpected Option variant {}", other)),
+        }
+    }
+
+    /// Limits a run works with: configured ones, with each zero replaced by
+    /// half the chain maximum (room for state changes between read and
+    /// submission), or by a default if the chain has none
+    pub fn working(configured: Self, max: Option<Self>) -> Self {
+        let defaults = max.map_or(DEFAULT_LIMITS, Self::halved);
+        Self::new(
+            match configured.size {
+                0 => defaults.size,
+                size => size,
+            },
+            match configured.item {
+                0 => defaults.item,
+                item => item,
+            },
+        )
+    }
+
+    /// `real_size_upper` for `continue_migrate` (twice the size limit, as the TypeScript bot does)
+    pub fn real_size_upper(self) -> u32 {
+        self.size.saturating_mul(2)
+    }
+
+    /// Half of each limit: the default working limits under a chain maximum
+    pub fn halved(self) -> Self {
+        Self::new(self.size / 2, self.item / 2)
+    }
+
+    /// Component-wise maximum
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.size.max(other.size), self.item.max(other.item))
+    }
+
+    /// Whether either limit is above the same limit of `max`
+    pub fn exceeds(self, max: Self) -> bool {
+        self.size > max.size || self.item > max.item
+    }
+
+    /// Both limits non-zero and, if given, within the chain maximum
+    pub fn validate(self, max: Option<Self>) -> Result<(), String> {
+        if self.size == 0 || self.item == 0 {
+            return Err(format!("{} (both must be non-zero)", self));
+        }
+        match max {
+            Some(max) if self.exceeds(max) => {
+                Err(format!("{} exceeds the chain maximum {}", self, max))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for MigrationLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "items={}, size={}", self.item, self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_round_trip() {
+        let limits = MigrationLimits::new(51_200, 1024);
+        assert_eq!(MigrationLimits::from_value(&limits.to_value()), Ok(limits));
+        assert_eq!(limits.to_string(), "items=1024, size=51200");
+    }
+
+    #[test]
+    fn test_from_value_requires_both_fields() {
+        let value = Value::named_composite([("size", Value::u128(10))]);
+        assert!(MigrationLimits::from_value(&value).is_err());
+        let value = Value::named_composite([
+            ("size", Value::u128(u64::MAX as u128)),
+            ("item", Value::u128(1)),
+        ]);
+        assert!(MigrationLimits::from_value(&value).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let max = MigrationLimits::new(102_400, 2048);
+        assert!(MigrationLimits::new(51_200, 1024)
+            .validate(Some(max))
+            .is_ok());
+        assert!(max.validate(Some(max)).is_ok());
+        assert!(MigrationLimits::new(0, 1024).validate(None).is_err());
+        assert!(MigrationLimits::new(51_200, 0).validate(Some(max)).is_err());
+        assert!(MigrationLimits::new(51_200, 4096)
+            .validate(Some(max))
+            .is_err());
+        assert!(MigrationLimits::new(51_200, 4096).validate(None).is_ok());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let limits = MigrationLimits::new(100, 10);
+        assert_eq!(limits.halved(), MigrationLimits::new(50, 5));
+        assert_eq!(
+            limits.max(MigrationLimits::new(50, 20)),
+            MigrationLimits::new(100, 20)
+        );
+        assert!(limits.exceeds(MigrationLimits::new(100, 9)));
+        assert!(!limits.exceeds(limits));
+        assert_eq!(
+            MigrationLimits::new(u32::MAX, 1).real_size_upper(),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn test_working() {
+        let max = MigrationLimits::new(102_400, 2048);
+        assert_eq!(
+            MigrationLimits::working(MigrationLimits::new(0, 0), Some(max)),
+            MigrationLimits::new(51_200, 1024)
+        );
+        assert_eq!(
+            MigrationLimits::working(MigrationLimits::new(0, 100), Some(max)),
+            MigrationLimits::new(51_200, 100)
+        );
+        assert_eq!(
+            MigrationLimits::working(MigrationLimits::new(0, 0), None),
+            DEFAULT_LIMITS
+        );
+    }
+
+    #[test]
+    fn test_option_value() {
+        let limits = MigrationLimits::new(51_200, 1024);
+        let some = Value::unnamed_variant("Some", [limits.to_value()]);
+        assert_eq!(MigrationLimits::from_option_value(&some), Ok(Some(limits)));
+        let none = Value::unnamed_variant("None", []);
+        assert_eq!(MigrationLimits::from_option_value(&none), Ok(None));
+        assert!(MigrationLimits::from_option_value(&limits.to_value()).is_err());
+    }
+}
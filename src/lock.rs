@@ -0,0 +1,244 @@
+//! Single-instance lock
+//!
+//! The lockfile carries an advisory `flock` and a record of its owner: PID,
+//! start time and a heartbeat the owner refreshes while it runs. The kernel
+//! drops the flock when its holder exits, so a crashed bot normally leaves
+//! nothing to clean up. When the flock is still held (a child that inherited
+//! the descriptor, a network filesystem), the record decides: the lock is
+//! only honoured while the owning PID is alive and its heartbeat is younger
+//! than `--lock-max-age-mins`, which a hung bot, or a PID since reused by an
+//! unrelated process, no longer refreshes. A stale lockfile is replaced:
+//! unlinked and recreated, so a leftover flock stays on the old inode.
+//!
+//! A clean exit clears the record, so a record found on an acquired lock is
+//! that of an instance which crashed.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+use tracing::debug;
+
+/// Who holds the lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub heartbeat: DateTime<Utc>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        let now = Utc::now();
+        Self {
+            pid: std::process::id(),
+            started_at: now,
+            heartbeat: now,
+        }
+    }
+
+    /// Whether the lock still belongs to this owner at `now`
+    pub fn is_live(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        let age = (now - self.heartbeat).to_std().unwrap_or_default();
+        pid_alive(self.pid) && age <= max_age
+    }
+}
+
+/// Whether a process with this PID exists
+#[cfg(target_os = "linux")]
+pub fn pid_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Without procfs only the heartbeat tells a dead owner apart
+#[cfg(not(target_os = "linux"))]
+pub fn pid_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Outcome of [`InstanceLock::acquire`]
+pub enum Acquire {
+    /// The lock is ours; `recovered` is the record of a crashed previous owner
+    Acquired {
+        lock: InstanceLock,
+        recovered: Option<LockOwner>,
+    },
+    /// A live instance holds the lock (`None`: it has not written its record yet)
+    Held(Option<LockOwner>),
+}
+
+/// The held lock; the record is cleared on drop
+pub struct InstanceLock {
+    file: File,
+    owner: LockOwner,
+}
+
+impl InstanceLock {
+    /// Take the lock at `path`, replacing a stale one
+    pub fn acquire(path: &Path, max_age: Duration) -> Result<Acquire> {
+        let mut replaced = None;
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+                .with_context(|| format!("Failed to open lockfile {}", path.display()))?;
+            let previous = read_owner(&mut file);
+            if file.try_lock_exclusive().is_ok() {
+                let mut lock = Self {
+                    file,
+                    owner: LockOwner::current(),
+                };
+                lock.write()?;
+                return Ok(Acquire::Acquired {
+                    lock,
+                    recovered: replaced.or(previous),
+                });
+            }
+            match previous {
+                Some(owner) if replaced.is_none() && !owner.is_live(Utc::now(), max_age) => {
+                    debug!("Replacing stale lockfile of pid {}", owner.pid);
+                    std::fs::remove_file(path).with_context(|| {
+                        format!("Failed to remove stale lockfile {}", path.display())
+                    })?;
+                    replaced = Some(owner);
+                }
+                owner => return Ok(Acquire::Held(owner)),
+            }
+        }
+    }
+
+    fn write(&mut self) -> Result<()> {
+        write_owner(&mut self.file, &self.owner)
+    }
+
+    /// Refresh the heartbeat every `every` until the process exits
+    pub fn keep_alive(&self, every: Duration) -> Result<()> {
+        let mut file = self.file.try_clone().context("Failed to clone lockfile")?;
+        let mut owner = self.owner.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                owner.heartbeat = Utc::now();
+                if let Err(e) = write_owner(&mut file, &owner) {
+                    debug!("Could not refresh the lockfile heartbeat: {:#}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Leave an empty record behind: a clean exit, not a crash
+        let _ = self.file.set_len(0);
+    }
+}
+
+fn read_owner(file: &mut File) -> Option<LockOwner> {
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_owner(file: &mut File, owner: &LockOwner) -> Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string(owner)?.as_bytes())?;
+    file.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_AGE: Duration = Duration::from_secs(600);
+
+    fn lockfile(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "westend-migrate-lock-{}-{}",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_owner_liveness() {
+        let now = Utc::now();
+        let mut owner = LockOwner::current();
+        assert!(owner.is_live(now, MAX_AGE));
+        // Alive, but no heartbeat for longer than the max age: hung or PID reused
+        owner.heartbeat = now - chrono::Duration::minutes(11);
+        assert!(!owner.is_live(now, MAX_AGE));
+        #[cfg(target_os = "linux")]
+        {
+            let dead = LockOwner {
+                pid: u32::MAX,
+                ..LockOwner::current()
+            };
+            assert!(!dead.is_live(now, MAX_AGE));
+        }
+    }
+
+    #[test]
+    fn test_second_instance_is_refused() {
+        let path = lockfile("held");
+        let Acquire::Acquired { lock, .. } = InstanceLock::acquire(&path, MAX_AGE).unwrap() else {
+            panic!("first acquire failed");
+        };
+        let second = InstanceLock::acquire(&path, MAX_AGE).unwrap();
+        let held_by = match second {
+            Acquire::Held(owner) => owner.map(|o| o.pid),
+            Acquire::Acquired { .. } => panic!("lock acquired twice"),
+        };
+        drop(lock);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(held_by, Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let path = lockfile("stale");
+        // A descriptor still holding the flock, with an owner that stopped
+        // refreshing its heartbeat long ago
+        let mut holder = File::create(&path).unwrap();
+        holder.lock_exclusive().unwrap();
+        let stale = LockOwner {
+            heartbeat: Utc::now() - chrono::Duration::hours(1),
+            ..LockOwner::current()
+        };
+        write_owner(&mut holder, &stale).unwrap();
+
+        let acquired = InstanceLock::acquire(&path, MAX_AGE).unwrap();
+        let recovered = match acquired {
+            Acquire::Acquired { recovered, .. } => recovered,
+            Acquire::Held(_) => panic!("stale lock not replaced"),
+        };
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recovered, Some(stale));
+    }
+
+    #[test]
+    fn test_clean_exit_clears_record() {
+        let path = lockfile("clean");
+        if let Acquire::Acquired { lock, .. } = InstanceLock::acquire(&path, MAX_AGE).unwrap() {
+            drop(lock);
+        }
+        let recovered = match InstanceLock::acquire(&path, MAX_AGE).unwrap() {
+            Acquire::Acquired { recovered, .. } => recovered,
+            Acquire::Held(_) => panic!("released lock still held"),
+        };
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recovered, None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Log file output with size- and time-based rotation
+//!
+//! The active file keeps its configured name; on rotation it is renamed to
+//! `<name>.1`, older files shift up (`.1` → `.2`, ...) and anything beyond
+//! `keep` files is deleted. Writes go through `tracing_appender::non_blocking`
+//! so a slow disk never stalls the bot loop.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Time-based rotation period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RotationPeriod {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl RotationPeriod {
+    /// Identifier of the period containing `time` (rotation happens when it changes)
+    fn key(&self, time: DateTime<Local>) -> String {
+        match self {
+            RotationPeriod::Never => String::new(),
+            RotationPeriod::Hourly => time.format("%Y-%m-%d %H").to_string(),
+            RotationPeriod::Daily => time.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Rotation policy for [`RotatingFile`]
+#[derive(Debug, Clone, Copy)]
+pub struct RotationPolicy {
+    /// Rotate before a write would grow the file past this size (0 = no size limit)
+    pub max_bytes: u64,
+    pub period: RotationPeriod,
+    /// Rotated files to keep besides the active one
+    pub keep: usize,
+}
+
+/// A log file that rotates itself according to a [`RotationPolicy`]
+pub struct RotatingFile {
+    path: PathBuf,
+    policy: RotationPolicy,
+    file: File,
+    size: u64,
+    period_key: String,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, policy: RotationPolicy) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        }
+        let file = open_append(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            policy,
+            file,
+            size,
+            period_key: policy.period.key(Local::now()),
+        })
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn should_rotate(&self, incoming: usize, now: DateTime<Local>) -> bool {
+        let over_size = self.policy.max_bytes > 0
+            && self.size > 0
+            && self.size + incoming as u64 > self.policy.max_bytes;
+        let new_period = self.policy.period.key(now) != self.period_key;
+        over_size || new_period
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.policy.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.policy.keep));
+            for n in (1..self.policy.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        self.period_key = self.policy.period.key(now);
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let now = Local::now();
+        if self.should_rotate(buf.len(), now) {
+            // Never lose a log line over a failed rotation - keep writing to the current file
+            if let Err(e) = self.rotate(now) {
+                eprintln!("Log rotation failed for {}: {}", self.path.display(), e);
+                self.period_key = self.policy.period.key(now);
+            }
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Parse a byte size like `50M`, `1G`, `512K` or `1048576` (binary units)
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let (digits, multiplier) = match input.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&input[..i], 1u64 << 10),
+        Some((i, 'M' | 'm')) => (&input[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&input[..i], 1 << 30),
+        _ => (input, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}' (expected e.g. 50M, 1G, 512K)", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "westend-migrate-logfile-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("bot.log")
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_size("512K"), Ok(512 << 10));
+        assert_eq!(parse_size("50M"), Ok(50 << 20));
+        assert_eq!(parse_size("2g"), Ok(2 << 30));
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("10MB").is_err());
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_n_files() {
+        let path = temp_log("size");
+        let policy = RotationPolicy {
+            max_bytes: 10,
+            period: RotationPeriod::Never,
+            keep: 2,
+        };
+        let mut file = RotatingFile::open(&path, policy).unwrap();
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |p: &Path| fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "dddddddd\n");
+        assert_eq!(read(&file.rotated_path(1)), "cccccccc\n");
+        assert_eq!(read(&file.rotated_path(2)), "bbbbbbbb\n");
+        assert!(!file.rotated_path(3).exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_period_change_rotates() {
+        let path = temp_log("period");
+        let policy = RotationPolicy {
+            max_bytes: 0,
+            period: RotationPeriod::Daily,
+            keep: 3,
+        };
+        let mut file = RotatingFile::open(&path, policy).unwrap();
+        file.write_all(b"yesterday\n").unwrap();
+
+        // Pretend the file was opened on a previous day
+        file.period_key = "2000-01-01".to_string();
+        file.write_all(b"today\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+        assert_eq!(
+            fs::read_to_string(file.rotated_path(1)).unwrap(),
+            "yesterday\n"
+        );
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
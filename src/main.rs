@@ -9,44 +9,164 @@
 //! const currentTask = await api.query.stateTrieMigration.migrationProcess();
 //! const tx = api.tx.stateTrieMigration.continueMigrate(limits, sizeUpperLimit, currentTask);
 
+mod account;
+mod approval;
+mod budget;
+mod compete;
+mod constants;
+mod counters;
+mod crosscheck;
 mod error;
+mod eta;
+mod events;
+mod exit;
+mod explorer;
+mod export;
+mod follower;
+mod forks;
+mod health;
+mod heartbeat;
+mod history;
+mod journal;
+mod keycache;
+mod keyspace;
+mod keystore;
+mod leaderboard;
+mod ledger;
+mod lock;
+mod logfile;
+mod mbm;
+mod network;
+mod nonce;
+mod notify;
+mod offline;
+mod optimistic;
+mod pacing;
+mod parachain;
+mod pipeline;
+mod policy;
+mod poster;
+mod progress;
+mod proof;
+mod proxy;
+mod push;
+mod recovery;
+mod registry;
+mod ring;
+mod risk;
+mod scandiff;
+mod secrets;
+mod signing;
+mod state;
+mod stats;
+mod task;
+mod trusted;
+mod tui;
 mod utils;
+mod vault;
+mod watch;
+mod witness;
 
+use account::{AccountBalance, AccountLayout};
 use anyhow::{Context, Result};
-use clap::Parser;
+use approval::{ApprovalQueue, ApprovalRequest, Decision};
+use budget::{CountingRpc, Quota};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use compete::{Compete, CompetitionTracker};
+use constants::ConstantsCache;
+use counters::CounterFile;
+use crosscheck::{CrossCheck, Verifier};
 use error::MigrationError;
+use eta::{RateTracker, RemainingKeys};
+use events::{BotEvent, EventLog, EventRecord, Timeline};
+use exit::Stop;
+use explorer::ExplorerClient;
+use export::ExportFormat;
+use forks::{ForkMonitor, ReadBasis};
+use health::Health;
+use heartbeat::{HeartbeatMode, JokeHeartbeat, OfflineHeartbeat, SessionProgress, StatsHeartbeat};
+use history::{History, HistoryEntry};
+use journal::{Journal, Outcome};
+use keycache::{CachedScan, KeyCacheWriter};
+use keyspace::{Checkpoint, KeyEstimate, KeyScanner, ScanProgress, StorageNames};
+use keystore::read_keystore;
+use ledger::LedgerSigner;
+use lock::{Acquire, InstanceLock};
+use logfile::{parse_size, RotatingFile, RotationPeriod, RotationPolicy};
+use nonce::NonceManager;
+use notify::{
+    disable_notifications, send_notification, send_progress, AckConfig, Action, EmailConfig,
+    EscalationRule, QuietHours, Route, Severity, SlackConfig, SlackTarget, TeamsConfig,
+    TwilioConfig, TwilioMode,
+};
+use offline::{OfflineAccount, UnsignedTx};
+use optimistic::NextWitness;
+use pacing::AdaptivePacer;
+use parachain::{ChainKind, Inclusion, RelayChain, Timing};
+use pipeline::{MigrationCall, SignedMigration, Trailing, Unsettled, Validation};
+use policy::{
+    ErrorClass, ACK_POLL_SECS, MAX_CONSECUTIVE_ERRORS, MAX_STALE_WITNESS_RETRIES,
+    NODE_HEALTH_POLL_SECS, NONCE_RETRY_WAIT_SECS, RETRY_WAIT_SECS,
+};
+use poster::{PostAuth, PostTarget};
+use progress::RunProgress;
+use push::EventHub;
+use recovery::InFlight;
+use registry::InstanceEntry;
+use ring::{Bench, SignerRing};
+use risk::RiskInputs;
 use secrecy::{ExposeSecret, SecretString};
-use std::fs::File;
+use secrets::SeedSource;
+use signing::{RemoteSigner, TxSigner};
+use state::StateMachine;
+use stats::{CampaignCaps, SessionStats};
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use fs2::FileExt;
 use subxt::{
     backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
     dynamic::{At, Value},
     rpc_params,
-    tx::Signer,
+    tx::{DynamicPayload, SubmittableExtrinsic, TxProgress},
+    utils::H256,
     OnlineClient, PolkadotConfig,
 };
-use subxt_signer::{bip39::Mnemonic, sr25519::Keypair};
+use task::{Task, TaskKind, TrieMigration, TrieState};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::fmt::format::Writer;
 use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
+use trusted::{Finality, TrustedFinality};
+use tui::Dashboard;
 use utils::{
-    check_balance_decrease, decode_validity_error, disable_notifications, fetch_dad_joke,
-    parse_migration_status, send_notification, MigrationStatus, ValidityError,
+    check_balance_decrease, decode_validity_error, format_duration, node_health_problem,
+    parse_deadline_arg, signer_from_seed, units_to_wnd, until_deadline, MigrationStatus,
+    ValidityError,
 };
+use vault::{Vault, VaultKey, VaultSigner};
+use westend_migration_bot::chain_events::MigrationEvent;
+use westend_migration_bot::limits::{self, MigrationLimits};
+use westend_migration_bot::pool::{self, NonceWait, PendingExtrinsic};
+use westend_migration_bot::{BotSnapshot, BotState};
+use witness::WitnessSnapshot;
+use zeroize::Zeroizing;
 
 const DEFAULT_WESTEND_RPC: &str = "wss://westend-asset-hub-rpc.polkadot.io";
+const DEFAULT_EXPLORER_URL: &str = "https://assethub-westend.subscan.io";
 
-// Timing constants
-const BLOCK_TIME_SECS: u64 = 6;
-const PENDING_TX_TIMEOUT_ITERATIONS: u32 = 20;
-const NONCE_RETRY_WAIT_SECS: u64 = 30;
-const RETRY_WAIT_SECS: u64 = 12;
-const BANNED_TX_WAIT_SECS: u64 = 60;
+// Timing constants (retry and backoff timing lives in `policy`)
 const HEARTBEAT_INTERVAL_SECS: u64 = 60;
-const MAX_CONSECUTIVE_ERRORS: u32 = 5; // Stop after this many consecutive failures
+/// How often an open approval request is checked for a decision
+const APPROVAL_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Timer showing local date/time
 struct LocalTimer;
@@ -58,30 +178,587 @@ impl FormatTime for LocalTimer {
     }
 }
 
+/// Console log format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    /// Compact human-readable lines
+    Compact,
+    /// One JSON object per line with structured fields (for Loki/Elasticsearch)
+    Json,
+}
+
+/// Machine-readable event stream on stdout (`--events`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum EventStream {
+    /// One JSON object per bot event, the same records as --event-log
+    Ndjson,
+}
+
 /// State-trie migration bot for Westend
 #[derive(Parser)]
 #[command(name = "westend-migrate")]
 #[command(about = "Bot to run signed state-trie migration on Westend")]
 struct Cli {
     /// Westend RPC endpoint
-    #[arg(short, long, default_value = DEFAULT_WESTEND_RPC, env = "WESTEND_RPC")]
+    #[arg(
+        short,
+        long,
+        default_value = DEFAULT_WESTEND_RPC,
+        env = "WESTEND_RPC",
+        global = true
+    )]
     rpc_url: String,
 
-    /// Secret seed phrase or hex seed for signing transactions.
+    /// Fallback RPC endpoints (comma-separated), switched to when the current
+    /// node keeps broadcasting our transaction to 0 peers
+    #[arg(
+        long = "fallback-rpc-url",
+        env = "FALLBACK_RPC_URLS",
+        value_delimiter = ','
+    )]
+    fallback_rpc_urls: Vec<String>,
+
+    /// Daily request quota of an endpoint's provider plan `url=requests` (repeatable):
+    /// alerts as it is approached and reached
+    #[arg(long = "rpc-quota", value_name = "URL=REQUESTS")]
+    rpc_quotas: Vec<Quota>,
+
+    /// Percentage of an --rpc-quota at which to warn
+    #[arg(long, default_value = "80", value_parser = clap::value_parser!(u8).range(1..=100))]
+    rpc_quota_warn_percent: u8,
+
+    /// Move to the next --fallback-rpc-url once the current endpoint's --rpc-quota
+    /// is used up for the day
+    #[arg(long, requires = "rpc_quotas")]
+    rpc_quota_failover: bool,
+
+    /// File keeping today's per-endpoint request counts across restarts
+    #[arg(long, env = "RPC_USAGE_FILE")]
+    rpc_usage_file: Option<PathBuf>,
+
+    /// Relay chain RPC endpoint: when the target is a parachain (Asset Hub),
+    /// check that each finalized migration block is included in the relay chain
+    #[arg(long, env = "RELAY_RPC_URL")]
+    relay_rpc_url: Option<String>,
+
+    /// Only count a tx as final once the relay chain has finalized the inclusion
+    /// of its parachain block (default: check and alert only)
+    #[arg(long, requires = "relay_rpc_url")]
+    relay_finality: bool,
+
+    /// Independent second RPC endpoint: MigrationProcess is re-read there before
+    /// each submission, which is held if the two endpoints disagree
+    #[arg(long, env = "VERIFY_RPC_URL")]
+    verify_rpc_url: Option<String>,
+
+    /// Check MigrationProcess against a storage proof (state_getReadProof)
+    /// from the endpoint before each submission, which is held if the proof
+    /// is missing, invalid or proves another value
+    #[arg(long, env = "VERIFY_PROOFS")]
+    verify_proofs: bool,
+
+    /// Relay chain spec for an embedded light client: only count a tx as final
+    /// once the light client, verifying GRANDPA justifications itself, has
+    /// finalized the same block (needs `--features light-client`)
+    #[arg(long, value_name = "CHAIN_SPEC", env = "TRUSTED_FINALITY")]
+    trusted_finality: Option<PathBuf>,
+
+    /// Parachain chain spec for --trusted-finality when the target is a
+    /// parachain (Asset Hub)
+    #[arg(long, value_name = "CHAIN_SPEC", requires = "trusted_finality")]
+    trusted_para_spec: Option<PathBuf>,
+
+    /// Hold submissions while the node has fewer peers than this (or is syncing)
+    #[arg(long, env = "MIN_PEERS", default_value = "2")]
+    min_peers: usize,
+
+    /// Secret seed phrase or hex seed for signing transactions, optionally
+    /// with a derivation path (`//hard`, `/soft`, `///password`).
     /// The seed is stored in memory-protected storage and zeroized on drop.
     /// WARNING: Use environment variable SIGNER_SEED for security
-    #[arg(long, env = "SIGNER_SEED")]
-    seed: SecretString,
+    #[arg(long, env = "SIGNER_SEED", hide_env_values = true, global = true)]
+    seed: Option<SecretString>,
+
+    /// Read the seed from a file instead (Docker/Kubernetes secret mount,
+    /// systemd LoadCredential); its contents are zeroized once parsed
+    #[arg(
+        long,
+        env = "SIGNER_SEED_FILE",
+        value_name = "PATH",
+        conflicts_with = "seed",
+        global = true
+    )]
+    seed_file: Option<PathBuf>,
+
+    /// Read the seed from a cloud secret manager at startup:
+    /// `aws:<secret ARN>` (AWS Secrets Manager, credentials from the
+    /// environment or the instance/task role) or
+    /// `gcp:projects/<p>/secrets/<name>[/versions/<v>]` (GCP Secret Manager,
+    /// the VM's service account)
+    #[arg(
+        long,
+        env = "SIGNER_SEED_SOURCE",
+        value_name = "SOURCE",
+        conflicts_with_all = ["seed", "seed_file"],
+        global = true
+    )]
+    seed_source: Option<SeedSource>,
+
+    /// Sign with an account exported from polkadot-js (encrypted JSON)
+    /// instead of a seed; its password is read from --keystore-password-file
+    /// or asked for on the terminal
+    #[arg(
+        long,
+        env = "SIGNER_KEYSTORE",
+        value_name = "PATH",
+        conflicts_with_all = ["seed", "seed_file", "seed_source"],
+        global = true
+    )]
+    keystore: Option<PathBuf>,
+
+    /// File holding the --keystore password
+    #[arg(
+        long,
+        env = "SIGNER_KEYSTORE_PASSWORD_FILE",
+        value_name = "PATH",
+        requires = "keystore",
+        global = true
+    )]
+    keystore_password_file: Option<PathBuf>,
+
+    /// Sign through an external signing daemon (JSON-RPC over HTTP) instead
+    /// of holding a key: the seed never lives on this host
+    #[arg(
+        long,
+        env = "REMOTE_SIGNER_URL",
+        value_name = "URL",
+        conflicts_with_all = ["seed", "seed_file", "seed_source", "keystore"],
+        global = true
+    )]
+    remote_signer: Option<String>,
+
+    /// Bearer token for --remote-signer
+    #[arg(
+        long,
+        env = "REMOTE_SIGNER_TOKEN",
+        hide_env_values = true,
+        global = true
+    )]
+    remote_signer_token: Option<SecretString>,
+
+    /// Sign with a Ledger running the Polkadot Generic app; each tx is
+    /// confirmed on the device (build with `--features ledger`)
+    #[arg(
+        long,
+        env = "SIGNER_LEDGER",
+        conflicts_with_all = ["seed", "seed_file", "seed_source", "keystore", "remote_signer"],
+        global = true
+    )]
+    ledger: bool,
+
+    /// Ledger account index (`m/44'/354'/<N>'/0'/0'`)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "0",
+        requires = "ledger",
+        global = true
+    )]
+    ledger_account: u32,
+
+    /// How long a tx may wait for confirmation on the Ledger before it is
+    /// given up and retried
+    #[arg(long, default_value = "120", requires = "ledger", global = true)]
+    ledger_timeout_secs: u64,
+
+    /// Metadata proof API the Ledger needs to decode each tx
+    #[arg(
+        long,
+        value_name = "URL",
+        default_value = "https://api.zondax.ch/polkadot/transaction/metadata",
+        requires = "ledger",
+        global = true
+    )]
+    ledger_proof_api: String,
+
+    /// Chain id to ask --ledger-proof-api for
+    #[arg(long, default_value = "wnd", requires = "ledger", global = true)]
+    ledger_chain: String,
+
+    /// HashiCorp Vault server for --vault-key
+    #[arg(
+        long,
+        env = "VAULT_ADDR",
+        value_name = "URL",
+        requires = "vault_key",
+        global = true
+    )]
+    vault_addr: Option<String>,
+
+    /// Sign with a Vault transit ed25519 key (`transit:[<mount>/]<key>`) or
+    /// read the seed at startup from a KV v2 secret
+    /// (`kv:<mount>/<path>[#<field>]`, field `seed` by default)
+    #[arg(
+        long,
+        env = "SIGNER_VAULT_KEY",
+        value_name = "KEY",
+        requires = "vault_addr",
+        conflicts_with_all = ["seed", "seed_file", "seed_source", "keystore", "remote_signer", "ledger"],
+        global = true
+    )]
+    vault_key: Option<VaultKey>,
+
+    /// Vault token for --vault-key; for `kv:` it is only used at startup, so
+    /// a short-lived one will do
+    #[arg(long, env = "VAULT_TOKEN", hide_env_values = true, global = true)]
+    vault_token: Option<SecretString>,
+
+    /// Dispatch every migration from this account through `Proxy.proxy`,
+    /// signed by one of its proxies: the funded account stays cold and the
+    /// bot only holds the proxy key. Deposits and balance checks then apply
+    /// to this account; the proxy only pays fees
+    #[arg(
+        long,
+        env = "SIGNER_PROXY_FOR",
+        value_name = "ADDRESS",
+        value_parser = offline::parse_account,
+        global = true
+    )]
+    proxy_for: Option<subxt::utils::AccountId32>,
+
+    /// Seed file of another account to rotate to when the current one has a
+    /// tx banned or stuck in the pool, or too little balance for the deposit
+    /// (repeatable); each needs funding of its own
+    #[arg(
+        long,
+        env = "SIGNER_ROTATE_SEED_FILES",
+        value_name = "PATH",
+        value_delimiter = ',',
+        global = true
+    )]
+    rotate_seed_file: Vec<PathBuf>,
+
+    /// Layout of `System::Account` balance data (auto-detected by default)
+    #[arg(long, value_enum, default_value = "auto", global = true)]
+    account_layout: AccountLayout,
+
+    /// Options of the migration loop; accepted here for one release, before
+    /// they moved to `run`
+    #[command(
+        flatten,
+        next_help_heading = "Run options (deprecated here: pass them after `run`)"
+    )]
+    run: RunArgs,
+
+    /// Show migration status and pending transactions, then exit
+    /// (deprecated: use `status`)
+    #[arg(long, hide = true)]
+    status: bool,
+
+    /// Watch-only: follow migration progress and events and send alerts, without
+    /// loading a signer (no seed needed, nothing is ever signed)
+    #[arg(long)]
+    watch: bool,
+
+    /// What to run: `trie` signs state-trie migration calls; `mbm` monitors
+    /// pallet_migrations multi-block migrations read-only (no seed needed)
+    #[arg(long, value_enum, default_value = "trie")]
+    task: TaskKind,
+
+    /// In --watch mode, alert when no items have been migrated for this many minutes
+    #[arg(long, default_value = "30")]
+    stall_alert_mins: u64,
+
+    /// Sponsor label attributed to this session's migrations in the history DB
+    #[arg(long, env = "SPONSOR")]
+    sponsor: Option<String>,
+
+    /// History database recording every finalized migration tx: a SQLite file path,
+    /// or a postgres:// URL (requires the `postgres` feature).
+    /// May be shared by several bot instances (different accounts/chains).
+    #[arg(long, env = "HISTORY_DB", hide_env_values = true, global = true)]
+    history_db: Option<String>,
+
+    /// Instance ID recorded with each history row (default: <account>@<genesis>)
+    #[arg(long, env = "INSTANCE_ID")]
+    instance_id: Option<String>,
+
+    /// SQLite journal of every broadcast transaction: nonce, limits, finalized block,
+    /// events, outcome and fee
+    #[arg(long, env = "JOURNAL_DB", global = true)]
+    journal_db: Option<PathBuf>,
+
+    /// Keep run and item/byte counters in this JSON file across restarts, so
+    /// --runs, the campaign caps and the summary count from the first start
+    #[arg(long, env = "STATE_FILE")]
+    state_file: Option<PathBuf>,
+
+    /// Append every bot event (JSON lines) to this file, for `replay`
+    #[arg(long, env = "EVENT_LOG")]
+    event_log: Option<PathBuf>,
+
+    /// Number of recent events included in critical notifications (0 = none)
+    #[arg(long, default_value = "10")]
+    incident_timeline: usize,
+
+    /// Periodically write a leaderboard of items migrated per account, scanned from
+    /// finalized blocks (markdown, or JSON if the path ends in .json)
+    #[arg(long, env = "LEADERBOARD")]
+    leaderboard: Option<PathBuf>,
+
+    /// Minutes between leaderboard updates
+    #[arg(long, default_value = "60")]
+    leaderboard_interval_mins: u64,
+
+    /// Finalized blocks scanned back from the head when the leaderboard starts
+    #[arg(long, default_value = "600")]
+    leaderboard_lookback: u32,
+
+    /// Periodically log how many items/bytes the whole network migrates per hour
+    /// (from all `Migrated` events in finalized blocks) and our share of it
+    #[arg(long)]
+    network_stats: bool,
+
+    /// Minutes between network statistics reports
+    #[arg(long, default_value = "10")]
+    network_stats_interval_mins: u64,
+
+    /// Periodically post a progress summary (percent complete, ETA, throughput)
+    /// as a comment: `github:owner/repo#N`, a GitHub issue URL or a forum topic URL
+    #[arg(long, env = "POST_PROGRESS", value_name = "TARGET")]
+    post_progress: Option<PostTarget>,
+
+    /// GitHub token, or Discourse API key, for --post-progress
+    #[arg(
+        long,
+        env = "POST_PROGRESS_TOKEN",
+        hide_env_values = true,
+        requires = "post_progress"
+    )]
+    post_progress_token: Option<SecretString>,
+
+    /// Forum user the --post-progress Discourse API key posts as
+    #[arg(long, env = "POST_PROGRESS_USER", requires = "post_progress")]
+    post_progress_user: Option<String>,
+
+    /// Hours between progress posts
+    #[arg(long, default_value = "24")]
+    post_progress_interval_hours: u64,
+
+    /// Print aggregate activity of all instances in --history-db, then exit
+    #[arg(long, requires = "history_db")]
+    history_report: bool,
+
+    /// Clear pending transactions from the pool before starting
+    /// (deprecated: use `pool clear`)
+    #[arg(long, hide = true)]
+    clear_pending: bool,
+
+    /// Minutes without a heartbeat after which another instance's lock is
+    /// considered stale and taken over
+    #[arg(
+        long,
+        env = "LOCK_MAX_AGE_MINS",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    lock_max_age_mins: u64,
+
+    /// Interactive terminal dashboard instead of console logs
+    /// (p: pause/resume, +/-: item limit, q: stop)
+    #[arg(long)]
+    tui: bool,
+
+    /// Enable verbose logging
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Log output format
+    #[arg(long, value_enum, env = "LOG_FORMAT", default_value = "compact")]
+    log_format: LogFormat,
+
+    /// Write every bot event (progress, submitted, finalized, error) to stdout
+    /// for jq or a supervisor; console logs move to stderr
+    #[arg(long, value_enum, conflicts_with = "tui")]
+    events: Option<EventStream>,
+
+    /// Also write logs to this file (console output continues)
+    #[arg(long, env = "LOG_FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Rotate the log file when it would exceed this size, e.g. 50M (0 = no size limit)
+    #[arg(long, default_value = "0", value_parser = parse_size)]
+    log_max_size: u64,
+
+    /// Rotate the log file every hour or day
+    #[arg(long, value_enum, default_value = "never")]
+    log_rotation: RotationPeriod,
+
+    /// Number of rotated log files to keep
+    #[arg(long, default_value = "7")]
+    log_keep: usize,
+
+    /// Heartbeat logged every minute: chain stats, a dad joke, or nothing
+    #[arg(long, value_enum, env = "HEARTBEAT", default_value = "stats")]
+    heartbeat: HeartbeatMode,
+
+    /// Disable desktop notifications (useful for headless servers)
+    #[arg(long)]
+    no_notify: bool,
+
+    /// Notification routing rule `sink=min-severity` (repeatable), e.g. `email=warning`.
+    /// Defaults: desktop=info, email=critical, slack=success, teams=success, twilio=critical
+    #[arg(long = "notify-route", value_name = "SINK=SEVERITY")]
+    notify_routes: Vec<Route>,
+
+    /// Escalation rule `severity[*repeats]=action[+action]` (repeatable), where an
+    /// action is a sink or `pause`, e.g. `warning*3=twilio`, `critical=twilio+pause`
+    #[arg(long = "escalate", value_name = "RULE")]
+    escalation_rules: Vec<EscalationRule>,
+
+    /// Window in which repeats of the same notification are counted for --escalate
+    #[arg(long, default_value = "60")]
+    escalation_window_mins: u64,
+
+    /// Local-time window (e.g. 23:00-07:00) during which only critical notifications
+    /// are delivered; the rest are batched into a summary when it ends
+    #[arg(long, env = "QUIET_HOURS", value_name = "HH:MM-HH:MM")]
+    quiet_hours: Option<QuietHours>,
+
+    /// SMTP server for critical-event emails (enables the email sink)
+    #[arg(long, env = "SMTP_HOST")]
+    smtp_host: Option<String>,
+
+    /// SMTP port (STARTTLS)
+    #[arg(long, env = "SMTP_PORT", default_value = "587")]
+    smtp_port: u16,
+
+    /// SMTP username
+    #[arg(long, env = "SMTP_USER")]
+    smtp_user: Option<String>,
+
+    /// SMTP password. WARNING: Use environment variable SMTP_PASSWORD for security
+    #[arg(long, env = "SMTP_PASSWORD", hide_env_values = true)]
+    smtp_password: Option<SecretString>,
+
+    /// Sender address for critical-event emails
+    #[arg(long, env = "SMTP_FROM")]
+    smtp_from: Option<String>,
+
+    /// Recipient address(es) for critical-event emails (comma-separated)
+    #[arg(long, env = "SMTP_TO", value_delimiter = ',')]
+    smtp_to: Vec<String>,
+
+    /// Slack incoming-webhook URL (enables the Slack sink)
+    #[arg(long, env = "SLACK_WEBHOOK_URL", hide_env_values = true)]
+    slack_webhook: Option<SecretString>,
+
+    /// Slack bot token for chat.postMessage (alternative to --slack-webhook)
+    #[arg(
+        long,
+        env = "SLACK_BOT_TOKEN",
+        hide_env_values = true,
+        requires = "slack_channel"
+    )]
+    slack_token: Option<SecretString>,
+
+    /// Slack channel for --slack-token
+    #[arg(long, env = "SLACK_CHANNEL")]
+    slack_channel: Option<String>,
+
+    /// Microsoft Teams incoming-webhook URL (enables the Teams sink)
+    #[arg(long, env = "TEAMS_WEBHOOK_URL", hide_env_values = true)]
+    teams_webhook: Option<SecretString>,
+
+    /// Twilio account SID (enables SMS/voice escalation of critical events)
+    #[arg(long, env = "TWILIO_ACCOUNT_SID", requires_all = ["twilio_from", "twilio_to"])]
+    twilio_sid: Option<String>,
+
+    /// Twilio auth token. WARNING: Use environment variable TWILIO_AUTH_TOKEN for security
+    #[arg(long, env = "TWILIO_AUTH_TOKEN", hide_env_values = true)]
+    twilio_token: Option<SecretString>,
+
+    /// Twilio phone number to send/call from (E.164)
+    #[arg(long, env = "TWILIO_FROM")]
+    twilio_from: Option<String>,
+
+    /// Phone number(s) to escalate to (E.164, comma-separated)
+    #[arg(long, env = "TWILIO_TO", value_delimiter = ',')]
+    twilio_to: Vec<String>,
+
+    /// Escalate by SMS or by voice call
+    #[arg(long, value_enum, env = "TWILIO_MODE", default_value = "sms")]
+    twilio_mode: TwilioMode,
+
+    /// "Explorer" link in Slack/Teams messages
+    #[arg(long, env = "EXPLORER_URL", default_value = DEFAULT_EXPLORER_URL)]
+    explorer_url: String,
+
+    /// Subscan API used by `history enrich`
+    #[arg(
+        long,
+        env = "EXPLORER_API_URL",
+        default_value = explorer::DEFAULT_API_URL,
+        global = true
+    )]
+    explorer_api_url: String,
+
+    /// Subscan API key used by `history enrich`
+    #[arg(long, env = "EXPLORER_API_KEY", hide_env_values = true, global = true)]
+    explorer_api_key: Option<SecretString>,
+
+    /// "Dashboard" link in Slack/Teams messages
+    #[arg(long, env = "DASHBOARD_URL")]
+    dashboard_url: Option<String>,
+
+    /// Notification kinds posted to Slack, e.g. transaction-confirmed,bot-stopped
+    /// (kebab-case notification titles; default: all routed notifications)
+    #[arg(long, env = "SLACK_EVENTS", value_delimiter = ',')]
+    slack_events: Vec<String>,
+
+    /// Serve /healthz and /readyz on this address, e.g. 0.0.0.0:8080
+    #[arg(long, env = "HEALTH_ADDR")]
+    health_addr: Option<SocketAddr>,
+
+    /// Push bot events as JSON to WebSocket clients on this address, e.g. 0.0.0.0:8081
+    #[arg(long, env = "WS_ADDR")]
+    ws_addr: Option<SocketAddr>,
+
+    /// Seconds without run loop activity before /healthz reports unhealthy
+    #[arg(long, env = "HEALTH_STALE_SECS", default_value = "900")]
+    health_stale_secs: u64,
+
+    /// Pause submissions when a critical notification is not acknowledged
+    /// (via the --health-addr server) within this many minutes (0 = off)
+    #[arg(long, env = "ACK_TIMEOUT_MINS", default_value = "0")]
+    ack_timeout_mins: u64,
+
+    /// Public base URL of the health server for acknowledgement links
+    /// (default: http://<health-addr>)
+    #[arg(long, env = "ACK_URL")]
+    ack_url: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    /// Number of items to migrate per transaction (0 = use chain max)
+/// Options of the migration loop (`run`)
+#[derive(Args)]
+struct RunArgs {
+    /// Number of items to migrate per transaction (0 = half the chain max)
     #[arg(long, default_value = "0")]
     item_limit: u32,
 
-    /// Size limit in bytes per transaction (0 = use chain max)
+    /// Size limit in bytes per transaction (0 = half the chain max)
     #[arg(long, default_value = "0")]
     size_limit: u32,
 
-    /// Delay between migration transactions (seconds)
+    /// Raise SignedMigrationMaxLimits with set_signed_max_limits when it is unset or
+    /// below the working limits (the signer must be the pallet's controller)
+    #[arg(long)]
+    set_max_limits: bool,
+
+    /// Delay between migration transactions (seconds, 0 = derive from finalization latency)
     #[arg(long, default_value = "0")]
     delay_secs: u64,
 
@@ -89,151 +766,940 @@ struct Cli {
     #[arg(long)]
     once: bool,
 
+    /// Once the migration is complete, keep running and alert if it ever
+    /// regresses (MigrationProcess no longer complete, sampled keys stored V0)
+    #[arg(long, env = "STAY_ALIVE")]
+    stay_alive: bool,
+
     /// Number of successful migrations to submit before exiting (0 = unlimited)
     #[arg(long, default_value = "0")]
     runs: u32,
 
+    /// Stop after migrating this many items in total this session (0 = unlimited)
+    #[arg(long, default_value = "0")]
+    max_items_total: u64,
+
+    /// Stop before the bytes migrated this session could exceed this total (0 = unlimited)
+    #[arg(long, default_value = "0")]
+    max_bytes_total: u64,
+
+    /// Stop after this wall-clock deadline (RFC 3339 timestamp or duration like 90m, 2h30m).
+    /// The in-flight transaction is allowed to finish before exiting.
+    #[arg(long, value_parser = parse_deadline_arg)]
+    deadline: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Exit instead of submitting unvalidated transactions when system_dryRun
+    /// is unavailable (deposit safety over uptime)
+    #[arg(long, env = "REQUIRE_VALIDATION")]
+    require_validation: bool,
+
+    /// Skip (and flag) submissions whose risk score exceeds this (0-100, 100 = never skip)
+    #[arg(long, env = "MAX_RISK", default_value = "100")]
+    max_risk: u32,
+
     /// Dry run - check status only, don't submit transactions
     #[arg(long)]
     dry_run: bool,
 
-    /// Show migration status and pending transactions, then exit
+    /// Log the SCALE-encoded call data and its decoding before each submission
+    /// (always logged at debug)
     #[arg(long)]
-    status: bool,
+    show_call_data: bool,
 
-    /// Clear pending transactions from the pool before starting
+    /// Use a MigrationProcess captured with `witness export` instead of reading
+    /// it from chain (dry runs only)
+    #[arg(long, requires = "dry_run")]
+    witness_file: Option<PathBuf>,
+
+    /// Back off (or yield entirely) while other accounts are submitting
+    /// continue_migrate, instead of racing them for the same witness
     #[arg(long)]
-    clear_pending: bool,
+    compete_backoff: bool,
+
+    /// Foreign migrations within 10 minutes at which the bot yields for 10 minutes
+    /// instead of delaying (0 = never yield)
+    #[arg(long, default_value = "10")]
+    compete_yield_after: usize,
+
+    /// Submit the next migration as soon as the previous one is in a best
+    /// block, signed at a locally counted nonce, and follow its finality in
+    /// the background (roughly doubles throughput; a reorg can invalidate
+    /// the next tx's witness)
+    #[arg(long, env = "PIPELINE")]
+    pipeline: bool,
+
+    /// On SIGINT/SIGTERM, seconds to wait for the in-flight transaction to finalize
+    #[arg(long, env = "DRAIN_TIMEOUT_SECS", default_value = "180")]
+    drain_timeout_secs: u64,
+
+    /// Write each built tx to this directory and submit it only once an approver
+    /// signs off (GET /approve/<token> on --health-addr, or <id>.approved holding the token)
+    #[arg(long, env = "APPROVAL_QUEUE")]
+    approval_queue: Option<PathBuf>,
+
+    /// Minutes an approval request stays open before its tx is dropped
+    #[arg(long, env = "APPROVAL_TIMEOUT_MINS", default_value = "60")]
+    approval_timeout_mins: u64,
+}
 
-    /// Enable verbose logging
-    #[arg(short, long)]
-    verbose: bool,
+/// `run` options given on the command line before any subcommand
+fn legacy_run_flags(matches: &ArgMatches) -> Vec<String> {
+    RunArgs::augment_args(clap::Command::new("run"))
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+        .collect()
+}
 
-    /// Disable desktop notifications (useful for headless servers)
-    #[arg(long)]
-    no_notify: bool,
+#[derive(Subcommand)]
+enum Command {
+    /// Run the migration loop (the default without a subcommand)
+    Run(RunArgs),
+    /// Show migration status, the signer account and pending transactions
+    Status,
+    /// Show, set or recommend signed migration limits
+    Limits {
+        #[command(subcommand)]
+        action: LimitsCommand,
+    },
+    /// Inspect the signer account (`account` alone is `account show`)
+    Account {
+        #[command(subcommand)]
+        action: Option<AccountCommand>,
+    },
+    /// Manage the node's transaction pool
+    Pool {
+        #[command(subcommand)]
+        action: PoolCommand,
+    },
+    /// Tools for the --history-db database
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+    /// Reconstruct session statistics and state from an --event-log file
+    Replay {
+        /// Event log written with --event-log
+        path: PathBuf,
+
+        /// Only replay events up to this time (RFC 3339)
+        #[arg(long)]
+        until: Option<chrono::DateTime<chrono::FixedOffset>>,
+
+        /// Number of trailing events to print
+        #[arg(long, default_value = "10")]
+        tail: usize,
+    },
+    /// Estimate the top-trie keys left to migrate by sampling state_getKeysPaged
+    /// after the last migrated key, and the share of the migration done
+    Remaining {
+        /// Pages read, one per equal slice of the remaining keyspace
+        #[arg(long, default_value = "32")]
+        samples: u32,
+
+        /// Keys per page (read in batches of at most 1000)
+        #[arg(long, default_value = "1000")]
+        page_size: u32,
+
+        /// Count every remaining key instead of sampling (slow, exact)
+        #[arg(long)]
+        full: bool,
+
+        /// Key ranges --full reads concurrently (best against an archive node)
+        #[arg(long, default_value = "1", requires = "full")]
+        parallel: usize,
+
+        /// Stream every key read to this file, one hex key per line
+        #[arg(long, value_name = "FILE")]
+        keys_out: Option<PathBuf>,
+
+        /// Save the scan's progress here; an interrupted scan started again
+        /// with the same file resumes where it stopped
+        #[arg(long, value_name = "FILE")]
+        checkpoint: Option<PathBuf>,
+
+        /// Key cache directory: --full caches the keys it reads (with their
+        /// value sizes) here, later runs count from the newest cached scan
+        #[arg(long, value_name = "DIR")]
+        key_cache: Option<PathBuf>,
+    },
+    /// Compare scans cached with `remaining --full --key-cache`
+    Scan {
+        #[command(subcommand)]
+        action: ScanCommand,
+    },
+    /// Capture and inspect MigrationProcess witnesses
+    Witness {
+        #[command(subcommand)]
+        action: WitnessCommand,
+    },
+    /// Sign on an air-gapped machine: build the next tx here, sign its
+    /// payload offline, submit the signature here
+    Tx {
+        #[command(subcommand)]
+        action: TxCommand,
+    },
+    /// List the bot instances running on this host
+    Ps,
+    /// Query a running bot through its --health-addr server
+    Ctl {
+        #[command(subcommand)]
+        action: CtlCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum LimitsCommand {
+    /// Show SignedMigrationMaxLimits, the pallet's AutoLimits and its constants
+    Get,
+    /// Set SignedMigrationMaxLimits with set_signed_max_limits (controller only)
+    Set {
+        /// Bytes per transaction
+        #[arg(long)]
+        size: u32,
+
+        /// Items per transaction
+        #[arg(long)]
+        item: u32,
+    },
+    /// Limits `run` would work with, the deposit they reserve and whether the
+    /// signer's balance covers it
+    Recommend,
+}
+
+#[derive(Subcommand)]
+enum AccountCommand {
+    /// Show the SS58 address, balances, nonce and the deposit each
+    /// continue_migrate would reserve at the current limits
+    Show,
+}
+
+#[derive(Subcommand)]
+enum PoolCommand {
+    /// List pending transactions, decoded: call, signer and nonce
+    /// (requires --rpc-methods=unsafe, as do the other pool commands)
+    List,
+    /// Remove pending transactions from the pool
+    Clear {
+        /// Only remove transactions signed by our account
+        #[arg(long)]
+        mine_only: bool,
+    },
+    /// Remove one pending transaction (and those depending on it)
+    Remove {
+        /// Extrinsic hash, as shown by `pool list`
+        #[arg(value_parser = pool::parse_extrinsic_hash)]
+        hash: H256,
+    },
+}
+
+#[derive(Subcommand)]
+enum CtlCommand {
+    /// Show what the running bot is doing (state, errors, last migration status)
+    Status {
+        /// Health server of the running bot (default: --health-addr)
+        #[arg(long)]
+        addr: Option<SocketAddr>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScanCommand {
+    /// Keys migrated, deleted and created between two cached scans, with the
+    /// block and account that migrated each (reads the blocks in between)
+    Diff {
+        /// Older scan (`<block hash>.keys.gz` in the key cache)
+        old: PathBuf,
+
+        /// Newer scan
+        new: PathBuf,
+
+        /// Write every migrated key with its size, block and account here (TSV)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only compare the scans; don't read the blocks in between
+        #[arg(long)]
+        no_attribution: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WitnessCommand {
+    /// Save the current MigrationProcess (for --witness-file)
+    Export {
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TxCommand {
+    /// Build the next continue_migrate for --account and save it unsigned,
+    /// with the signer payload to sign offline
+    Build {
+        /// Signing account (SS58); its key is not needed
+        #[arg(long, value_parser = offline::parse_account)]
+        account: subxt::utils::AccountId32,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Also print the payload as a QR code for Polkadot Vault
+        #[arg(long)]
+        qr: bool,
+    },
+    /// Submit a tx saved by `tx build` with its offline signature
+    Submit {
+        /// File written by `tx build`
+        file: PathBuf,
+
+        /// sr25519 signature of the payload (hex)
+        #[arg(long)]
+        signature: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Export every recorded tx for offline analysis: finalized txs from
+    /// --history-db (parquet), or the --journal-db submission journal (csv, json)
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value = "parquet")]
+        format: ExportFormat,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Fill in block, timestamp, fee and events of recorded txs from the block
+    /// explorer API (requires --explorer-api-key)
+    Enrich {
+        /// Most txs looked up in this run
+        #[arg(long, default_value = "100")]
+        limit: usize,
+    },
+}
+
+impl Cli {
+    /// Move the pre-subcommand forms onto the subcommands: `run` options into
+    /// `self.run`, `--status` into `status`. Returns deprecation notices to
+    /// log once logging is set up.
+    fn normalize(&mut self, matches: &ArgMatches) -> Result<Vec<String>> {
+        let legacy = legacy_run_flags(matches);
+        let mut notices = Vec::new();
+        match self.command.take() {
+            Some(Command::Run(args)) => {
+                if !legacy.is_empty() {
+                    anyhow::bail!("{} must come after `run`", legacy.join(", "));
+                }
+                self.run = args;
+            }
+            command => {
+                self.command = command;
+                if !legacy.is_empty() {
+                    notices.push(format!(
+                        "{} without `run` is deprecated and will stop working in the \
+                         next release; use `westend-migrate run {}`",
+                        legacy.join(", "),
+                        legacy.join(" ")
+                    ));
+                }
+            }
+        }
+        if self.status {
+            notices.push("--status is deprecated; use `westend-migrate status`".to_string());
+            self.command.get_or_insert(Command::Status);
+        }
+        if self.clear_pending {
+            notices.push(
+                "--clear-pending is deprecated; use `westend-migrate pool clear`".to_string(),
+            );
+        }
+        Ok(notices)
+    }
+
+    /// Modes that never sign: no seed and no instance lock needed
+    fn read_only(&self) -> bool {
+        self.watch || self.task == TaskKind::Mbm
+    }
+
+    /// Account of `tx build` / `tx submit`, whose key stays offline
+    fn offline_account(&self) -> Result<Option<subxt::utils::AccountId32>> {
+        match &self.command {
+            Some(Command::Tx {
+                action: TxCommand::Build { account, .. },
+            }) => Ok(Some(account.clone())),
+            Some(Command::Tx {
+                action: TxCommand::Submit { file, .. },
+            }) => Ok(Some(UnsignedTx::load(file)?.account_id()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn log_rotation_policy(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes: self.log_max_size,
+            period: self.log_rotation,
+            keep: self.log_keep,
+        }
+    }
+
+    /// Build the Slack sink configuration, if enabled
+    fn slack_config(&self) -> Result<Option<SlackConfig>> {
+        let target = match (&self.slack_webhook, &self.slack_token) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Use either --slack-webhook or --slack-token, not both")
+            }
+            (Some(url), None) => SlackTarget::Webhook(url.clone()),
+            (None, Some(token)) => SlackTarget::Bot {
+                token: token.clone(),
+                channel: self.slack_channel.clone().unwrap_or_default(),
+            },
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(SlackConfig {
+            target,
+            explorer_url: Some(self.explorer_url.clone()),
+            dashboard_url: self.dashboard_url.clone(),
+            events: self.slack_events.clone(),
+        }))
+    }
+
+    /// Build the Twilio escalation configuration, if enabled
+    fn twilio_config(&self) -> Result<Option<TwilioConfig>> {
+        let Some(sid) = &self.twilio_sid else {
+            return Ok(None);
+        };
+        let auth_token = self
+            .twilio_token
+            .clone()
+            .context("--twilio-token (TWILIO_AUTH_TOKEN) is required with --twilio-sid")?;
+
+        Ok(Some(TwilioConfig {
+            account_sid: sid.clone(),
+            auth_token,
+            from: self.twilio_from.clone().unwrap_or_default(),
+            to: self.twilio_to.clone(),
+            mode: self.twilio_mode,
+        }))
+    }
+
+    /// Build the Teams sink configuration, if enabled
+    fn teams_config(&self) -> Option<TeamsConfig> {
+        self.teams_webhook.as_ref().map(|url| TeamsConfig {
+            webhook_url: url.clone(),
+            explorer_url: Some(self.explorer_url.clone()),
+            dashboard_url: self.dashboard_url.clone(),
+        })
+    }
+
+    /// Build the acknowledgement configuration, if the health server can receive them
+    fn ack_config(&self) -> Result<Option<AckConfig>> {
+        let pauses = self
+            .escalation_rules
+            .iter()
+            .any(|rule| rule.actions.contains(&Action::Pause));
+        let Some(addr) = self.health_addr else {
+            if self.ack_timeout_mins > 0 || pauses {
+                anyhow::bail!(
+                    "--ack-timeout-mins and `pause` escalations require --health-addr \
+                     to receive acknowledgements"
+                );
+            }
+            return Ok(None);
+        };
+        Ok(Some(AckConfig {
+            timeout: (self.ack_timeout_mins > 0)
+                .then(|| Duration::from_secs(self.ack_timeout_mins * 60)),
+            base_url: self
+                .ack_url
+                .clone()
+                .unwrap_or_else(|| format!("http://{}", addr)),
+        }))
+    }
+
+    /// Base URL for approval links, if the health server can receive them
+    fn approval_base_url(&self) -> Option<String> {
+        self.ack_url
+            .clone()
+            .or_else(|| self.health_addr.map(|addr| format!("http://{}", addr)))
+    }
+
+    /// Build the SMTP sink configuration, if enabled
+    fn email_config(&self) -> Result<Option<EmailConfig>> {
+        let Some(host) = &self.smtp_host else {
+            return Ok(None);
+        };
+
+        let from = self
+            .smtp_from
+            .as_deref()
+            .context("--smtp-from is required when --smtp-host is set")?
+            .parse()
+            .context("Invalid --smtp-from address")?;
+        if self.smtp_to.is_empty() {
+            anyhow::bail!("--smtp-to is required when --smtp-host is set");
+        }
+        let to = self
+            .smtp_to
+            .iter()
+            .map(|addr| addr.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .context("Invalid --smtp-to address")?;
+
+        Ok(Some(EmailConfig {
+            host: host.clone(),
+            port: self.smtp_port,
+            username: self.smtp_user.clone(),
+            password: self.smtp_password.clone(),
+            from,
+            to,
+        }))
+    }
+}
+
+/// Read the seed from `--seed-file`; the file contents are zeroized once copied
+/// into the secret
+fn read_seed_file(path: &Path) -> Result<SecretString, MigrationError> {
+    let contents = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+        MigrationError::InvalidSeed(format!("Failed to read {}: {}", path.display(), e))
+    })?);
+    let seed = contents.trim();
+    if seed.is_empty() {
+        return Err(MigrationError::InvalidSeed(format!(
+            "{} is empty",
+            path.display()
+        )));
+    }
+    Ok(SecretString::new(seed.to_string()))
+}
+
+/// Ask for the seed on the terminal, without echo, when none was configured;
+/// `None` when not attached to a terminal (or nothing was entered)
+fn prompt_seed() -> Result<Option<SecretString>, MigrationError> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    let input = Zeroizing::new(
+        rpassword::prompt_password("Signer seed (mnemonic or 0x hex, input hidden): ")
+            .map_err(|e| MigrationError::InvalidSeed(format!("Failed to read seed: {}", e)))?,
+    );
+    let seed = input.trim();
+    Ok((!seed.is_empty()).then(|| SecretString::new(seed.to_string())))
+}
+
+/// Connect to `url`, sharing one RPC client between the legacy methods (dry
+/// runs, nonces, pool) and the subxt client
+async fn connect(
+    url: &str,
+) -> Result<(
+    RpcClient,
+    LegacyRpcMethods<PolkadotConfig>,
+    OnlineClient<PolkadotConfig>,
+)> {
+    info!("Connecting to {}", url);
+
+    // Create RPC client for dry_run calls
+    let rpc_client = RpcClient::from_url(url).await.map_err(|e| {
+        MigrationError::ConnectionFailed(format!("Failed to create RPC client: {}", e))
+    })?;
+    // Every call is counted against the endpoint's --rpc-quota
+    let rpc_client = CountingRpc::wrap(rpc_client, url);
+    let rpc = LegacyRpcMethods::<PolkadotConfig>::new(rpc_client.clone());
+
+    // Create OnlineClient from the same RPC client
+    let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone())
+        .await
+        .map_err(|e| {
+            MigrationError::ConnectionFailed(format!("Failed to connect to Westend: {}", e))
+        })?;
+    Ok((rpc_client, rpc, client))
+}
+
+/// A finalized tx whose migrated items/bytes are attributed on the next status fetch
+struct PendingProgress {
+    status_before: MigrationStatus,
+    tx_hash: Option<H256>,
+    fee: u128,
 }
 
 struct MigrationBot {
     client: OnlineClient<PolkadotConfig>,
     rpc: LegacyRpcMethods<PolkadotConfig>,
     raw_rpc: RpcClient,
-    signer: Keypair,
+    /// Index into `[rpc_url, fallback_rpc_urls...]` of the current endpoint
+    endpoint: usize,
+    /// Signer accounts; the active one signs (--rotate-seed-file adds more)
+    signer: SignerRing,
+    /// Next nonce of each signer account, counted locally
+    nonces: NonceManager,
+    /// --pipeline: finality of txs confirmed at best-block inclusion, as
+    /// their background watchers report it
+    trailing: (
+        tokio::sync::mpsc::UnboundedSender<Trailing>,
+        Mutex<tokio::sync::mpsc::UnboundedReceiver<Trailing>>,
+    ),
+    /// --pipeline: txs whose background watchers have not reported yet
+    unsettled: Mutex<Unsettled>,
+    /// `MigrationProcess` read at the inclusion block of the tx in flight,
+    /// used as the next witness once its finalized event confirms it
+    next_witness: NextWitness,
     config: Cli,
+    history: Option<History>,
+    journal: Option<Journal>,
+    counters: Option<CounterFile>,
+    events: Option<EventLog>,
+    push: Option<EventHub>,
+    dashboard: Option<Arc<Dashboard>>,
+    timeline: Timeline,
+    health: Arc<Health>,
+    /// What the run loop is doing, published in the health snapshot
+    state: Mutex<StateMachine>,
+    instance_id: String,
     dry_run_supported: AtomicBool,
+    /// Reads and nonces follow the finalized head (set once forks are detected)
+    finalized_basis: AtomicBool,
+    /// The work the run loop drives (state-trie migration)
+    task: TrieMigration,
+    constants: ConstantsCache,
+    /// Block time and finalization timeout of the target chain
+    timing: Timing,
+    /// Relay chain for inclusion checks (--relay-rpc-url)
+    relay: Option<RelayChain>,
+    /// Second endpoint the task state is cross-checked against (--verify-rpc-url)
+    verifier: Option<Verifier>,
+    /// Approval queue each tx is held in before signing (--approval-queue)
+    approvals: Option<ApprovalQueue>,
+    /// Light client finality is checked against (--trusted-finality)
+    trusted: Option<TrustedFinality>,
+    /// Latest `state_trieMigrationStatus` count of keys left, for the ETA and shutdown report
+    remaining_keys: RemainingKeys,
+    /// Latest session summary, for the shutdown report
+    session_summary: Mutex<Option<String>>,
     shutdown: CancellationToken,
 }
 
 impl MigrationBot {
     async fn new(config: Cli) -> Result<Self> {
-        info!("Connecting to {}", config.rpc_url);
-
-        // Create RPC client for dry_run calls
-        let rpc_client = RpcClient::from_url(&config.rpc_url).await.map_err(|e| {
-            MigrationError::ConnectionFailed(format!("Failed to create RPC client: {}", e))
-        })?;
-        let rpc = LegacyRpcMethods::<PolkadotConfig>::new(rpc_client.clone());
-
-        // Create OnlineClient from the same RPC client
-        let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone())
-            .await
-            .map_err(|e| {
-                MigrationError::ConnectionFailed(format!("Failed to connect to Westend: {}", e))
-            })?;
+        let (rpc_client, rpc, client) = connect(&config.rpc_url).await?;
 
         let genesis = client.genesis_hash();
         info!("Connected to chain with genesis: {:?}", genesis);
 
+        let chain = parachain::detect(&client).await?;
+        let timing = Timing::read(&client, chain);
+        info!(
+            "Target is a {} ({}s blocks, finalization timeout {}s)",
+            chain,
+            timing.block_time.as_secs(),
+            timing.finalization_timeout.as_secs()
+        );
+        let relay = match (&config.relay_rpc_url, chain) {
+            (None, _) => None,
+            (Some(url), ChainKind::Parachain { para_id }) => {
+                info!("Verifying inclusion via relay chain {}", url);
+                Some(RelayChain::connect(url, para_id).await?)
+            }
+            (Some(_), ChainKind::Relay) => {
+                anyhow::bail!("--relay-rpc-url is set, but the target is not a parachain")
+            }
+        };
+        let verifier = match &config.verify_rpc_url {
+            Some(url) => {
+                let (_, rpc, client) = connect(url).await?;
+                info!("Cross-checking migration state against {}", url);
+                Some(Verifier::new(url, rpc, client, genesis)?)
+            }
+            None => None,
+        };
+        let shutdown = CancellationToken::new();
+        let trusted = match (&config.trusted_finality, &config.trusted_para_spec, chain) {
+            (None, _, _) => None,
+            (Some(_), None, ChainKind::Parachain { .. }) => anyhow::bail!(
+                "The target is a parachain: --trusted-finality needs --trusted-para-spec too"
+            ),
+            (Some(_), Some(_), ChainKind::Relay) => {
+                anyhow::bail!("--trusted-para-spec is set, but the target is not a parachain")
+            }
+            (Some(relay_spec), para_spec, _) => {
+                info!("Starting light client from {}", relay_spec.display());
+                Some(
+                    TrustedFinality::start(relay_spec, para_spec.as_deref(), shutdown.clone())
+                        .await?,
+                )
+            }
+        };
+        let approvals = match &config.run.approval_queue {
+            Some(dir) => {
+                info!("Holding each tx for approval in {}", dir.display());
+                Some(ApprovalQueue::open(dir)?)
+            }
+            None => None,
+        };
+
         // Parse the seed from SecretString (zeroizes on drop)
         // We expose the secret briefly only during parsing, then it's protected in the Keypair
-        let signer = {
-            let seed_str = config.seed.expose_secret();
-            if seed_str.starts_with("0x") {
-                // Hex seed - use zeroizing buffer
-                let seed_bytes = hex::decode(seed_str.trim_start_matches("0x"))
-                    .map_err(|e| MigrationError::InvalidSeed(format!("Invalid hex: {}", e)))?;
-                let seed_array: [u8; 32] = seed_bytes.try_into().map_err(|_| {
-                    MigrationError::InvalidSeed("Seed must be 32 bytes".to_string())
-                })?;
-                // Note: seed_array will be zeroized by scope exit
-                Keypair::from_secret_key(seed_array)
-                    .map_err(|e| MigrationError::InvalidSeed(format!("Invalid seed: {:?}", e)))?
-            } else {
-                // Mnemonic phrase
-                let mnemonic = Mnemonic::parse(seed_str).map_err(|e| {
-                    MigrationError::InvalidSeed(format!("Invalid mnemonic: {:?}", e))
-                })?;
-                Keypair::from_phrase(&mnemonic, None).map_err(|e| {
-                    MigrationError::InvalidSeed(format!("Failed to derive: {:?}", e))
-                })?
+        let signer: Box<dyn TxSigner> = if let Some(account) = config.offline_account()? {
+            Box::new(OfflineAccount(account))
+        } else if let Some(url) = &config.remote_signer {
+            info!("Signing through remote signer {}", url);
+            let remote = RemoteSigner::connect(url, config.remote_signer_token.clone())
+                .await
+                .map_err(|e| MigrationError::InvalidSeed(format!("{:#}", e)))?;
+            Box::new(remote)
+        } else if config.ledger {
+            let ledger = LedgerSigner::connect(
+                config.ledger_account,
+                &config.ledger_proof_api,
+                &config.ledger_chain,
+                Duration::from_secs(config.ledger_timeout_secs),
+            )
+            .await
+            .map_err(|e| MigrationError::InvalidSeed(format!("{:#}", e)))?;
+            Box::new(ledger)
+        } else if let Some(key) = &config.vault_key {
+            let vault_error =
+                |e: anyhow::Error| MigrationError::InvalidSeed(format!("{}: {:#}", key, e));
+            let vault = Vault::new(
+                config.vault_addr.as_deref().unwrap_or_default(),
+                config.vault_token.clone().ok_or_else(|| {
+                    MigrationError::InvalidSeed("--vault-key requires VAULT_TOKEN".to_string())
+                })?,
+            )
+            .map_err(vault_error)?;
+            info!("Signing with Vault {}", key);
+            match key {
+                VaultKey::Transit { mount, name } => Box::new(
+                    VaultSigner::connect(vault, mount, name)
+                        .await
+                        .map_err(vault_error)?,
+                ),
+                VaultKey::Kv { mount, path, field } => {
+                    let seed = vault
+                        .read_seed(mount, path, field)
+                        .await
+                        .map_err(vault_error)?;
+                    Box::new(signer_from_seed(seed.expose_secret())?)
+                }
             }
+        } else if let Some(path) = &config.keystore {
+            Box::new(read_keystore(
+                path,
+                config.keystore_password_file.as_deref(),
+            )?)
+        } else {
+            let file_seed = config
+                .seed_file
+                .as_deref()
+                .map(read_seed_file)
+                .transpose()?;
+            let sourced = match &config.seed_source {
+                Some(source) => {
+                    info!("Reading the seed from {}", source);
+                    let seed = source
+                        .fetch()
+                        .await
+                        .map_err(|e| MigrationError::InvalidSeed(format!("{}: {:#}", source, e)));
+                    Some(seed?)
+                }
+                None => None,
+            };
+            let prompted = match (&file_seed, &sourced, &config.seed) {
+                (None, None, None) => prompt_seed()?,
+                _ => None,
+            };
+            let seed = file_seed
+                .as_ref()
+                .or(sourced.as_ref())
+                .or(config.seed.as_ref())
+                .or(prompted.as_ref())
+                .ok_or_else(|| {
+                    MigrationError::InvalidSeed(
+                        "SIGNER_SEED, --seed-file, --seed-source, --keystore, --remote-signer, \
+                         --ledger or --vault-key is required"
+                            .to_string(),
+                    )
+                })?;
+            Box::new(signer_from_seed(seed.expose_secret())?)
         };
+        let mut signers = vec![signer];
+        for path in &config.rotate_seed_file {
+            let extra = signer_from_seed(read_seed_file(path)?.expose_secret())?;
+            let account = TxSigner::account_id(&extra);
+            if signers.iter().any(|s| s.account_id() == account) {
+                return Err(MigrationError::InvalidSeed(format!(
+                    "{}: account {} is already a signer",
+                    path.display(),
+                    account
+                ))
+                .into());
+            }
+            signers.push(Box::new(extra));
+        }
+        let signer = SignerRing::new(signers);
 
-        let account_id = <Keypair as Signer<PolkadotConfig>>::account_id(&signer);
+        let account_id = signer.account_id();
         info!("Using account: {}", account_id);
+        for account in signer.accounts().skip(1) {
+            info!("Rotating to: {}", account);
+        }
+        if let Some(sponsor) = &config.sponsor {
+            info!("Sponsor: {}", sponsor);
+        }
+
+        let instance_id = config.instance_id.clone().unwrap_or_else(|| {
+            let account = account_id.to_string();
+            let genesis = hex::encode(genesis.as_bytes());
+            format!("{}@0x{}", &account[..8.min(account.len())], &genesis[..8])
+        });
+
+        let history = match &config.history_db {
+            Some(path) => {
+                info!("Recording history (instance {})", instance_id);
+                Some(History::open(path)?)
+            }
+            None => None,
+        };
+        let journal = match &config.journal_db {
+            Some(path) => {
+                let journal = Journal::open(path)?;
+                let entries = journal.entries()?;
+                info!(
+                    "Journaling submissions to {} ({} recorded)",
+                    path.display(),
+                    entries.len()
+                );
+                Some(journal)
+            }
+            None => None,
+        };
+        let counters = config
+            .state_file
+            .as_deref()
+            .map(|path| CounterFile::new(path, &instance_id));
+        let timeline = Timeline::new(config.incident_timeline);
+        let health = Arc::new(Health::new(Duration::from_secs(config.health_stale_secs)));
+        let events = match &config.event_log {
+            Some(path) => {
+                info!("Recording events to {}", path.display());
+                Some(EventLog::open(path)?)
+            }
+            None => None,
+        };
+        let witness = match &config.run.witness_file {
+            Some(_) if config.verify_proofs => {
+                anyhow::bail!("--verify-proofs checks the live witness; drop --witness-file")
+            }
+            Some(path) => {
+                let snapshot = WitnessSnapshot::load(path)?;
+                let spec_version = client.runtime_version().spec_version;
+                info!(
+                    "Using witness from {} (block {:?}, captured {})",
+                    path.display(),
+                    snapshot.block,
+                    snapshot.captured_at
+                );
+                if snapshot.spec_version != spec_version {
+                    warn!(
+                        "Witness was captured on spec version {}, runtime is now {}",
+                        snapshot.spec_version, spec_version
+                    );
+                }
+                Some(snapshot.decode(&client.metadata())?)
+            }
+            None => None,
+        };
+        let push = config.ws_addr.map(|_| EventHub::new());
+        let dashboard = config.tui.then(|| Arc::new(Dashboard::new()));
+        let (trailing_tx, trailing_rx) = tokio::sync::mpsc::unbounded_channel();
 
         Ok(Self {
             client,
             rpc,
             raw_rpc: rpc_client,
+            endpoint: 0,
             signer,
+            nonces: NonceManager::new(),
+            trailing: (trailing_tx, Mutex::new(trailing_rx)),
+            unsettled: Mutex::new(Unsettled::new()),
+            next_witness: NextWitness::new(),
             config,
+            history,
+            journal,
+            counters,
+            events,
+            push,
+            dashboard,
+            timeline,
+            health,
+            state: Mutex::new(StateMachine::new(Instant::now())),
+            instance_id,
             dry_run_supported: AtomicBool::new(true), // Assume supported until proven otherwise
-            shutdown: CancellationToken::new(),
+            finalized_basis: AtomicBool::new(false),
+            task: TrieMigration::new(witness),
+            constants: ConstantsCache::new(),
+            timing,
+            relay,
+            verifier,
+            approvals,
+            trusted,
+            remaining_keys: RemainingKeys::default(),
+            session_summary: Mutex::new(None),
+            shutdown,
         })
     }
 
-    /// Query current migration task from storage
-    /// Returns both the raw Value (for tx) and parsed status (for display)
-    async fn get_migration_task(&self) -> Result<Option<(Value<()>, MigrationStatus)>> {
-        // Query MigrationProcess - this is what we pass to continue_migrate
-        let progress_query =
-            subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+    /// Fetch the task's state (MigrationProcess) at the read block
+    async fn fetch_state(&self) -> Result<Option<TrieState>> {
+        self.fetch_state_at(self.read_block().await?).await
+    }
+
+    async fn fetch_state_at(&self, at: H256) -> Result<Option<TrieState>> {
+        let state = self.task.fetch(&self.client, at).await?;
+        if state.is_none() {
+            info!(
+                "No {} progress found - migration may not be active",
+                self.task.name()
+            );
+        }
+        Ok(state)
+    }
 
-        let task_thunk = self
+    /// The pallet's `AutoLimits` (set while an unsigned migration runs)
+    async fn get_auto_limits(&self) -> Result<Option<MigrationLimits>> {
+        let query = subxt::dynamic::storage("StateTrieMigration", "AutoLimits", vec![]);
+        let thunk = self
             .client
             .storage()
-            .at_latest()
-            .await?
-            .fetch(&progress_query)
+            .at(self.read_block().await?)
+            .fetch(&query)
             .await?;
-
-        match task_thunk {
-            Some(thunk) => {
-                // Get the decoded value for inspection
-                let decoded = thunk.to_value()?;
-
-                // Parse status for display
-                let status = parse_migration_status(&decoded);
-
-                // Convert Value<TypeId> to Value<()> for use in transaction
-                // This is the key - we pass the queried value directly like TypeScript does
-                let witness_task = decoded.map_context(|_| ());
-
-                Ok(Some((witness_task, status)))
-            }
-            None => {
-                info!("No migration progress found - migration may not be active");
-                Ok(None)
-            }
+        match thunk {
+            Some(thunk) => Ok(MigrationLimits::from_option_value(&thunk.to_value()?)
+                .map_err(MigrationError::InvalidLimits)?),
+            None => Ok(None),
         }
     }
 
     /// Set SignedMigrationMaxLimits on chain (requires controller permission)
-    async fn set_max_limits(&self, size: u32, item: u32) -> Result<()> {
-        let limits = Value::named_composite([
-            ("size", Value::u128(size as u128)),
-            ("item", Value::u128(item as u128)),
-        ]);
-
-        let tx = subxt::dynamic::tx("StateTrieMigration", "set_signed_max_limits", vec![limits]);
+    async fn set_max_limits(&self, limits: MigrationLimits) -> Result<()> {
+        let tx = subxt::dynamic::tx(
+            "StateTrieMigration",
+            "set_signed_max_limits",
+            vec![limits.to_value()],
+        );
 
         let signed_tx = self
-            .client
-            .tx()
-            .create_signed(&tx, &self.signer, Default::default())
+            .create_signed(&tx)
             .await
             .context("Failed to create set_signed_max_limits tx")?;
+        // Used outside the migration pipeline: read the next nonce afresh
+        self.nonces.resync(&self.signer.account_id());
 
         let mut progress = signed_tx
             .submit_and_watch()
@@ -274,7 +1740,7 @@ impl MigrationBot {
         info!("Monitoring account nonce for pending tx finalization...");
 
         // Get current nonce
-        let account_id = <Keypair as Signer<PolkadotConfig>>::account_id(&self.signer);
+        let account_id = self.signer.account_id();
         let initial_nonce = match self.get_account_nonce(&account_id).await {
             Ok(n) => n,
             Err(_) => {
@@ -290,37 +1756,107 @@ impl MigrationBot {
         info!("Current nonce: {}, waiting for change...", initial_nonce);
 
         // Poll every block time until nonce changes or timeout
-        for i in 0..PENDING_TX_TIMEOUT_ITERATIONS {
-            tokio::time::sleep(Duration::from_secs(BLOCK_TIME_SECS)).await;
-
-            match self.get_account_nonce(&account_id).await {
-                Ok(new_nonce) if new_nonce != initial_nonce => {
-                    info!(
-                        "Nonce changed: {} -> {}, pending tx finalized!",
-                        initial_nonce, new_nonce
-                    );
-                    return;
-                }
-                Ok(_) => {
-                    if i % 5 == 4 {
-                        info!(
-                            "Still waiting for pending tx... ({}s)",
-                            (i + 1) as u64 * BLOCK_TIME_SECS
-                        );
-                    }
-                }
-                Err(e) => {
-                    warn!("Nonce query failed: {:?}", e);
-                }
+        match pool::wait_for_nonce_change(
+            initial_nonce,
+            self.timing.block_time,
+            self.timing.pending_polls(),
+            || self.get_account_nonce(&account_id),
+        )
+        .await
+        {
+            NonceWait::Changed { from, to } => {
+                info!("Nonce changed: {} -> {}, pending tx finalized!", from, to)
             }
+            NonceWait::TimedOut => warn!("Timeout waiting for pending tx, proceeding anyway..."),
+        }
+    }
+
+    /// Sleep for `duration`, returning early once shutdown is requested or
+    /// --deadline is reached
+    async fn idle(&self, duration: Duration) {
+        let duration = until_deadline(duration, self.config.run.deadline, chrono::Local::now());
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => {}
+            _ = self.shutdown.cancelled() => {}
+        }
+    }
+
+    /// URL of the endpoint currently connected to
+    fn endpoint_url(&self) -> &str {
+        self.endpoint_at(self.endpoint)
+    }
+
+    /// URL at `index` in `[rpc_url, fallback_rpc_urls...]`
+    fn endpoint_at(&self, index: usize) -> &str {
+        match index {
+            0 => &self.config.rpc_url,
+            n => &self.config.fallback_rpc_urls[n - 1],
+        }
+    }
+
+    /// Reconnect to the next endpoint in `[rpc_url, fallback_rpc_urls...]`
+    /// (the same one again when there are no fallbacks)
+    async fn switch_endpoint(&mut self) -> Result<()> {
+        let next = (self.endpoint + 1) % (1 + self.config.fallback_rpc_urls.len());
+        self.switch_to(next).await
+    }
+
+    /// Reconnect to the endpoint at `next`
+    async fn switch_to(&mut self, next: usize) -> Result<()> {
+        let url = self.endpoint_at(next).to_string();
+        let (raw_rpc, rpc, client) = connect(&url).await?;
+        if client.genesis_hash() != self.client.genesis_hash() {
+            anyhow::bail!(
+                "{} serves a different chain (genesis {:?})",
+                url,
+                client.genesis_hash()
+            );
         }
+        self.raw_rpc = raw_rpc;
+        self.rpc = rpc;
+        self.client = client;
+        self.endpoint = next;
+        Ok(())
+    }
 
-        warn!("Timeout waiting for pending tx, proceeding anyway...");
+    /// Move off an endpoint whose daily --rpc-quota is used up, to the next
+    /// one with budget left (stays put when there is none)
+    async fn shift_load(&mut self) {
+        let count = 1 + self.config.fallback_rpc_urls.len();
+        let Some(next) = (1..count)
+            .map(|i| (self.endpoint + i) % count)
+            .find(|i| !budget::exhausted(self.endpoint_at(*i)))
+        else {
+            debug!(
+                "No endpoint has RPC budget left, staying on {}",
+                self.endpoint_url()
+            );
+            return;
+        };
+        let from = self.endpoint_url().to_string();
+        if let Err(e) = self.switch_to(next).await {
+            warn!("Failed to switch endpoint: {:#}", e);
+            return;
+        }
+        let msg = format!(
+            "{} used up its daily quota; moved to {}",
+            from,
+            self.endpoint_url()
+        );
+        warn!("{}", msg);
+        send_notification(Severity::Warning, "RPC Failover", &msg);
     }
 
     /// Get account nonce using system_accountNextIndex RPC
-    /// This includes pending transactions, unlike storage queries
+    /// This includes pending transactions, unlike storage queries.
+    /// On the finalized basis the nonce is read from finalized state instead.
     async fn get_account_nonce(&self, account_id: &subxt::utils::AccountId32) -> Result<u32> {
+        if self.read_basis() == ReadBasis::Finalized {
+            return self
+                .stored_nonce(account_id, self.read_block().await?)
+                .await;
+        }
+
         // Use RPC call which includes pending transactions
         let params = rpc_params![account_id.to_string()];
         let nonce: u32 = self
@@ -331,91 +1867,215 @@ impl MigrationBot {
         Ok(nonce)
     }
 
-    /// Query SignedMigrationMaxLimits from chain
-    async fn get_max_limits(&self) -> Result<Option<(u32, u32)>> {
-        let limits_query =
-            subxt::dynamic::storage("StateTrieMigration", "SignedMigrationMaxLimits", vec![]);
-
-        let limits_thunk = self
-            .client
-            .storage()
-            .at_latest()
-            .await?
-            .fetch(&limits_query)
-            .await?;
-
-        match limits_thunk {
-            Some(thunk) => {
-                let decoded = thunk.to_value()?;
-                let size = decoded.at("size").and_then(|v| v.as_u128()).unwrap_or(0) as u32;
-                let item = decoded.at("item").and_then(|v| v.as_u128()).unwrap_or(0) as u32;
-                Ok(Some((size, item)))
-            }
-            None => Ok(None),
+    /// Nonce to sign `account_id`'s next tx with: the local count, or
+    /// [`Self::get_account_nonce`] when there is none yet
+    async fn next_nonce(&self, account_id: &subxt::utils::AccountId32) -> Result<u32> {
+        if let Some(nonce) = self.nonces.get(account_id) {
+            return Ok(nonce);
         }
+        let nonce = self.get_account_nonce(account_id).await?;
+        self.nonces.seed(account_id, nonce);
+        Ok(nonce)
     }
 
-    /// Check account balance
-    async fn check_balance(&self) -> Result<u128> {
-        let account_id = <Keypair as Signer<PolkadotConfig>>::account_id(&self.signer);
-
-        let balance_query = subxt::dynamic::storage(
+    /// Account nonce in the state of block `at` (ignores the pool)
+    async fn stored_nonce(&self, account_id: &subxt::utils::AccountId32, at: H256) -> Result<u32> {
+        let account_query = subxt::dynamic::storage(
             "System",
             "Account",
-            vec![Value::from_bytes(AsRef::<[u8]>::as_ref(&account_id))],
+            vec![Value::from_bytes(AsRef::<[u8]>::as_ref(account_id))],
         );
+        let account_info = self.client.storage().at(at).fetch(&account_query).await?;
+        let nonce = match account_info {
+            Some(info) => info
+                .to_value()?
+                .at("nonce")
+                .and_then(|v| v.as_u128())
+                .unwrap_or(0),
+            None => 0,
+        };
+        Ok(nonce as u32)
+    }
 
-        let account_info = self
-            .client
+    /// Account nonce at the finalized head
+    async fn finalized_nonce(&self, account_id: &subxt::utils::AccountId32) -> Result<u32> {
+        let head = self.rpc.chain_get_finalized_head().await?;
+        self.stored_nonce(account_id, head).await
+    }
+
+    fn read_basis(&self) -> ReadBasis {
+        if self.finalized_basis.load(Ordering::Relaxed) {
+            ReadBasis::Finalized
+        } else {
+            ReadBasis::Best
+        }
+    }
+
+    /// Block that storage reads are made at, per the current read basis
+    async fn read_block(&self) -> Result<H256> {
+        match self.read_basis() {
+            ReadBasis::Best => self
+                .rpc
+                .chain_get_block_hash(None)
+                .await?
+                .context("Node returned no best block hash"),
+            ReadBasis::Finalized => Ok(self.rpc.chain_get_finalized_head().await?),
+        }
+    }
+
+    /// Sample the best head and check whether the one seen last time was
+    /// reorged out; switches to the finalized basis once forks are persistent
+    async fn observe_forks(&self, forks: &mut ForkMonitor) -> Result<()> {
+        let best_hash = self
+            .rpc
+            .chain_get_block_hash(None)
+            .await?
+            .context("Node returned no best block hash")?;
+        let best_number = self
+            .rpc
+            .chain_get_header(Some(best_hash))
+            .await?
+            .context("Node returned no best header")?
+            .number;
+
+        let diverged = match forks.last_best() {
+            Some((number, hash)) if number <= best_number => self
+                .rpc
+                .chain_get_block_hash(Some(number.into()))
+                .await?
+                .is_some_and(|canonical| canonical != hash),
+            // The best head went backwards: the previous one was on a dropped fork
+            Some(_) => true,
+            None => false,
+        };
+        if diverged {
+            debug!("Best block from the previous sample was reorged out");
+        }
+
+        if forks.record((best_number, best_hash), diverged) {
+            self.finalized_basis.store(true, Ordering::Relaxed);
+            let msg = format!(
+                "Best chain diverged in {:.0}% of recent samples; reading state and nonces \
+                 at the finalized head from now on",
+                forks.divergence_ratio() * 100.0
+            );
+            warn!("Persistent forks detected: {}", msg);
+            send_notification(Severity::Warning, "Forks Detected", &msg);
+            self.health.update(|h| h.read_basis = forks.basis());
+        }
+        Ok(())
+    }
+
+    /// Query SignedMigrationMaxLimits from chain
+    async fn get_max_limits(&self) -> Result<Option<MigrationLimits>> {
+        let limits_query =
+            subxt::dynamic::storage("StateTrieMigration", "SignedMigrationMaxLimits", vec![]);
+
+        let limits_thunk = self
+            .client
+            .storage()
+            .at(self.read_block().await?)
+            .fetch(&limits_query)
+            .await?;
+
+        match limits_thunk {
+            Some(thunk) => {
+                let limits = MigrationLimits::from_value(&thunk.to_value()?)
+                    .map_err(MigrationError::InvalidLimits)?;
+                Ok(Some(limits))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Account migrations are dispatched from: the signer, or the account
+    /// it is a proxy for (--proxy-for)
+    fn origin_account(&self) -> subxt::utils::AccountId32 {
+        self.config
+            .proxy_for
+            .clone()
+            .unwrap_or_else(|| self.signer.account_id())
+    }
+
+    /// `--proxy-for`: fail unless every signer account can dispatch for that
+    /// account right away
+    async fn check_proxy(&self, real: &subxt::utils::AccountId32) -> Result<()> {
+        let query = subxt::dynamic::storage(
+            "Proxy",
+            "Proxies",
+            vec![Value::from_bytes(AsRef::<[u8]>::as_ref(real))],
+        );
+        let proxies = match self
+            .client
             .storage()
-            .at_latest()
+            .at(self.read_block().await?)
+            .fetch(&query)
             .await?
+        {
+            Some(thunk) => proxy::decode_proxies(thunk.encoded())?,
+            None => Vec::new(),
+        };
+        for delegate in self.signer.accounts() {
+            proxy::check_delegate(&proxies, delegate, real)
+                .map_err(|e| MigrationError::InvalidSeed(format!("--proxy-for: {}", e)))?;
+        }
+        info!(
+            "Dispatching as {} through its proxy {}",
+            real,
+            self.signer.account_id()
+        );
+        Ok(())
+    }
+
+    /// Decoded balances of the origin account (`None` if the account doesn't exist)
+    async fn account_balance(&self) -> Result<Option<AccountBalance>> {
+        let account_id = self.origin_account();
+
+        let balance_query = subxt::dynamic::storage(
+            "System",
+            "Account",
+            vec![Value::from_bytes(AsRef::<[u8]>::as_ref(&account_id))],
+        );
+
+        let account_info = self
+            .client
+            .storage()
+            .at(self.read_block().await?)
             .fetch(&balance_query)
             .await?;
 
         match account_info {
             Some(info) => {
-                let value = info.to_value()?;
-                if let Some(data) = value.at("data") {
-                    if let Some(free) = data.at("free") {
-                        if let Some(balance) = free.as_u128() {
-                            return Ok(balance);
-                        }
-                    }
-                }
-                Ok(0)
+                let value = info
+                    .to_value()
+                    .map_err(|e| MigrationError::AccountDecode(e.to_string()))?;
+                let balance = AccountBalance::decode(&value, self.config.account_layout)
+                    .map_err(MigrationError::AccountDecode)?;
+                Ok(Some(balance))
             }
-            None => Ok(0),
+            None => Ok(None),
         }
     }
 
-    /// Get pending extrinsics from the transaction pool (requires unsafe RPC)
-    async fn get_pending_extrinsics(&self) -> Result<Vec<String>> {
-        use subxt::backend::rpc::RpcParams;
-
-        let result: Vec<String> = self
-            .raw_rpc
-            .request("author_pendingExtrinsics", RpcParams::new())
-            .await
-            .context("Failed to get pending extrinsics (requires --rpc-methods=unsafe)")?;
-
-        Ok(result)
+    /// Check account (free) balance; an account that doesn't exist has none
+    async fn check_balance(&self) -> Result<u128> {
+        Ok(self.account_balance().await?.map_or(0, |b| b.free))
     }
 
-    /// Remove a specific extrinsic from the pool by its hash (requires unsafe RPC)
-    async fn remove_extrinsic(&self, ext_hash: &str) -> Result<Vec<String>> {
-        use subxt::backend::rpc::RpcParams;
-
-        let mut params = RpcParams::new();
-        params.push(vec![ext_hash])?;
-
-        let result: Vec<String> = self
-            .raw_rpc
-            .request("author_removeExtrinsic", params)
-            .await
-            .context("Failed to remove extrinsic")?;
+    /// Deposit held for a signed migration of `items` items (base + per item)
+    fn signed_deposit(&self, items: u32) -> Result<u128> {
+        self.constants
+            .get(&self.client)
+            .signed_deposit(items)
+            .context("StateTrieMigration deposit constants not in runtime metadata")
+    }
 
-        Ok(result)
+    /// Balances::ExistentialDeposit from the runtime metadata
+    fn existential_deposit(&self) -> Result<u128> {
+        self.constants
+            .get(&self.client)
+            .existential_deposit
+            .context("Balances::ExistentialDeposit not in runtime metadata")
     }
 
     /// Show status information and pending transactions
@@ -423,7 +2083,11 @@ impl MigrationBot {
         info!("=== Migration Status ===");
 
         // Get migration task
-        if let Some((_, status)) = self.get_migration_task().await? {
+        if let Some(TrieState {
+            witness: task,
+            status,
+        }) = self.fetch_state().await?
+        {
             info!(
                 "Top trie:   {} ({} items)",
                 if status.top_complete {
@@ -433,6 +2097,20 @@ impl MigrationBot {
                 },
                 status.top_items
             );
+            if let Some(percent) =
+                keyspace::position_percent(&task).filter(|_| !status.top_complete)
+            {
+                info!(
+                    "Top trie position: ~{:.1}% of the keyspace (see `remaining` for a sampled estimate)",
+                    percent
+                );
+            }
+            if let Some(name) = self.current_storage_item(&task) {
+                info!("Migrating: {}", name);
+            }
+            if let Some(key) = &status.top_last_key {
+                info!("Top last key:   {}", key);
+            }
             info!(
                 "Child trie: {} ({} items)",
                 if status.child_complete {
@@ -442,37 +2120,28 @@ impl MigrationBot {
                 },
                 status.child_items
             );
+            if let Some(key) = &status.child_last_key {
+                // While a child trie is in progress the top key is that trie's root key
+                info!("Child last key: {} (child root: top last key)", key);
+            }
             info!("Total size migrated: {} bytes", status.size);
         } else {
             warn!("No migration progress found");
         }
-
-        // Get balance
-        let balance = self.check_balance().await?;
-        let balance_wnd = balance as f64 / 1_000_000_000_000.0;
-        info!("Account balance: {:.4} WND", balance_wnd);
-
-        // Get nonce
-        let account_id = <Keypair as Signer<PolkadotConfig>>::account_id(&self.signer);
-        let nonce = self.get_account_nonce(&account_id).await?;
-        info!("Account nonce: {}", nonce);
+        info!("Read basis: {}", self.read_basis());
+        info!("Chain constants: {}", self.constants.get(&self.client));
+        self.show_account().await?;
 
         // Get pending extrinsics
         info!("\n=== Transaction Pool ===");
-        match self.get_pending_extrinsics().await {
+        match pool::pending_extrinsics(&self.raw_rpc).await {
             Ok(pending) => {
                 if pending.is_empty() {
                     info!("No pending transactions in pool");
                 } else {
                     info!("Pending transactions: {}", pending.len());
                     for (i, ext) in pending.iter().enumerate() {
-                        // Show first 20 chars of each extrinsic
-                        let preview = if ext.len() > 40 {
-                            format!("{}...{}", &ext[..20], &ext[ext.len() - 16..])
-                        } else {
-                            ext.clone()
-                        };
-                        debug!("  [{}] {}", i, preview);
+                        info!("  [{}] {:?} {}", i, ext.hash(), self.describe_pending(ext));
                     }
                 }
             }
@@ -487,17 +2156,481 @@ impl MigrationBot {
         Ok(())
     }
 
-    /// Clear all pending extrinsics from our account
-    async fn clear_pending_transactions(&self) -> Result<usize> {
+    /// Show the signer account: address, balances and nonce
+    async fn show_account(&self) -> Result<()> {
+        let account_id = self.signer.account_id();
+        info!("Account: {}", account_id);
+        if let Some(real) = &self.config.proxy_for {
+            info!("Proxy for: {} (balances and deposits below are its)", real);
+        }
+
+        // Get balance
+        let balance = match self.account_balance().await {
+            Ok(Some(balance)) => balance,
+            Ok(None) => {
+                warn!("Account does not exist on chain (never funded)");
+                AccountBalance::default()
+            }
+            Err(e) => {
+                warn!("Could not read balance: {}", e);
+                AccountBalance::default()
+            }
+        };
+        let balance_wnd = balance.free as f64 / 1_000_000_000_000.0;
+        info!("Account balance: {:.4} WND", balance_wnd);
+        match self.existential_deposit() {
+            Ok(ed) => info!(
+                "Reserved: {:.4} WND, frozen: {:.4} WND, transferable: {:.4} WND ({} layout)",
+                units_to_wnd(balance.reserved),
+                units_to_wnd(balance.frozen),
+                units_to_wnd(balance.transferable(ed)),
+                balance.layout
+            ),
+            Err(e) => warn!("Could not read existential deposit: {}", e),
+        }
+
+        // Get nonce
+        let nonce = self.get_account_nonce(&account_id).await?;
+        info!("Account nonce: {}", nonce);
+        for account in self.signer.accounts().skip(1) {
+            let nonce = self.get_account_nonce(account).await?;
+            info!("Rotates to: {} (nonce {})", account, nonce);
+        }
+
+        // Deposit reserved by each continue_migrate at the limits `run` would use
+        let max = match self.get_max_limits().await {
+            Ok(max) => max,
+            Err(e) => {
+                warn!("Could not read SignedMigrationMaxLimits: {}", e);
+                None
+            }
+        };
+        let limits = MigrationLimits::working(self.limits(), max);
+        match self.signed_deposit(limits.item) {
+            Ok(deposit) => {
+                info!(
+                    "Deposit per continue_migrate at {}: {:.4} WND",
+                    limits,
+                    units_to_wnd(deposit)
+                );
+                if balance.free < deposit {
+                    warn!("Free balance does not cover the deposit; see `limits recommend`");
+                }
+            }
+            Err(e) => warn!("Could not compute the deposit: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Show the chain's signed migration limits and the pallet's constants
+    async fn show_limits(&self) -> Result<()> {
+        info!("=== Migration Limits ===");
+        match self.get_max_limits().await? {
+            Some(max) => {
+                info!("SignedMigrationMaxLimits: {}", max);
+                match self.signed_deposit(max.item) {
+                    Ok(deposit) => info!("Deposit at the max: {:.4} WND", units_to_wnd(deposit)),
+                    Err(e) => warn!("Could not compute the deposit: {}", e),
+                }
+            }
+            None => info!("SignedMigrationMaxLimits: not set (signed migrations disabled)"),
+        }
+        match self.get_auto_limits().await? {
+            Some(auto) => info!("AutoLimits: {} (unsigned migration running)", auto),
+            None => info!("AutoLimits: not set (no unsigned migration)"),
+        }
+        info!("Chain constants: {}", self.constants.get(&self.client));
+        Ok(())
+    }
+
+    /// Show the limits `run` would start with, the deposit each tx reserves
+    /// and whether the signer's free balance covers it
+    async fn recommend_limits(&self) -> Result<()> {
+        let max = self.get_max_limits().await?;
+        let limits = MigrationLimits::working(self.limits(), max);
+        info!("Recommended limits: {}", limits);
+        if let Some(max) = max.filter(|max| limits.exceeds(*max)) {
+            warn!(
+                "Above SignedMigrationMaxLimits {}: run `limits set` first, or `run --set-max-limits`",
+                max
+            );
+        }
+        let deposit = self.signed_deposit(limits.item)?;
+        let free = self.check_balance().await?;
+        info!(
+            "Deposit per tx: {:.4} WND (free balance {:.4} WND)",
+            units_to_wnd(deposit),
+            units_to_wnd(free)
+        );
+        if free < deposit {
+            let affordable = (1..limits.item)
+                .rev()
+                .find(|items| self.signed_deposit(*items).is_ok_and(|d| d <= free));
+            match affordable {
+                Some(items) => warn!(
+                    "Balance does not cover the deposit; at most --item-limit {} fits",
+                    items
+                ),
+                None => warn!("Balance does not cover the deposit of a single item"),
+            }
+        }
+        Ok(())
+    }
+
+    /// `tx build`: the next continue_migrate, unsigned, saved to `path`
+    async fn build_offline_tx(&self, path: &Path, qr: bool) -> Result<()> {
+        let state = self
+            .fetch_state()
+            .await?
+            .context("No MigrationProcess found - migration may not be active")?;
+        anyhow::ensure!(
+            !self.task.is_complete(&state),
+            "Migration is complete; nothing to sign"
+        );
+        let max_limits = self.get_max_limits().await?.ok_or_else(|| {
+            MigrationError::InvalidLimits(
+                "SignedMigrationMaxLimits is not set; set it with `limits set`".to_string(),
+            )
+        })?;
+        let limits = MigrationLimits::working(self.limits(), Some(max_limits));
+        limits
+            .validate(Some(max_limits))
+            .map_err(MigrationError::InvalidLimits)?;
+        let call = self.build_call(&state, limits)?;
+        let account_id = self.signer.account_id();
+        let nonce = self.get_account_nonce(&account_id).await?;
+        let unsigned = UnsignedTx::build(&self.client, &call, &account_id, nonce.into())?;
+        unsigned.save(path)?;
+        println!("Tx: {} (nonce {})", limits, nonce);
+        println!("Call: {}", call.decoded);
+        println!("Payload to sign: {}", unsigned.signer_payload);
+        if qr {
+            let code = qrcode::QrCode::new(unsigned.vault_payload()?)
+                .context("Payload does not fit in a QR code")?;
+            println!(
+                "{}",
+                code.render::<qrcode::render::unicode::Dense1x2>()
+                    .quiet_zone(true)
+                    .build()
+            );
+        }
+        println!(
+            "Saved to {}; once signed: westend-migrate tx submit {} --signature <hex>",
+            path.display(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// `tx submit`: sign `unsigned` with `signature` and broadcast it, if the
+    /// chain has not moved on since it was built
+    async fn submit_offline_tx(
+        &self,
+        unsigned: &UnsignedTx,
+        signature: &str,
+    ) -> Result<Option<H256>> {
+        let bytes = unsigned.sign(signature)?;
+        anyhow::ensure!(
+            unsigned.genesis_hash == self.client.genesis_hash(),
+            "Tx was built for another chain (genesis {:?})",
+            unsigned.genesis_hash
+        );
+        let spec_version = self.client.runtime_version().spec_version;
+        anyhow::ensure!(
+            unsigned.spec_version == spec_version,
+            "Runtime upgraded from {} to {} since `tx build`; build and sign again",
+            unsigned.spec_version,
+            spec_version
+        );
+        let account_id = self.signer.account_id();
+        let nonce = self.get_account_nonce(&account_id).await?;
+        anyhow::ensure!(
+            u64::from(nonce) == unsigned.nonce,
+            "Account nonce is {} but the tx was built for {}; build and sign again",
+            nonce,
+            unsigned.nonce
+        );
+        // The witness must still be the live one, or the call fails
+        let limits = MigrationLimits::new(unsigned.size_limit, unsigned.item_limit);
+        let state = self
+            .fetch_state()
+            .await?
+            .context("No MigrationProcess found - migration may not be active")?;
+        anyhow::ensure!(
+            self.build_call(&state, limits)?.call_data == unsigned.call_data()?,
+            "Migration progressed since `tx build`; its witness is stale, build and sign again"
+        );
+
+        let mut signed = SignedMigration::from_bytes(&self.client, bytes, account_id, nonce);
+        signed.limits = Some(limits);
+        info!("Submitting offline-signed tx {:?}", signed.hash());
+        let progress = self.broadcast(&signed).await?;
+        self.confirm(progress, signed).await
+    }
+
+    /// Save the current MigrationProcess with the block and runtime it was read at
+    async fn export_witness(&self, path: &std::path::Path) -> Result<()> {
+        let at = self.read_block().await?;
+        let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+        let thunk = self
+            .client
+            .storage()
+            .at(at)
+            .fetch(&query)
+            .await?
+            .context("No MigrationProcess found - migration may not be active")?;
+        let snapshot = WitnessSnapshot {
+            block: at,
+            spec_version: self.client.runtime_version().spec_version,
+            captured_at: chrono::Utc::now(),
+            encoded: format!("0x{}", hex::encode(thunk.encoded())),
+            decoded: thunk.to_value()?.to_string(),
+        };
+        snapshot.save(path)?;
+        info!("Saved MigrationProcess at {:?} to {}", at, path.display());
+        Ok(())
+    }
+
+    /// Storage item ("Pallet::Item") the top trie migration is currently in
+    fn current_storage_item(&self, task: &Value<()>) -> Option<String> {
+        let key = keyspace::last_top_key(task).ok().flatten()?;
+        StorageNames::from_metadata(&self.client.metadata()).resolve(&key)
+    }
+
+    /// Configured working limits (`--size-limit`, `--item-limit`)
+    fn limits(&self) -> MigrationLimits {
+        MigrationLimits::new(self.config.run.size_limit, self.config.run.item_limit)
+    }
+
+    /// Estimate remaining top-trie keys and the percentage migrated
+    /// `remaining`: estimate from `sampling` (samples, page size), or count
+    /// every key when `None`, `parallel` ranges at a time; keys read are
+    /// streamed to `keys_out`, progress is kept in `checkpoint`. A full count
+    /// fills `key_cache`, which an estimate counts from when it can
+    async fn show_remaining(
+        &self,
+        sampling: Option<(u32, u32)>,
+        parallel: usize,
+        keys_out: Option<&Path>,
+        checkpoint: Option<&Path>,
+        key_cache: Option<&Path>,
+    ) -> Result<()> {
+        let at = self.read_block().await?;
+        let Some(TrieState {
+            witness: task,
+            status,
+        }) = self.fetch_state().await?
+        else {
+            warn!("No migration progress found");
+            return Ok(());
+        };
+        let mut last_key = match keyspace::last_top_key(&task) {
+            Ok(key) => key,
+            Err(e) => {
+                info!("Nothing to estimate: {}", e);
+                return Ok(());
+            }
+        };
+        if let (Some(_), Some(dir)) = (sampling, key_cache) {
+            if let Some(cached) = CachedScan::latest(dir)? {
+                let block = cached.block;
+                match cached.count_after(last_key.as_deref())? {
+                    Some(remaining) => {
+                        let estimate = KeyEstimate {
+                            remaining,
+                            sampled: 0,
+                            exact: true,
+                        };
+                        info!(
+                            "Remaining top keys: {} (scan cached at {:?}, keys created since \
+                             not counted)",
+                            remaining, block
+                        );
+                        info!(
+                            "Top trie: {} items migrated, {:.1}% complete",
+                            status.top_items,
+                            estimate.percent_complete(status.top_items)
+                        );
+                        return Ok(());
+                    }
+                    None => info!(
+                        "The scan cached at {:?} started after the last migrated key, sampling",
+                        block
+                    ),
+                }
+            }
+        }
+        let mut checkpoint = match checkpoint {
+            Some(path) => {
+                let fresh = ScanProgress::new(at, last_key.as_deref(), sampling);
+                Some(Checkpoint::open(path, fresh)?)
+            }
+            None => None,
+        };
+        if let Some(checkpoint) = checkpoint.as_ref().filter(|c| c.resumed) {
+            // Counts stay relative to where the interrupted scan started
+            last_key = checkpoint.progress.start_key()?;
+            info!(
+                "Resuming the scan started at {:?} ({} keys counted, {} strata read)",
+                checkpoint.progress.block,
+                checkpoint.progress.counted(),
+                checkpoint.progress.strata.len()
+            );
+        }
+        match &last_key {
+            Some(key) => info!("Last migrated key: 0x{}", hex::encode(key)),
+            None => info!("Top trie not started yet"),
+        }
+
+        let mut out = match keys_out {
+            Some(path) => {
+                // A resumed scan appends to the keys written before the interruption
+                let resumed = checkpoint.as_ref().is_some_and(|c| c.resumed);
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(resumed)
+                    .truncate(!resumed)
+                    .open(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                Some(std::io::BufWriter::new(file))
+            }
+            None => None,
+        };
+        let mut cache = match key_cache.filter(|_| sampling.is_none()) {
+            // Keys counted before the interruption would be missing from it
+            Some(_) if checkpoint.as_ref().is_some_and(|c| c.resumed) => {
+                warn!("Not caching the keys of a resumed scan");
+                None
+            }
+            Some(dir) => Some(KeyCacheWriter::create(dir, at, last_key.as_deref())?),
+            None => None,
+        };
+        let mut scanner = KeyScanner::new(&self.raw_rpc, at);
+        if let Some(out) = out.as_mut() {
+            scanner = scanner.with_output(out);
+        }
+        if let Some(cache) = cache.as_mut() {
+            scanner = scanner.with_cache(cache);
+        }
+        let estimate = match sampling {
+            Some((samples, page_size)) => {
+                info!(
+                    "Sampling {} pages of {} keys at {:?}...",
+                    samples, page_size, at
+                );
+                keyspace::estimate_remaining(
+                    &mut scanner,
+                    last_key.as_deref(),
+                    samples,
+                    page_size,
+                    checkpoint.as_mut(),
+                )
+                .await?
+            }
+            None => {
+                info!("Counting every remaining key at {:?}...", at);
+                keyspace::count_remaining(
+                    &mut scanner,
+                    last_key.as_deref(),
+                    parallel,
+                    checkpoint.as_mut(),
+                )
+                .await?
+            }
+        };
+        let read = scanner.read();
+        if let (Some(out), Some(path)) = (out.as_mut(), keys_out) {
+            std::io::Write::flush(out)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            info!("{} keys written to {}", read, path.display());
+        }
+        if let Some(cache) = cache {
+            info!("{} keys cached at {:?}", cache.finish()?, at);
+        }
+        info!(
+            "Remaining top keys: {}{} ({} keys sampled)",
+            if estimate.exact { "" } else { "~" },
+            estimate.remaining,
+            estimate.sampled
+        );
+        info!(
+            "Top trie: {} items migrated, {:.1}% complete",
+            status.top_items,
+            estimate.percent_complete(status.top_items)
+        );
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.finish()?;
+        }
+        Ok(())
+    }
+
+    /// What a pool entry does (its encoding when it does not decode)
+    fn describe_pending(&self, ext: &PendingExtrinsic) -> String {
+        match ext.decode(&self.client.metadata()) {
+            Ok(decoded)
+                if decoded
+                    .signer
+                    .as_ref()
+                    .is_some_and(|signer| self.signer.accounts().any(|ours| ours == signer)) =>
+            {
+                format!("{} [ours]", decoded)
+            }
+            Ok(decoded) => decoded.to_string(),
+            Err(e) => format!("{} ({})", ext.preview(), e),
+        }
+    }
+
+    /// `pool list`: every pending extrinsic, decoded
+    async fn list_pool(&self) -> Result<()> {
+        let pending = pool::pending_extrinsics(&self.raw_rpc)
+            .await
+            .context("Could not list the pool (requires --rpc-methods=unsafe)")?;
+        if pending.is_empty() {
+            println!("No pending transactions in pool");
+            return Ok(());
+        }
+        for ext in &pending {
+            println!("{:?}  {}", ext.hash(), self.describe_pending(ext));
+        }
+        println!("{} pending transaction(s)", pending.len());
+        Ok(())
+    }
+
+    /// `pool remove`: drop one extrinsic from the pool
+    async fn remove_from_pool(&self, hash: H256) -> Result<()> {
+        let removed = pool::remove_extrinsics(&self.raw_rpc, &[hash]).await?;
+        if removed.is_empty() {
+            anyhow::bail!("{:?} is not in the pool", hash);
+        }
+        for hash in removed {
+            info!("Removed extrinsic: {:?}", hash);
+        }
+        Ok(())
+    }
+
+    /// Clear pending extrinsics from the pool; with `mine_only`, only those
+    /// signed by our accounts
+    async fn clear_pending_transactions(&self, mine_only: bool) -> Result<usize> {
         info!("Checking for pending transactions to clear...");
 
-        let pending = match self.get_pending_extrinsics().await {
+        let mut pending = match pool::pending_extrinsics(&self.raw_rpc).await {
             Ok(p) => p,
             Err(e) => {
                 warn!("Could not get pending extrinsics: {}", e);
                 return Ok(0);
             }
         };
+        if mine_only {
+            let metadata = self.client.metadata();
+            pending.retain(|ext| {
+                ext.decode(&metadata).is_ok_and(|decoded| {
+                    decoded
+                        .signer
+                        .is_some_and(|signer| self.signer.accounts().any(|ours| *ours == signer))
+                })
+            });
+        }
 
         if pending.is_empty() {
             info!("No pending transactions to clear");
@@ -511,21 +2644,16 @@ impl MigrationBot {
 
         let mut cleared = 0;
         for ext in &pending {
-            // Calculate the blake2-256 hash of the extrinsic
-            use blake2::{Blake2s256, Digest};
-            let ext_bytes = hex::decode(ext.trim_start_matches("0x")).unwrap_or_default();
-            let hash = Blake2s256::digest(&ext_bytes);
-            let hash_hex = format!("0x{}", hex::encode(hash));
-
-            match self.remove_extrinsic(&hash_hex).await {
+            let hash = ext.hash();
+            match pool::remove_extrinsics(&self.raw_rpc, &[hash]).await {
                 Ok(removed) => {
                     if !removed.is_empty() {
-                        info!("Removed extrinsic: {}", hash_hex);
+                        info!("Removed extrinsic: {:?}", hash);
                         cleared += 1;
                     }
                 }
                 Err(e) => {
-                    debug!("Could not remove extrinsic {}: {}", hash_hex, e);
+                    debug!("Could not remove extrinsic {:?}: {}", hash, e);
                 }
             }
         }
@@ -541,49 +2669,190 @@ impl MigrationBot {
 
     /// Submit a continue_migrate transaction
     /// Mirrors TypeScript: api.tx.stateTrieMigration.continueMigrate(limits, sizeUpperLimit, currentTask)
-    async fn submit_migration(&self, witness_task: Value<()>) -> Result<()> {
-        info!(
-            "Tx: items={}, size={}",
-            self.config.item_limit, self.config.size_limit
-        );
+    /// Returns the hash of the submitted tx (None in --dry-run mode)
+    async fn submit_migration(
+        &self,
+        state: &TrieState,
+        limits: MigrationLimits,
+    ) -> Result<Option<H256>> {
+        info!("Tx: {}", limits);
+        let call = self.build_call(state, limits)?;
+        if let (Some(queue), false) = (&self.approvals, self.config.run.dry_run) {
+            // Approve before the dry run, so a witness gone stale while the
+            // request was open fails validation instead of being submitted
+            self.await_approval(queue, &call).await?;
+        }
+        let (validation, dry_run_tx) = self.validate_call(&call).await?;
+        if validation == Validation::Unchecked {
+            debug!("Submitting without pre-submission validation");
+        }
 
-        // Capture nonce before submission for timeout verification
-        let account_id = <Keypair as Signer<PolkadotConfig>>::account_id(&self.signer);
-        let expected_nonce = self.get_account_nonce(&account_id).await.unwrap_or(0);
+        if self.config.run.dry_run {
+            info!("[DRY RUN] Would submit continue_migrate transaction");
+            return Ok(None);
+        }
 
-        // MigrationLimits { size: u32, item: u32 }
-        let limits = Value::named_composite([
-            ("size", Value::u128(self.config.size_limit as u128)),
-            ("item", Value::u128(self.config.item_limit as u128)),
-        ]);
+        // The dry run vouched for this witness only: make sure the chain
+        // hasn't moved past it in the meantime
+        self.refresh_witness(state).await?;
 
-        // real_size_upper: u32 - TypeScript uses sizeLimit * 2
-        let real_size_upper = Value::u128((self.config.size_limit * 2) as u128);
+        // Sign afresh for submission rather than reusing the dry-run tx
+        // This avoids AncientBirthBlock errors when dry run takes time
+        // A device signer submits the tx already confirmed for the dry run,
+        // so each migration is confirmed once
+        let reuse = self.signer.confirms_on_device().then_some(dry_run_tx);
+        // Nothing is in flight yet: a shutdown during validation stops here
+        if self.shutdown.is_cancelled() {
+            return Err(MigrationError::ShuttingDown.into());
+        }
+        let signed = self.sign_call(&call, reuse).await?;
+        self.next_witness.submitted(state.status.clone());
+        let progress = self.broadcast(&signed).await?;
+        self.confirm(progress, signed).await
+    }
 
-        // Build the continue_migrate call
-        // Parameters: limits, real_size_upper, witness_task
-        let tx = subxt::dynamic::tx(
-            "StateTrieMigration",
-            "continue_migrate",
-            vec![limits, real_size_upper, witness_task],
+    /// Re-read `MigrationProcess` right before signing; `WitnessChanged` if
+    /// it is no longer the witness the call was built from and dry run with
+    async fn refresh_witness(&self, state: &TrieState) -> Result<()> {
+        if self.config.run.witness_file.is_some() {
+            return Ok(());
+        }
+        let current = self.fetch_state().await?;
+        if current.as_ref() == Some(state) {
+            return Ok(());
+        }
+        let change = match current {
+            Some(current) => format!(
+                "top_items {} -> {}, child_items {} -> {}",
+                state.status.top_items,
+                current.status.top_items,
+                state.status.child_items,
+                current.status.child_items
+            ),
+            None => "MigrationProcess is gone".to_string(),
+        };
+        warn!(
+            "Witness changed before signing ({}), not submitting",
+            change
         );
+        Err(MigrationError::WitnessChanged(change).into())
+    }
 
-        // Create signed transaction for dry run validation
-        // Retry loop handles stale nonce (when previous tx finalized between nonce fetch and dry run)
-        const MAX_DRY_RUN_RETRIES: u32 = 3;
-        let mut dry_run_tx = None;
-
-        for retry in 0..MAX_DRY_RUN_RETRIES {
-            // Re-sign transaction to get fresh nonce
-            let signed_tx = self
-                .client
-                .tx()
-                .create_signed(&tx, &self.signer, Default::default())
-                .await
-                .context("Failed to create signed tx for dry run")?;
+    /// Pipeline stage 1: encode the call and log its call data
+    fn build_call(&self, state: &TrieState, limits: MigrationLimits) -> Result<MigrationCall> {
+        let mut call = self.task.build(&self.client, state, limits)?;
+        if let Some(real) = &self.config.proxy_for {
+            call = call.via_proxy(&self.client, real)?;
+        }
+        if self.config.run.show_call_data {
+            info!("Call data: 0x{}", hex::encode(&call.call_data));
+            info!("Decoded: {}", call.decoded);
+        } else {
+            debug!("Call data: 0x{}", hex::encode(&call.call_data));
+            debug!("Decoded: {}", call.decoded);
+        }
+        Ok(call)
+    }
 
-            // DRY RUN using system_dryRun RPC - actually executes the call
-            // This catches dispatch errors (like SizeUpperBoundExceeded) that would cause slashing
+    /// Queue `call` for sign-off and wait for the decision (--approval-queue)
+    async fn await_approval(&self, queue: &ApprovalQueue, call: &MigrationCall) -> Result<()> {
+        self.enter(BotState::Paused);
+        let account_id = self.signer.account_id();
+        let nonce = self.get_account_nonce(&account_id).await?;
+        let mut request = ApprovalRequest::new(
+            &account_id.to_string(),
+            nonce,
+            call.limits.to_string(),
+            &call.call_data,
+            call.decoded.clone(),
+        );
+        let path = queue.submit(&request)?;
+
+        let mut msg = format!(
+            "Tx {} awaits approval: {} (nonce {})\nRequest: {}\n",
+            request.id,
+            call.limits,
+            nonce,
+            path.display()
+        );
+        if let Some(base) = self.config.approval_base_url() {
+            msg.push_str(&format!(
+                "Approve: {base}/approve/{token}\nReject: {base}/reject/{token}\n",
+                base = base.trim_end_matches('/'),
+                token = request.token
+            ));
+        }
+        msg.push_str(&format!(
+            "Or write the token to {}.approved (or .rejected) in the queue directory",
+            request.id
+        ));
+        info!("{}", msg);
+        send_notification(Severity::Warning, "Approval Required", &msg);
+
+        let timeout = Duration::from_secs(self.config.run.approval_timeout_mins * 60);
+        let decision = queue
+            .wait(
+                &mut request,
+                APPROVAL_POLL_INTERVAL,
+                timeout,
+                &self.shutdown,
+            )
+            .await?;
+        match decision {
+            Decision::Approved => {
+                info!("Tx {} approved", request.id);
+                Ok(())
+            }
+            Decision::Rejected | Decision::Expired | Decision::Cancelled => {
+                let reason = format!("tx {} {}", request.id, request.decision.unwrap_or_default());
+                if decision != Decision::Cancelled {
+                    send_notification(Severity::Warning, "Approval Withheld", &reason);
+                }
+                Err(MigrationError::NotApproved(reason).into())
+            }
+        }
+    }
+
+    /// Sign `payload` with the bot's signer; a tx the signing device did not
+    /// sign is reported, as nobody may be watching the device
+    async fn create_signed(
+        &self,
+        payload: &DynamicPayload,
+    ) -> Result<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+        let nonce = self.next_nonce(&self.signer.account_id()).await?;
+        let result = signing::create_signed(&self.client, payload, &self.signer, nonce).await;
+        if let Some(MigrationError::NotSigned(reason)) =
+            result.as_ref().err().and_then(|e| e.downcast_ref())
+        {
+            send_notification(Severity::Warning, "Tx Not Signed", reason);
+        }
+        result
+    }
+
+    /// Pipeline stage 2: dry run the call, if the node allows it; also
+    /// returns the tx that was dry run
+    async fn validate_call(
+        &self,
+        call: &MigrationCall,
+    ) -> Result<(
+        Validation,
+        SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    )> {
+        self.enter(BotState::Validating);
+        // Create signed transaction for dry run validation
+        // Retry loop handles stale nonce (when previous tx finalized between nonce fetch and dry run)
+        const MAX_DRY_RUN_RETRIES: u32 = 3;
+        let mut validation = None;
+
+        for retry in 0..MAX_DRY_RUN_RETRIES {
+            // Re-sign transaction to get fresh nonce
+            let signed_tx = self
+                .create_signed(&call.payload)
+                .await
+                .context("Failed to create signed tx for dry run")?;
+
+            // DRY RUN using system_dryRun RPC - actually executes the call
+            // This catches dispatch errors (like SizeUpperBoundExceeded) that would cause slashing
             // NOTE: system_dryRun requires --rpc-methods=unsafe on the node
             // We remember if it's not supported to skip on future calls
             if self.dry_run_supported.load(Ordering::Relaxed) {
@@ -601,21 +2870,19 @@ impl MigrationBot {
                         match dry_run_bytes.into_dry_run_result(&self.client.metadata()) {
                             Ok(DryRunResult::Success) => {
                                 info!("Dry run OK");
-                                dry_run_tx = Some(signed_tx);
+                                validation = Some((Validation::DryRunOk, signed_tx));
                                 break; // Success - exit retry loop
                             }
                             Ok(DryRunResult::DispatchError(dispatch_err)) => {
                                 let err_str = format!("{:?}", dispatch_err);
                                 error!("Dry run FAILED - dispatch error: {}", err_str);
-
-                                if err_str.contains("SizeUpperBoundExceeded") {
-                                    return Err(MigrationError::SizeExceeded.into());
-                                }
-                                return Err(MigrationError::DryRunDispatchError(err_str).into());
+                                return Err(pipeline::dispatch_error(err_str).into());
                             }
                             Ok(DryRunResult::TransactionValidityError) => {
                                 // Decode the raw bytes to get detailed validity error
                                 let validity_error = decode_validity_error(&raw_bytes);
+                                // A stale or future nonce means our count is off: read it afresh
+                                self.nonces.resync(&self.signer.account_id());
 
                                 // If stale nonce, retry immediately with fresh signature
                                 if matches!(validity_error, ValidityError::Stale) && retry < MAX_DRY_RUN_RETRIES - 1 {
@@ -632,7 +2899,14 @@ impl MigrationBot {
                             }
                             Err(e) => {
                                 warn!("Could not decode dry run result: {:?}", e);
-                                dry_run_tx = Some(signed_tx);
+                                if self.config.run.require_validation {
+                                    return Err(MigrationError::ValidationUnavailable(format!(
+                                        "could not decode dry run result: {:?}",
+                                        e
+                                    ))
+                                    .into());
+                                }
+                                validation = Some((Validation::Unchecked, signed_tx));
                                 break;
                             }
                         }
@@ -644,9 +2918,16 @@ impl MigrationBot {
                             warn!(
                                 "system_dryRun not available (requires --rpc-methods=unsafe on node)"
                             );
+                            if self.config.run.require_validation {
+                                return Err(MigrationError::ValidationUnavailable(
+                                    "system_dryRun requires --rpc-methods=unsafe on the node"
+                                        .to_string(),
+                                )
+                                .into());
+                            }
                             warn!("Disabling dry run for this session - USE AT YOUR OWN RISK!");
                             self.dry_run_supported.store(false, Ordering::Relaxed);
-                            dry_run_tx = Some(signed_tx);
+                            validation = Some((Validation::Unchecked, signed_tx));
                             break;
                         } else {
                             error!("Dry run RPC error: {}", err_str);
@@ -656,35 +2937,69 @@ impl MigrationBot {
                 }
             } else {
                 // Dry run not supported, just use the signed tx
-                dry_run_tx = Some(signed_tx);
+                validation = Some((Validation::Unchecked, signed_tx));
                 break;
             }
         }
 
-        let dry_run_tx = dry_run_tx.ok_or_else(|| {
-            MigrationError::DryRunDispatchError("Failed to create valid transaction after retries".to_string())
-        })?;
-
-        if self.config.dry_run {
-            info!("[DRY RUN] Would submit continue_migrate transaction");
-            return Ok(());
-        }
+        validation.ok_or_else(|| {
+            MigrationError::DryRunDispatchError(
+                "Failed to create valid transaction after retries".to_string(),
+            )
+            .into()
+        })
+    }
 
-        // Create FRESH signed transaction for submission
-        // This avoids AncientBirthBlock errors when dry run takes time
-        let fresh_signed_tx = self
-            .client
-            .tx()
-            .create_signed(&tx, &self.signer, Default::default())
-            .await
-            .context("Failed to create fresh signed tx for submission")?;
+    /// Pipeline stage 3: sign the call with the current nonce, unless `signed`
+    /// is already
+    async fn sign_call(
+        &self,
+        call: &MigrationCall,
+        signed: Option<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>>,
+    ) -> Result<SignedMigration> {
+        self.enter(BotState::Submitting);
+        // Capture nonce before submission for timeout verification
+        let account_id = self.signer.account_id();
+        let nonce_before = self.next_nonce(&account_id).await?;
+        self.signer.record_nonce(nonce_before);
+        let tx = match signed {
+            Some(tx) => tx,
+            None => self
+                .create_signed(&call.payload)
+                .await
+                .context("Failed to create fresh signed tx for submission")?,
+        };
+        Ok(SignedMigration {
+            tx,
+            account: account_id,
+            nonce_before,
+            limits: Some(call.limits),
+        })
+    }
 
-        // Submit the freshly-signed transaction and watch
-        let mut progress = match fresh_signed_tx.submit_and_watch().await {
-            Ok(p) => p,
+    /// Pipeline stage 4: submit to the pool and start watching
+    async fn broadcast(
+        &self,
+        signed: &SignedMigration,
+    ) -> Result<TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+        self.enter(BotState::Submitting);
+        let tx_hash = format!("{:?}", signed.hash());
+        let account = signed.account.to_string();
+        self.record_journal(|j| {
+            j.submitted(&tx_hash, &account, signed.nonce_before, signed.limits)
+        });
+        match signed.tx.submit_and_watch().await {
+            Ok(progress) => {
+                self.nonces.submitted(&signed.account, signed.nonce_before);
+                Ok(progress)
+            }
             Err(e) => {
+                self.nonces.resync(&signed.account);
                 let err_str = format!("{:?}", e);
                 let migration_err = MigrationError::from_rpc_error(&err_str);
+                self.record_journal(|j| {
+                    j.finish(&tx_hash, &Outcome::Failed(migration_err.to_string()))
+                });
 
                 // Log appropriate warning based on error type
                 match &migration_err {
@@ -704,18 +3019,61 @@ impl MigrationBot {
                     _ => {}
                 }
 
-                return Err(migration_err.into());
+                Err(migration_err.into())
             }
-        };
+        }
+    }
+
+    /// Resubmit a tx `account` already signed at `nonce_before` (e.g. after
+    /// switching endpoints) and watch it
+    async fn rebroadcast(
+        &self,
+        tx_bytes: Vec<u8>,
+        account: subxt::utils::AccountId32,
+        nonce_before: u32,
+    ) -> Result<Option<H256>> {
+        let signed = SignedMigration::from_bytes(&self.client, tx_bytes, account, nonce_before);
+        info!(tx_hash = ?signed.hash(), "Rebroadcasting via {}", self.endpoint_url());
+        let progress = self.broadcast(&signed).await?;
+        self.confirm(progress, signed).await
+    }
+
+    /// Pipeline stage 5: follow a submitted tx until it is finalized
+    async fn confirm(
+        &self,
+        progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        signed: SignedMigration,
+    ) -> Result<Option<H256>> {
+        self.enter(BotState::AwaitingFinality);
+        let tx_hash = format!("{:?}", signed.hash());
+        let account = signed.account.clone();
+        let result = self.watch_tx(progress, signed).await;
+        if let Err(e) = &result {
+            self.nonces.resync(&account);
+            self.record_journal(|j| j.finish(&tx_hash, &Outcome::Failed(format!("{:#}", e))));
+        }
+        result
+    }
 
+    async fn watch_tx(
+        &self,
+        mut progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        signed: SignedMigration,
+    ) -> Result<Option<H256>> {
+        let tx_hash = signed.hash();
+        let expected_nonce = signed.nonce_before;
+        let account_id = &signed.account;
         // Wait for FINALIZATION (not just inclusion) - this is critical!
         // TypeScript bot uses sendAndFinalize() which waits for finalization
         // State only propagates reliably after finalization
         //
-        // IMPORTANT: Add timeout because WebSocket subscriptions can lose events
-        let finalization_timeout = Duration::from_secs(120); // 2 minutes max wait
+        // IMPORTANT: Add timeout because WebSocket subscriptions can lose events.
+        // Parachain blocks finalize only once the relay chain has included them,
+        // so the timeout depends on the chain (see `parachain::Timing`)
+        let finalization_timeout = self.timing.finalization_timeout;
         let start_time = Instant::now();
         let mut included = false;
+        let mut zero_peer_broadcasts = 0;
 
         while let Some(status) = progress.next().await {
             // Check timeout
@@ -723,10 +3081,10 @@ impl MigrationBot {
                 warn!("Finalization timeout after {:?}, checking nonce...", start_time.elapsed());
 
                 // Verify TX was applied by checking if nonce changed
-                let current_nonce = self.get_account_nonce(&account_id).await?;
+                let current_nonce = self.get_account_nonce(account_id).await?;
                 if current_nonce > expected_nonce {
                     info!("Nonce advanced ({} -> {}), TX was finalized (missed event)", expected_nonce, current_nonce);
-                    return Ok(());
+                    return Ok(Some(tx_hash));
                 } else {
                     return Err(MigrationError::SubmissionFailed(
                         "Finalization timeout - TX may be stuck".to_string()
@@ -737,21 +3095,111 @@ impl MigrationBot {
             match status? {
                 subxt::tx::TxStatus::Broadcasted { num_peers } => {
                     info!("Broadcast to {} peers", num_peers);
+                    // Nothing reaches a block author from a node without peers;
+                    // give up early instead of waiting for the finalization timeout
+                    if num_peers == 0 && !included {
+                        zero_peer_broadcasts += 1;
+                        if policy::should_switch_endpoint(zero_peer_broadcasts) {
+                            return Err(MigrationError::NoPeers {
+                                broadcasts: zero_peer_broadcasts,
+                                tx_bytes: signed.bytes(),
+                                account: signed.account.clone(),
+                                nonce_before: expected_nonce,
+                            }
+                            .into());
+                        }
+                    }
                 }
                 subxt::tx::TxStatus::InBestBlock(block) => {
-                    info!("Included {:?}...", block.block_hash());
+                    info!(
+                        tx_hash = ?tx_hash,
+                        block_hash = ?block.block_hash(),
+                        "Included {:?}...",
+                        block.block_hash()
+                    );
+                    self.emit(BotEvent::TxInBlock {
+                        tx_hash: format!("{:?}", tx_hash),
+                        block_hash: format!("{:?}", block.block_hash()),
+                    });
                     included = true;
+                    // --pipeline: the next tx goes out now, finality is followed
+                    // in the background
+                    if self.config.run.pipeline {
+                        for evt in block.fetch_events().await?.iter() {
+                            let evt = evt?;
+                            self.check_stale_witness(&evt)?;
+                            self.check_proxy_executed(&evt)?;
+                        }
+                        self.unsettled
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push(tx_hash, signed.account.clone(), expected_nonce);
+                        self.follow_finality(progress, tx_hash, signed.account.clone());
+                        return Ok(Some(tx_hash));
+                    }
+                    // Read the next witness now, while finality is pending
+                    self.read_at_inclusion(block.block_hash()).await;
                     // Don't break here - continue waiting for finalization
                 }
+                subxt::tx::TxStatus::NoLongerInBestBlock => {
+                    // Common on parachains: a collator's block that was never
+                    // backed is replaced; the tx returns to the pool
+                    info!("Block retracted, tx back in the pool awaiting inclusion");
+                    included = false;
+                    self.next_witness.retracted();
+                }
                 subxt::tx::TxStatus::InFinalizedBlock(block) => {
-                    info!("Finalized {:?}", block.block_hash());
+                    info!(
+                        tx_hash = ?tx_hash,
+                        block_hash = ?block.block_hash(),
+                        "Finalized {:?}",
+                        block.block_hash()
+                    );
 
                     let events = block.fetch_events().await?;
+                    let mut migration_events = Vec::new();
+                    let mut migrated = None;
                     for evt in events.iter().flatten() {
                         if evt.pallet_name() == "StateTrieMigration" {
                             info!("  → {}.{}", evt.pallet_name(), evt.variant_name());
+                            migration_events.push(evt.variant_name().to_string());
+                            if let Some(MigrationEvent::Migrated { top, child, .. }) =
+                                evt.field_values().ok().and_then(|fields| {
+                                    MigrationEvent::decode(evt.variant_name(), &fields)
+                                })
+                            {
+                                migrated = Some((top, child));
+                            }
                         }
+                        let checked = self
+                            .check_stale_witness(&evt)
+                            .and_then(|()| self.check_proxy_executed(&evt));
+                        if let Err(e) = checked {
+                            // Failed in this block: nothing for the next witness to confirm
+                            self.next_witness.failed();
+                            self.record_journal(|j| {
+                                j.finish(
+                                    &format!("{:?}", tx_hash),
+                                    &Outcome::Failed(format!("{:#}", e)),
+                                )
+                            });
+                            return Err(e);
+                        }
+                    }
+                    self.verify_relay_inclusion(block.block_hash()).await?;
+                    self.verify_trusted_finality(block.block_hash()).await?;
+                    if let Err(reason) = self.next_witness.finalized(block.block_hash(), migrated) {
+                        debug!("Next witness will be queried: {}", reason);
                     }
+                    self.record_journal(|j| {
+                        j.finish(
+                            &format!("{:?}", tx_hash),
+                            &Outcome::Finalized {
+                                block_hash: format!("{:?}", block.block_hash()),
+                                events: migration_events,
+                            },
+                        )
+                    });
                     break; // Only break after finalization
                 }
                 subxt::tx::TxStatus::Error { message } => {
@@ -769,255 +3217,1674 @@ impl MigrationBot {
         // If we exit the loop without breaking (stream ended), check if TX succeeded
         if included {
             warn!("Progress stream ended without finalization event, checking nonce...");
-            let current_nonce = self.get_account_nonce(&account_id).await?;
+            let current_nonce = self.get_account_nonce(account_id).await?;
             if current_nonce > expected_nonce {
                 info!("Nonce advanced ({} -> {}), TX was finalized (stream ended early)", expected_nonce, current_nonce);
+                return Ok(Some(tx_hash));
+            }
+        }
+
+        Ok(Some(tx_hash))
+    }
+
+    /// Read `MigrationProcess` at `block`, a best block including our tx, for
+    /// [`NextWitness`] to confirm once the tx finalizes. Failures only mean
+    /// the witness is queried as usual.
+    async fn read_at_inclusion(&self, block: H256) {
+        if self.config.run.witness_file.is_some() {
+            return;
+        }
+        match self.task.fetch(&self.client, block).await {
+            Ok(Some(state)) => self.next_witness.included(optimistic::Read {
+                block,
+                state,
+                at: Instant::now(),
+            }),
+            Ok(None) => {}
+            Err(e) => debug!("Could not read MigrationProcess at {:?}: {:#}", block, e),
+        }
+    }
+
+    /// Fail with `BadWitness` when our call failed because another migration
+    /// advanced `MigrationProcess` first (`System.ExtrinsicFailed`, or the
+    /// `Proxy.ProxyExecuted` result with --proxy-for)
+    fn check_stale_witness(&self, evt: &subxt::events::EventDetails<PolkadotConfig>) -> Result<()> {
+        let bytes = evt.field_bytes();
+        let dispatch_error = match (evt.pallet_name(), evt.variant_name()) {
+            ("System", "ExtrinsicFailed") => bytes,
+            // `Result<(), DispatchError>`: 1 for `Err`, then the error
+            ("Proxy", "ProxyExecuted") if bytes.first() == Some(&1) => &bytes[1..],
+            _ => return Ok(()),
+        };
+        let Ok(err) =
+            subxt::error::DispatchError::decode_from(dispatch_error, self.client.metadata())
+        else {
+            return Ok(());
+        };
+        let err_str = format!("{:?}", err);
+        if !err_str.contains("BadWitness") {
+            return Ok(());
+        }
+        warn!("continue_migrate failed with a stale witness: {}", err_str);
+        Err(MigrationError::BadWitness(err_str).into())
+    }
+
+    /// With --proxy-for, fail on a `Proxy.ProxyExecuted` carrying an error:
+    /// `Proxy.proxy` succeeds even when the call it dispatched fails
+    fn check_proxy_executed(
+        &self,
+        evt: &subxt::events::EventDetails<PolkadotConfig>,
+    ) -> Result<()> {
+        if self.config.proxy_for.is_none()
+            || evt.pallet_name() != "Proxy"
+            || evt.variant_name() != "ProxyExecuted"
+        {
+            return Ok(());
+        }
+        if let Some(e) = proxy::executed_error(&evt.field_values()?) {
+            error!("Proxied call failed: {}", e);
+            return Err(MigrationError::SubmissionFailed(format!(
+                "proxied continue_migrate failed: {}",
+                e
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// --pipeline: follow a tx that is in a best block to finality in the
+    /// background; [`Self::settle_trailing`] picks up the outcome
+    fn follow_finality(
+        &self,
+        progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        tx_hash: H256,
+        account: subxt::utils::AccountId32,
+    ) {
+        let trailing = self.trailing.0.clone();
+        let timeout = self.timing.finalization_timeout;
+        tokio::spawn(async move {
+            let outcome = pipeline::await_finality(progress, timeout).await;
+            // The receiver lives as long as the bot
+            let _ = trailing.send(Trailing {
+                tx_hash,
+                account,
+                outcome,
+            });
+        });
+    }
+
+    /// --pipeline: journal the txs whose finality was followed in the
+    /// background
+    fn settle_trailing(&self, stats: &mut SessionStats) {
+        let mut trailing = self.trailing.1.lock().unwrap_or_else(|e| e.into_inner());
+        while let Ok(settled) = trailing.try_recv() {
+            self.settle(settled, stats);
+        }
+    }
+
+    /// --pipeline: wait, up to the finalization timeout, for the txs still
+    /// followed in the background, so the session's counts are final
+    async fn drain_trailing(&self, stats: &mut SessionStats) {
+        let unsettled = || self.unsettled.lock().unwrap_or_else(|e| e.into_inner()).len();
+        if unsettled() == 0 {
+            return;
+        }
+        info!("Waiting for {} pipelined txs to finalize", unsettled());
+        let timeout = self.timing.finalization_timeout;
+        let drained = tokio::time::timeout(timeout, async {
+            while unsettled() > 0 {
+                let next = std::future::poll_fn(|cx| {
+                    self.trailing
+                        .1
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .poll_recv(cx)
+                })
+                .await;
+                // The sender lives as long as the bot
+                let Some(settled) = next else { break };
+                self.settle(settled, stats);
+            }
+        })
+        .await;
+        if drained.is_err() {
+            warn!(
+                "{} pipelined txs not finalized within {}s of stopping",
+                unsettled(),
+                timeout.as_secs()
+            );
+        }
+    }
+
+    /// A tx that never finalized has its account's nonce read afresh, and
+    /// drops the next witness and the txs signed after it: both counted on
+    /// it, so the next iteration reads `MigrationProcess` again. Its run and
+    /// those of the dropped txs, counted at inclusion, count as failed.
+    fn settle(&self, settled: Trailing, stats: &mut SessionStats) {
+        let dropped = self
+            .unsettled
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .settle(&settled);
+        let tx_hash = format!("{:?}", settled.tx_hash);
+        match settled.outcome {
+            Ok((block_hash, events)) => {
+                debug!("Tx {} finalized in {:?}", tx_hash, block_hash);
+                self.record_journal(|j| {
+                    j.finish(
+                        &tx_hash,
+                        &Outcome::Finalized {
+                            block_hash: format!("{:?}", block_hash),
+                            events,
+                        },
+                    )
+                });
+            }
+            Err(reason) => {
+                self.record_journal(|j| j.finish(&tx_hash, &Outcome::Failed(reason.clone())));
+                // Already dropped along with an earlier tx
+                let Some(dropped) = dropped else {
+                    debug!("Dropped tx {} did not finalize: {}", tx_hash, reason);
+                    return;
+                };
+                let msg = format!(
+                    "Tx {} was in a best block but did not finalize: {}",
+                    tx_hash, reason
+                );
+                warn!("{}", msg);
+                send_notification(Severity::Warning, "Pipelined Tx Not Finalized", &msg);
+                self.nonces.resync(&settled.account);
+                self.next_witness.failed();
+                stats.not_finalized(1 + dropped.len() as u32);
+                for later in dropped {
+                    let later = format!("{:?}", later);
+                    warn!("Dropping tx {}, signed after {}", later, tx_hash);
+                    self.record_journal(|j| {
+                        j.finish(
+                            &later,
+                            &Outcome::Failed(format!(
+                                "signed after {}, which did not finalize",
+                                tx_hash
+                            )),
+                        )
+                    });
+                }
+            }
+        }
+    }
+
+    /// Compare the state read at `at` with the --verify-rpc-url endpoint;
+    /// `false` holds the submission. An endpoint lagging behind, or failing,
+    /// is logged but does not hold submissions.
+    async fn cross_check(&self, verifier: &Verifier, at: H256, state: &TrieState) -> bool {
+        match verifier
+            .check(&self.task, at, state, self.timing.block_time)
+            .await
+        {
+            Ok(CrossCheck::Match) => {
+                debug!("{} agrees on the migration state", verifier.url());
+                true
+            }
+            Ok(CrossCheck::UnknownBlock) => {
+                warn!(
+                    "{} does not know block {:?}; submitting without cross-check",
+                    verifier.url(),
+                    at
+                );
+                true
+            }
+            Ok(CrossCheck::Diverged { ours, theirs }) => {
+                let msg = format!(
+                    "{} and {} disagree on MigrationProcess at {:?}; holding submission.\n\
+                     ours: {}\ntheirs: {}",
+                    self.endpoint_url(),
+                    verifier.url(),
+                    at,
+                    ours,
+                    theirs
+                );
+                warn!("{}", msg);
+                send_notification(Severity::Warning, "Endpoints Disagree", &msg);
+                false
+            }
+            Err(e) => {
+                warn!("Cross-check against {} failed: {:#}", verifier.url(), e);
+                true
+            }
+        }
+    }
+
+    /// Check `state`, read at `at`, against a storage proof served by the
+    /// endpoint; `false` holds the submission, as does a missing or invalid proof
+    async fn verify_proof(&self, at: H256, state: &TrieState) -> bool {
+        let msg = match self.proven_task(at).await {
+            Ok(Some(proven)) if proven == state.witness => {
+                debug!("MigrationProcess at {:?} matches its storage proof", at);
+                return true;
+            }
+            Ok(proven) => format!(
+                "{} served a MigrationProcess at {:?} that its storage proof does not \
+                 back; holding submission.\nread: {:?}\nproven: {:?}",
+                self.endpoint_url(),
+                at,
+                state.witness,
+                proven
+            ),
+            Err(e) => format!(
+                "Could not verify MigrationProcess at {:?} against a storage proof from {}: \
+                 {:#}; holding submission",
+                at,
+                self.endpoint_url(),
+                e
+            ),
+        };
+        warn!("{}", msg);
+        send_notification(Severity::Warning, "Unproven Migration State", &msg);
+        false
+    }
+
+    /// `MigrationProcess` at `at` as proven against the block's state root
+    async fn proven_task(&self, at: H256) -> Result<Option<Value<()>>> {
+        let header = self
+            .rpc
+            .chain_get_header(Some(at))
+            .await?
+            .with_context(|| format!("Block {:?} not found", at))?;
+        let key = proof::storage_key("StateTrieMigration", "MigrationProcess");
+        let bytes = proof::proven_storage(&self.raw_rpc, at, header.state_root, &key).await?;
+        let metadata = self.client.metadata();
+        bytes
+            .map(|bytes| witness::decode_task(&bytes, &metadata))
+            .transpose()
+    }
+
+    /// With --relay-rpc-url, check that the relay chain has finalized the
+    /// inclusion of our parachain block. Failures are alerted; only with
+    /// --relay-finality do they fail the tx instead of counting it as final.
+    async fn verify_relay_inclusion(&self, block_hash: H256) -> Result<()> {
+        let Some(relay) = &self.relay else {
+            return Ok(());
+        };
+        let strict = self.config.relay_finality;
+        let checked = async {
+            let number = self
+                .rpc
+                .chain_get_header(Some(block_hash))
+                .await?
+                .with_context(|| format!("Header of {:?} not found", block_hash))?
+                .number;
+            let inclusion = relay
+                .wait_for_inclusion(
+                    number,
+                    block_hash,
+                    self.timing.block_time,
+                    self.timing.finalization_timeout,
+                )
+                .await?;
+            anyhow::Ok((number, inclusion))
+        }
+        .await;
+        let (severity, title, problem) = match checked {
+            Ok((number, Inclusion::Included)) => {
+                info!("Relay chain has finalized the inclusion of #{}", number);
+                return Ok(());
+            }
+            Ok((number, Inclusion::Pending)) => (
+                Severity::Warning,
+                "Relay Inclusion Unverified",
+                format!(
+                    "Parachain block #{} ({:?}) is finalized by {} but the relay chain has \
+                     not included it within {}s.",
+                    number,
+                    block_hash,
+                    self.endpoint_url(),
+                    self.timing.finalization_timeout.as_secs()
+                ),
+            ),
+            Ok((number, Inclusion::Conflict { relay_hash })) => (
+                Severity::Critical,
+                "Relay Chain Conflict",
+                format!(
+                    "{} reports #{} as {:?}, but the relay chain included {:?} at that height.",
+                    self.endpoint_url(),
+                    number,
+                    block_hash,
+                    relay_hash
+                ),
+            ),
+            Err(e) if strict => (
+                Severity::Warning,
+                "Relay Inclusion Unverified",
+                format!("Relay inclusion check failed: {:#}", e),
+            ),
+            Err(e) => {
+                warn!("Relay inclusion check failed: {:#}", e);
+                return Ok(());
+            }
+        };
+        warn!("{}", problem);
+        send_notification(severity, title, &problem);
+        if strict {
+            return Err(MigrationError::SubmissionFailed(problem).into());
+        }
+        Ok(())
+    }
+
+    /// With --trusted-finality, fail unless the light client has finalized
+    /// `block_hash` too
+    async fn verify_trusted_finality(&self, block_hash: H256) -> Result<()> {
+        let Some(trusted) = &self.trusted else {
+            return Ok(());
+        };
+        let checked = async {
+            let number = self
+                .rpc
+                .chain_get_header(Some(block_hash))
+                .await?
+                .with_context(|| format!("Header of {:?} not found", block_hash))?
+                .number;
+            let finality = trusted
+                .wait_for_finality(number, block_hash, self.timing.finalization_timeout)
+                .await?;
+            anyhow::Ok((number, finality))
+        }
+        .await;
+        let (severity, title, problem) = match checked {
+            Ok((number, Finality::Final)) => {
+                info!("Light client has finalized #{}", number);
                 return Ok(());
             }
+            Ok((number, Finality::Pending)) => (
+                Severity::Warning,
+                "Finality Unverified",
+                format!(
+                    "Block #{} ({:?}) is finalized by {} but the light client has not \
+                     finalized it within {}s.",
+                    number,
+                    block_hash,
+                    self.endpoint_url(),
+                    self.timing.finalization_timeout.as_secs()
+                ),
+            ),
+            Ok((number, Finality::Conflict { trusted_hash })) => (
+                Severity::Critical,
+                "Finality Conflict",
+                format!(
+                    "{} reports #{} as finalized {:?}, but the light client finalized {:?} \
+                     at that height.",
+                    self.endpoint_url(),
+                    number,
+                    block_hash,
+                    trusted_hash
+                ),
+            ),
+            Err(e) => (
+                Severity::Warning,
+                "Finality Unverified",
+                format!("Light client finality check failed: {:#}", e),
+            ),
+        };
+        warn!("{}", problem);
+        send_notification(severity, title, &problem);
+        Err(MigrationError::SubmissionFailed(problem).into())
+    }
+
+    /// Move the run loop to `to`: logged, and published in the health snapshot
+    fn enter(&self, to: BotState) {
+        let transition = self
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .enter(to, Instant::now());
+        let Some(transition) = transition else {
+            return;
+        };
+        if !transition.valid {
+            warn!(
+                "Unexpected state transition {} -> {}",
+                transition.from, transition.to
+            );
+        } else if matches!(
+            to,
+            BotState::Backoff | BotState::Paused | BotState::Complete
+        ) {
+            info!(
+                state = %to,
+                "State: {} -> {} (after {:.1}s)",
+                transition.from,
+                to,
+                transition.after.as_secs_f64()
+            );
+        } else {
+            debug!(
+                state = %to,
+                "State: {} -> {} (after {:.1}s)",
+                transition.from,
+                to,
+                transition.after.as_secs_f64()
+            );
+        }
+        self.health.update(|h| {
+            h.state = to;
+            h.state_since = Some(chrono::Utc::now());
+        });
+    }
+
+    /// Deal with txs a previous run left in flight (journaled as pending)
+    /// before submitting anything that could conflict with them
+    async fn recover_in_flight(&self) -> Result<()> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let entries = journal.entries()?;
+        let in_flight = recovery::in_flight(&entries);
+        for entry in entries.iter().filter(|e| e.outcome == "pending") {
+            if !in_flight.iter().any(|f| f.tx_hash == entry.tx_hash) {
+                self.record_journal(|j| {
+                    j.finish(
+                        &entry.tx_hash,
+                        &Outcome::Failed("superseded by a re-signed tx".to_string()),
+                    )
+                });
+            }
         }
+        if in_flight.is_empty() {
+            return Ok(());
+        }
+
+        // Without unsafe RPC the pool can't be listed; a pool-aware nonce ahead of
+        // the finalized one still tells that something of ours is pooled
+        let pooled = match pool::pending_extrinsics(&self.raw_rpc).await {
+            Ok(pending) => Some(pending.iter().map(|e| e.hash()).collect::<Vec<_>>()),
+            Err(e) => {
+                debug!("Could not list the pool ({}), using the account nonce", e);
+                None
+            }
+        };
 
+        for entry in in_flight {
+            warn!("Tx left in flight by a previous run: {}", entry);
+            // Rows journaled before the account was recorded are the signer's
+            let account_id = match &entry.account {
+                Some(account) => account
+                    .parse::<subxt::utils::AccountId32>()
+                    .with_context(|| format!("Invalid account in journal: {}", account))?,
+                None => self.signer.account_id(),
+            };
+            let finalized_nonce = self.finalized_nonce(&account_id).await?;
+            let next_index = pool::account_next_index(&self.raw_rpc, &account_id.to_string())
+                .await
+                .unwrap_or(finalized_nonce);
+            let tx_hash = hex::decode(entry.tx_hash.trim_start_matches("0x"))
+                .ok()
+                .filter(|bytes| bytes.len() == 32)
+                .map(|bytes| H256::from_slice(&bytes))
+                .with_context(|| format!("Invalid tx hash in journal: {}", entry.tx_hash))?;
+            let in_pool = match &pooled {
+                Some(hashes) => hashes.contains(&tx_hash),
+                None => entry.nonce >= finalized_nonce && next_index > entry.nonce,
+            };
+            let included = recovery::find_in_recent_blocks(
+                &self.client,
+                &self.rpc,
+                tx_hash,
+                recovery::LOOKBACK_BLOCKS,
+            )
+            .await?;
+            let found = recovery::classify(included, in_pool, entry.nonce, finalized_nonce);
+            match found {
+                InFlight::Included {
+                    block_hash,
+                    number,
+                    events,
+                } => {
+                    info!(
+                        "Adopted tx {}: finalized in block #{} ({:?})",
+                        entry.tx_hash, number, block_hash
+                    );
+                    self.record_journal(|j| {
+                        j.finish(
+                            &entry.tx_hash,
+                            &Outcome::Finalized {
+                                block_hash: format!("{:?}", block_hash),
+                                events,
+                            },
+                        )
+                    });
+                }
+                InFlight::Pooled => {
+                    info!(
+                        "Tx {} is still in the pool, waiting for it instead of submitting",
+                        entry.tx_hash
+                    );
+                    let outcome = pool::wait_for_nonce_change(
+                        finalized_nonce,
+                        self.timing.block_time,
+                        self.timing.pending_polls(),
+                        || self.finalized_nonce(&account_id),
+                    )
+                    .await;
+                    if outcome == NonceWait::TimedOut {
+                        warn!("Timeout waiting for in-flight tx, proceeding anyway...");
+                        continue;
+                    }
+                    // A few blocks suffice: the tx was in the pool a moment ago
+                    let included =
+                        recovery::find_in_recent_blocks(&self.client, &self.rpc, tx_hash, 10)
+                            .await?;
+                    let outcome = match included {
+                        Some((block_hash, number, events)) => {
+                            info!(
+                                "Adopted tx {}: finalized in block #{}",
+                                entry.tx_hash, number
+                            );
+                            Outcome::Finalized {
+                                block_hash: format!("{:?}", block_hash),
+                                events,
+                            }
+                        }
+                        None => Outcome::Failed("nonce used by another tx".to_string()),
+                    };
+                    self.record_journal(|j| j.finish(&entry.tx_hash, &outcome));
+                }
+                InFlight::NonceUsed => {
+                    warn!(
+                        "Tx {} not found in the last {} blocks, but nonce {} is used",
+                        entry.tx_hash,
+                        recovery::LOOKBACK_BLOCKS,
+                        entry.nonce
+                    );
+                    self.record_journal(|j| {
+                        j.finish(
+                            &entry.tx_hash,
+                            &Outcome::Failed(format!(
+                                "outcome unknown after restart (nonce {} used)",
+                                entry.nonce
+                            )),
+                        )
+                    });
+                }
+                InFlight::Dropped => {
+                    info!("Tx {} was dropped, its nonce is free", entry.tx_hash);
+                    self.record_journal(|j| {
+                        j.finish(
+                            &entry.tx_hash,
+                            &Outcome::Failed("dropped before restart".to_string()),
+                        )
+                    });
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Persist the run counters to --state-file, if enabled (failures are logged, not fatal),
+    /// and keep the session summary for the shutdown report
+    fn save_counters(&self, stats: &SessionStats) {
+        if let Ok(mut summary) = self.session_summary.lock() {
+            *summary = Some(stats.summary());
+        }
+        if let Some(counters) = &self.counters {
+            if let Err(e) = counters.save(stats) {
+                warn!("Failed to save counters: {:#}", e);
+            }
+        }
+    }
+
+    /// Write to the submission journal, if enabled (failures are logged, not fatal)
+    fn record_journal(&self, write: impl FnOnce(&Journal) -> Result<()>) {
+        if let Some(journal) = &self.journal {
+            if let Err(e) = write(journal) {
+                warn!("Failed to write journal: {:#}", e);
+            }
+        }
+    }
+
+    /// Attribute a finalized tx's progress to the session and the history DB
+    fn record_progress(
+        &self,
+        stats: &mut SessionStats,
+        pending: PendingProgress,
+        after: &MigrationStatus,
+    ) -> (u64, u64) {
+        let (items, bytes) = stats.record_progress(&pending.status_before, after);
+        self.emit(BotEvent::Progress { items, bytes });
+        self.save_counters(stats);
+
+        if let (Some(history), Some(tx_hash)) = (&self.history, pending.tx_hash) {
+            let entry = HistoryEntry {
+                timestamp: chrono::Utc::now().timestamp(),
+                instance_id: self.instance_id.clone(),
+                chain: format!("{:?}", self.client.genesis_hash()),
+                account: self.signer.account_id().to_string(),
+                sponsor: self.config.sponsor.clone(),
+                tx_hash: format!("{:?}", tx_hash),
+                items,
+                bytes,
+                fee: pending.fee,
+            };
+            if let Err(e) = history.record(&entry) {
+                warn!("Failed to record history: {:?}", e);
+            }
+        }
+
+        (items, bytes)
+    }
+
+    /// Record an event in the incident timeline and the --event-log, if enabled
+    fn emit(&self, event: BotEvent) {
+        let record = EventRecord {
+            timestamp: chrono::Utc::now(),
+            event,
+        };
+        if let Some(events) = &self.events {
+            if let Err(e) = events.append(&record) {
+                warn!("Failed to record event: {:?}", e);
+            }
+        }
+        if self.config.events.is_some() {
+            if let Err(e) = events::write_record(&mut std::io::stdout().lock(), &record) {
+                warn!("Failed to write event to stdout: {:?}", e);
+            }
+        }
+        if let Some(push) = &self.push {
+            push.publish(&record);
+        }
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.apply(&record);
+        }
+        self.timeline.push(record);
+    }
+
+    /// Apply `f` to the --tui dashboard state, if enabled
+    fn update_dashboard(&self, f: impl FnOnce(&mut tui::DashboardState)) {
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.update(f);
+        }
+    }
+
+    /// Critical notification body with the recent-event timeline appended
+    /// (and the signing account, when there are several)
+    fn incident_body(&self, message: &str) -> String {
+        let mut body = message.to_string();
+        if self.signer.rotates() {
+            body.push_str(&format!("\nAccount: {}", self.signer.label()));
+        }
+        let timeline = self.timeline.render();
+        if !timeline.is_empty() {
+            body.push_str(&format!("\n\nRecent events:\n{}", timeline));
+        }
+        body
+    }
+
+    /// Set the active signer account aside and switch to the next one that
+    /// isn't (--rotate-seed-file); false when there is none to switch to
+    fn rotate(&self, reason: Bench) -> bool {
+        if !self.signer.rotates() {
+            return false;
+        }
+        let from = self.signer.label();
+        let Some(next) = self.signer.bench(reason, Instant::now()) else {
+            warn!(
+                "Account {} is benched ({}), but so is every other account",
+                from, reason
+            );
+            return false;
+        };
+        let msg = format!("{} benched ({}); signing as {} now", from, reason, next);
+        warn!("{}", msg);
+        send_notification(Severity::Warning, "Signer Rotated", &msg);
+        for line in self.signer.summary(Instant::now()) {
+            info!("  {}", line);
+        }
+        true
+    }
+
+    /// Tell every notification channel how the run ended (see `exit::shutdown_report`)
+    fn send_shutdown_report(&self, result: Result<&Stop, &anyhow::Error>) {
+        let session = self.session_summary.lock().ok().and_then(|s| s.clone());
+        let status = self.health.snapshot().and_then(|s| s.status);
+        let Some((severity, body)) = exit::shutdown_report(
+            result,
+            session.as_deref(),
+            status.as_ref(),
+            self.remaining_keys.get(),
+        ) else {
+            return;
+        };
+        let body = match result {
+            Ok(_) => body,
+            Err(_) => self.incident_body(&body),
+        };
+        notify::send_everywhere(severity, "Bot Stopped", &body);
+    }
+
+    /// Log per-sponsor contribution totals from the history DB
+    fn log_sponsor_totals(&self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        match history.sponsor_totals() {
+            Ok(totals) => {
+                for t in totals {
+                    info!(
+                        "Sponsor {}: {} txs, {} items / {} bytes, fees {:.6} WND",
+                        t.sponsor,
+                        t.txs,
+                        t.items,
+                        t.bytes,
+                        units_to_wnd(t.fees)
+                    );
+                }
+            }
+            Err(e) => warn!("Could not read sponsor totals: {:?}", e),
+        }
+    }
+
     /// Run the migration bot
-    async fn run(&mut self) -> Result<()> {
-        // Handle --status flag
-        if self.config.status {
-            return self.show_status().await;
+    async fn run(&mut self) -> Result<Stop> {
+        match self.config.command {
+            Some(Command::Status) => {
+                self.show_status().await?;
+                return Ok(Stop::Command);
+            }
+            Some(Command::Limits { ref action }) => {
+                match action {
+                    LimitsCommand::Get => self.show_limits().await?,
+                    LimitsCommand::Set { size, item } => {
+                        let limits = MigrationLimits::new(*size, *item);
+                        limits
+                            .validate(None)
+                            .map_err(MigrationError::InvalidLimits)?;
+                        info!("Setting chain limits: {}", limits);
+                        self.set_max_limits(limits).await?;
+                    }
+                    LimitsCommand::Recommend => self.recommend_limits().await?,
+                }
+                return Ok(Stop::Command);
+            }
+            Some(Command::Account {
+                action: None | Some(AccountCommand::Show),
+            }) => {
+                self.show_account().await?;
+                return Ok(Stop::Command);
+            }
+            Some(Command::Pool { ref action }) => {
+                match action {
+                    PoolCommand::List => self.list_pool().await?,
+                    PoolCommand::Clear { mine_only } => {
+                        self.clear_pending_transactions(*mine_only).await?;
+                    }
+                    PoolCommand::Remove { hash } => self.remove_from_pool(*hash).await?,
+                }
+                return Ok(Stop::Command);
+            }
+            _ => {}
+        }
+        if let Some(Command::Remaining {
+            samples,
+            page_size,
+            full,
+            parallel,
+            ref keys_out,
+            ref checkpoint,
+            ref key_cache,
+        }) = self.config.command
+        {
+            if parallel > 1 && keys_out.is_some() {
+                anyhow::bail!("--keys-out writes keys in order and needs --parallel 1");
+            }
+            if parallel > 1 && full && key_cache.is_some() {
+                anyhow::bail!("--key-cache writes keys in order and needs --parallel 1");
+            }
+            let sampling = (!full).then_some((samples, page_size));
+            self.show_remaining(
+                sampling,
+                parallel,
+                keys_out.as_deref(),
+                checkpoint.as_deref(),
+                key_cache.as_deref(),
+            )
+            .await?;
+            return Ok(Stop::Command);
+        }
+        if let Some(Command::Witness {
+            action: WitnessCommand::Export { output },
+        }) = &self.config.command
+        {
+            self.export_witness(output).await?;
+            return Ok(Stop::Command);
+        }
+        if let Some(Command::Tx { action }) = &self.config.command {
+            match action {
+                TxCommand::Build { output, qr, .. } => self.build_offline_tx(output, *qr).await?,
+                TxCommand::Submit { file, signature } => {
+                    let unsigned = UnsignedTx::load(file)?;
+                    if let Some(hash) = self.submit_offline_tx(&unsigned, signature).await? {
+                        println!("Finalized in block {:?}", hash);
+                    }
+                }
+            }
+            return Ok(Stop::Command);
         }
 
         // Handle --clear-pending flag
         if self.config.clear_pending {
-            self.clear_pending_transactions().await?;
-            if self.config.once || self.config.runs == 0 {
-                return Ok(()); // Exit after clearing if --once or no runs specified
+            self.clear_pending_transactions(false).await?;
+            if self.config.run.once || self.config.run.runs == 0 {
+                return Ok(Stop::Command); // Exit after clearing if --once or no runs specified
             }
         }
 
+        if !self.config.run.dry_run {
+            self.recover_in_flight().await?;
+        }
+
         info!("Starting migration bot...");
+        if self.config.run.pipeline && (self.relay.is_some() || self.trusted.is_some()) {
+            anyhow::bail!(
+                "--pipeline confirms txs at best-block inclusion; it can't be combined with \
+                 --relay-rpc-url or --trusted-finality, which check each tx's finality"
+            );
+        }
+        info!("Chain constants: {}", self.constants.get(&self.client));
+        let account_id = self.signer.account_id();
+        register_instance(
+            InstanceEntry {
+                pid: std::process::id(),
+                instance_id: self.instance_id.clone(),
+                mode: if self.config.run.dry_run {
+                    "dry-run"
+                } else {
+                    "run"
+                }
+                .to_string(),
+                chain: chain_name(&self.rpc, &self.client).await,
+                account: Some(account_id.to_string()),
+                rpc_url: self.config.rpc_url.clone(),
+                health_addr: self.config.health_addr,
+                started_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                snapshot: None,
+            },
+            &self.health,
+            &self.shutdown,
+        );
         send_notification(
+            Severity::Info,
             "Westend Bot Started",
             "Bot is running and monitoring migration.",
-            false,
         );
 
-        // Spawn heartbeat task (shows dad jokes every 60s) with graceful shutdown
-        let shutdown_token = self.shutdown.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
-            interval.tick().await; // Skip first immediate tick
-            loop {
-                tokio::select! {
-                    _ = shutdown_token.cancelled() => {
-                        debug!("Heartbeat task shutting down");
-                        break;
-                    }
-                    _ = interval.tick() => {
-                        if let Some(joke) = fetch_dad_joke().await {
-                            info!("💓 {}", joke);
-                        }
-                    }
-                }
+        // Spawn heartbeat task (every 60s) with graceful shutdown
+        let heartbeat_interval = Duration::from_secs(HEARTBEAT_INTERVAL_SECS);
+        let (progress_tx, progress_rx) = tokio::sync::watch::channel(SessionProgress::default());
+        match self.config.heartbeat {
+            HeartbeatMode::Stats => {
+                let account_id = self.signer.account_id();
+                let provider =
+                    StatsHeartbeat::new(self.client.clone(), self.rpc.clone(), account_id);
+                heartbeat::spawn(provider, heartbeat_interval, self.shutdown.clone());
             }
-        });
+            HeartbeatMode::Offline => {
+                let provider = OfflineHeartbeat::new(progress_rx);
+                heartbeat::spawn(provider, heartbeat_interval, self.shutdown.clone());
+            }
+            HeartbeatMode::Joke => {
+                heartbeat::spawn(JokeHeartbeat, heartbeat_interval, self.shutdown.clone());
+            }
+            HeartbeatMode::Off => {}
+        }
+
+        if let Some(path) = &self.config.leaderboard {
+            let account_id = self.signer.account_id();
+            leaderboard::spawn(
+                self.client.clone(),
+                self.rpc.clone(),
+                account_id.to_string(),
+                path.clone(),
+                Duration::from_secs(self.config.leaderboard_interval_mins * 60),
+                self.config.leaderboard_lookback,
+                self.shutdown.clone(),
+            );
+        }
+
+        if let Some(target) = &self.config.post_progress {
+            let token = self.config.post_progress_token.clone().with_context(|| {
+                "--post-progress needs POST_PROGRESS_TOKEN (GitHub token or forum API key)"
+            })?;
+            poster::spawn(
+                target.clone(),
+                PostAuth {
+                    token,
+                    username: self.config.post_progress_user.clone(),
+                },
+                progress_tx.subscribe(),
+                self.remaining_keys.clone(),
+                Duration::from_secs(self.config.post_progress_interval_hours.max(1) * 3600),
+                self.shutdown.clone(),
+            )?;
+        }
+
+        // One block follower shared by everything that watches other migrators
+        let our_accounts: Vec<String> = self.signer.accounts().map(|a| a.to_string()).collect();
+        let follower = (self.config.network_stats || self.config.run.compete_backoff)
+            .then(|| follower::spawn(self.client.clone(), self.shutdown.clone()));
+        if let (true, Some(follower)) = (self.config.network_stats, &follower) {
+            network::spawn(
+                follower,
+                our_accounts.clone(),
+                Duration::from_secs(self.config.network_stats_interval_mins * 60),
+                self.shutdown.clone(),
+            );
+        }
+
+        if let Some(addr) = self.config.health_addr {
+            health::serve(addr, self.health.clone(), self.shutdown.clone()).await?;
+        }
+        if let (Some(addr), Some(hub)) = (self.config.ws_addr, &self.push) {
+            push::serve(addr, hub.clone(), self.shutdown.clone()).await?;
+        }
+        let dashboard_task = self
+            .dashboard
+            .as_ref()
+            .map(|dashboard| tui::spawn(dashboard.clone(), self.shutdown.clone()));
 
         // Check chain limits and determine what to use
         let current_limits = self.get_max_limits().await?;
-
-        // If config is 0, use chain max; otherwise use config value
+        let limits = MigrationLimits::working(self.limits(), current_limits);
+        limits
+            .validate(None)
+            .map_err(MigrationError::InvalidLimits)?;
+        self.config.run.item_limit = limits.item;
+        self.config.run.size_limit = limits.size;
         match current_limits {
-            Some((max_size, max_item)) => {
-                // Use half of chain max if config is 0.
-                // Rationale: Using 50% of max limits provides safety margin for:
-                // 1. State changes between query and submission (witness_task mismatch)
-                // 2. Avoiding SizeUpperBoundExceeded errors that could cause slashing
-                // 3. Leaving room for other transactions in the block
-                // The TypeScript reference also uses conservative limits.
-                if self.config.item_limit == 0 {
-                    self.config.item_limit = max_item / 2;
-                }
-                if self.config.size_limit == 0 {
-                    self.config.size_limit = max_size / 2;
-                }
-
-                // Check if we need to update chain limits (config exceeds chain max)
-                if self.config.item_limit > max_item || self.config.size_limit > max_size {
+            Some(max) if !limits.exceeds(max) => info!("Using limits: {}", limits),
+            // Only on request: set_signed_max_limits is a controller call
+            _ if self.config.run.set_max_limits => {
+                info!("Setting chain limits: {}", limits);
+                self.set_max_limits(limits).await?;
+            }
+            Some(max) => {
+                return Err(MigrationError::InvalidLimits(format!(
+                    "{} exceeds SignedMigrationMaxLimits {}; raise them with `limits set` \
+                     or pass --set-max-limits",
+                    limits, max
+                ))
+                .into())
+            }
+            None => {
+                return Err(MigrationError::InvalidLimits(
+                    "SignedMigrationMaxLimits is not set; set it with `limits set` \
+                     or pass --set-max-limits"
+                        .to_string(),
+                )
+                .into())
+            }
+        }
+
+        // Chain maximums in effect now (raised above if the config exceeded them)
+        let max_limits = current_limits.map_or(self.limits(), |max| max.max(self.limits()));
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.set_item_limits(self.config.run.item_limit, max_limits.item);
+        }
+
+        // Preflight: a missing account and an undecodable one need different fixes
+        if let Some(real) = &self.config.proxy_for {
+            self.check_proxy(real).await?;
+        }
+        let account_id = self.origin_account();
+        let balance = match self.account_balance().await {
+            Ok(Some(balance)) => balance.free,
+            Ok(None) => {
+                warn!("Account {} does not exist on chain - fund it first", account_id);
+                if !self.config.run.dry_run {
+                    return Err(MigrationError::AccountNotFound(account_id.to_string()).into());
+                }
+                0
+            }
+            Err(e) => {
+                error!("Preflight: could not read the account balance: {}", e);
+                if matches!(
+                    e.downcast_ref::<MigrationError>(),
+                    Some(MigrationError::AccountDecode(_))
+                ) {
+                    error!(
+                        "The runtime's AccountData layout may not match --account-layout {}",
+                        self.config.account_layout
+                    );
+                }
+                return Err(e);
+            }
+        };
+        info!("Account balance: {} units", balance);
+
+        if balance == 0 {
+            warn!("Account has zero balance! Transactions will fail.");
+            if !self.config.run.dry_run {
+                return Err(MigrationError::ZeroBalance.into());
+            }
+        }
+
+        // Track successful migrations for --runs limit and campaign caps,
+        // continuing from the --state-file counters after a restart
+        let mut stats = match &self.counters {
+            Some(counters) => match counters.load()? {
+                Some(saved) => {
                     info!(
-                        "Updating chain limits: items={}, size={}",
-                        self.config.item_limit, self.config.size_limit
+                        "Resuming counters from {} (saved {}): {} successful, {} failed, {} items / {} bytes",
+                        counters.path().display(),
+                        saved.updated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        saved.successful_runs,
+                        saved.failed_runs,
+                        saved.items_migrated,
+                        saved.bytes_migrated
                     );
-                    self.set_max_limits(self.config.size_limit, self.config.item_limit)
-                        .await?;
-                } else {
+                    saved.restore()
+                }
+                None => SessionStats::new(),
+            },
+            None => SessionStats::new(),
+        };
+        let caps = CampaignCaps {
+            max_items: self.config.run.max_items_total,
+            max_bytes: self.config.run.max_bytes_total,
+        };
+        // Last finalized tx, attributed once the following status is known
+        let mut progress_baseline: Option<PendingProgress> = None;
+        let mut consecutive_errors: u32 = 0;
+        // Stale-witness failures retried in a row without counting them
+        let mut stale_witness_retries: u32 = 0;
+        let mut paused = false;
+        let mut node_unhealthy = false;
+        let mut pacer = AdaptivePacer::new();
+        let mut competition = match (&follower, self.config.run.compete_backoff) {
+            (Some(follower), true) => Some((
+                follower.subscribe(),
+                CompetitionTracker::new(our_accounts.clone(), self.config.run.compete_yield_after),
+            )),
+            _ => None,
+        };
+        let mut forks = ForkMonitor::new();
+        let mut rates = RateTracker::new(eta::RATE_WINDOW);
+        let mut remaining_refreshed = None;
+        // Latest "~14h remaining" estimate, appended to confirmation notifications
+        let mut eta_line: Option<String> = None;
+        let target_runs = self.config.run.runs;
+
+        if target_runs > 0 {
+            if stats.successful_runs >= target_runs {
+                info!(
+                    "Already completed {} of {} runs (--state-file), nothing to do",
+                    stats.successful_runs, target_runs
+                );
+                return Ok(Stop::RunsReached(stats.successful_runs));
+            }
+            info!(
+                "Will submit {} migration transaction(s)",
+                target_runs - stats.successful_runs
+            );
+        }
+        // The --tui dashboard has its own goal gauge
+        let run_progress = (target_runs > 0 && !self.config.tui)
+            .then(|| RunProgress::new(target_runs, stats.successful_runs));
+        if let Some(deadline) = self.config.run.deadline {
+            info!("Deadline: {}", deadline.format("%Y-%m-%d %H:%M:%S"));
+        }
+        if let Some(quiet) = self.config.quiet_hours {
+            info!("Quiet hours: {} (critical notifications only)", quiet);
+        }
+        self.emit(BotEvent::Started {
+            account: self.signer.account_id().to_string(),
+            chain: format!("{:?}", self.client.genesis_hash()),
+            item_limit: self.config.run.item_limit,
+            size_limit: self.config.run.size_limit,
+        });
+
+        let stop = loop {
+            if self.shutdown.is_cancelled() {
+                info!("Shutdown requested, not submitting further transactions");
+                break Stop::Shutdown { drained: true };
+            }
+
+            // Deliver the morning summary even if no new notification triggers it
+            notify::release_held();
+            self.settle_trailing(&mut stats);
+
+            if let Some(deadline) = self.config.run.deadline {
+                if chrono::Local::now() >= deadline {
                     info!(
-                        "Using limits: items={}, size={}",
-                        self.config.item_limit, self.config.size_limit
+                        "Deadline {} reached, stopping",
+                        deadline.format("%Y-%m-%d %H:%M:%S")
                     );
+                    send_notification(Severity::Info, "Deadline Reached", &stats.summary());
+                    break Stop::Deadline;
                 }
             }
-            None => {
-                // No chain limits set - use sensible defaults if config is 0
-                if self.config.item_limit == 0 {
-                    self.config.item_limit = 4096;
+
+            // Keybinds of the --tui dashboard
+            if let Some(dashboard) = &self.dashboard {
+                if dashboard.paused() {
+                    self.enter(BotState::Paused);
+                    self.idle(Duration::from_secs(1)).await;
+                    continue;
                 }
-                if self.config.size_limit == 0 {
-                    self.config.size_limit = 409600;
+                self.config.run.item_limit = dashboard.item_limit();
+            }
+
+            if self.config.rpc_quota_failover && budget::exhausted(self.endpoint_url()) {
+                self.shift_load().await;
+            }
+
+            // Fail-safe: a critical notification went unacknowledged, or an escalation paused us
+            if let Some(overdue) = notify::overdue_ack() {
+                if !paused {
+                    paused = true;
+                    warn!(
+                        "'{}' awaits acknowledgement, pausing submissions",
+                        overdue.summary
+                    );
+                    send_notification(
+                        Severity::Warning,
+                        "Submissions Paused",
+                        &format!(
+                            "'{}' awaits acknowledgement. Open /ack/{} on the health \
+                             server to resume.",
+                            overdue.summary, overdue.token
+                        ),
+                    );
                 }
-                info!(
-                    "Setting chain limits: items={}, size={}",
-                    self.config.item_limit, self.config.size_limit
+                self.health.update(|h| h.paused = true);
+                self.enter(BotState::Paused);
+                self.idle(Duration::from_secs(ACK_POLL_SECS)).await;
+                continue;
+            }
+            if paused {
+                paused = false;
+                info!("Acknowledged, resuming submissions");
+                send_notification(
+                    Severity::Info,
+                    "Submissions Resumed",
+                    "Notification acknowledged.",
                 );
-                self.set_max_limits(self.config.size_limit, self.config.item_limit)
-                    .await?;
+                self.health.update(|h| h.paused = false);
             }
-        }
 
-        // Check balance
-        let balance = self.check_balance().await?;
-        info!("Account balance: {} units", balance);
-
-        if balance == 0 {
-            warn!("Account has zero balance! Transactions will fail.");
-            if !self.config.dry_run {
-                return Err(MigrationError::ZeroBalance.into());
+            // Preflight: a syncing or poorly connected node serves stale nonces
+            // and witnesses, so hold submissions until it recovers
+            match self.rpc.system_health().await {
+                Ok(node) => {
+                    if let Some(problem) =
+                        node_health_problem(node.peers, node.is_syncing, self.config.min_peers)
+                    {
+                        if !node_unhealthy {
+                            node_unhealthy = true;
+                            warn!("Holding submissions: {}", problem);
+                            send_notification(
+                                Severity::Warning,
+                                "Node Unhealthy",
+                                &format!(
+                                    "{}: {}. Holding submissions until it recovers.",
+                                    self.endpoint_url(),
+                                    problem
+                                ),
+                            );
+                        }
+                        self.health.update(|h| h.connected = false);
+                        self.enter(BotState::Paused);
+                        self.idle(Duration::from_secs(NODE_HEALTH_POLL_SECS)).await;
+                        continue;
+                    }
+                    if node_unhealthy {
+                        node_unhealthy = false;
+                        info!("Node healthy again ({} peers), resuming", node.peers);
+                        send_notification(
+                            Severity::Info,
+                            "Node Recovered",
+                            &format!("{} peers, in sync. Resuming submissions.", node.peers),
+                        );
+                    }
+                }
+                Err(e) => warn!("Could not query node health: {}", e),
             }
-        }
-
-        // Track successful migrations for --runs limit
-        let mut successful_runs: u32 = 0;
-        let mut consecutive_errors: u32 = 0;
-        let target_runs = self.config.runs;
 
-        if target_runs > 0 {
-            info!("Will submit {} migration transaction(s)", target_runs);
-        }
+            // Fork check: persistent best-chain reorgs move reads to the finalized head
+            if let Err(e) = self.observe_forks(&mut forks).await {
+                warn!("Could not sample best head for fork detection: {}", e);
+            }
 
-        loop {
-            // Get current migration task
-            let (witness_task, status) = match self.get_migration_task().await? {
-                Some(result) => result,
+            // Get current migration task; after a finalized tx of ours, the
+            // value read at its inclusion block saves the round trip
+            self.enter(BotState::Fetching);
+            let (state_block, state, witness_read_at) = match self.next_witness.take() {
+                Some(read) => {
+                    debug!(
+                        "Next witness read at {:?}, confirmed by its Migrated event",
+                        read.block
+                    );
+                    (read.block, Some(read.state), read.at)
+                }
+                None => {
+                    let witness_read_at = Instant::now();
+                    let state_block = self.read_block().await?;
+                    (
+                        state_block,
+                        self.fetch_state_at(state_block).await?,
+                        witness_read_at,
+                    )
+                }
+            };
+            let state = match state {
+                Some(state) => state,
                 None => {
                     warn!("Could not fetch migration progress");
-                    if self.config.once {
-                        break;
+                    if self.config.run.once {
+                        break Stop::Held("migration progress unavailable".to_string());
                     }
-                    tokio::time::sleep(Duration::from_secs(self.config.delay_secs)).await;
+                    self.enter(BotState::Backoff);
+                    self.idle(Duration::from_secs(self.config.run.delay_secs))
+                        .await;
                     continue;
                 }
             };
 
+            let status = state.status.clone();
+            let mut position = keyspace::position_percent(&state.witness)
+                .filter(|_| !status.top_complete)
+                .map(|p| format!(" ~{:.1}%", p))
+                .unwrap_or_default();
+            if let Some(name) = self.current_storage_item(&state.witness) {
+                position.push_str(&format!(" in {}", name));
+            }
             info!(
-                "Status: top={}/{} child={}/{} size={}",
+                top_items = status.top_items,
+                child_items = status.child_items,
+                size = status.size,
+                "Status: top={}/{}{} child={}/{} size={}{}",
                 if status.top_complete { "done" } else { "wip" },
                 status.top_items,
+                position,
                 if status.child_complete { "done" } else { "wip" },
                 status.child_items,
-                status.size
+                status.size,
+                match self.read_basis() {
+                    ReadBasis::Best => "",
+                    ReadBasis::Finalized => " (finalized basis: forks detected)",
+                }
             );
+            self.emit(BotEvent::Status(status.clone()));
+            self.health.update(|h| {
+                h.connected = true;
+                h.status = Some(status.clone());
+            });
+
+            self.remaining_keys.refresh(&self.raw_rpc, &mut remaining_refreshed);
+
+            // Attribute the last finalized tx's progress (pallet counters are cumulative)
+            if let Some(pending) = progress_baseline.take() {
+                let (items, bytes) = self.record_progress(&mut stats, pending, &status);
+                info!(
+                    items,
+                    size = bytes,
+                    "Migrated {} items / {} bytes (session: {} items / {} bytes)",
+                    items, bytes, stats.items_migrated, stats.bytes_migrated
+                );
+
+                rates.record(Instant::now(), items, bytes);
+                if let (Some((items_per_min, bytes_per_min)), Some(remaining)) =
+                    (rates.per_minute(), self.remaining_keys.get())
+                {
+                    if let Some(eta) = eta::eta(remaining, items_per_min) {
+                        let line = format!(
+                            "~{} remaining at current rate ({:.0} items/min, {:.1} KB/min, {} keys left)",
+                            format_duration(eta),
+                            items_per_min,
+                            bytes_per_min / 1024.0,
+                            remaining
+                        );
+                        info!("ETA: {}", line);
+                        eta_line = Some(line);
+                    }
+                }
+            }
+            progress_tx.send_replace(SessionProgress::new(&stats, Some(&status)));
 
-            if status.is_complete() {
+            if self.task.is_complete(&state) {
+                self.enter(BotState::Complete);
                 info!("Migration is COMPLETE!");
                 send_notification(
+                    Severity::Success,
                     "Migration Complete",
                     "The Westend state trie migration is complete!",
-                    false,
                 );
-                break;
+                if self.config.run.stay_alive {
+                    watch::stay_alive(&self.client, &self.raw_rpc, &self.health, &self.shutdown)
+                        .await?;
+                }
+                break Stop::Complete;
             }
 
+            let item_limit = match caps.next_item_limit(
+                &stats,
+                self.config.run.item_limit,
+                self.config.run.size_limit,
+            ) {
+                Ok(limit) => limit,
+                Err(reason) => {
+                    info!("Campaign cap reached: {}", reason);
+                    send_notification(Severity::Info, "Campaign Cap Reached", &reason);
+                    break Stop::CapReached(reason);
+                }
+            };
+
+            let limits = MigrationLimits::new(self.config.run.size_limit, item_limit);
+            limits
+                .validate(Some(max_limits))
+                .map_err(MigrationError::InvalidLimits)?;
+
             // Check balance BEFORE tx (migration should be FREE for controller)
             let balance_before = self.check_balance().await?;
+            self.update_dashboard(|d| d.balance = Some(balance_before));
+            self.signer.record_balance(balance_before);
+            // An account that can't cover the deposit hands over to the next
+            // one (with --proxy-for the balance isn't the signer's)
+            if self.config.proxy_for.is_none()
+                && self.signer.rotates()
+                && self
+                    .signed_deposit(item_limit)
+                    .is_ok_and(|deposit| balance_before < deposit)
+                && self.rotate(Bench::LowBalance)
+            {
+                continue;
+            }
+
+            let risk = risk::assess(&RiskInputs {
+                validated: self.dry_run_supported.load(Ordering::Relaxed),
+                witness_age: witness_read_at.elapsed(),
+                limits,
+                max_limits,
+                deposit: self.signed_deposit(item_limit).ok(),
+                balance: balance_before,
+            });
+            if risk.score > self.config.run.max_risk {
+                let msg = format!(
+                    "Risk {} exceeds --max-risk {}; skipping this submission",
+                    risk, self.config.run.max_risk
+                );
+                warn!("{}", msg);
+                send_notification(Severity::Warning, "Risky Submission Skipped", &msg);
+                if self.config.run.once {
+                    break Stop::Held("risk above --max-risk".to_string());
+                }
+                self.enter(BotState::Backoff);
+                self.idle(Duration::from_secs(RETRY_WAIT_SECS)).await;
+                continue;
+            }
+            debug!("Submission risk: {}", risk);
+
+            // A second endpoint must agree on the witness before we pay for it
+            if let Some(verifier) = &self.verifier {
+                if !self.cross_check(verifier, state_block, &state).await {
+                    if self.config.run.once {
+                        break Stop::Held("endpoints disagree on migration state".to_string());
+                    }
+                    self.enter(BotState::Backoff);
+                    self.idle(Duration::from_secs(RETRY_WAIT_SECS)).await;
+                    continue;
+                }
+            }
+            // ... and the endpoint must prove it against the block's state root
+            if self.config.verify_proofs && !self.verify_proof(state_block, &state).await {
+                if self.config.run.once {
+                    break Stop::Held("migration state not backed by a storage proof".to_string());
+                }
+                self.enter(BotState::Backoff);
+                self.idle(Duration::from_secs(RETRY_WAIT_SECS)).await;
+                continue;
+            }
 
             // Submit migration transaction
-            match self.submit_migration(witness_task).await {
-                Ok(()) => {
-                    successful_runs += 1;
-                    info!("Tx #{} ✓", successful_runs);
+            let submit_start = Instant::now();
+            let status_before = status.clone();
+            self.emit(BotEvent::TxSubmitted { item_limit });
+            // On shutdown, give the in-flight tx up to --drain-timeout-secs to finalize
+            let drain_timeout = Duration::from_secs(self.config.run.drain_timeout_secs);
+            let submission = tokio::select! {
+                result = self.submit_migration(&state, limits) => result,
+                _ = async {
+                    self.shutdown.cancelled().await;
+                    tokio::time::sleep(drain_timeout).await;
+                } => {
+                    warn!(
+                        "In-flight transaction not finalized {}s after shutdown request, exiting",
+                        drain_timeout.as_secs()
+                    );
+                    break Stop::Shutdown { drained: false };
+                }
+            };
+            // Broadcasting to 0 peers is a connectivity problem on our node's
+            // side: move to another endpoint and resend the same signed tx
+            let submission = match submission.map_err(|e| e.downcast::<MigrationError>()) {
+                Err(Ok(MigrationError::NoPeers {
+                    broadcasts,
+                    tx_bytes,
+                    account,
+                    nonce_before,
+                })) => {
+                    let from = self.endpoint_url().to_string();
+                    if let Err(e) = self.switch_endpoint().await {
+                        warn!("Failed to switch endpoint: {:#}", e);
+                    }
+                    let msg = format!(
+                        "{} broadcast the tx to 0 peers {} times; rebroadcasting via {}",
+                        from,
+                        broadcasts,
+                        self.endpoint_url()
+                    );
+                    warn!("{}", msg);
+                    send_notification(Severity::Warning, "No Peers", &msg);
+                    self.rebroadcast(tx_bytes, account, nonce_before).await
+                }
+                Err(Ok(MigrationError::ShuttingDown)) => {
+                    info!("Shutdown requested, not signing the prepared transaction");
+                    break Stop::Shutdown { drained: true };
+                }
+                Err(Ok(err)) => Err(err.into()),
+                Err(Err(e)) => Err(e),
+                Ok(tx_hash) => Ok(tx_hash),
+            };
+            match submission {
+                Ok(tx_hash) => {
+                    stale_witness_retries = 0;
+                    pacer.record_finalized(submit_start.elapsed());
+                    self.emit(BotEvent::TxFinalized {
+                        tx_hash: tx_hash.map(|h| format!("{:?}", h)),
+                        elapsed_secs: submit_start.elapsed().as_secs_f64(),
+                    });
+                    stats.successful_runs += 1;
+                    self.health
+                        .update(|h| h.last_success = Some(chrono::Utc::now()));
+                    let successful_runs = stats.successful_runs;
+                    if let Some(bar) = &run_progress {
+                        bar.record(successful_runs);
+                    }
+                    info!(
+                        tx_hash = ?tx_hash,
+                        items = item_limit,
+                        size = self.config.run.size_limit,
+                        "Tx #{} ✓",
+                        successful_runs
+                    );
 
                     let runs_left = if target_runs > 0 {
                         (target_runs - successful_runs).to_string()
                     } else {
                         "Unlimited".to_string()
                     };
-                    let msg = format!(
+                    let mut msg = format!(
                         "Migrated {} items ({} bytes)\nRun: {} | Remaining: {}",
-                        self.config.item_limit, self.config.size_limit, successful_runs, runs_left
+                        item_limit, self.config.run.size_limit, successful_runs, runs_left
                     );
-                    send_notification("Transaction Confirmed", &msg, false);
+                    if let Some(eta) = &eta_line {
+                        msg.push_str(&format!("\nETA: {}", eta));
+                    }
+                    if self.signer.rotates() {
+                        msg.push_str(&format!("\nAccount: {}", self.signer.label()));
+                    }
+                    // Progress towards the session goal, if there is one
+                    let goal = if target_runs > 0 {
+                        Some(successful_runs as f64 / target_runs as f64)
+                    } else if caps.max_items > 0 {
+                        Some(stats.items_migrated as f64 / caps.max_items as f64)
+                    } else {
+                        None
+                    };
+                    self.update_dashboard(|d| d.goal = goal);
+                    match goal {
+                        Some(fraction) => send_progress(
+                            Severity::Success,
+                            "Transaction Confirmed",
+                            &msg,
+                            fraction,
+                        ),
+                        None => send_notification(Severity::Success, "Transaction Confirmed", &msg),
+                    }
 
                     // Check balance AFTER tx - should be unchanged (free tx)
                     let balance_after = self.check_balance().await?;
+                    if self.dashboard.is_some() {
+                        let account_id = self.signer.account_id();
+                        let nonce = self.get_account_nonce(&account_id).await.ok();
+                        self.update_dashboard(|d| {
+                            d.balance = Some(balance_after);
+                            d.nonce = nonce;
+                        });
+                    }
+                    let pending = PendingProgress {
+                        status_before,
+                        tx_hash,
+                        fee: balance_before.saturating_sub(balance_after),
+                    };
+                    if let Some(hash) = tx_hash {
+                        self.record_journal(|j| j.set_fee(&format!("{:?}", hash), pending.fee));
+                    }
                     if let Some(lost_wnd) = check_balance_decrease(balance_before, balance_after) {
                         error!(
                             "⚠️  BALANCE DECREASED by {:.6} WND! Possible slashing!",
                             lost_wnd
                         );
                         error!("Before: {}, After: {}", balance_before, balance_after);
+                        self.emit(BotEvent::BalanceDecreased { lost_wnd });
                         send_notification(
+                            Severity::Critical,
                             "CRITICAL WARNING",
-                            &format!("Balance decreased by {:.6} WND! Bot stopped.", lost_wnd),
-                            true,
+                            &self.incident_body(&format!(
+                                "Balance decreased by {:.6} WND! Bot stopped.",
+                                lost_wnd
+                            )),
                         );
+                        // Keep a record of the costly tx before stopping
+                        if let Ok(Some(after)) = self.fetch_state().await {
+                            self.record_progress(&mut stats, pending, &after.status);
+                        }
                         // Stop immediately if we're losing funds
                         return Err(MigrationError::BalanceDecreased { lost_wnd }.into());
                     } else {
                         info!("Balance OK (free tx)");
+                        progress_baseline = Some(pending);
                     }
 
                     // Check if we've reached target runs
                     if target_runs > 0 && successful_runs >= target_runs {
                         info!("Done: {} migrations", successful_runs);
-                        break;
+                        break Stop::RunsReached(successful_runs);
                     }
                 }
                 Err(e) => {
+                    stats.failed_runs += 1;
+                    self.enter(BotState::Backoff);
+
                     // Try to downcast to MigrationError for structured handling
                     let migration_err = e.downcast_ref::<MigrationError>();
-
-                    if let Some(err) = migration_err {
-                        if err.requires_pool_wait() {
+                    self.emit(BotEvent::TxFailed {
+                        error: e.to_string(),
+                        recoverable: migration_err.is_some_and(|err| err.is_recoverable()),
+                    });
+
+                    let mut class = ErrorClass::of(migration_err);
+                    // A witness that keeps going stale counts after all
+                    if class == ErrorClass::StaleWitness {
+                        stale_witness_retries += 1;
+                        if stale_witness_retries > MAX_STALE_WITNESS_RETRIES {
+                            class = ErrorClass::Failure;
+                        }
+                    } else {
+                        // Only back-to-back stale witnesses count
+                        stale_witness_retries = 0;
+                    }
+                    // Another account takes over from one whose tx is banned or stuck
+                    let rotated = migration_err
+                        .and_then(Bench::of)
+                        .is_some_and(|reason| self.rotate(reason));
+                    let decision = if rotated {
+                        policy::Decision {
+                            action: policy::Action::Rotated,
+                            consecutive_errors,
+                        }
+                    } else {
+                        policy::on_failure(class, consecutive_errors)
+                    };
+                    consecutive_errors = decision.consecutive_errors;
+                    match decision.action {
+                        policy::Action::Stop => {
+                            error!("Stopping: {:#}", e);
+                            return Err(e);
+                        }
+                        policy::Action::WaitForPool => {
                             // Pool has pending tx - wait for it to finalize (not counted as error)
                             warn!("Pool conflict detected, waiting for pending tx to finalize...");
-                            consecutive_errors = 0; // Reset on recoverable error
-                            self.wait_for_pending_tx().await;
-                        } else if matches!(err, MigrationError::TxBanned) {
-                            // Temporarily banned - wait longer (not counted as error)
-                            warn!("TX temporarily banned, waiting {}s...", BANNED_TX_WAIT_SECS);
-                            consecutive_errors = 0; // Reset on recoverable error
-                            tokio::time::sleep(Duration::from_secs(BANNED_TX_WAIT_SECS)).await;
-                        } else if err.is_recoverable() {
-                            // Other recoverable errors - retry with backoff
-                            warn!("Recoverable error: {}, retrying...", err);
-                            tokio::time::sleep(Duration::from_secs(RETRY_WAIT_SECS)).await;
-                        } else {
-                            // Non-recoverable error
-                            consecutive_errors += 1;
-                            error!(
-                                "Migration transaction failed ({}/{}): {}",
-                                consecutive_errors, MAX_CONSECUTIVE_ERRORS, err
+                            pacer.record_pool_conflict();
+                            let left = until_deadline(
+                                Duration::MAX,
+                                self.config.run.deadline,
+                                chrono::Local::now(),
                             );
-
-                            if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                                error!("Too many consecutive errors, stopping bot");
-                                return Err(MigrationError::TooManyErrors {
-                                    count: consecutive_errors,
-                                    last_error: err.to_string(),
+                            if tokio::time::timeout(left, self.wait_for_pending_tx())
+                                .await
+                                .is_err()
+                            {
+                                info!("Deadline reached while waiting for the pending tx");
+                            }
+                        }
+                        policy::Action::Retry(delay) => {
+                            match class {
+                                ErrorClass::Banned => {
+                                    warn!("TX temporarily banned, waiting {}s...", delay.as_secs())
+                                }
+                                ErrorClass::Transient => {
+                                    warn!("Recoverable error: {:#}, retrying...", e)
+                                }
+                                _ => {
+                                    error!(
+                                        "Migration transaction failed ({}/{}): {:#}",
+                                        consecutive_errors, MAX_CONSECUTIVE_ERRORS, e
+                                    );
+                                    warn!("Waiting {} seconds before retry...", delay.as_secs());
                                 }
-                                .into());
                             }
-
-                            warn!("Waiting {} seconds before retry...", RETRY_WAIT_SECS);
-                            tokio::time::sleep(Duration::from_secs(RETRY_WAIT_SECS)).await;
+                            self.idle(delay).await;
                         }
-                    } else {
-                        // Unknown error type - treat as non-recoverable
-                        consecutive_errors += 1;
-                        error!(
-                            "Migration transaction failed ({}/{}): {:?}",
-                            consecutive_errors, MAX_CONSECUTIVE_ERRORS, e
-                        );
-
-                        if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
+                        // The loop reads MigrationProcess afresh at the top
+                        policy::Action::Requery => {
+                            warn!("{:#}; re-reading the witness and retrying", e);
+                        }
+                        // The next account has nothing pending: no need to wait
+                        policy::Action::Rotated => {}
+                        policy::Action::GiveUp => {
+                            error!(
+                                "Migration transaction failed ({}/{}): {:#}",
+                                consecutive_errors, MAX_CONSECUTIVE_ERRORS, e
+                            );
                             error!("Too many consecutive errors, stopping bot");
                             return Err(MigrationError::TooManyErrors {
                                 count: consecutive_errors,
@@ -1025,75 +4892,778 @@ impl MigrationBot {
                             }
                             .into());
                         }
-
-                        warn!("Waiting {} seconds before retry...", RETRY_WAIT_SECS);
-                        tokio::time::sleep(Duration::from_secs(RETRY_WAIT_SECS)).await;
                     }
                 }
             }
 
-            if self.config.once {
+            progress_tx.send_modify(|progress| {
+                progress.successful_runs = stats.successful_runs;
+                progress.failed_runs = stats.failed_runs;
+            });
+            self.save_counters(&stats);
+            self.health
+                .update(|h| h.consecutive_errors = consecutive_errors);
+
+            if self.config.run.once {
                 info!("--once flag set, exiting after single run");
-                break;
+                break Stop::Once;
+            }
+
+            self.enter(BotState::Idle);
+
+            // Other accounts migrating: wait longer, or stand aside for a while
+            if let Some((blocks, tracker)) = &mut competition {
+                while let Ok(block) = blocks.try_recv() {
+                    tracker.record(&block, Instant::now());
+                }
+                match tracker.advice(Instant::now()) {
+                    Compete::Proceed => {}
+                    Compete::Delay(delay) => {
+                        self.enter(BotState::Backoff);
+                        info!(
+                            "Other migrators active ({} txs in the last 10 min), waiting an extra {}s",
+                            tracker.foreign_count(Instant::now()),
+                            delay.as_secs()
+                        );
+                        self.idle(delay).await;
+                    }
+                    Compete::Yield(duration) => {
+                        self.enter(BotState::Backoff);
+                        let msg = format!(
+                            "{} migrations by other accounts in the last 10 min; yielding for {}",
+                            tracker.foreign_count(Instant::now()),
+                            format_duration(duration)
+                        );
+                        info!("{}", msg);
+                        send_notification(Severity::Info, "Yielding to Other Migrators", &msg);
+                        self.idle(duration).await;
+                    }
+                }
             }
 
             // Wait before next iteration (if configured)
-            if self.config.delay_secs > 0 {
+            if self.config.run.delay_secs > 0 {
                 info!(
                     "Waiting {} seconds before next migration...",
-                    self.config.delay_secs
+                    self.config.run.delay_secs
                 );
-                tokio::time::sleep(Duration::from_secs(self.config.delay_secs)).await;
+                self.idle(Duration::from_secs(self.config.run.delay_secs))
+                    .await;
+            } else {
+                // No fixed delay - pace from observed finalization lag and pool conflicts
+                let delay = pacer.next_delay();
+                if !delay.is_zero() {
+                    info!(
+                        "Adaptive delay: {}s (avg finalization {:.1}s)",
+                        delay.as_secs(),
+                        pacer
+                            .avg_finalization()
+                            .map(|d| d.as_secs_f64())
+                            .unwrap_or(0.0)
+                    );
+                    self.idle(delay).await;
+                }
+            }
+        };
+
+        // Account for the final tx, which has no following loop iteration
+        if let Some(pending) = progress_baseline.take() {
+            if let Ok(Some(after)) = self.fetch_state().await {
+                self.record_progress(&mut stats, pending, &after.status);
             }
         }
 
+        self.drain_trailing(&mut stats).await;
+        self.save_counters(&stats);
+        if let Some(bar) = &run_progress {
+            bar.finish();
+        }
+        info!("Session summary: {}", stats.summary());
+        self.log_sponsor_totals();
+        self.emit(BotEvent::Stopped {
+            reason: stop.to_string(),
+        });
+
         // Signal shutdown to background tasks
         self.shutdown.cancel();
-        Ok(())
+        // Let the dashboard restore the terminal before the process exits
+        if let Some(task) = dashboard_task {
+            let _ = task.await;
+        }
+        Ok(stop)
+    }
+}
+
+/// Formatting layer for `format` writing to `writer`
+fn log_layer<W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .with_target(false);
+    match format {
+        LogFormat::Compact => layer.with_timer(LocalTimer).compact().boxed(),
+        // RFC 3339 UTC timestamps; event fields at the top level of each object
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+    }
+}
+
+/// Print sessions reconstructed from an event log, then the trailing events
+fn print_replay(
+    records: &[events::EventRecord],
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    tail: usize,
+) {
+    let local = |t: chrono::DateTime<chrono::Utc>| {
+        t.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+
+    let sessions = events::replay(records, until);
+    if sessions.is_empty() {
+        println!("No events recorded");
+        return;
+    }
+
+    for (n, s) in sessions.iter().enumerate() {
+        println!(
+            "Session {} ({}): started {}, last event {}",
+            n + 1,
+            s.account.as_deref().unwrap_or("?"),
+            s.started.map(local).unwrap_or_else(|| "?".to_string()),
+            s.last_event.map(local).unwrap_or_else(|| "?".to_string())
+        );
+        println!(
+            "  {} successful, {} failed, {} items / {} bytes",
+            s.successful_runs, s.failed_runs, s.items_migrated, s.bytes_migrated
+        );
+        if let Some(status) = &s.last_status {
+            println!(
+                "  last status: top={} child={} size={}{}",
+                status.top_items,
+                status.child_items,
+                status.size,
+                if status.is_complete() {
+                    " (complete)"
+                } else {
+                    ""
+                }
+            );
+        }
+        if let Some(error) = &s.last_error {
+            println!("  last error: {}", error);
+        }
+        println!(
+            "  stopped: {}",
+            s.stopped
+                .as_deref()
+                .unwrap_or("no stop event (killed, crashed or still running)")
+        );
+    }
+
+    let replayed: Vec<_> = records
+        .iter()
+        .filter(|r| until.is_none_or(|until| r.timestamp <= until))
+        .collect();
+    println!();
+    println!("Last {} events:", tail.min(replayed.len()));
+    for record in &replayed[replayed.len().saturating_sub(tail)..] {
+        println!(
+            "  {}  {}",
+            local(record.timestamp),
+            serde_json::to_string(&record.event).unwrap_or_default()
+        );
+    }
+}
+
+/// Print per-instance activity from a (possibly shared) history database
+fn print_history_report(history: &History) -> Result<()> {
+    let totals = history.instance_totals()?;
+    if totals.is_empty() {
+        println!("No transactions recorded");
+        return Ok(());
+    }
+
+    println!(
+        "{:<24} {:<12} {:<12} {:>6} {:>10} {:>12} {:>10}  LAST SEEN",
+        "INSTANCE", "CHAIN", "ACCOUNT", "TXS", "ITEMS", "BYTES", "FEES (WND)"
+    );
+    let (mut txs, mut items, mut bytes, mut fees) = (0u64, 0u64, 0u64, 0u128);
+    for t in &totals {
+        let last_seen = chrono::DateTime::from_timestamp(t.last_seen, 0)
+            .map(|ts| {
+                ts.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!(
+            "{:<24} {:<12.12} {:<12.12} {:>6} {:>10} {:>12} {:>10.6}  {}",
+            t.instance_id,
+            t.chain,
+            t.account,
+            t.txs,
+            t.items,
+            t.bytes,
+            units_to_wnd(t.fees),
+            last_seen
+        );
+        txs += t.txs;
+        items += t.items;
+        bytes += t.bytes;
+        fees += t.fees;
+    }
+    println!(
+        "{:<24} {:<12} {:<12} {:>6} {:>10} {:>12} {:>10.6}",
+        "TOTAL",
+        "",
+        "",
+        txs,
+        items,
+        bytes,
+        units_to_wnd(fees)
+    );
+    Ok(())
+}
+
+/// Print a running bot's snapshot (`ctl status`)
+fn print_ctl_status(snapshot: &BotSnapshot) {
+    let now = chrono::Utc::now();
+    let ago =
+        |t: chrono::DateTime<chrono::Utc>| format_duration((now - t).to_std().unwrap_or_default());
+    let since = snapshot
+        .state_since
+        .map(|t| format!(" for {}", ago(t)))
+        .unwrap_or_default();
+    println!("State:              {}{}", snapshot.state, since);
+    println!(
+        "Connected:          {} (reads at {})",
+        snapshot.connected, snapshot.read_basis
+    );
+    println!("Running for:        {}", ago(snapshot.started_at));
+    println!("Last activity:      {} ago", ago(snapshot.last_activity));
+    match snapshot.last_success {
+        Some(t) => println!("Last finalized tx:  {} ago", ago(t)),
+        None => println!("Last finalized tx:  none this session"),
+    }
+    println!("Consecutive errors: {}", snapshot.consecutive_errors);
+    if snapshot.paused {
+        println!("Paused:             awaiting acknowledgement");
+    }
+    if let Some(status) = &snapshot.status {
+        println!(
+            "Migration:          top={}/{} child={}/{} size={}",
+            if status.top_complete { "done" } else { "wip" },
+            status.top_items,
+            if status.child_complete { "done" } else { "wip" },
+            status.child_items,
+            status.size
+        );
+    }
+}
+
+/// Chain name reported by the node, or the genesis hash if it doesn't say
+async fn chain_name(
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    client: &OnlineClient<PolkadotConfig>,
+) -> String {
+    match rpc.system_chain().await {
+        Ok(name) => name,
+        Err(_) => format!("{:?}", client.genesis_hash()),
+    }
+}
+
+/// List this process for `ps`; an unwritable registry only costs the listing
+fn register_instance(entry: InstanceEntry, health: &Arc<Health>, shutdown: &CancellationToken) {
+    let dir = Path::new(registry::REGISTRY_DIR);
+    if let Err(e) = registry::spawn(dir, entry, health.clone(), shutdown.clone()) {
+        warn!("Could not register in {}: {:#}", dir.display(), e);
     }
 }
 
+/// `scan diff`: compare two cached scans and, when `rpc_url` is given,
+/// attribute the migrated keys to the blocks and accounts in between
+async fn print_scan_diff(
+    old: &Path,
+    new: &Path,
+    output: Option<&Path>,
+    rpc_url: Option<&str>,
+) -> Result<()> {
+    use std::io::Write;
+
+    let (old, new) = (CachedScan::open(old)?, CachedScan::open(new)?);
+    let (old_block, new_block) = (old.block, new.block);
+    let diff = scandiff::diff_scans(old, new)?;
+    println!("Scans at {:?} and {:?}", old_block, new_block);
+    println!(
+        "Migrated: {} keys ({} value bytes)",
+        diff.migrated.len(),
+        diff.migrated_bytes()
+    );
+    println!(
+        "Deleted: {} keys, created: {} keys (not yet migrated)",
+        diff.deleted, diff.created
+    );
+
+    let attributions = match rpc_url {
+        Some(url) if !diff.migrated.is_empty() => {
+            let (_, rpc, client) = connect(url).await?;
+            let from = scandiff::block_number(&rpc, old_block).await?;
+            let to = scandiff::block_number(&rpc, new_block).await?;
+            anyhow::ensure!(from < to, "The first scan must be the older one");
+            println!("Reading blocks #{}..#{}...", from + 1, to);
+            let blocks = scandiff::migrating_blocks(&client, &rpc, from, to).await?;
+            scandiff::attribute(&diff.migrated, &blocks)
+        }
+        _ => vec![None; diff.migrated.len()],
+    };
+    if rpc_url.is_some() {
+        for (account, share) in scandiff::by_account(&diff.migrated, &attributions) {
+            println!(
+                "  {:<50} {:>10} keys {:>14} bytes",
+                account.as_deref().unwrap_or("(unattributed)"),
+                share.keys,
+                share.bytes
+            );
+        }
+    }
+
+    if let Some(path) = output {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(file);
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        for (record, attribution) in diff.migrated.iter().zip(&attributions) {
+            writeln!(
+                out,
+                "0x{}\t{}\t{}\t{}",
+                hex::encode(&record.key),
+                or_dash(record.size.map(|size| size.to_string())),
+                or_dash(attribution.as_ref().map(|a| a.block.to_string())),
+                or_dash(attribution.as_ref().map(|a| a.account.clone()))
+            )?;
+        }
+        out.flush()
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!(
+            "{} migrated keys written to {}",
+            diff.migrated.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Print the instances running on this host (`ps`)
+fn print_instances(entries: &[InstanceEntry]) {
+    if entries.is_empty() {
+        println!("No running instances");
+        return;
+    }
+    let now = chrono::Utc::now();
+    let short_address = |address: &str| match address.len() {
+        0..=12 => address.to_string(),
+        len => format!("{}..{}", &address[..6], &address[len - 4..]),
+    };
+    println!(
+        "{:<8} {:<24} {:<8} {:<20} {:<14} {:<10} {:<18} PROGRESS",
+        "PID", "INSTANCE", "MODE", "CHAIN", "ACCOUNT", "UPTIME", "STATE"
+    );
+    for entry in entries {
+        let account = entry
+            .account
+            .as_deref()
+            .map_or("-".to_string(), short_address);
+        let state = entry
+            .snapshot
+            .as_ref()
+            .map_or("-".to_string(), |s| s.state.to_string());
+        println!(
+            "{:<8} {:<24} {:<8} {:<20} {:<14} {:<10} {:<18} {}",
+            entry.pid,
+            entry.instance_id,
+            entry.mode,
+            entry.chain,
+            account,
+            format_duration((now - entry.started_at).to_std().unwrap_or_default()),
+            state,
+            entry.progress()
+        );
+    }
+}
+
+/// Look up unenriched txs on the explorer, within its free-tier rate limit
+async fn enrich_history(history: &History, explorer: &ExplorerClient, limit: usize) -> Result<()> {
+    let pending = history.unenriched(limit)?;
+    let (mut enriched, mut missing) = (0, 0);
+    for tx_hash in &pending {
+        match explorer.extrinsic(tx_hash).await? {
+            Some(enrichment) => {
+                history.enrich(&enrichment)?;
+                enriched += 1;
+            }
+            None => missing += 1,
+        }
+        tokio::time::sleep(EXPLORER_REQUEST_INTERVAL).await;
+    }
+    println!(
+        "Enriched {} of {} txs ({} not found on the explorer yet)",
+        enriched,
+        pending.len(),
+        missing
+    );
+    Ok(())
+}
+
 const LOCKFILE_PATH: &str = "/tmp/westend-migrate.lock";
+const NOTIFY_FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+const EXPLORER_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Resolves on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// `--watch` / `--task mbm`: follow the chain read-only, with no signer loaded
+async fn run_watch(cli: &Cli) -> Result<()> {
+    let (_, rpc, client) = connect(&cli.rpc_url).await?;
+    info!(
+        "Connected to chain with genesis: {:?}",
+        client.genesis_hash()
+    );
+
+    let shutdown = CancellationToken::new();
+    let health = Arc::new(Health::new(Duration::from_secs(cli.health_stale_secs)));
+    if let Some(addr) = cli.health_addr {
+        health::serve(addr, health.clone(), shutdown.clone()).await?;
+    }
+    register_instance(
+        InstanceEntry {
+            pid: std::process::id(),
+            instance_id: cli
+                .instance_id
+                .clone()
+                .unwrap_or_else(|| format!("watch@{:?}", client.genesis_hash())),
+            mode: "watch".to_string(),
+            chain: chain_name(&rpc, &client).await,
+            account: None,
+            rpc_url: cli.rpc_url.clone(),
+            health_addr: cli.health_addr,
+            started_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            snapshot: None,
+        },
+        &health,
+        &shutdown,
+    );
+
+    let signal = shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        signal.cancel();
+    });
+
+    let result = match cli.task {
+        TaskKind::Trie => {
+            watch::run(
+                client,
+                health,
+                Duration::from_secs(cli.stall_alert_mins * 60),
+                shutdown.clone(),
+            )
+            .await
+        }
+        TaskKind::Mbm => mbm::run(client, health, shutdown.clone()).await,
+    };
+    shutdown.cancel();
+    if let Err(e) = &result {
+        send_notification(Severity::Critical, "Watcher Stopped", &e.to_string());
+    }
+    result
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> Result<ExitCode> {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let mut notices = cli.normalize(&matches)?;
+
+    // Read-only report - doesn't need the instance lock or a chain connection
+    if cli.history_report {
+        if let Some(path) = &cli.history_db {
+            print_history_report(&History::open(path)?)?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Ps) = &cli.command {
+        print_instances(&registry::list(Path::new(registry::REGISTRY_DIR))?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Replay { path, until, tail }) = &cli.command {
+        let events = events::read_events(path)?;
+        let until = until.map(|t| t.with_timezone(&chrono::Utc));
+        print_replay(&events, until, *tail);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Talks to the running bot, which holds the instance lock
+    if let Some(Command::Ctl { action }) = &cli.command {
+        match action {
+            CtlCommand::Status { addr } => {
+                let addr = addr
+                    .or(cli.health_addr)
+                    .context("--addr or --health-addr is required for `ctl status`")?;
+                print_ctl_status(&health::fetch_status(addr).await?);
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Scan {
+        action:
+            ScanCommand::Diff {
+                old,
+                new,
+                output,
+                no_attribution,
+            },
+    }) = &cli.command
+    {
+        let url = (!no_attribution).then_some(cli.rpc_url.as_str());
+        print_scan_diff(old, new, output.as_deref(), url).await?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    // Acquire exclusive lock to prevent multiple instances
-    let lockfile = File::create(LOCKFILE_PATH)
-        .context("Failed to create lockfile")?;
+    if let Some(Command::History {
+        action: HistoryCommand::Export { format, output },
+    }) = &cli.command
+    {
+        if format.is_journal() {
+            let path = cli
+                .journal_db
+                .as_deref()
+                .context("--journal-db is required for CSV/JSON export")?;
+            let entries = Journal::open(path)?.entries()?;
+            export::export_journal(&entries, *format, output)?;
+            println!("Exported {} txs to {}", entries.len(), output.display());
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
 
-    if lockfile.try_lock_exclusive().is_err() {
-        eprintln!("ERROR: Another instance is already running (lockfile: {})", LOCKFILE_PATH);
-        eprintln!("If this is incorrect, delete the lockfile and try again.");
-        std::process::exit(1);
+    if let Some(Command::History { action }) = &cli.command {
+        let location = cli
+            .history_db
+            .as_deref()
+            .context("--history-db is required for history commands")?;
+        let history = History::open(location)?;
+        match action {
+            HistoryCommand::Export { format, output } => {
+                let entries = history.entries()?;
+                export::export_history(&entries, *format, output)?;
+                println!("Exported {} txs to {}", entries.len(), output.display());
+            }
+            HistoryCommand::Enrich { limit } => {
+                let api_key = cli
+                    .explorer_api_key
+                    .clone()
+                    .context("--explorer-api-key is required for `history enrich`")?;
+                let explorer = ExplorerClient::new(&cli.explorer_api_url, api_key);
+                enrich_history(&history, &explorer, *limit).await?;
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
     }
-    // Lock is held for the lifetime of the process and released on exit
+
+    // Acquire exclusive lock to prevent multiple instances.
+    // Watchers never sign, so any number of them can run next to the bot.
+    let _lockfile = if cli.read_only() {
+        None
+    } else {
+        let max_age = Duration::from_secs(cli.lock_max_age_mins * 60);
+        match InstanceLock::acquire(Path::new(LOCKFILE_PATH), max_age)? {
+            Acquire::Acquired { lock, recovered } => {
+                if let Some(owner) = recovered {
+                    notices.push(format!(
+                        "Took over the lock of an instance that did not exit cleanly \
+                         (pid {}, started {}, last heartbeat {})",
+                        owner.pid, owner.started_at, owner.heartbeat
+                    ));
+                }
+                lock.keep_alive(max_age / 4)?;
+                Some(lock)
+            }
+            Acquire::Held(owner) => {
+                match owner {
+                    Some(owner) => eprintln!(
+                        "ERROR: Another instance is already running (pid {}, started {}, \
+                         last heartbeat {}; lockfile: {})",
+                        owner.pid, owner.started_at, owner.heartbeat, LOCKFILE_PATH
+                    ),
+                    None => eprintln!(
+                        "ERROR: Another instance is already running (lockfile: {})",
+                        LOCKFILE_PATH
+                    ),
+                }
+                eprintln!(
+                    "To see what it is doing: westend-migrate ctl status --addr <its health addr>"
+                );
+                return Ok(ExitCode::from(exit::LOCKED));
+            }
+        }
+    };
+    // Lock is held for the lifetime of the process; a clean exit clears its record
 
     // Disable desktop notifications if running headless
     if cli.no_notify {
         disable_notifications();
     }
+    for route in &cli.notify_routes {
+        notify::set_route(*route);
+    }
+    if let Some(email) = cli.email_config()? {
+        notify::configure_email(email);
+    }
+    if let Some(slack) = cli.slack_config()? {
+        notify::configure_slack(slack);
+    }
+    if let Some(teams) = cli.teams_config() {
+        notify::configure_teams(teams);
+    }
+    if let Some(twilio) = cli.twilio_config()? {
+        notify::configure_twilio(twilio);
+    }
+    if let Some(quiet) = cli.quiet_hours {
+        notify::configure_quiet_hours(quiet);
+    }
+    if let Some(ack) = cli.ack_config()? {
+        notify::configure_ack(ack);
+    }
+    if !cli.escalation_rules.is_empty() {
+        notify::configure_escalation(
+            cli.escalation_rules.clone(),
+            Duration::from_secs(cli.escalation_window_mins * 60),
+        );
+    }
+    budget::configure(
+        &cli.rpc_quotas,
+        cli.rpc_quota_warn_percent,
+        cli.rpc_usage_file.as_deref(),
+    )?;
 
     // Initialize logging
     let log_level = if cli.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| log_level.into()),
-        )
-        .with_timer(LocalTimer)
-        .with_target(false)
-        .compact()
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| log_level.into());
+    // The --tui dashboard owns the terminal; logs go only to --log-file then
+    let mut layers = Vec::new();
+    // With --events, stdout carries only the event stream
+    if cli.events.is_some() {
+        layers.push(log_layer(cli.log_format, std::io::stderr, true));
+    } else if !cli.tui {
+        layers.push(log_layer(cli.log_format, std::io::stdout, true));
+    }
+    // Keeps the background log writer alive; dropping it flushes the file
+    let _log_guard = match &cli.log_file {
+        Some(path) => {
+            let file = RotatingFile::open(path, cli.log_rotation_policy())?;
+            let (writer, guard) = tracing_appender::non_blocking(file);
+            layers.push(log_layer(cli.log_format, writer, false));
+            Some(guard)
+        }
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(env_filter)
         .init();
 
     info!(
         "Westend State-Trie Migration Bot v{}",
         env!("CARGO_PKG_VERSION")
     );
+    for notice in notices {
+        warn!("{}", notice);
+    }
+
+    if cli.read_only() {
+        let result = run_watch(&cli).await;
+        budget::save();
+        // Watchers run until a shutdown signal
+        let result = result.map(|()| Stop::Shutdown { drained: true });
+        if let Some((severity, body)) = exit::shutdown_report(result.as_ref(), None, None, None) {
+            notify::send_everywhere(severity, "Watcher Stopped", &body);
+        }
+        notify::flush(NOTIFY_FLUSH_TIMEOUT);
+        return Ok(match result {
+            Ok(stop) => stop.into(),
+            Err(e) => exit::report(&e),
+        });
+    }
+
+    let mut bot = match MigrationBot::new(cli).await {
+        Ok(bot) => bot,
+        Err(e) => {
+            if let Some((severity, body)) = exit::shutdown_report(Err(&e), None, None, None) {
+                notify::send_everywhere(severity, "Bot Stopped", &body);
+                notify::flush(NOTIFY_FLUSH_TIMEOUT);
+            }
+            return Ok(exit::report(&e));
+        }
+    };
+
+    // First SIGINT/SIGTERM drains the in-flight tx and stops; a second one aborts
+    let shutdown = bot.shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutdown requested, finishing in-flight transaction (signal again to abort)");
+        shutdown.cancel();
+        shutdown_signal().await;
+        warn!("Second shutdown signal, aborting");
+        notify::flush(Duration::ZERO);
+        std::process::exit(130);
+    });
+
+    let result = bot.run().await;
+    // Error paths skip run()'s own cancel; background tasks (and the dashboard) stop here
+    bot.shutdown.cancel();
+
+    if let Err(e) = &result {
+        bot.emit(BotEvent::Stopped {
+            reason: format!("error: {}", e),
+        });
+    }
+    bot.send_shutdown_report(result.as_ref());
 
-    let mut bot = MigrationBot::new(cli).await?;
-    bot.run().await?;
+    budget::save();
+    // Give pending email/Slack/Teams/Twilio messages a chance to go out before the process exits
+    notify::flush(NOTIFY_FLUSH_TIMEOUT);
 
-    Ok(())
+    // Distinct exit codes per outcome (see `exit`)
+    Ok(match result {
+        Ok(stop) => {
+            info!("Exiting with code {} ({})", stop.code(), stop);
+            stop.into()
+        }
+        Err(e) => exit::report(&e),
+    })
 }
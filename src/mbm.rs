@@ -0,0 +1,283 @@
+//! Multi-block migration monitoring (`--task mbm`)
+//!
+//! Chains using `pallet_migrations` run runtime-upgrade migrations over
+//! several blocks by themselves: there is nothing to sign, but an upgrade
+//! whose cursor goes `Stuck` halts most extrinsics until governance steps in.
+//! This task follows finalized blocks, logs the cursor (idle, active
+//! migration index, stuck) and every `MultiBlockMigrations` event, and
+//! notifies on upgrade start, completion and failure through the usual
+//! alerting stack.
+
+use crate::health::Health;
+use crate::notify::{send_notification, Severity};
+use crate::task::Task;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use subxt::dynamic::{At, Value};
+use subxt::ext::scale_value::ValueDef;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// Runtime name of `pallet_migrations`
+pub const PALLET: &str = "MultiBlockMigrations";
+
+/// Decoded `MultiBlockMigrations::Cursor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MbmCursor {
+    /// Migration `index` of the upgrade is running, started at block `started_at`
+    Active { index: u32, started_at: Option<u32> },
+    /// A migration failed and the configured failure handling left it stuck
+    Stuck,
+}
+
+impl fmt::Display for MbmCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MbmCursor::Active {
+                index,
+                started_at: Some(block),
+            } => write!(
+                f,
+                "migration #{} active (upgrade started at #{})",
+                index, block
+            ),
+            MbmCursor::Active { index, .. } => write!(f, "migration #{} active", index),
+            MbmCursor::Stuck => write!(f, "STUCK"),
+        }
+    }
+}
+
+/// Parse a `MigrationCursor` value (`Active(ActiveCursor { .. })` or `Stuck`)
+pub fn parse_cursor<T>(value: &Value<T>) -> Option<MbmCursor> {
+    let ValueDef::Variant(variant) = &value.value else {
+        return None;
+    };
+    match variant.name.as_str() {
+        "Stuck" => Some(MbmCursor::Stuck),
+        "Active" => {
+            let active = variant.values.at(0)?;
+            Some(MbmCursor::Active {
+                index: active.at("index")?.as_u128()? as u32,
+                started_at: active
+                    .at("started_at")
+                    .and_then(|v| v.as_u128())
+                    .map(|n| n as u32),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Notification for a `MultiBlockMigrations` event, if it warrants one
+pub fn event_alert(variant: &str) -> Option<(Severity, &'static str)> {
+    match variant {
+        "UpgradeStarted" => Some((Severity::Info, "Multi-Block Upgrade Started")),
+        "UpgradeCompleted" => Some((Severity::Success, "Multi-Block Upgrade Completed")),
+        "UpgradeFailed" => Some((Severity::Critical, "Multi-Block Upgrade Failed")),
+        "MigrationFailed" => Some((Severity::Critical, "Multi-Block Migration Failed")),
+        _ => None,
+    }
+}
+
+/// `pallet_migrations` cursor state at a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MbmState {
+    /// `None` when no upgrade is running
+    pub cursor: Option<MbmCursor>,
+}
+
+/// Monitor-only task over `pallet_migrations`
+pub struct MultiBlockMigrations;
+
+impl Task for MultiBlockMigrations {
+    type State = MbmState;
+
+    fn name(&self) -> &'static str {
+        "multi-block migrations"
+    }
+
+    fn fetch(
+        &self,
+        client: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> impl Future<Output = Result<Option<MbmState>>> + Send {
+        let client = client.clone();
+        async move {
+            if client.metadata().pallet_by_name(PALLET).is_none() {
+                return Ok(None);
+            }
+            let query = subxt::dynamic::storage(PALLET, "Cursor", vec![]);
+            let cursor = match client.storage().at(at).fetch(&query).await? {
+                Some(thunk) => {
+                    let value = thunk.to_value()?;
+                    let cursor = parse_cursor(&value);
+                    if cursor.is_none() {
+                        debug!("Unrecognized {} cursor: {}", PALLET, value);
+                    }
+                    cursor
+                }
+                None => None,
+            };
+            Ok(Some(MbmState { cursor }))
+        }
+    }
+
+    /// No upgrade in progress
+    fn is_complete(&self, state: &MbmState) -> bool {
+        state.cursor.is_none()
+    }
+}
+
+/// Follow finalized blocks until `shutdown` is cancelled
+pub async fn run(
+    client: OnlineClient<PolkadotConfig>,
+    health: Arc<Health>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let task = MultiBlockMigrations;
+    info!("Monitoring {} (no signer loaded)", task.name());
+    send_notification(
+        Severity::Info,
+        "Westend Watcher Started",
+        "Monitoring multi-block migrations (read-only).",
+    );
+
+    let mut blocks = client
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .context("Failed to subscribe to finalized blocks")?;
+    let mut last: Option<MbmState> = None;
+
+    loop {
+        let block = tokio::select! {
+            _ = shutdown.cancelled() => {
+                info!("Watcher shutting down");
+                return Ok(());
+            }
+            next = blocks.next() => match next {
+                Some(block) => block?,
+                None => anyhow::bail!("Finalized block subscription ended"),
+            },
+        };
+        let number = block.number();
+
+        for event in block.events().await?.iter() {
+            let event = event?;
+            if event.pallet_name() != PALLET {
+                continue;
+            }
+            let fields = event
+                .field_values()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            info!(
+                "#{}: {}.{} {}",
+                number,
+                PALLET,
+                event.variant_name(),
+                fields
+            );
+            if let Some((severity, summary)) = event_alert(event.variant_name()) {
+                send_notification(
+                    severity,
+                    summary,
+                    &format!("Block #{}: {} {}", number, event.variant_name(), fields),
+                );
+            }
+        }
+
+        let state = task
+            .fetch(&client, block.hash())
+            .await?
+            .with_context(|| format!("Runtime has no {} pallet", PALLET))?;
+        health.update(|h| {
+            h.connected = true;
+            h.last_success = Some(chrono::Utc::now());
+        });
+        if last.as_ref() == Some(&state) {
+            continue;
+        }
+        match &state.cursor {
+            None => info!("#{}: no upgrade in progress", number),
+            Some(MbmCursor::Stuck) => {
+                error!("#{}: multi-block upgrade is STUCK", number);
+                send_notification(
+                    Severity::Critical,
+                    "Multi-Block Upgrade Stuck",
+                    &format!(
+                        "{} cursor is Stuck at block #{}; extrinsics are likely halted \
+                         until governance intervenes.",
+                        PALLET, number
+                    ),
+                );
+            }
+            Some(cursor) => info!("#{}: {}", number, cursor),
+        }
+        last = Some(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::ext::scale_value::Composite;
+
+    #[test]
+    fn test_parse_active_cursor() {
+        let active = Value::named_composite(vec![
+            ("index", Value::u128(2)),
+            (
+                "inner_cursor",
+                Value::variant("None", Composite::unnamed(vec![])),
+            ),
+            ("started_at", Value::u128(1_234)),
+        ]);
+        let cursor = Value::variant("Active", Composite::unnamed(vec![active]));
+        let parsed = parse_cursor(&cursor).unwrap();
+        assert_eq!(
+            parsed,
+            MbmCursor::Active {
+                index: 2,
+                started_at: Some(1_234)
+            }
+        );
+        assert_eq!(
+            parsed.to_string(),
+            "migration #2 active (upgrade started at #1234)"
+        );
+    }
+
+    #[test]
+    fn test_parse_stuck_and_unknown() {
+        let stuck = Value::variant("Stuck", Composite::unnamed(vec![]));
+        assert_eq!(parse_cursor(&stuck), Some(MbmCursor::Stuck));
+        assert_eq!(parse_cursor(&Value::u128(1)), None);
+    }
+
+    #[test]
+    fn test_event_alerts() {
+        assert!(matches!(
+            event_alert("UpgradeFailed"),
+            Some((Severity::Critical, _))
+        ));
+        assert!(matches!(
+            event_alert("UpgradeCompleted"),
+            Some((Severity::Success, _))
+        ));
+        assert!(event_alert("MigrationAdvanced").is_none());
+    }
+
+    #[test]
+    fn test_idle_is_complete() {
+        let task = MultiBlockMigrations;
+        assert!(task.is_complete(&MbmState { cursor: None }));
+        assert!(!task.is_complete(&MbmState {
+            cursor: Some(MbmCursor::Stuck)
+        }));
+    }
+}
@@ -0,0 +1,279 @@
+//! Network-wide migration statistics (`--network-stats`)
+//!
+//! Aggregates every `StateTrieMigration.Migrated` event from the block
+//! follower, not just ours, into an hourly rate of items and bytes migrated
+//! by the whole network and the share that came from our account. Events
+//! carry item counts only, so bytes come from the growth of
+//! `MigrationProcess.size` between migrating blocks and are split across a
+//! block's events in proportion to their items.
+
+use crate::follower::{BlockMigrations, Follower};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Migration totals of one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Sample {
+    items: u64,
+    bytes: u64,
+    our_items: u64,
+    our_bytes: u64,
+}
+
+/// Network migration rate over a trailing window
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NetworkRate {
+    pub items_per_hour: f64,
+    pub bytes_per_hour: f64,
+    pub our_items_per_hour: f64,
+    pub our_bytes_per_hour: f64,
+}
+
+impl NetworkRate {
+    /// Our share of the items migrated, in percent
+    pub fn our_share(&self) -> f64 {
+        if self.items_per_hour > 0.0 {
+            self.our_items_per_hour / self.items_per_hour * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for NetworkRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} items/h, {:.0} bytes/h (ours {:.0} items/h, {:.1}%)",
+            self.items_per_hour,
+            self.bytes_per_hour,
+            self.our_items_per_hour,
+            self.our_share()
+        )
+    }
+}
+
+/// Sliding window of per-block migration totals
+#[derive(Debug)]
+pub struct NetworkStats {
+    /// Our signer accounts (more than one with --rotate-seed-file)
+    our_accounts: Vec<String>,
+    window: Duration,
+    started: Instant,
+    samples: VecDeque<(Instant, Sample)>,
+    last_size: Option<u64>,
+}
+
+impl NetworkStats {
+    pub fn new(our_accounts: Vec<String>, window: Duration, now: Instant) -> Self {
+        Self {
+            our_accounts,
+            window,
+            started: now,
+            samples: VecDeque::new(),
+            last_size: None,
+        }
+    }
+
+    /// Add a finalized block
+    pub fn record(&mut self, block: &BlockMigrations, now: Instant) {
+        let items: u64 = block.migrated.iter().map(|e| e.items()).sum();
+        let our_items: u64 = block
+            .migrated
+            .iter()
+            .filter(|e| {
+                e.signer
+                    .as_ref()
+                    .is_some_and(|s| self.our_accounts.contains(s))
+            })
+            .map(|e| e.items())
+            .sum();
+        // The first size seen has no baseline; count its items but not its bytes
+        let bytes = match (self.last_size, block.size) {
+            (Some(last), Some(size)) => size.saturating_sub(last),
+            _ => 0,
+        };
+        if block.size.is_some() {
+            self.last_size = block.size;
+        }
+        let our_bytes = if items > 0 {
+            (bytes as u128 * our_items as u128 / items as u128) as u64
+        } else {
+            0
+        };
+        if items > 0 {
+            self.samples.push_back((
+                now,
+                Sample {
+                    items,
+                    bytes,
+                    our_items,
+                    our_bytes,
+                },
+            ));
+        }
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= self.window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Hourly rates over the window (or the time since start, if shorter)
+    pub fn rate(&mut self, now: Instant) -> NetworkRate {
+        self.prune(now);
+        let elapsed = now.duration_since(self.started).min(self.window);
+        let hours = elapsed.as_secs_f64() / 3600.0;
+        if hours <= 0.0 {
+            return NetworkRate::default();
+        }
+        let total = self
+            .samples
+            .iter()
+            .fold(Sample::default(), |acc, (_, s)| Sample {
+                items: acc.items + s.items,
+                bytes: acc.bytes + s.bytes,
+                our_items: acc.our_items + s.our_items,
+                our_bytes: acc.our_bytes + s.our_bytes,
+            });
+        NetworkRate {
+            items_per_hour: total.items as f64 / hours,
+            bytes_per_hour: total.bytes as f64 / hours,
+            our_items_per_hour: total.our_items as f64 / hours,
+            our_bytes_per_hour: total.our_bytes as f64 / hours,
+        }
+    }
+}
+
+/// Spawn the statistics task: aggregate blocks from `follower` and log the
+/// network rate every `interval`
+pub fn spawn(
+    follower: &Follower,
+    our_accounts: Vec<String>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    let mut blocks = follower.subscribe();
+    tokio::spawn(async move {
+        let mut stats = NetworkStats::new(our_accounts, Duration::from_secs(3600), Instant::now());
+        let mut report = tokio::time::interval(interval);
+        report.tick().await;
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    debug!("Network stats task shutting down");
+                    break;
+                }
+                _ = report.tick() => {
+                    let rate = stats.rate(Instant::now());
+                    info!(
+                        items_per_hour = rate.items_per_hour,
+                        bytes_per_hour = rate.bytes_per_hour,
+                        our_share = rate.our_share(),
+                        "Network: {}",
+                        rate
+                    );
+                }
+                block = blocks.recv() => match block {
+                    Ok(block) => {
+                        if !block.migrated.is_empty() {
+                            debug!("#{}: {} Migrated events", block.number, block.migrated.len());
+                        }
+                        stats.record(&block, Instant::now());
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Network stats: missed {} blocks", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::follower::MigratedEvent;
+
+    const OURS: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn block(number: u32, events: &[(u64, Option<&str>)], size: Option<u64>) -> BlockMigrations {
+        BlockMigrations {
+            number,
+            migrated: events
+                .iter()
+                .map(|(top, signer)| MigratedEvent {
+                    top: *top,
+                    child: 0,
+                    signer: signer.map(str::to_string),
+                })
+                .collect(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_rate_and_share() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut stats = NetworkStats::new(vec![OURS.to_string()], Duration::from_secs(3600), start);
+
+        // Baseline size: items count, bytes do not
+        stats.record(
+            &block(1, &[(1000, Some("other"))], Some(10_000)),
+            minutes(1),
+        );
+        stats.record(&block(2, &[], None), minutes(2));
+        stats.record(
+            &block(
+                3,
+                &[(1000, Some(OURS)), (3000, Some("other"))],
+                Some(50_000),
+            ),
+            minutes(10),
+        );
+
+        let rate = stats.rate(minutes(30));
+        assert_eq!(rate.items_per_hour, 10_000.0);
+        assert_eq!(rate.bytes_per_hour, 80_000.0);
+        assert_eq!(rate.our_items_per_hour, 2_000.0);
+        assert_eq!(rate.our_bytes_per_hour, 20_000.0);
+        assert_eq!(rate.our_share(), 20.0);
+    }
+
+    #[test]
+    fn test_window_drops_old_blocks() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut stats = NetworkStats::new(vec![OURS.to_string()], Duration::from_secs(3600), start);
+
+        stats.record(&block(1, &[(500, None)], Some(1_000)), minutes(5));
+        stats.record(&block(2, &[(700, Some(OURS))], Some(2_000)), minutes(70));
+
+        let rate = stats.rate(minutes(90));
+        assert_eq!(rate.items_per_hour, 700.0);
+        assert_eq!(rate.bytes_per_hour, 1_000.0);
+        assert_eq!(rate.our_share(), 100.0);
+    }
+
+    #[test]
+    fn test_empty() {
+        let start = Instant::now();
+        let mut stats = NetworkStats::new(vec![OURS.to_string()], Duration::from_secs(3600), start);
+        assert_eq!(stats.rate(start), NetworkRate::default());
+        assert_eq!(
+            stats.rate(start + Duration::from_secs(60)).to_string(),
+            "0 items/h, 0 bytes/h (ours 0 items/h, 0.0%)"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Local nonce counter
+//!
+//! Each signer account's next nonce is read from the node once
+//! (`system_accountNextIndex`) and then counted locally: a broadcast the pool
+//! accepted advances it, any failure forgets it so that the next tx reads it
+//! again. Signing then never waits for the node to catch up with our previous
+//! tx, which is what lets `--pipeline` sign the next `continue_migrate` the
+//! moment the previous one is in a best block.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use subxt::utils::AccountId32;
+
+/// Next nonce per signer account
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    /// Keyed by the account's bytes (`AccountId32` isn't `Hash`)
+    next: Mutex<HashMap<[u8; 32], u32>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next(&self) -> std::sync::MutexGuard<'_, HashMap<[u8; 32], u32>> {
+        self.next.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Locally counted next nonce of `account`, if there is one
+    pub fn get(&self, account: &AccountId32) -> Option<u32> {
+        self.next().get(&account.0).copied()
+    }
+
+    /// Count on from `nonce`, as read from the node
+    pub fn seed(&self, account: &AccountId32, nonce: u32) {
+        self.next().insert(account.0, nonce);
+    }
+
+    /// The pool accepted a tx at `nonce`: the next one uses the nonce after it
+    pub fn submitted(&self, account: &AccountId32, nonce: u32) {
+        let mut next = self.next();
+        let entry = next.entry(account.0).or_insert(0);
+        *entry = (*entry).max(nonce.saturating_add(1));
+    }
+
+    /// Forget the count after a failure; the next tx reads it from the node
+    pub fn resync(&self, account: &AccountId32) {
+        self.next().remove(&account.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: AccountId32 = AccountId32([1; 32]);
+    const BOB: AccountId32 = AccountId32([2; 32]);
+
+    #[test]
+    fn test_counts_locally_until_resynced() {
+        let nonces = NonceManager::new();
+        assert_eq!(nonces.get(&ALICE), None);
+
+        nonces.seed(&ALICE, 7);
+        nonces.submitted(&ALICE, 7);
+        nonces.submitted(&ALICE, 8);
+        assert_eq!(nonces.get(&ALICE), Some(9));
+        // A rebroadcast of an older tx doesn't move the count back
+        nonces.submitted(&ALICE, 7);
+        assert_eq!(nonces.get(&ALICE), Some(9));
+
+        nonces.resync(&ALICE);
+        assert_eq!(nonces.get(&ALICE), None);
+    }
+
+    #[test]
+    fn test_seed_and_submitted() {
+        let nonces = NonceManager::new();
+        // A broadcast accepted before any read starts the count after it
+        nonces.submitted(&ALICE, 4);
+        assert_eq!(nonces.get(&ALICE), Some(5));
+        // A read from the node replaces the count, even a lower one
+        nonces.seed(&ALICE, 2);
+        assert_eq!(nonces.get(&ALICE), Some(2));
+        nonces.submitted(&ALICE, 2);
+        assert_eq!(nonces.get(&ALICE), Some(3));
+    }
+
+    #[test]
+    fn test_counter_after_resync() {
+        let nonces = NonceManager::new();
+        nonces.seed(&ALICE, 7);
+        nonces.submitted(&ALICE, 7);
+        nonces.resync(&ALICE);
+        // Resyncing twice is harmless
+        nonces.resync(&ALICE);
+        assert_eq!(nonces.get(&ALICE), None);
+
+        // The node says the tx at 7 never applied: count on from there
+        nonces.seed(&ALICE, 7);
+        assert_eq!(nonces.get(&ALICE), Some(7));
+        nonces.submitted(&ALICE, 7);
+        assert_eq!(nonces.get(&ALICE), Some(8));
+    }
+
+    #[test]
+    fn test_accounts_are_counted_apart() {
+        let nonces = NonceManager::new();
+        nonces.seed(&ALICE, 3);
+        nonces.submitted(&BOB, 10);
+        assert_eq!(nonces.get(&ALICE), Some(3));
+        assert_eq!(nonces.get(&BOB), Some(11));
+        nonces.resync(&BOB);
+        assert_eq!(nonces.get(&ALICE), Some(3));
+    }
+}
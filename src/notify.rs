@@ -0,0 +1,673 @@
+//! Notification sinks
+//!
+//! Every notification carries a [`Severity`]; each sink has a minimum
+//! severity it receives. By default the desktop gets everything and the
+//! optional SMTP sink only critical events, so there is a paper trail even
+//! when no one is watching the desktop; Twilio SMS/calls escalate critical
+//! events to a phone. Remote sinks (email, Slack, Teams, Twilio) deliver
+//! on background threads that `flush` waits for before exit.
+//!
+//! During optional quiet hours only critical notifications are delivered;
+//! everything else is held and delivered as a single summary once the quiet
+//! window ends.
+//!
+//! With acknowledgements enabled, every critical notification carries an
+//! acknowledgement link; the bot loop pauses submissions while one is overdue.
+//!
+//! Escalation rules (`escalation`) add sinks or pause submissions based on
+//! severity and how often the same notification repeats; escalated
+//! notifications are never held for quiet hours.
+
+mod ack;
+mod escalation;
+mod slack;
+mod teams;
+mod twilio;
+
+pub use ack::{AckConfig, PendingAck};
+pub use escalation::{Action, EscalationRule};
+pub use slack::{SlackConfig, SlackTarget};
+pub use teams::TeamsConfig;
+pub use twilio::{TwilioConfig, TwilioMode};
+
+use ack::AckTracker;
+use chrono::NaiveTime;
+use escalation::{Escalation, Escalator};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex, OnceLock, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Global flag to disable desktop notifications
+static NOTIFICATIONS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// SMTP sink configuration (set once at startup)
+static EMAIL: OnceLock<EmailConfig> = OnceLock::new();
+
+/// Slack sink configuration (set once at startup)
+static SLACK: OnceLock<SlackConfig> = OnceLock::new();
+
+/// Teams sink configuration (set once at startup)
+static TEAMS: OnceLock<TeamsConfig> = OnceLock::new();
+
+/// Twilio sink configuration (set once at startup)
+static TWILIO: OnceLock<TwilioConfig> = OnceLock::new();
+
+/// Outstanding remote deliveries, joined by `flush` before exit
+static PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Quiet-hours window (set once at startup)
+static QUIET_HOURS: OnceLock<QuietHours> = OnceLock::new();
+
+/// Non-critical notifications held back during quiet hours
+static HELD: Mutex<Vec<Held>> = Mutex::new(Vec::new());
+
+/// Acknowledgement settings (set once at startup)
+static ACK: OnceLock<AckConfig> = OnceLock::new();
+
+/// Critical notifications awaiting acknowledgement
+static ACKS: LazyLock<Mutex<AckTracker>> = LazyLock::new(Default::default);
+
+/// Escalation rules and repeat tracking (set once at startup)
+static ESCALATION: OnceLock<Mutex<Escalator>> = OnceLock::new();
+
+/// Minimum severity delivered to each sink
+static ROUTES: LazyLock<RwLock<HashMap<Sink, Severity>>> =
+    LazyLock::new(|| RwLock::new(default_routes()));
+
+/// Notification severity, ordered from least to most important
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, clap::ValueEnum)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Critical,
+}
+
+/// Notification destinations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum Sink {
+    Desktop,
+    Email,
+    Slack,
+    Teams,
+    Twilio,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Info => "info",
+            Severity::Success => "success",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Routing rule `sink=severity`: the sink receives `severity` and above
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    pub sink: Sink,
+    pub min_severity: Severity,
+}
+
+impl FromStr for Route {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use clap::ValueEnum;
+
+        let (sink, severity) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid route '{}' (expected sink=severity)", s))?;
+        Ok(Route {
+            sink: Sink::from_str(sink.trim(), true)?,
+            min_severity: Severity::from_str(severity.trim(), true)?,
+        })
+    }
+}
+
+fn default_routes() -> HashMap<Sink, Severity> {
+    HashMap::from([
+        (Sink::Desktop, Severity::Info),
+        (Sink::Email, Severity::Critical),
+        (Sink::Slack, Severity::Success),
+        (Sink::Teams, Severity::Success),
+        (Sink::Twilio, Severity::Critical),
+    ])
+}
+
+/// Override the minimum severity for a sink
+pub fn set_route(route: Route) {
+    if let Ok(mut routes) = ROUTES.write() {
+        routes.insert(route.sink, route.min_severity);
+    }
+}
+
+/// Whether a notification of `severity` should be delivered to `sink`
+fn routed(sink: Sink, severity: Severity) -> bool {
+    ROUTES
+        .read()
+        .ok()
+        .and_then(|routes| routes.get(&sink).copied())
+        .is_some_and(|min| severity >= min)
+}
+
+/// Local-time window `HH:MM-HH:MM` during which only critical notifications
+/// are delivered. The window may wrap past midnight (e.g. `23:00-07:00`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time '{}' (expected HH:MM)", t.trim()))
+        };
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("invalid quiet hours '{}' (expected HH:MM-HH:MM)", s))?;
+        let quiet = QuietHours {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if quiet.start == quiet.end {
+            return Err("quiet hours start and end must differ".to_string());
+        }
+        Ok(quiet)
+    }
+}
+
+/// A notification held back during quiet hours
+#[derive(Debug, Clone)]
+struct Held {
+    severity: Severity,
+    summary: String,
+}
+
+/// SMTP settings for critical-event emails
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<SecretString>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+}
+
+/// Disable desktop notifications globally
+pub fn disable_notifications() {
+    NOTIFICATIONS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Enable the SMTP sink (routed at `critical` unless overridden)
+pub fn configure_email(config: EmailConfig) {
+    if EMAIL.set(config).is_err() {
+        warn!("Email notifications already configured, ignoring");
+    }
+}
+
+/// Enable the Slack sink (routed at `success` unless overridden)
+pub fn configure_slack(config: SlackConfig) {
+    if SLACK.set(config).is_err() {
+        warn!("Slack notifications already configured, ignoring");
+    }
+}
+
+/// Enable the Teams sink (routed at `success` unless overridden)
+pub fn configure_teams(config: TeamsConfig) {
+    if TEAMS.set(config).is_err() {
+        warn!("Teams notifications already configured, ignoring");
+    }
+}
+
+/// Enable the Twilio escalation sink (routed at `critical` unless overridden)
+pub fn configure_twilio(config: TwilioConfig) {
+    if TWILIO.set(config).is_err() {
+        warn!("Twilio escalation already configured, ignoring");
+    }
+}
+
+/// Hold non-critical notifications during `quiet` (local time)
+pub fn configure_quiet_hours(quiet: QuietHours) {
+    if QUIET_HOURS.set(quiet).is_err() {
+        warn!("Quiet hours already configured, ignoring");
+    }
+}
+
+/// Accept acknowledgements; critical notifications must be acknowledged
+/// within `config.timeout`, if set
+pub fn configure_ack(config: AckConfig) {
+    if ACK.set(config).is_err() {
+        warn!("Acknowledgements already configured, ignoring");
+    }
+}
+
+/// Acknowledge a critical notification; returns its title if `token` was pending
+pub fn acknowledge(token: &str) -> Option<String> {
+    let acked = ACKS.lock().ok()?.acknowledge(token)?;
+    info!("Acknowledged '{}' ({})", acked.summary, acked.token);
+    Some(acked.summary)
+}
+
+/// Oldest notification still unacknowledged past its deadline
+pub fn overdue_ack() -> Option<PendingAck> {
+    ACKS.lock().ok()?.overdue(Instant::now()).cloned()
+}
+
+/// Evaluate `rules` for every notification, counting repeats within `window`
+pub fn configure_escalation(rules: Vec<EscalationRule>, window: Duration) {
+    if ESCALATION
+        .set(Mutex::new(Escalator::new(rules, window)))
+        .is_err()
+    {
+        warn!("Escalation rules already configured, ignoring");
+    }
+}
+
+fn in_quiet_hours() -> bool {
+    QUIET_HOURS
+        .get()
+        .is_some_and(|quiet| quiet.contains(chrono::Local::now().time()))
+}
+
+/// Send a notification to every sink whose route accepts `severity`.
+/// During quiet hours non-critical notifications are held for the summary.
+pub fn send_notification(severity: Severity, summary: &str, body: &str) {
+    notify(severity, summary, body, None);
+}
+
+/// Like [`send_notification`], with overall progress (0.0-1.0) for sinks that can render it
+pub fn send_progress(severity: Severity, summary: &str, body: &str, progress: f64) {
+    notify(severity, summary, body, Some(progress));
+}
+
+fn notify(severity: Severity, summary: &str, body: &str, progress: Option<f64>) {
+    let now = Instant::now();
+    let escalation = ESCALATION
+        .get()
+        .and_then(|escalator| escalator.lock().ok())
+        .map(|mut escalator| escalator.evaluate(severity, summary, now))
+        .unwrap_or_default();
+    let escalated = escalation.pause || !escalation.sinks.is_empty();
+
+    if severity < Severity::Critical && !escalated && in_quiet_hours() {
+        debug!("Quiet hours: holding notification '{}'", summary);
+        if let Ok(mut held) = HELD.lock() {
+            held.push(Held {
+                severity,
+                summary: summary.to_string(),
+            });
+        }
+        return;
+    }
+
+    release_held();
+
+    // Escalation pauses are due immediately, critical notifications after the timeout
+    let ack = ACK.get().and_then(|config| {
+        let (deadline, note) = if escalation.pause {
+            (now, "Submissions paused until acknowledged".to_string())
+        } else if severity == Severity::Critical {
+            let timeout = config.timeout?;
+            (
+                now + timeout,
+                format!(
+                    "Acknowledge within {} min or submissions pause",
+                    timeout.as_secs() / 60
+                ),
+            )
+        } else {
+            return None;
+        };
+        let token = ACKS.lock().ok()?.raise(summary, deadline);
+        let url = config.url(&token);
+        Some((format!("{}\n\n{}: {}", body, note, url), url))
+    });
+    match ack {
+        Some((body, url)) => deliver(severity, summary, &body, progress, Some(&url), &escalation),
+        None => deliver(severity, summary, body, progress, None, &escalation),
+    }
+}
+
+/// Send a notification to every configured sink, whatever the routes and
+/// quiet hours say (the shutdown report)
+pub fn send_everywhere(severity: Severity, summary: &str, body: &str) {
+    use clap::ValueEnum;
+
+    let everywhere = Escalation {
+        sinks: Sink::value_variants().to_vec(),
+        pause: false,
+    };
+    deliver(severity, summary, body, None, None, &everywhere);
+}
+
+/// Deliver the summary of notifications held during quiet hours, once they have ended
+pub fn release_held() {
+    if in_quiet_hours() {
+        return;
+    }
+    let held: Vec<_> = match HELD.lock() {
+        Ok(mut held) => held.drain(..).collect(),
+        Err(_) => return,
+    };
+    if let Some((severity, body)) = summarize_held(&held) {
+        let title = match QUIET_HOURS.get() {
+            Some(quiet) => format!("Quiet Hours Summary ({})", quiet),
+            None => "Quiet Hours Summary".to_string(),
+        };
+        deliver(severity, &title, &body, None, None, &Escalation::default());
+    }
+}
+
+/// Severity and body of the quiet-hours summary, grouped by notification title
+fn summarize_held(held: &[Held]) -> Option<(Severity, String)> {
+    let severity = held.iter().map(|h| h.severity).max()?;
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for h in held {
+        *counts.entry(h.summary.as_str()).or_default() += 1;
+    }
+    let lines: Vec<_> = counts
+        .iter()
+        .map(|(summary, count)| format!("{} × {}", count, summary))
+        .collect();
+
+    Some((
+        severity,
+        format!(
+            "{} notifications held during quiet hours:\n{}",
+            held.len(),
+            lines.join("\n")
+        ),
+    ))
+}
+
+fn deliver(
+    severity: Severity,
+    summary: &str,
+    body: &str,
+    progress: Option<f64>,
+    ack_url: Option<&str>,
+    escalation: &Escalation,
+) {
+    // Severity routes plus any sinks added by escalation rules
+    let to = |sink| routed(sink, severity) || escalation.sinks.contains(&sink);
+
+    if to(Sink::Desktop) {
+        send_desktop(severity, summary, body);
+    }
+
+    if to(Sink::Email) {
+        if let Some(config) = EMAIL.get() {
+            send_email(config, severity, summary, body);
+        }
+    }
+
+    if to(Sink::Slack) {
+        if let Some(config) = SLACK.get().filter(|c| c.accepts(summary)) {
+            let payload = slack::message(config, severity, summary, body, progress, ack_url);
+            let summary = summary.to_string();
+            spawn_delivery(move || match slack::deliver(config, payload) {
+                Ok(()) => debug!("Slack notification sent: {}", summary),
+                Err(e) => warn!("Failed to send Slack notification: {}", e),
+            });
+        }
+    }
+
+    if to(Sink::Teams) {
+        if let Some(config) = TEAMS.get() {
+            let payload = teams::message(config, severity, summary, body, progress, ack_url);
+            let summary = summary.to_string();
+            spawn_delivery(move || match teams::deliver(config, payload) {
+                Ok(()) => debug!("Teams notification sent: {}", summary),
+                Err(e) => warn!("Failed to send Teams notification: {}", e),
+            });
+        }
+    }
+
+    if to(Sink::Twilio) {
+        if let Some(config) = TWILIO.get() {
+            let (summary, body) = (summary.to_string(), body.to_string());
+            spawn_delivery(move || match twilio::deliver(config, &summary, &body) {
+                Ok(()) => debug!("Twilio escalation sent: {}", summary),
+                Err(e) => warn!("Failed to send Twilio escalation: {}", e),
+            });
+        }
+    }
+}
+
+/// Run a blocking delivery on its own thread so the bot loop isn't stalled
+fn spawn_delivery(f: impl FnOnce() + Send + 'static) {
+    let handle = std::thread::spawn(f);
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|h| !h.is_finished());
+        pending.push(handle);
+    }
+}
+
+/// Wait (bounded) for in-flight remote deliveries before the process exits
+pub fn flush(timeout: Duration) {
+    if let Ok(held) = HELD.lock() {
+        if let Some((_, body)) = summarize_held(&held) {
+            info!("Exiting during quiet hours - {}", body.replace('\n', ", "));
+        }
+    }
+
+    let handles: Vec<_> = match PENDING.lock() {
+        Ok(mut pending) => pending.drain(..).collect(),
+        Err(_) => return,
+    };
+
+    let deadline = Instant::now() + timeout;
+    for handle in handles {
+        while !handle.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        if handle.is_finished() {
+            let _ = handle.join();
+        } else {
+            warn!("Timed out waiting for notification delivery");
+            return;
+        }
+    }
+}
+
+fn send_desktop(severity: Severity, summary: &str, body: &str) {
+    // Skip if notifications are disabled (e.g., running on headless server)
+    if NOTIFICATIONS_DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    use notify_rust::{Notification, Timeout, Urgency};
+
+    let (timeout, urgency) = match severity {
+        Severity::Critical => (Timeout::Never, Urgency::Critical),
+        Severity::Warning => (Timeout::Milliseconds(15000), Urgency::Normal),
+        Severity::Success => (Timeout::Milliseconds(5000), Urgency::Normal),
+        Severity::Info => (Timeout::Milliseconds(5000), Urgency::Low),
+    };
+
+    if let Err(e) = Notification::new()
+        .summary(summary)
+        .body(body)
+        .appname("Westend Migration Bot")
+        .timeout(timeout)
+        .urgency(urgency)
+        .show()
+    {
+        warn!("Failed to send notification: {:?}", e);
+    }
+}
+
+fn send_email(config: &'static EmailConfig, severity: Severity, summary: &str, body: &str) {
+    let subject = format!("[westend-migrate] [{}] {}", severity, summary);
+    let text = format!(
+        "{}\n\n{}\n\nTime: {}\n",
+        summary,
+        body,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %Z")
+    );
+
+    spawn_delivery(move || {
+        if let Err(e) = deliver_email(config, &subject, text) {
+            warn!("Failed to send email notification: {}", e);
+        } else {
+            debug!("Email notification sent: {}", subject);
+        }
+    });
+}
+
+fn deliver_email(config: &EmailConfig, subject: &str, text: String) -> anyhow::Result<()> {
+    let mut builder = Message::builder()
+        .from(config.from.clone())
+        .subject(subject);
+    for to in &config.to {
+        builder = builder.to(to.clone());
+    }
+    let message = builder.body(text)?;
+
+    let mut transport = SmtpTransport::starttls_relay(&config.host)?.port(config.port);
+    if let (Some(user), Some(password)) = (&config.username, &config.password) {
+        transport = transport.credentials(Credentials::new(
+            user.clone(),
+            password.expose_secret().to_string(),
+        ));
+    }
+
+    transport.build().send(&message)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(Severity::Info < Severity::Success);
+        assert!(Severity::Success < Severity::Warning);
+        assert!(Severity::Warning < Severity::Critical);
+    }
+
+    #[test]
+    fn test_parse_route() {
+        assert_eq!(
+            "email=warning".parse::<Route>(),
+            Ok(Route {
+                sink: Sink::Email,
+                min_severity: Severity::Warning,
+            })
+        );
+        assert_eq!(
+            "Desktop=CRITICAL".parse::<Route>().unwrap().min_severity,
+            Severity::Critical
+        );
+    }
+
+    #[test]
+    fn test_parse_route_invalid() {
+        assert!("email".parse::<Route>().is_err());
+        assert!("pager=critical".parse::<Route>().is_err());
+        assert!("email=loud".parse::<Route>().is_err());
+    }
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_quiet_hours() {
+        let quiet: QuietHours = "23:00-07:00".parse().unwrap();
+        assert_eq!(quiet.start, time("23:00"));
+        assert_eq!(quiet.end, time("07:00"));
+        assert_eq!(quiet.to_string(), "23:00-07:00");
+
+        assert!("23:00".parse::<QuietHours>().is_err());
+        assert!("25:00-07:00".parse::<QuietHours>().is_err());
+        assert!("07:00-07:00".parse::<QuietHours>().is_err());
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_midnight() {
+        let quiet: QuietHours = "23:00-07:00".parse().unwrap();
+        assert!(quiet.contains(time("23:00")));
+        assert!(quiet.contains(time("02:30")));
+        assert!(!quiet.contains(time("07:00")));
+        assert!(!quiet.contains(time("12:00")));
+    }
+
+    #[test]
+    fn test_quiet_hours_same_day() {
+        let quiet: QuietHours = "12:00-13:30".parse().unwrap();
+        assert!(quiet.contains(time("12:45")));
+        assert!(!quiet.contains(time("11:59")));
+        assert!(!quiet.contains(time("13:30")));
+    }
+
+    #[test]
+    fn test_summarize_held() {
+        assert!(summarize_held(&[]).is_none());
+
+        let held = |severity, summary: &str| Held {
+            severity,
+            summary: summary.to_string(),
+        };
+        let (severity, body) = summarize_held(&[
+            held(Severity::Success, "Transaction Confirmed"),
+            held(Severity::Info, "Deadline Reached"),
+            held(Severity::Success, "Transaction Confirmed"),
+        ])
+        .unwrap();
+        assert_eq!(severity, Severity::Success);
+        assert!(body.starts_with("3 notifications held"));
+        assert!(body.contains("2 × Transaction Confirmed"));
+        assert!(body.contains("1 × Deadline Reached"));
+    }
+
+    #[test]
+    fn test_default_routes() {
+        let routes = default_routes();
+        assert_eq!(routes[&Sink::Desktop], Severity::Info);
+        assert_eq!(routes[&Sink::Email], Severity::Critical);
+        assert_eq!(routes[&Sink::Slack], Severity::Success);
+        assert_eq!(routes[&Sink::Teams], Severity::Success);
+        assert_eq!(routes[&Sink::Twilio], Severity::Critical);
+    }
+}
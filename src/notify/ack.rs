@@ -0,0 +1,112 @@
+//! Acknowledgement of critical notifications
+//!
+//! Each critical notification gets a short token. The operator acknowledges
+//! it through the health server (`GET /ack/<token>`, linked from the message);
+//! if any critical notification stays unacknowledged past the timeout, the
+//! bot pauses submissions until it is acknowledged. Escalation rules with a
+//! `pause` action raise an acknowledgement that is due immediately.
+
+use blake2::{Blake2s256, Digest};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Acknowledgement settings
+pub struct AckConfig {
+    /// Unacknowledged time after which submissions pause (`None` = critical
+    /// notifications need no acknowledgement; escalation pauses still do)
+    pub timeout: Option<Duration>,
+    /// Public base URL of the health server, for acknowledgement links
+    pub base_url: String,
+}
+
+impl AckConfig {
+    pub fn url(&self, token: &str) -> String {
+        format!("{}/ack/{}", self.base_url.trim_end_matches('/'), token)
+    }
+}
+
+/// A critical notification awaiting acknowledgement
+#[derive(Debug, Clone)]
+pub struct PendingAck {
+    pub token: String,
+    pub summary: String,
+    /// Submissions pause from this moment until acknowledged
+    pub deadline: Instant,
+}
+
+/// Outstanding acknowledgements
+#[derive(Debug, Default)]
+pub struct AckTracker {
+    pending: Vec<PendingAck>,
+    issued: u64,
+}
+
+impl AckTracker {
+    /// Register a notification due for acknowledgement by `deadline` and return its token
+    pub fn raise(&mut self, summary: &str, deadline: Instant) -> String {
+        self.issued += 1;
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let seed = format!(
+            "{}:{}:{}:{}",
+            std::process::id(),
+            nanos,
+            self.issued,
+            summary
+        );
+        let token = hex::encode(&Blake2s256::digest(seed.as_bytes())[..6]);
+
+        self.pending.push(PendingAck {
+            token: token.clone(),
+            summary: summary.to_string(),
+            deadline,
+        });
+        token
+    }
+
+    /// Acknowledge `token`; `None` if it is unknown or already acknowledged
+    pub fn acknowledge(&mut self, token: &str) -> Option<PendingAck> {
+        let index = self.pending.iter().position(|p| p.token == token)?;
+        Some(self.pending.remove(index))
+    }
+
+    /// Oldest notification left unacknowledged past its deadline
+    pub fn overdue(&self, now: Instant) -> Option<&PendingAck> {
+        self.pending.iter().find(|p| now >= p.deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overdue_until_acknowledged() {
+        let start = Instant::now();
+        let mut tracker = AckTracker::default();
+        let first = tracker.raise("CRITICAL WARNING", start + Duration::from_secs(600));
+        let second = tracker.raise("CRITICAL WARNING", start + Duration::from_secs(900));
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 12);
+
+        assert!(tracker.overdue(start + Duration::from_secs(599)).is_none());
+        let later = start + Duration::from_secs(700);
+        assert_eq!(tracker.overdue(later).unwrap().token, first);
+
+        assert!(tracker.acknowledge(&first).is_some());
+        assert!(tracker.acknowledge(&first).is_none());
+        assert!(tracker.overdue(later).is_none());
+        let much_later = start + Duration::from_secs(1000);
+        assert_eq!(tracker.overdue(much_later).unwrap().token, second);
+    }
+
+    #[test]
+    fn test_ack_url() {
+        let config = AckConfig {
+            timeout: Some(Duration::from_secs(600)),
+            base_url: "http://bot.example:8080/".to_string(),
+        };
+        assert_eq!(config.url("abc123"), "http://bot.example:8080/ack/abc123");
+    }
+}
@@ -0,0 +1,197 @@
+//! Escalation rules evaluated for every notification
+//!
+//! A rule `SEVERITY[*N]=ACTION[+ACTION...]` fires when a notification of at
+//! least `SEVERITY` has been sent `N` times (default 1) under the same title
+//! within the escalation window. Actions are extra sinks to deliver to, on
+//! top of the severity routes, or `pause` to stop submitting until the
+//! notification is acknowledged:
+//!
+//! ```text
+//! warning=slack            every warning also goes to Slack
+//! warning*3=twilio         the third identical warning within the window texts the operator
+//! critical=twilio+pause    critical events text the operator and pause submissions
+//! ```
+
+use super::{Severity, Sink};
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// What a matching rule does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Notify(Sink),
+    Pause,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use clap::ValueEnum;
+
+        match s.trim() {
+            "pause" => Ok(Action::Pause),
+            sink => Sink::from_str(sink, true).map(Action::Notify).map_err(|_| {
+                format!(
+                    "invalid escalation action '{}' (expected a sink or pause)",
+                    sink
+                )
+            }),
+        }
+    }
+}
+
+/// One escalation rule (see the module docs for the syntax)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscalationRule {
+    pub min_severity: Severity,
+    /// Occurrences of the same notification within the window needed to fire
+    pub repeats: usize,
+    pub actions: Vec<Action>,
+}
+
+impl FromStr for EscalationRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use clap::ValueEnum;
+
+        let (condition, actions) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid escalation rule '{}' (expected SEVERITY[*N]=ACTION[+ACTION])",
+                s
+            )
+        })?;
+        let (severity, repeats) = match condition.split_once('*') {
+            Some((severity, repeats)) => {
+                let repeats = repeats
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| format!("invalid repeat count '{}'", repeats.trim()))?;
+                (severity, repeats)
+            }
+            None => (condition, 1),
+        };
+        Ok(EscalationRule {
+            min_severity: Severity::from_str(severity.trim(), true)?,
+            repeats,
+            actions: actions
+                .split('+')
+                .map(str::parse)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Actions triggered for one notification
+#[derive(Debug, Default, PartialEq)]
+pub struct Escalation {
+    pub sinks: Vec<Sink>,
+    pub pause: bool,
+}
+
+/// Evaluates escalation rules, tracking how often each notification repeats
+pub struct Escalator {
+    rules: Vec<EscalationRule>,
+    window: Duration,
+    seen: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Escalator {
+    pub fn new(rules: Vec<EscalationRule>, window: Duration) -> Self {
+        Self {
+            rules,
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Record a notification and collect the actions of every rule it triggers
+    pub fn evaluate(&mut self, severity: Severity, summary: &str, now: Instant) -> Escalation {
+        let seen = self.seen.entry(summary.to_string()).or_default();
+        seen.push_back(now);
+        while seen
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) > self.window)
+        {
+            seen.pop_front();
+        }
+        let repeats = seen.len();
+
+        let mut escalation = Escalation::default();
+        let triggered = self
+            .rules
+            .iter()
+            .filter(|rule| severity >= rule.min_severity && repeats >= rule.repeats);
+        for action in triggered.flat_map(|rule| &rule.actions) {
+            match action {
+                Action::Notify(sink) if !escalation.sinks.contains(sink) => {
+                    escalation.sinks.push(*sink)
+                }
+                Action::Notify(_) => {}
+                Action::Pause => escalation.pause = true,
+            }
+        }
+        escalation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            "critical=twilio+pause".parse::<EscalationRule>(),
+            Ok(EscalationRule {
+                min_severity: Severity::Critical,
+                repeats: 1,
+                actions: vec![Action::Notify(Sink::Twilio), Action::Pause],
+            })
+        );
+        let rule: EscalationRule = "Warning*3=email".parse().unwrap();
+        assert_eq!(rule.min_severity, Severity::Warning);
+        assert_eq!(rule.repeats, 3);
+
+        assert!("warning".parse::<EscalationRule>().is_err());
+        assert!("warning*0=email".parse::<EscalationRule>().is_err());
+        assert!("warning=pager".parse::<EscalationRule>().is_err());
+        assert!("loud=email".parse::<EscalationRule>().is_err());
+    }
+
+    #[test]
+    fn test_repeats_within_window() {
+        let rules = vec![
+            "warning=slack".parse().unwrap(),
+            "warning*3=twilio".parse().unwrap(),
+            "critical=twilio+pause".parse().unwrap(),
+        ];
+        let mut escalator = Escalator::new(rules, Duration::from_secs(3600));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(
+            escalator.evaluate(Severity::Info, "Started", at(0)),
+            Escalation::default()
+        );
+        for secs in [0, 60] {
+            let escalation = escalator.evaluate(Severity::Warning, "Pool Stuck", at(secs));
+            assert_eq!(escalation.sinks, vec![Sink::Slack]);
+        }
+        // Third occurrence within the hour escalates
+        let escalation = escalator.evaluate(Severity::Warning, "Pool Stuck", at(120));
+        assert_eq!(escalation.sinks, vec![Sink::Slack, Sink::Twilio]);
+        assert!(!escalation.pause);
+        // The first two have aged out of the window
+        let escalation = escalator.evaluate(Severity::Warning, "Pool Stuck", at(3700));
+        assert_eq!(escalation.sinks, vec![Sink::Slack]);
+
+        let escalation = escalator.evaluate(Severity::Critical, "Bot Stopped", at(3700));
+        assert_eq!(escalation.sinks, vec![Sink::Slack, Sink::Twilio]);
+        assert!(escalation.pause);
+    }
+}
@@ -0,0 +1,242 @@
+//! Slack sink: incoming webhook or bot token (`chat.postMessage`)
+//!
+//! Messages use Block Kit: a header with a severity emoji, the body, an
+//! optional emoji progress bar, and buttons linking to the block explorer
+//! and dashboard (plus an acknowledgement button on critical alerts).
+
+use super::Severity;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// Cells in the emoji progress bar
+const PROGRESS_CELLS: usize = 10;
+
+/// Where Slack messages are posted
+pub enum SlackTarget {
+    Webhook(SecretString),
+    Bot {
+        token: SecretString,
+        channel: String,
+    },
+}
+
+/// Slack sink configuration
+pub struct SlackConfig {
+    pub target: SlackTarget,
+    pub explorer_url: Option<String>,
+    pub dashboard_url: Option<String>,
+    /// Notification kinds (see [`event_kind`]) to post; empty = all routed notifications
+    pub events: Vec<String>,
+}
+
+impl SlackConfig {
+    /// Whether notifications titled `summary` should be posted
+    pub fn accepts(&self, summary: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| *e == event_kind(summary))
+    }
+}
+
+/// Event kind of a notification, derived from its title:
+/// "Transaction Confirmed" → `transaction-confirmed`
+pub fn event_kind(summary: &str) -> String {
+    summary
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `🟩🟩🟩⬜⬜⬜⬜⬜⬜⬜ 30%`
+pub fn progress_bar(fraction: f64) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let filled = (fraction * PROGRESS_CELLS as f64).round() as usize;
+    format!(
+        "{}{} {:.0}%",
+        "🟩".repeat(filled),
+        "⬜".repeat(PROGRESS_CELLS - filled),
+        fraction * 100.0
+    )
+}
+
+fn severity_emoji(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "ℹ️",
+        Severity::Success => "✅",
+        Severity::Warning => "⚠️",
+        Severity::Critical => "🚨",
+    }
+}
+
+/// Block Kit payload (with plain-text fallback) for one notification
+pub fn message(
+    config: &SlackConfig,
+    severity: Severity,
+    summary: &str,
+    body: &str,
+    progress: Option<f64>,
+    ack_url: Option<&str>,
+) -> Value {
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": {
+                "type": "plain_text",
+                "text": format!("{} {}", severity_emoji(severity), summary),
+            },
+        }),
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": body },
+        }),
+    ];
+
+    if let Some(fraction) = progress {
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": progress_bar(fraction) }],
+        }));
+    }
+
+    let mut buttons: Vec<Value> = [
+        ("Explorer", &config.explorer_url),
+        ("Dashboard", &config.dashboard_url),
+    ]
+    .into_iter()
+    .filter_map(|(label, url)| {
+        url.as_ref().map(|url| {
+            json!({
+                "type": "button",
+                "text": { "type": "plain_text", "text": label },
+                "url": url,
+            })
+        })
+    })
+    .collect();
+    if let Some(url) = ack_url {
+        buttons.insert(
+            0,
+            json!({
+                "type": "button",
+                "text": { "type": "plain_text", "text": "Acknowledge" },
+                "style": "danger",
+                "url": url,
+            }),
+        );
+    }
+    if !buttons.is_empty() {
+        blocks.push(json!({ "type": "actions", "elements": buttons }));
+    }
+
+    json!({
+        "text": format!("[{}] {}: {}", severity, summary, body),
+        "blocks": blocks,
+    })
+}
+
+/// Post a message (blocking - called from a delivery thread)
+pub fn deliver(config: &SlackConfig, mut payload: Value) -> anyhow::Result<()> {
+    let client = reqwest::blocking::Client::new();
+    match &config.target {
+        SlackTarget::Webhook(url) => {
+            client
+                .post(url.expose_secret())
+                .json(&payload)
+                .send()?
+                .error_for_status()?;
+        }
+        SlackTarget::Bot { token, channel } => {
+            payload["channel"] = json!(channel);
+            let response: Value = client
+                .post(POST_MESSAGE_URL)
+                .bearer_auth(token.expose_secret())
+                .json(&payload)
+                .send()?
+                .error_for_status()?
+                .json()?;
+            // The Web API reports failures in the body with HTTP 200
+            if response["ok"] != json!(true) {
+                anyhow::bail!("chat.postMessage failed: {}", response["error"]);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(events: &[&str]) -> SlackConfig {
+        SlackConfig {
+            target: SlackTarget::Webhook(SecretString::new("https://hooks.slack.test".into())),
+            explorer_url: Some("https://assethub-westend.subscan.io".to_string()),
+            dashboard_url: None,
+            events: events.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_event_kind() {
+        assert_eq!(event_kind("Transaction Confirmed"), "transaction-confirmed");
+        assert_eq!(event_kind("CRITICAL WARNING"), "critical-warning");
+        assert_eq!(
+            event_kind("Quiet Hours Summary (23:00-07:00)"),
+            "quiet-hours-summary-23-00-07-00"
+        );
+    }
+
+    #[test]
+    fn test_accepts_event_filter() {
+        assert!(config(&[]).accepts("Bot Stopped"));
+        let filtered = config(&["transaction-confirmed", "bot-stopped"]);
+        assert!(filtered.accepts("Transaction Confirmed"));
+        assert!(filtered.accepts("Bot Stopped"));
+        assert!(!filtered.accepts("Westend Bot Started"));
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0.3), "🟩🟩🟩⬜⬜⬜⬜⬜⬜⬜ 30%");
+        assert_eq!(progress_bar(0.0), "⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜ 0%");
+        assert_eq!(progress_bar(1.7), "🟩🟩🟩🟩🟩🟩🟩🟩🟩🟩 100%");
+    }
+
+    #[test]
+    fn test_message_blocks() {
+        let payload = message(
+            &config(&[]),
+            Severity::Success,
+            "Transaction Confirmed",
+            "Migrated 1024 items",
+            Some(0.5),
+            None,
+        );
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0]["text"]["text"], "✅ Transaction Confirmed");
+        assert_eq!(blocks[2]["type"], "context");
+        // Only the configured explorer button
+        let buttons = blocks[3]["elements"].as_array().unwrap();
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0]["url"], "https://assethub-westend.subscan.io");
+
+        let payload = message(&config(&[]), Severity::Info, "Started", "ok", None, None);
+        assert_eq!(payload["blocks"].as_array().unwrap().len(), 3);
+
+        let payload = message(
+            &config(&[]),
+            Severity::Critical,
+            "CRITICAL WARNING",
+            "Balance decreased",
+            None,
+            Some("http://bot.example:8080/ack/abc123"),
+        );
+        let buttons = payload["blocks"][2]["elements"].as_array().unwrap();
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[0]["text"]["text"], "Acknowledge");
+        assert_eq!(buttons[0]["url"], "http://bot.example:8080/ack/abc123");
+    }
+}
@@ -0,0 +1,126 @@
+//! Microsoft Teams sink: incoming webhook with an Adaptive Card payload
+
+use super::Severity;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+
+/// Teams sink configuration
+pub struct TeamsConfig {
+    pub webhook_url: SecretString,
+    pub explorer_url: Option<String>,
+    pub dashboard_url: Option<String>,
+}
+
+/// Adaptive Card text color for a severity
+fn color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "Default",
+        Severity::Success => "Good",
+        Severity::Warning => "Warning",
+        Severity::Critical => "Attention",
+    }
+}
+
+/// Webhook payload wrapping an Adaptive Card for one notification
+pub fn message(
+    config: &TeamsConfig,
+    severity: Severity,
+    summary: &str,
+    body: &str,
+    progress: Option<f64>,
+    ack_url: Option<&str>,
+) -> Value {
+    let mut facts = vec![
+        json!({ "title": "Severity", "value": severity.to_string() }),
+        json!({
+            "title": "Time",
+            "value": chrono::Local::now().format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+        }),
+    ];
+    if let Some(fraction) = progress {
+        facts.push(json!({
+            "title": "Progress",
+            "value": format!("{:.0}%", fraction.clamp(0.0, 1.0) * 100.0),
+        }));
+    }
+
+    let actions: Vec<Value> = [
+        ("Acknowledge", ack_url),
+        ("Explorer", config.explorer_url.as_deref()),
+        ("Dashboard", config.dashboard_url.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(title, url)| {
+        url.map(|url| json!({ "type": "Action.OpenUrl", "title": title, "url": url }))
+    })
+    .collect();
+
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "contentUrl": null,
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": [
+                    {
+                        "type": "TextBlock",
+                        "text": summary,
+                        "size": "Large",
+                        "weight": "Bolder",
+                        "color": color(severity),
+                        "wrap": true,
+                    },
+                    { "type": "TextBlock", "text": body, "wrap": true },
+                    { "type": "FactSet", "facts": facts },
+                ],
+                "actions": actions,
+            },
+        }],
+    })
+}
+
+/// Post a message (blocking - called from a delivery thread)
+pub fn deliver(config: &TeamsConfig, payload: Value) -> anyhow::Result<()> {
+    reqwest::blocking::Client::new()
+        .post(config.webhook_url.expose_secret())
+        .json(&payload)
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_card() {
+        let config = TeamsConfig {
+            webhook_url: SecretString::new("https://example.webhook.office.com".into()),
+            explorer_url: Some("https://assethub-westend.subscan.io".to_string()),
+            dashboard_url: Some("https://grafana.example.com".to_string()),
+        };
+        let payload = message(
+            &config,
+            Severity::Critical,
+            "Bot Stopped",
+            "Too many consecutive errors",
+            Some(0.42),
+            Some("http://bot.example:8080/ack/abc123"),
+        );
+
+        let card = &payload["attachments"][0]["content"];
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(card["body"][0]["text"], "Bot Stopped");
+        assert_eq!(card["body"][0]["color"], "Attention");
+        let facts = card["body"][2]["facts"].as_array().unwrap();
+        assert_eq!(facts[0]["value"], "critical");
+        assert_eq!(facts[2]["value"], "42%");
+        let actions = card["actions"].as_array().unwrap();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[0]["title"], "Acknowledge");
+    }
+}
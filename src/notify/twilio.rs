@@ -0,0 +1,118 @@
+//! Twilio escalation sink: SMS or voice call for the most severe events
+//!
+//! Routed at `critical` by default, so a sleeping operator is woken for
+//! slashing-grade events only.
+
+use secrecy::{ExposeSecret, SecretString};
+
+const API_BASE: &str = "https://api.twilio.com/2010-04-01/Accounts";
+
+/// Longest SMS body sent (longer messages are split and billed per segment)
+const MAX_SMS_CHARS: usize = 300;
+
+/// How Twilio reaches the operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TwilioMode {
+    Sms,
+    Call,
+}
+
+/// Twilio sink configuration
+pub struct TwilioConfig {
+    pub account_sid: String,
+    pub auth_token: SecretString,
+    pub from: String,
+    pub to: Vec<String>,
+    pub mode: TwilioMode,
+}
+
+/// SMS text: title and body, truncated to [`MAX_SMS_CHARS`]
+pub fn sms_body(summary: &str, body: &str) -> String {
+    let text = format!("westend-migrate: {}\n{}", summary, body);
+    if text.chars().count() <= MAX_SMS_CHARS {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(MAX_SMS_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// TwiML for a voice call reading out the title and the first line of the body twice
+pub fn twiml(summary: &str, body: &str) -> String {
+    let first_line = body.lines().next().unwrap_or_default();
+    format!(
+        "<Response><Say loop=\"2\">Westend migration bot alert. {}. {}</Say></Response>",
+        xml_escape(summary),
+        xml_escape(first_line)
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Text or call every recipient (blocking - called from a delivery thread)
+pub fn deliver(config: &TwilioConfig, summary: &str, body: &str) -> anyhow::Result<()> {
+    let client = reqwest::blocking::Client::new();
+    let (endpoint, content_key, content) = match config.mode {
+        TwilioMode::Sms => ("Messages.json", "Body", sms_body(summary, body)),
+        TwilioMode::Call => ("Calls.json", "Twiml", twiml(summary, body)),
+    };
+    let url = format!("{}/{}/{}", API_BASE, config.account_sid, endpoint);
+
+    let mut failures = Vec::new();
+    for to in &config.to {
+        let params = [
+            ("To", to.as_str()),
+            ("From", config.from.as_str()),
+            (content_key, content.as_str()),
+        ];
+        let result = client
+            .post(&url)
+            .basic_auth(&config.account_sid, Some(config.auth_token.expose_secret()))
+            .form(&params)
+            .send()
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            failures.push(format!("{}: {}", to, e));
+        }
+    }
+
+    if !failures.is_empty() {
+        anyhow::bail!("{}", failures.join("; "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sms_body_truncated() {
+        assert_eq!(
+            sms_body("CRITICAL WARNING", "Balance decreased"),
+            "westend-migrate: CRITICAL WARNING\nBalance decreased"
+        );
+
+        let long = sms_body("Bot Stopped", &"x".repeat(1000));
+        assert_eq!(long.chars().count(), MAX_SMS_CHARS);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_twiml_escaped_first_line() {
+        assert_eq!(
+            twiml(
+                "CRITICAL WARNING",
+                "Balance <decreased> & stopped\n\nRecent events:"
+            ),
+            "<Response><Say loop=\"2\">Westend migration bot alert. CRITICAL WARNING. \
+             Balance &lt;decreased&gt; &amp; stopped</Say></Response>"
+        );
+    }
+}
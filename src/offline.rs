@@ -0,0 +1,281 @@
+//! Air-gapped signing (`tx build`, `tx submit`)
+//!
+//! `tx build` does everything `run` does before signing — reads the witness,
+//! picks the limits, encodes `continue_migrate` with the account's next nonce
+//! — and saves the result as an [`UnsignedTx`]: the call, the signer payload
+//! to sign offline and the extrinsic with a placeholder signature. `--qr`
+//! also prints the payload as a QR code in Polkadot Vault's format.
+//! `tx submit --signature` imports only the signature: it is verified against
+//! the account, spliced into the saved extrinsic and broadcast, after checking
+//! that the nonce, runtime and witness are still the ones it was built for.
+
+use crate::pipeline::MigrationCall;
+use crate::signing::TxSigner;
+use anyhow::{Context, Result};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use parity_scale_codec::{Compact, Decode};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use subxt::config::DefaultExtrinsicParamsBuilder;
+use subxt::utils::{AccountId32, MultiSignature, H256};
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519;
+
+/// Signed extrinsic, format version 4
+const SIGNED_V4: u8 = 0x84;
+/// `MultiAddress::Id`
+const ADDRESS_ID: u8 = 0x00;
+/// `MultiSignature::Sr25519`
+const SR25519: u8 = 0x01;
+
+/// Polkadot Vault (UOS) prefix: substrate, sr25519, sign transaction
+const VAULT_PREFIX: [u8; 3] = [0x53, 0x01, 0x02];
+
+/// A `continue_migrate` tx waiting for its signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    /// Signing account (SS58)
+    pub account: String,
+    pub nonce: u64,
+    pub size_limit: u32,
+    pub item_limit: u32,
+    /// Runtime and chain the payload commits to
+    pub spec_version: u32,
+    pub genesis_hash: H256,
+    pub built_at: chrono::DateTime<chrono::Utc>,
+    /// Encoded call, 0x-prefixed hex
+    pub call_data: String,
+    /// Human-readable call (informational)
+    #[serde(default)]
+    pub decoded: String,
+    /// What the offline key signs, 0x-prefixed hex
+    pub signer_payload: String,
+    /// The extrinsic with an all-zero sr25519 signature, 0x-prefixed hex
+    pub unsigned: String,
+}
+
+impl UnsignedTx {
+    /// Build `call` for `account` at `nonce`
+    pub fn build(
+        client: &OnlineClient<PolkadotConfig>,
+        call: &MigrationCall,
+        account: &AccountId32,
+        nonce: u64,
+    ) -> Result<Self> {
+        let params = DefaultExtrinsicParamsBuilder::<PolkadotConfig>::new()
+            .nonce(nonce)
+            .build();
+        let partial = client
+            .tx()
+            .create_partial_signed_offline(&call.payload, params)
+            .context("Failed to build the unsigned tx")?;
+        let placeholder = MultiSignature::Sr25519([0; 64]);
+        let unsigned =
+            partial.sign_with_address_and_signature(&account.clone().into(), &placeholder);
+        Ok(Self {
+            account: account.to_string(),
+            nonce,
+            size_limit: call.limits.size,
+            item_limit: call.limits.item,
+            spec_version: client.runtime_version().spec_version,
+            genesis_hash: client.genesis_hash(),
+            built_at: chrono::Utc::now(),
+            call_data: format!("0x{}", hex::encode(&call.call_data)),
+            decoded: call.decoded.clone(),
+            signer_payload: format!("0x{}", hex::encode(partial.signer_payload())),
+            unsigned: format!("0x{}", hex::encode(unsigned.encoded())),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write unsigned tx {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read unsigned tx {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid unsigned tx file {}", path.display()))
+    }
+
+    pub fn account_id(&self) -> Result<AccountId32> {
+        parse_account(&self.account).map_err(anyhow::Error::msg)
+    }
+
+    pub fn call_data(&self) -> Result<Vec<u8>> {
+        decode_hex(&self.call_data, "call_data")
+    }
+
+    /// Payload in Polkadot Vault's QR format
+    pub fn vault_payload(&self) -> Result<Vec<u8>> {
+        let mut data = VAULT_PREFIX.to_vec();
+        data.extend_from_slice(&self.account_id()?.0);
+        data.extend_from_slice(&decode_hex(&self.signer_payload, "signer_payload")?);
+        data.extend_from_slice(self.genesis_hash.as_bytes());
+        Ok(data)
+    }
+
+    /// The extrinsic signed with `signature` (hex; 64 bytes, or 65 starting
+    /// with the sr25519 `MultiSignature` tag as Vault returns it), once it
+    /// verifies for the account
+    pub fn sign(&self, signature: &str) -> Result<Vec<u8>> {
+        let signature = parse_signature(signature)?;
+        let payload = decode_hex(&self.signer_payload, "signer_payload")?;
+        anyhow::ensure!(
+            sr25519::verify(
+                &sr25519::Signature(signature),
+                &payload,
+                &sr25519::PublicKey(self.account_id()?.0)
+            ),
+            "Signature does not verify for {} over this tx's payload",
+            self.account
+        );
+        with_signature(&decode_hex(&self.unsigned, "unsigned")?, &signature)
+    }
+}
+
+/// An account whose key stays offline: it names the signer for `tx build`
+/// and `tx submit` but cannot sign
+pub struct OfflineAccount(pub AccountId32);
+
+impl TxSigner for OfflineAccount {
+    fn account_id(&self) -> AccountId32 {
+        self.0.clone()
+    }
+
+    fn sign<'a>(&'a self, _payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        async move {
+            anyhow::bail!(
+                "{} signs offline; sign the payload from `tx build` and pass it to `tx submit`",
+                self.0
+            )
+        }
+        .boxed()
+    }
+}
+
+/// Parse an SS58 address (clap value parser for `--account`)
+pub fn parse_account(s: &str) -> Result<AccountId32, String> {
+    AccountId32::from_str(s).map_err(|e| format!("invalid SS58 address '{}': {:?}", s, e))
+}
+
+/// Replace the placeholder signature of `unsigned` with `signature`
+fn with_signature(unsigned: &[u8], signature: &[u8; 64]) -> Result<Vec<u8>> {
+    let mut body = unsigned;
+    Compact::<u32>::decode(&mut body).context("Unsigned tx has no length prefix")?;
+    let start = unsigned.len() - body.len();
+    // Version, then `MultiAddress::Id` and the account
+    let offset = start + 1 + 1 + 32;
+    let placeholder = unsigned
+        .get(offset..offset + 65)
+        .context("Unsigned tx is too short")?;
+    anyhow::ensure!(
+        unsigned[start] == SIGNED_V4
+            && unsigned[start + 1] == ADDRESS_ID
+            && placeholder[0] == SR25519
+            && placeholder[1..].iter().all(|byte| *byte == 0),
+        "Unsigned tx has no placeholder signature to replace"
+    );
+    let mut signed = unsigned.to_vec();
+    signed[offset + 1..offset + 65].copy_from_slice(signature);
+    Ok(signed)
+}
+
+fn parse_signature(signature: &str) -> Result<[u8; 64]> {
+    let bytes = decode_hex(signature, "signature")?;
+    let bytes = match bytes.split_first() {
+        Some((&SR25519, rest)) if bytes.len() == 65 => rest,
+        _ => &bytes[..],
+    };
+    bytes.try_into().map_err(|_| {
+        anyhow::anyhow!(
+            "Signature is {} bytes, expected a 64-byte sr25519 signature",
+            bytes.len()
+        )
+    })
+}
+
+fn decode_hex(hex_str: &str, what: &str) -> Result<Vec<u8>> {
+    hex::decode(hex_str.trim().trim_start_matches("0x"))
+        .with_context(|| format!("`{}` is not hex", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::signer_from_seed;
+    use parity_scale_codec::Encode;
+
+    /// An unsigned tx for `account`, as `build` would save it
+    fn unsigned_tx(account: [u8; 32], payload: &[u8]) -> UnsignedTx {
+        let mut body = vec![SIGNED_V4, ADDRESS_ID];
+        body.extend_from_slice(&account);
+        body.push(SR25519);
+        body.extend_from_slice(&[0; 64]);
+        body.extend_from_slice(&[9; 10]);
+        let mut unsigned = Compact(body.len() as u32).encode();
+        unsigned.extend_from_slice(&body);
+        UnsignedTx {
+            account: AccountId32(account).to_string(),
+            nonce: 7,
+            size_limit: 409_600,
+            item_limit: 4096,
+            spec_version: 1_016_001,
+            genesis_hash: H256::repeat_byte(0xe1),
+            built_at: chrono::Utc::now(),
+            call_data: "0x0000".to_string(),
+            decoded: String::new(),
+            signer_payload: format!("0x{}", hex::encode(payload)),
+            unsigned: format!("0x{}", hex::encode(unsigned)),
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        let keypair = signer_from_seed(&format!("0x{}", "11".repeat(32))).unwrap();
+        let tx = unsigned_tx(keypair.public_key().0, b"payload");
+        let signature = keypair.sign(b"payload").0;
+
+        let signed = tx.sign(&hex::encode(signature)).unwrap();
+        let unsigned = decode_hex(&tx.unsigned, "unsigned").unwrap();
+        assert_eq!(signed.len(), unsigned.len());
+        // Two-byte length prefix, version, address
+        assert_eq!(signed[..37], unsigned[..37]);
+        assert_eq!(signed[37..101], signature);
+        assert_eq!(signed[101..], [9; 10]);
+
+        // As Polkadot Vault returns it
+        let tagged = format!("0x01{}", hex::encode(signature));
+        assert_eq!(tx.sign(&tagged).unwrap(), signed);
+
+        let other = keypair.sign(b"other").0;
+        assert!(tx.sign(&hex::encode(other)).is_err());
+        assert!(tx.sign("0xabcd").is_err());
+        // Already signed
+        assert!(with_signature(&signed, &signature).is_err());
+    }
+
+    #[test]
+    fn test_vault_payload() {
+        let tx = unsigned_tx([2; 32], b"payload");
+        let data = tx.vault_payload().unwrap();
+        assert_eq!(data[..3], VAULT_PREFIX);
+        assert_eq!(data[3..35], [2; 32]);
+        assert_eq!(&data[35..42], b"payload");
+        assert_eq!(data[42..], [0xe1; 32]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("unsigned-{}.json", std::process::id()));
+        let tx = unsigned_tx([2; 32], b"payload");
+        tx.save(&path).unwrap();
+        assert_eq!(UnsignedTx::load(&path).unwrap(), tx);
+        assert_eq!(tx.account_id().unwrap(), AccountId32([2; 32]));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
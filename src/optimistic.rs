@@ -0,0 +1,210 @@
+//! Next witness confirmed by our own `Migrated` event
+//!
+//! After a `continue_migrate` finalized, the loop used to read
+//! `MigrationProcess` again before it could build the next call. The event
+//! only carries item counts, not the last key or size, so the value is read
+//! while the tx still waits for finality, at the best block that included
+//! it. Once the tx finalizes in that same block, its `Migrated` event tells
+//! what the value must show: the counters of the witness it was submitted
+//! with, plus the items it migrated. If they agree, the read becomes the
+//! next witness; on any mismatch the loop queries as before.
+
+use crate::task::TrieState;
+use crate::utils::MigrationStatus;
+use std::sync::Mutex;
+use std::time::Instant;
+use subxt::utils::H256;
+
+/// Whether `read` is what `basis` becomes after migrating `top` / `child`
+/// items; the mismatch otherwise
+pub fn check(
+    basis: &MigrationStatus,
+    top: u64,
+    child: u64,
+    read: &MigrationStatus,
+) -> Result<(), String> {
+    let expected = (basis.top_items + top, basis.child_items + child);
+    let actual = (read.top_items, read.child_items);
+    if actual != expected {
+        return Err(format!(
+            "expected {}/{} top/child items, read {}/{}",
+            expected.0, expected.1, actual.0, actual.1
+        ));
+    }
+    if read.size < basis.size {
+        return Err(format!(
+            "size went back from {} to {}",
+            basis.size, read.size
+        ));
+    }
+    Ok(())
+}
+
+/// A `MigrationProcess` read, with the block and time it was read at
+#[derive(Debug, Clone)]
+pub struct Read {
+    pub block: H256,
+    pub state: TrieState,
+    pub at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Slots {
+    /// Status of the witness the tx in flight was built from
+    basis: Option<MigrationStatus>,
+    /// Read at the best block that includes it
+    included: Option<Read>,
+    /// Confirmed by its finalized event, for the next iteration
+    ready: Option<Read>,
+}
+
+/// The next witness of the tx in flight
+#[derive(Debug, Default)]
+pub struct NextWitness {
+    slots: Mutex<Slots>,
+}
+
+impl NextWitness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn slots(&self) -> std::sync::MutexGuard<'_, Slots> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A tx built from `basis` is about to be submitted
+    pub fn submitted(&self, basis: MigrationStatus) {
+        *self.slots() = Slots {
+            basis: Some(basis),
+            ..Slots::default()
+        };
+    }
+
+    /// `MigrationProcess` as read at a best block including the tx
+    pub fn included(&self, read: Read) {
+        self.slots().included = Some(read);
+    }
+
+    /// The including block was retracted
+    pub fn retracted(&self) {
+        self.slots().included = None;
+    }
+
+    /// The tx failed: drop its basis and any read for it
+    pub fn failed(&self) {
+        *self.slots() = Slots::default();
+    }
+
+    /// The tx finalized in `block`, its `Migrated` event (if any) carrying
+    /// `migrated` top / child items: keep the inclusion read if it checks
+    /// out, otherwise say why not
+    pub fn finalized(&self, block: H256, migrated: Option<(u64, u64)>) -> Result<(), String> {
+        let mut slots = self.slots();
+        let basis = slots.basis.take();
+        let included = slots.included.take();
+        let (Some(basis), Some(read)) = (basis, included) else {
+            return Err("no read at inclusion".to_string());
+        };
+        if read.block != block {
+            return Err(format!(
+                "read at {:?}, finalized in {:?}",
+                read.block, block
+            ));
+        }
+        let (top, child) = migrated.ok_or_else(|| "no Migrated event".to_string())?;
+        check(&basis, top, child, &read.state.status)?;
+        slots.ready = Some(read);
+        Ok(())
+    }
+
+    /// The confirmed next witness, once
+    pub fn take(&self) -> Option<Read> {
+        self.slots().ready.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::dynamic::Value;
+
+    fn status(top_items: u64, child_items: u64, size: u64) -> MigrationStatus {
+        MigrationStatus {
+            top_items,
+            child_items,
+            size,
+            ..MigrationStatus::default()
+        }
+    }
+
+    fn read(block: H256, status: MigrationStatus) -> Read {
+        Read {
+            block,
+            state: TrieState {
+                witness: Value::unnamed_composite(Vec::<Value>::new()),
+                status,
+            },
+            at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let basis = status(100, 4, 5000);
+        assert!(check(&basis, 256, 0, &status(356, 4, 9000)).is_ok());
+        let off = check(&basis, 256, 0, &status(300, 4, 9000)).unwrap_err();
+        assert_eq!(off, "expected 356/4 top/child items, read 300/4");
+        assert!(check(&basis, 256, 0, &status(356, 4, 10)).is_err());
+    }
+
+    #[test]
+    fn test_confirmed_read_is_taken_once() {
+        let next = NextWitness::new();
+        let block = H256::repeat_byte(1);
+        next.submitted(status(100, 0, 5000));
+        next.included(read(block, status(110, 0, 6000)));
+        assert_eq!(next.finalized(block, Some((10, 0))), Ok(()));
+        assert_eq!(next.take().map(|r| r.block), Some(block));
+        assert!(next.take().is_none());
+    }
+
+    #[test]
+    fn test_mismatches_fall_back() {
+        let next = NextWitness::new();
+        let block = H256::repeat_byte(1);
+
+        next.submitted(status(100, 0, 5000));
+        assert!(next.finalized(block, Some((10, 0))).is_err());
+
+        // Finalized in another block than the one read at
+        next.submitted(status(100, 0, 5000));
+        next.included(read(H256::repeat_byte(2), status(110, 0, 6000)));
+        assert!(next.finalized(block, Some((10, 0))).is_err());
+
+        // Retracted, or another migration in the same block
+        next.submitted(status(100, 0, 5000));
+        next.included(read(block, status(110, 0, 6000)));
+        next.retracted();
+        assert!(next.finalized(block, Some((10, 0))).is_err());
+        next.submitted(status(100, 0, 5000));
+        next.included(read(block, status(120, 0, 6000)));
+        assert!(next.finalized(block, Some((10, 0))).is_err());
+
+        next.submitted(status(100, 0, 5000));
+        next.included(read(block, status(110, 0, 6000)));
+        assert!(next.finalized(block, None).is_err());
+        assert!(next.take().is_none());
+    }
+
+    #[test]
+    fn test_failed_drops_the_read() {
+        let next = NextWitness::new();
+        let block = H256::repeat_byte(1);
+        next.submitted(status(100, 0, 5000));
+        next.included(read(block, status(110, 0, 6000)));
+        next.failed();
+        assert!(next.finalized(block, Some((10, 0))).is_err());
+        assert!(next.take().is_none());
+    }
+}
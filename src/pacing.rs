@@ -0,0 +1,148 @@
+//! Adaptive inter-transaction pacing
+//!
+//! When `--delay-secs 0` is given, the delay between migrations is derived
+//! from observed finalization latency and transaction pool behavior instead
+//! of submitting back-to-back.
+
+use std::time::Duration;
+
+/// Finalization latency considered healthy (~5 blocks at 6s)
+const EXPECTED_FINALIZATION_SECS: u64 = 30;
+
+/// Base backoff applied per consecutive pool conflict (one block)
+const CONFLICT_BACKOFF_SECS: u64 = 6;
+
+/// Upper bound for the derived delay
+const MAX_ADAPTIVE_DELAY_SECS: u64 = 120;
+
+/// Smoothing factor for the finalization latency moving average
+const LATENCY_EMA_ALPHA: f64 = 0.3;
+
+/// Derives an inter-transaction delay from recent chain behavior
+#[derive(Debug, Default)]
+pub struct AdaptivePacer {
+    /// Exponential moving average of submission → finalization latency (secs)
+    avg_finalization_secs: Option<f64>,
+    /// Pool conflicts seen since the last run of clean finalizations
+    conflict_streak: u32,
+}
+
+impl AdaptivePacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the latency of a successfully finalized transaction
+    pub fn record_finalized(&mut self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        self.avg_finalization_secs = Some(match self.avg_finalization_secs {
+            Some(avg) => avg + LATENCY_EMA_ALPHA * (secs - avg),
+            None => secs,
+        });
+        // Each clean finalization cools down the conflict backoff by one step
+        self.conflict_streak = self.conflict_streak.saturating_sub(1);
+    }
+
+    /// Record a pool conflict (priority too low / stale nonce)
+    pub fn record_pool_conflict(&mut self) {
+        self.conflict_streak = self.conflict_streak.saturating_add(1);
+    }
+
+    /// Average finalization latency observed so far
+    pub fn avg_finalization(&self) -> Option<Duration> {
+        self.avg_finalization_secs.map(Duration::from_secs_f64)
+    }
+
+    /// Delay to wait before the next submission
+    ///
+    /// Healthy finality yields no delay. Finalization lag beyond the expected
+    /// value adds half of the excess, and each outstanding pool conflict
+    /// doubles a one-block backoff.
+    pub fn next_delay(&self) -> Duration {
+        let lag_excess = self
+            .avg_finalization_secs
+            .map(|avg| (avg - EXPECTED_FINALIZATION_SECS as f64).max(0.0) / 2.0)
+            .unwrap_or(0.0);
+
+        let conflict_backoff = if self.conflict_streak == 0 {
+            0.0
+        } else {
+            let exp = (self.conflict_streak - 1).min(5);
+            (CONFLICT_BACKOFF_SECS << exp) as f64
+        };
+
+        let delay = (lag_excess + conflict_backoff).min(MAX_ADAPTIVE_DELAY_SECS as f64);
+        Duration::from_secs(delay.round() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_delay_without_observations() {
+        assert_eq!(AdaptivePacer::new().next_delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_healthy_finality_no_delay() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_finalized(Duration::from_secs(24));
+        pacer.record_finalized(Duration::from_secs(30));
+        assert_eq!(pacer.next_delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_slow_finality_adds_delay() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_finalized(Duration::from_secs(90));
+        // (90 - 30) / 2
+        assert_eq!(pacer.next_delay(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_latency_is_smoothed() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_finalized(Duration::from_secs(30));
+        pacer.record_finalized(Duration::from_secs(130));
+        // avg = 30 + 0.3 * 100 = 60
+        let avg = pacer.avg_finalization().unwrap().as_secs_f64();
+        assert!((avg - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pool_conflicts_back_off_exponentially() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_pool_conflict();
+        assert_eq!(pacer.next_delay(), Duration::from_secs(6));
+        pacer.record_pool_conflict();
+        assert_eq!(pacer.next_delay(), Duration::from_secs(12));
+        pacer.record_pool_conflict();
+        assert_eq!(pacer.next_delay(), Duration::from_secs(24));
+    }
+
+    #[test]
+    fn test_finalization_cools_down_conflicts() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_pool_conflict();
+        pacer.record_pool_conflict();
+        pacer.record_finalized(Duration::from_secs(20));
+        assert_eq!(pacer.next_delay(), Duration::from_secs(6));
+        pacer.record_finalized(Duration::from_secs(20));
+        assert_eq!(pacer.next_delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let mut pacer = AdaptivePacer::new();
+        pacer.record_finalized(Duration::from_secs(600));
+        for _ in 0..10 {
+            pacer.record_pool_conflict();
+        }
+        assert_eq!(
+            pacer.next_delay(),
+            Duration::from_secs(MAX_ADAPTIVE_DELAY_SECS)
+        );
+    }
+}
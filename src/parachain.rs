@@ -0,0 +1,281 @@
+//! Parachain awareness (Westend Asset Hub)
+//!
+//! The default target is Asset Hub, not the relay chain. Its blocks come
+//! from collators under async backing: a block is built on an unincluded
+//! segment, backed and included by the relay chain a few relay blocks later,
+//! and only finalized once that relay block is. Finality therefore lags
+//! further behind the best block than on the relay chain, and best blocks
+//! that never got backed are retracted more often. The chain kind is detected
+//! from the runtime (`ParachainInfo`) and the finalization timeout and
+//! pending-tx polling are scaled to it.
+//!
+//! With `--relay-rpc-url`, finalized inclusions are additionally checked
+//! against the relay chain's `Paras::Heads` for our para ID: the block
+//! holding our tx, or a descendant, must be the head the relay chain has
+//! finalized. By default a missing inclusion is only alerted;
+//! `--relay-finality` withholds counting the tx as final until it is seen,
+//! so a misbehaving parachain RPC node cannot report finality on its own.
+
+use crate::policy::{BLOCK_TIME_SECS, PENDING_TX_TIMEOUT_ITERATIONS};
+use anyhow::{Context, Result};
+use parity_scale_codec::{Compact, Decode};
+use std::fmt;
+use std::time::Duration;
+use subxt::dynamic::{At, Value};
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::debug;
+
+/// Finality lag budget of a parachain block: unincluded segment, backing,
+/// inclusion and relay-chain GRANDPA
+const PARACHAIN_FINALIZATION_TIMEOUT_SECS: u64 = 300;
+
+/// What kind of chain the bot is connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainKind {
+    Relay,
+    Parachain { para_id: u32 },
+}
+
+impl fmt::Display for ChainKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainKind::Relay => write!(f, "relay chain"),
+            ChainKind::Parachain { para_id } => write!(f, "parachain {}", para_id),
+        }
+    }
+}
+
+/// Detect the chain kind from the runtime: parachains have `ParachainInfo`
+pub async fn detect(client: &OnlineClient<PolkadotConfig>) -> Result<ChainKind> {
+    if client.metadata().pallet_by_name("ParachainInfo").is_none() {
+        return Ok(ChainKind::Relay);
+    }
+    let query = subxt::dynamic::storage("ParachainInfo", "ParachainId", vec![]);
+    let value = client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&query)
+        .await?
+        .context("ParachainInfo::ParachainId is not set")?
+        .to_value()?;
+    let para_id = as_u32(&value).context("ParachainInfo::ParachainId is not a number")?;
+    Ok(ChainKind::Parachain { para_id })
+}
+
+/// A `u32`, possibly wrapped in a newtype such as `ParaId`
+fn as_u32<T>(value: &Value<T>) -> Option<u32> {
+    value
+        .as_u128()
+        .or_else(|| value.at(0)?.as_u128())
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+/// Timeouts for waiting on our transactions, scaled to the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub block_time: Duration,
+    /// How long a submitted tx may take from broadcast to finalization
+    pub finalization_timeout: Duration,
+}
+
+impl Timing {
+    /// `slot` is the chain's block time if the runtime states one
+    pub fn for_chain(kind: ChainKind, slot: Option<Duration>) -> Self {
+        let block_time = slot
+            .filter(|slot| !slot.is_zero())
+            .unwrap_or(Duration::from_secs(BLOCK_TIME_SECS));
+        let relay_timeout = block_time * PENDING_TX_TIMEOUT_ITERATIONS;
+        let finalization_timeout = match kind {
+            ChainKind::Relay => relay_timeout,
+            ChainKind::Parachain { .. } => {
+                relay_timeout.max(Duration::from_secs(PARACHAIN_FINALIZATION_TIMEOUT_SECS))
+            }
+        };
+        Self {
+            block_time,
+            finalization_timeout,
+        }
+    }
+
+    /// Read the block time from `Timestamp::MinimumPeriod` (half a slot);
+    /// async-backing runtimes that set it to zero fall back to 6s
+    pub fn read(client: &OnlineClient<PolkadotConfig>, kind: ChainKind) -> Self {
+        let query = subxt::dynamic::constant("Timestamp", "MinimumPeriod");
+        let slot = match client
+            .constants()
+            .at(&query)
+            .and_then(|t| Ok(t.to_value()?))
+        {
+            Ok(value) => value
+                .as_u128()
+                .map(|ms| Duration::from_millis(ms as u64 * 2)),
+            Err(e) => {
+                debug!("Timestamp::MinimumPeriod unavailable: {}", e);
+                None
+            }
+        };
+        Self::for_chain(kind, slot)
+    }
+
+    /// Block-time polls that fit in the finalization timeout
+    pub fn pending_polls(&self) -> u32 {
+        (self.finalization_timeout.as_secs() / self.block_time.as_secs().max(1)) as u32
+    }
+}
+
+/// Block number of an encoded parachain header (`HeadData`)
+pub fn head_number(head: &[u8]) -> Option<u32> {
+    let mut rest = head.get(32..)?;
+    Compact::<u32>::decode(&mut rest).ok().map(|n| n.0)
+}
+
+/// What the relay chain says about a parachain block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inclusion {
+    /// The block, or a descendant, is included in a finalized relay block
+    Included,
+    /// The finalized relay chain has not reached the block yet
+    Pending,
+    /// The relay chain included a different block at the same height
+    Conflict { relay_hash: H256 },
+}
+
+/// Compare the relay chain's included head with parachain block `number`
+/// (`hash`)
+pub fn check_inclusion(head: (u32, H256), number: u32, hash: H256) -> Inclusion {
+    let (included, relay_hash) = head;
+    if included > number || (included == number && relay_hash == hash) {
+        Inclusion::Included
+    } else if included == number {
+        Inclusion::Conflict { relay_hash }
+    } else {
+        Inclusion::Pending
+    }
+}
+
+/// Read-only connection to the relay chain a parachain is validated by
+pub struct RelayChain {
+    client: OnlineClient<PolkadotConfig>,
+    para_id: u32,
+}
+
+impl RelayChain {
+    pub async fn connect(url: &str, para_id: u32) -> Result<Self> {
+        let client = OnlineClient::<PolkadotConfig>::from_url(url)
+            .await
+            .with_context(|| format!("Failed to connect to relay chain {}", url))?;
+        if client.metadata().pallet_by_name("Paras").is_none() {
+            anyhow::bail!("{} is not a relay chain (no Paras pallet)", url);
+        }
+        Ok(Self { client, para_id })
+    }
+
+    /// Number and hash of our para's head as included in the relay chain's
+    /// latest finalized block
+    pub async fn included_head(&self) -> Result<Option<(u32, H256)>> {
+        let query =
+            subxt::dynamic::storage("Paras", "Heads", vec![Value::u128(self.para_id as u128)]);
+        let Some(thunk) = self
+            .client
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+        else {
+            return Ok(None);
+        };
+        // `HeadData` is a SCALE-encoded `Vec<u8>` holding the para header
+        let head = Vec::<u8>::decode(&mut thunk.encoded()).context("Malformed Paras::Heads")?;
+        let number = head_number(&head).context("Malformed parachain header")?;
+        Ok(Some((number, H256(sp_crypto_hashing::blake2_256(&head)))))
+    }
+
+    /// Poll until the relay chain has finalized the inclusion of para block
+    /// `number` (`hash`) or a descendant; still `Pending` after `timeout`
+    pub async fn wait_for_inclusion(
+        &self,
+        number: u32,
+        hash: H256,
+        poll: Duration,
+        timeout: Duration,
+    ) -> Result<Inclusion> {
+        let start = std::time::Instant::now();
+        loop {
+            if let Some(head) = self.included_head().await? {
+                debug!("Relay chain includes para head #{} ({:?})", head.0, head.1);
+                let inclusion = check_inclusion(head, number, hash);
+                if inclusion != Inclusion::Pending {
+                    return Ok(inclusion);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Ok(Inclusion::Pending);
+            }
+            tokio::time::sleep(poll).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parity_scale_codec::Encode;
+
+    #[test]
+    fn test_timing() {
+        let relay = Timing::for_chain(ChainKind::Relay, Some(Duration::from_secs(6)));
+        assert_eq!(relay.finalization_timeout, Duration::from_secs(120));
+        assert_eq!(relay.pending_polls(), PENDING_TX_TIMEOUT_ITERATIONS);
+
+        // Async-backing runtimes report a zero MinimumPeriod
+        let para = Timing::for_chain(ChainKind::Parachain { para_id: 1000 }, Some(Duration::ZERO));
+        assert_eq!(para.block_time, Duration::from_secs(6));
+        assert_eq!(para.finalization_timeout, Duration::from_secs(300));
+        assert_eq!(para.pending_polls(), 50);
+
+        // Pre-async-backing 12s blocks
+        let slow = Timing::for_chain(
+            ChainKind::Parachain { para_id: 1000 },
+            Some(Duration::from_secs(12)),
+        );
+        assert_eq!(slow.pending_polls(), 25);
+    }
+
+    #[test]
+    fn test_head_number() {
+        let mut header = vec![0xaa; 32];
+        header.extend(Compact(4_321_000u32).encode());
+        header.extend([0u8; 64]);
+        assert_eq!(head_number(&header), Some(4_321_000));
+        assert_eq!(head_number(&[0; 16]), None);
+    }
+
+    #[test]
+    fn test_check_inclusion() {
+        let ours = H256::repeat_byte(1);
+        let other = H256::repeat_byte(2);
+        assert_eq!(check_inclusion((100, ours), 100, ours), Inclusion::Included);
+        assert_eq!(
+            check_inclusion((101, other), 100, ours),
+            Inclusion::Included
+        );
+        assert_eq!(check_inclusion((99, other), 100, ours), Inclusion::Pending);
+        assert_eq!(
+            check_inclusion((100, other), 100, ours),
+            Inclusion::Conflict { relay_hash: other }
+        );
+    }
+
+    #[test]
+    fn test_para_id_newtype() {
+        assert_eq!(as_u32(&Value::u128(1000)), Some(1000));
+        assert_eq!(
+            as_u32(&Value::unnamed_composite(vec![Value::u128(1000)])),
+            Some(1000)
+        );
+        assert_eq!(as_u32(&Value::string("x")), None);
+    }
+}
@@ -0,0 +1,323 @@
+//! Stages of a `continue_migrate` submission
+//!
+//! build → validate → sign → broadcast → confirm. Each stage hands a typed
+//! result to the next: [`MigrationCall`] (the encoded call), [`Validation`]
+//! (what the dry run established) and [`SignedMigration`] (bytes ready to
+//! broadcast, with the nonce needed to confirm them). `submit_migration`
+//! chains all five; rebroadcasting and modes that stop early reuse the same
+//! stages instead of their own copies. Offline signing splits the chain:
+//! `tx build` stops after stage 1 and `tx submit` resumes at stage 4. With
+//! `--proxy-for`, stage 1 wraps the call in `Proxy.proxy` before anything
+//! else sees it. With `--pipeline`, stage 5 ends at best-block inclusion and
+//! [`await_finality`] follows the rest in the background.
+
+use crate::error::MigrationError;
+use crate::limits::MigrationLimits;
+use crate::utils::describe_call;
+use anyhow::{Context, Result};
+use std::time::Duration;
+use subxt::dynamic::Value;
+use subxt::ext::scale_value;
+use subxt::tx::{DynamicPayload, SubmittableExtrinsic, TxProgress, TxStatus};
+use subxt::utils::{AccountId32, H256};
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Stage 1: an encoded `continue_migrate` call
+pub struct MigrationCall {
+    pub payload: DynamicPayload,
+    pub limits: MigrationLimits,
+    /// Exact call bytes, for checking in polkadot-js apps (Developer > Extrinsics > Decode)
+    pub call_data: Vec<u8>,
+    /// Human-readable form of the call
+    pub decoded: String,
+}
+
+impl MigrationCall {
+    pub fn build(
+        client: &OnlineClient<PolkadotConfig>,
+        witness_task: Value<()>,
+        limits: MigrationLimits,
+    ) -> Result<Self> {
+        let real_size_upper = Value::u128(limits.real_size_upper() as u128);
+        let limits_value = limits.to_value();
+        let decoded = describe_call(
+            "StateTrieMigration",
+            "continue_migrate",
+            &[
+                ("limits", &limits_value),
+                ("real_size_upper", &real_size_upper),
+                ("witness_task", &witness_task),
+            ],
+        );
+        // Parameters: limits, real_size_upper, witness_task
+        let payload = subxt::dynamic::tx(
+            "StateTrieMigration",
+            "continue_migrate",
+            vec![limits_value, real_size_upper, witness_task],
+        );
+        let call_data = client
+            .tx()
+            .call_data(&payload)
+            .context("Failed to encode continue_migrate call")?;
+        Ok(Self {
+            payload,
+            limits,
+            call_data,
+            decoded,
+        })
+    }
+
+    /// The same call dispatched from `real` through
+    /// `Proxy.proxy(real, None, call)`, signed by one of its proxies
+    pub fn via_proxy(
+        self,
+        client: &OnlineClient<PolkadotConfig>,
+        real: &AccountId32,
+    ) -> Result<Self> {
+        let metadata = client.metadata();
+        let call = scale_value::scale::decode_as_type(
+            &mut &self.call_data[..],
+            metadata.outer_enums().call_enum_ty(),
+            metadata.types(),
+        )
+        .context("Failed to decode the call as a RuntimeCall")?
+        .remove_context();
+        let decoded = format!(
+            "Proxy.proxy(real: {}, force_proxy_type: None, call: {})",
+            real, self.decoded
+        );
+        // real: MultiAddress::Id, force_proxy_type: None
+        let real = Value::unnamed_variant("Id", [Value::from_bytes(real)]);
+        let force_proxy_type = Value::unnamed_variant("None", []);
+        let payload = subxt::dynamic::tx("Proxy", "proxy", vec![real, force_proxy_type, call]);
+        let call_data = client
+            .tx()
+            .call_data(&payload)
+            .context("Failed to encode proxy call")?;
+        Ok(Self {
+            payload,
+            limits: self.limits,
+            call_data,
+            decoded,
+        })
+    }
+}
+
+/// Stage 2: what pre-submission validation established
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validation {
+    /// `system_dryRun` executed the call successfully
+    DryRunOk,
+    /// The call could not be validated (dry run unavailable or undecodable)
+    Unchecked,
+}
+
+/// Stage 3: a signed extrinsic ready to broadcast
+pub struct SignedMigration {
+    pub tx: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    /// Account that signed it
+    pub account: AccountId32,
+    /// Account nonce before this tx, to detect it applied when events are missed
+    pub nonce_before: u32,
+    /// Limits of the call (unknown for rebroadcast bytes)
+    pub limits: Option<MigrationLimits>,
+}
+
+impl SignedMigration {
+    /// Wrap already-signed bytes (e.g. to rebroadcast via another endpoint)
+    pub fn from_bytes(
+        client: &OnlineClient<PolkadotConfig>,
+        bytes: Vec<u8>,
+        account: AccountId32,
+        nonce_before: u32,
+    ) -> Self {
+        Self {
+            tx: SubmittableExtrinsic::from_bytes(client.clone(), bytes),
+            account,
+            nonce_before,
+            limits: None,
+        }
+    }
+
+    pub fn hash(&self) -> H256 {
+        self.tx.hash()
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.tx.encoded().to_vec()
+    }
+}
+
+/// Stage 5 in the background (`--pipeline`): follow a tx already in a best
+/// block until it is finalized. The finalized block and its
+/// `StateTrieMigration` events, or why it never got there.
+pub async fn await_finality(
+    mut progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    timeout: Duration,
+) -> Result<(H256, Vec<String>), String> {
+    let follow = async {
+        while let Some(status) = progress.next().await {
+            match status.map_err(|e| e.to_string())? {
+                TxStatus::InFinalizedBlock(block) => {
+                    let events = block.fetch_events().await.map_err(|e| e.to_string())?;
+                    let migration_events = events
+                        .iter()
+                        .flatten()
+                        .filter(|evt| evt.pallet_name() == "StateTrieMigration")
+                        .map(|evt| evt.variant_name().to_string())
+                        .collect();
+                    return Ok((block.block_hash(), migration_events));
+                }
+                TxStatus::Error { message }
+                | TxStatus::Invalid { message }
+                | TxStatus::Dropped { message } => return Err(message),
+                _ => {}
+            }
+        }
+        Err("status stream ended before finalization".to_string())
+    };
+    tokio::time::timeout(timeout, follow)
+        .await
+        .unwrap_or_else(|_| Err(format!("not finalized within {}s", timeout.as_secs())))
+}
+
+/// A `--pipeline` tx whose finality was followed in the background
+pub struct Trailing {
+    pub tx_hash: H256,
+    pub account: AccountId32,
+    /// Finalized block and `StateTrieMigration` events, or why it never finalized
+    pub outcome: Result<(H256, Vec<String>), String>,
+}
+
+/// `--pipeline` txs confirmed at best-block inclusion, their finality still
+/// followed in the background
+#[derive(Debug, Default)]
+pub struct Unsettled {
+    /// Hash, signer and nonce, in signing order
+    txs: Vec<(H256, AccountId32, u32)>,
+}
+
+impl Unsettled {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tx at `nonce` of `account` went into a best block
+    pub fn push(&mut self, tx_hash: H256, account: AccountId32, nonce: u32) {
+        self.txs.push((tx_hash, account, nonce));
+    }
+
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    /// Take `trailing` out of flight; `None` if it no longer was. A tx that
+    /// did not finalize takes along the txs its account signed after it,
+    /// whose nonces and witnesses counted on it: their hashes are returned.
+    pub fn settle(&mut self, trailing: &Trailing) -> Option<Vec<H256>> {
+        let index = self
+            .txs
+            .iter()
+            .position(|(tx_hash, ..)| *tx_hash == trailing.tx_hash)?;
+        let (_, account, nonce) = self.txs.remove(index);
+        let mut dropped = Vec::new();
+        if trailing.outcome.is_err() {
+            self.txs.retain(|(tx_hash, other, later)| {
+                let after = *other == account && *later > nonce;
+                if after {
+                    dropped.push(*tx_hash);
+                }
+                !after
+            });
+        }
+        Some(dropped)
+    }
+}
+
+/// Map a dry-run dispatch error to the error the bot acts on
+pub fn dispatch_error(err_str: String) -> MigrationError {
+    if err_str.contains("SizeUpperBoundExceeded") {
+        MigrationError::SizeExceeded
+    } else if err_str.contains("BadWitness") {
+        MigrationError::BadWitness(err_str)
+    } else {
+        MigrationError::DryRunDispatchError(err_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: AccountId32 = AccountId32([1; 32]);
+    const BOB: AccountId32 = AccountId32([2; 32]);
+
+    fn trailing(tx_hash: H256, outcome: Result<(H256, Vec<String>), String>) -> Trailing {
+        Trailing {
+            tx_hash,
+            account: ALICE,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_failed_trailing_drops_later_txs() {
+        let (first, second, third, other) = (
+            H256::repeat_byte(1),
+            H256::repeat_byte(2),
+            H256::repeat_byte(3),
+            H256::repeat_byte(4),
+        );
+        let mut unsettled = Unsettled::new();
+        unsettled.push(first, ALICE, 7);
+        unsettled.push(second, ALICE, 8);
+        unsettled.push(other, BOB, 3);
+        unsettled.push(third, ALICE, 9);
+
+        let failed = trailing(first, Err("not finalized within 60s".to_string()));
+        assert_eq!(unsettled.settle(&failed), Some(vec![second, third]));
+        // Another account's tx stays in flight
+        let finalized = Trailing {
+            account: BOB,
+            ..trailing(other, Ok((H256::zero(), vec!["Migrated".to_string()])))
+        };
+        assert_eq!(unsettled.settle(&finalized), Some(vec![]));
+        // The outcome of a dropped tx arrives later
+        assert_eq!(
+            unsettled.settle(&trailing(second, Err("Invalid".to_string()))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_finalized_trailing_keeps_later_txs() {
+        let (first, second) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        let mut unsettled = Unsettled::new();
+        unsettled.push(first, ALICE, 7);
+        unsettled.push(second, ALICE, 8);
+        assert_eq!(
+            unsettled.settle(&trailing(first, Ok((H256::zero(), vec![])))),
+            Some(vec![])
+        );
+        assert_eq!(
+            unsettled.settle(&trailing(second, Err("Dropped".to_string()))),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_dispatch_error() {
+        assert!(matches!(
+            dispatch_error("Module { pallet: StateTrieMigration, SizeUpperBoundExceeded }".into()),
+            MigrationError::SizeExceeded
+        ));
+        assert!(matches!(
+            dispatch_error("BadWitness".into()),
+            MigrationError::BadWitness(e) if e == "BadWitness"
+        ));
+        assert!(matches!(
+            dispatch_error("Module { pallet: Balances, InsufficientBalance }".into()),
+            MigrationError::DryRunDispatchError(_)
+        ));
+    }
+}
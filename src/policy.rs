@@ -0,0 +1,211 @@
+//! Retry, timeout and backoff policy
+//!
+//! Every "how long do we wait, and do we keep going" decision of the main
+//! loop lives here as constants and pure functions, so the behaviour after a
+//! failed submission can be unit-tested without a chain. The loop classifies
+//! the error, asks [`on_failure`] what to do, and only carries out the
+//! returned [`Action`].
+
+use crate::error::MigrationError;
+use std::time::Duration;
+
+/// Expected block time
+pub const BLOCK_TIME_SECS: u64 = 6;
+/// Blocks to watch the nonce for a pending tx before giving up on it
+pub const PENDING_TX_TIMEOUT_ITERATIONS: u32 = 20;
+/// Fallback wait for a pending tx when the nonce cannot be read
+pub const NONCE_RETRY_WAIT_SECS: u64 = 30;
+/// Wait before retrying after a failure or a skipped submission
+pub const RETRY_WAIT_SECS: u64 = 12;
+/// Wait after the pool temporarily banned our tx
+pub const BANNED_TX_WAIT_SECS: u64 = 60;
+/// Stop after this many consecutive failures
+pub const MAX_CONSECUTIVE_ERRORS: u32 = 5;
+/// Re-check for an acknowledgement while paused
+pub const ACK_POLL_SECS: u64 = 10;
+/// Broadcasts to 0 peers before switching endpoints
+pub const ZERO_PEER_BROADCAST_LIMIT: u32 = 3;
+/// Re-check an unhealthy node while holding submissions
+pub const NODE_HEALTH_POLL_SECS: u64 = 30;
+/// Stale-witness retries in a row before they count as failures
+pub const MAX_STALE_WITNESS_RETRIES: u32 = 3;
+
+/// How a failed submission is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Must stop at once, without retries
+    Fatal,
+    /// Another tx from our account is pending; wait for it to finalize
+    PoolConflict,
+    /// The pool temporarily banned our tx
+    Banned,
+    /// Another migration advanced `MigrationProcess` past our witness
+    StaleWitness,
+    /// Recoverable; retry without counting it against the error budget
+    Transient,
+    /// Anything else, including errors that are not a [`MigrationError`]
+    Failure,
+}
+
+impl ErrorClass {
+    pub fn of(err: Option<&MigrationError>) -> Self {
+        match err {
+            Some(err) if err.is_fatal() => ErrorClass::Fatal,
+            Some(err) if err.requires_pool_wait() => ErrorClass::PoolConflict,
+            Some(MigrationError::TxBanned) => ErrorClass::Banned,
+            Some(err) if err.is_stale_witness() => ErrorClass::StaleWitness,
+            Some(err) if err.is_recoverable() => ErrorClass::Transient,
+            _ => ErrorClass::Failure,
+        }
+    }
+}
+
+/// What the loop does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Stop with the error as is
+    Stop,
+    /// Wait for our pending tx to finalize, then retry
+    WaitForPool,
+    /// Sleep, then retry
+    Retry(Duration),
+    /// Read the witness afresh and retry right away
+    Requery,
+    /// Another signer account took over (--rotate-seed-file); retry with it
+    /// right away
+    Rotated,
+    /// The error budget is spent; stop with `TooManyErrors`
+    GiveUp,
+}
+
+/// Outcome of [`on_failure`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub action: Action,
+    /// Consecutive error count to carry into the next iteration
+    pub consecutive_errors: u32,
+}
+
+/// Decide what to do after a failed submission, given how many consecutive
+/// failures preceded it
+pub fn on_failure(class: ErrorClass, consecutive_errors: u32) -> Decision {
+    let (action, consecutive_errors) = match class {
+        ErrorClass::Fatal => (Action::Stop, consecutive_errors),
+        // Pool waits are expected and reset the budget
+        ErrorClass::PoolConflict => (Action::WaitForPool, 0),
+        ErrorClass::Banned => (Action::Retry(Duration::from_secs(BANNED_TX_WAIT_SECS)), 0),
+        ErrorClass::StaleWitness => (Action::Requery, consecutive_errors),
+        ErrorClass::Transient => (
+            Action::Retry(Duration::from_secs(RETRY_WAIT_SECS)),
+            consecutive_errors,
+        ),
+        ErrorClass::Failure => {
+            let count = consecutive_errors + 1;
+            if count >= MAX_CONSECUTIVE_ERRORS {
+                (Action::GiveUp, count)
+            } else {
+                (Action::Retry(Duration::from_secs(RETRY_WAIT_SECS)), count)
+            }
+        }
+    };
+    Decision {
+        action,
+        consecutive_errors,
+    }
+}
+
+/// Whether repeated broadcasts to no peers warrant switching endpoints
+pub fn should_switch_endpoint(zero_peer_broadcasts: u32) -> bool {
+    zero_peer_broadcasts >= ZERO_PEER_BROADCAST_LIMIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification() {
+        let class = |err: MigrationError| ErrorClass::of(Some(&err));
+        assert_eq!(
+            class(MigrationError::ValidationUnavailable("x".into())),
+            ErrorClass::Fatal
+        );
+        assert_eq!(
+            class(MigrationError::PoolConflict),
+            ErrorClass::PoolConflict
+        );
+        assert_eq!(class(MigrationError::NonceStale), ErrorClass::PoolConflict);
+        assert_eq!(class(MigrationError::TxBanned), ErrorClass::Banned);
+        assert_eq!(class(MigrationError::NonceFuture), ErrorClass::Transient);
+        assert_eq!(
+            class(MigrationError::RpcError("timeout".into())),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            class(MigrationError::BadWitness("BadWitness".into())),
+            ErrorClass::StaleWitness
+        );
+        assert_eq!(
+            class(MigrationError::WitnessChanged("top_items 1 -> 2".into())),
+            ErrorClass::StaleWitness
+        );
+        assert_eq!(class(MigrationError::SizeExceeded), ErrorClass::Failure);
+        assert_eq!(ErrorClass::of(None), ErrorClass::Failure);
+    }
+
+    #[test]
+    fn test_pool_waits_reset_the_budget() {
+        let decision = on_failure(ErrorClass::PoolConflict, 3);
+        assert_eq!(decision.action, Action::WaitForPool);
+        assert_eq!(decision.consecutive_errors, 0);
+
+        let decision = on_failure(ErrorClass::Banned, 3);
+        assert_eq!(
+            decision.action,
+            Action::Retry(Duration::from_secs(BANNED_TX_WAIT_SECS))
+        );
+        assert_eq!(decision.consecutive_errors, 0);
+    }
+
+    #[test]
+    fn test_transient_errors_do_not_count() {
+        let decision = on_failure(ErrorClass::Transient, 2);
+        assert_eq!(
+            decision.action,
+            Action::Retry(Duration::from_secs(RETRY_WAIT_SECS))
+        );
+        assert_eq!(decision.consecutive_errors, 2);
+    }
+
+    #[test]
+    fn test_stale_witness_requeries_without_counting() {
+        let decision = on_failure(ErrorClass::StaleWitness, 2);
+        assert_eq!(decision.action, Action::Requery);
+        assert_eq!(decision.consecutive_errors, 2);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_consecutive_failures() {
+        let mut count = 0;
+        for _ in 1..MAX_CONSECUTIVE_ERRORS {
+            let decision = on_failure(ErrorClass::Failure, count);
+            assert!(matches!(decision.action, Action::Retry(_)));
+            count = decision.consecutive_errors;
+        }
+        let decision = on_failure(ErrorClass::Failure, count);
+        assert_eq!(decision.action, Action::GiveUp);
+        assert_eq!(decision.consecutive_errors, MAX_CONSECUTIVE_ERRORS);
+    }
+
+    #[test]
+    fn test_fatal_stops_immediately() {
+        let decision = on_failure(ErrorClass::Fatal, 0);
+        assert_eq!(decision.action, Action::Stop);
+    }
+
+    #[test]
+    fn test_endpoint_switch_threshold() {
+        assert!(!should_switch_endpoint(ZERO_PEER_BROADCAST_LIMIT - 1));
+        assert!(should_switch_endpoint(ZERO_PEER_BROADCAST_LIMIT));
+    }
+}
@@ -0,0 +1,348 @@
+//! Transaction pool hygiene for Substrate nodes
+//!
+//! Inspect the pool (`author_pendingExtrinsics`) and decode what is in it
+//! against runtime metadata, remove extrinsics from it
+//! (`author_removeExtrinsic`), resubmit already-signed bytes and wait for an
+//! account's pending transaction to land. Nothing here is specific to the
+//! state-trie migration: any operator whose signer can get stuck behind its
+//! own pool entry can reuse it.
+//!
+//! Listing and removal are unsafe RPC methods; against a public node started
+//! without `--rpc-methods=unsafe` they fail with [`PoolError::Rpc`].
+//!
+//! ```no_run
+//! # async fn example(rpc: subxt::backend::rpc::RpcClient) -> Result<(), westend_migration_bot::pool::PoolError> {
+//! use westend_migration_bot::pool;
+//!
+//! let pending = pool::pending_extrinsics(&rpc).await?;
+//! let hashes: Vec<_> = pending.iter().map(|ext| ext.hash()).collect();
+//! let removed = pool::remove_extrinsics(&rpc, &hashes).await?;
+//! println!("removed {} of {} pool entries", removed.len(), pending.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use parity_scale_codec::{Compact, Decode};
+use std::future::Future;
+use std::time::Duration;
+use subxt::backend::rpc::RpcClient;
+use subxt::ext::scale_value::scale::decode_as_type;
+use subxt::utils::{AccountId32, H256};
+use subxt::{rpc_params, Metadata};
+
+/// Errors from pool RPC calls
+#[derive(Debug, thiserror::Error)]
+pub enum PoolError {
+    /// The RPC call failed (for listing/removal, often: unsafe methods disabled)
+    #[error("{method} failed: {source}")]
+    Rpc {
+        method: &'static str,
+        #[source]
+        source: subxt::Error,
+    },
+    /// The node returned an extrinsic that is not valid hex
+    #[error("node returned a malformed extrinsic: {0}")]
+    MalformedExtrinsic(#[from] hex::FromHexError),
+    /// The extrinsic does not decode against the runtime metadata
+    #[error("cannot decode extrinsic: {0}")]
+    Undecodable(String),
+}
+
+fn rpc_error(method: &'static str) -> impl FnOnce(subxt::Error) -> PoolError {
+    move |source| PoolError::Rpc { method, source }
+}
+
+/// An encoded extrinsic as held by the pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingExtrinsic {
+    pub bytes: Vec<u8>,
+}
+
+impl PendingExtrinsic {
+    /// Parse the `0x`-prefixed hex form returned by the node
+    pub fn from_hex(hex_str: &str) -> Result<Self, PoolError> {
+        Ok(Self {
+            bytes: hex::decode(hex_str.trim_start_matches("0x"))?,
+        })
+    }
+
+    /// Hash the pool knows the extrinsic by (blake2-256 of its encoding)
+    pub fn hash(&self) -> H256 {
+        extrinsic_hash(&self.bytes)
+    }
+
+    /// Short `0x1234...abcd` form of the encoding, for logs
+    pub fn preview(&self) -> String {
+        let hex = hex::encode(&self.bytes);
+        if hex.len() > 40 {
+            format!("0x{}...{}", &hex[..20], &hex[hex.len() - 16..])
+        } else {
+            format!("0x{}", hex)
+        }
+    }
+}
+
+/// What a pending extrinsic does, decoded against runtime metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedExtrinsic {
+    pub pallet: String,
+    pub call: String,
+    /// Signer (`None`: unsigned, or an address other than a plain account ID)
+    pub signer: Option<AccountId32>,
+    /// `CheckNonce` value of a signed extrinsic
+    pub nonce: Option<u64>,
+}
+
+impl std::fmt::Display for DecodedExtrinsic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.pallet, self.call)?;
+        match (&self.signer, self.nonce) {
+            (Some(signer), Some(nonce)) => write!(f, " from {} (nonce {})", signer, nonce),
+            (Some(signer), None) => write!(f, " from {}", signer),
+            (None, _) => write!(f, " (unsigned)"),
+        }
+    }
+}
+
+impl PendingExtrinsic {
+    /// Decode pallet, call, signer and nonce against `metadata` (extrinsic
+    /// format v4, the one Substrate pools hold)
+    pub fn decode(&self, metadata: &Metadata) -> Result<DecodedExtrinsic, PoolError> {
+        let undecodable = |what: &str| PoolError::Undecodable(what.to_string());
+        let cursor = &mut &self.bytes[..];
+        Compact::<u32>::decode(cursor).map_err(|_| undecodable("no length prefix"))?;
+        let version = u8::decode(cursor).map_err(|_| undecodable("no version byte"))?;
+        if version & 0x7f != 4 {
+            return Err(PoolError::Undecodable(format!(
+                "unsupported extrinsic version {}",
+                version & 0x7f
+            )));
+        }
+
+        let (mut signer, mut nonce) = (None, None);
+        if version & 0x80 != 0 {
+            let extrinsic = metadata.extrinsic();
+            signer = account_from_address(take(cursor, extrinsic.address_ty(), metadata)?);
+            take(cursor, extrinsic.signature_ty(), metadata)?;
+            for extension in extrinsic.signed_extensions() {
+                let extra = take(cursor, extension.extra_ty(), metadata)?;
+                if extension.identifier() == "CheckNonce" {
+                    nonce = Compact::<u64>::decode(&mut &extra[..]).ok().map(|n| n.0);
+                }
+            }
+        }
+
+        let [pallet_index, call_index, ..] = cursor[..] else {
+            return Err(undecodable("no call"));
+        };
+        let pallet = metadata
+            .pallet_by_index(pallet_index)
+            .ok_or_else(|| PoolError::Undecodable(format!("unknown pallet {}", pallet_index)))?;
+        let call = pallet.call_variant_by_index(call_index).ok_or_else(|| {
+            PoolError::Undecodable(format!("unknown call {} of {}", call_index, pallet.name()))
+        })?;
+        Ok(DecodedExtrinsic {
+            pallet: pallet.name().to_string(),
+            call: call.name.clone(),
+            signer,
+            nonce,
+        })
+    }
+}
+
+/// Skip a value of type `ty` at the cursor, returning its encoding
+fn take<'a>(cursor: &mut &'a [u8], ty: u32, metadata: &Metadata) -> Result<&'a [u8], PoolError> {
+    let start = *cursor;
+    decode_as_type(cursor, ty, metadata.types())
+        .map_err(|e| PoolError::Undecodable(e.to_string()))?;
+    Ok(&start[..start.len() - cursor.len()])
+}
+
+/// Account of an encoded `MultiAddress::Id` (or bare `AccountId32`) address
+fn account_from_address(address: &[u8]) -> Option<AccountId32> {
+    let id = match address {
+        [0, id @ ..] if id.len() == 32 => id,
+        id if id.len() == 32 => id,
+        _ => return None,
+    };
+    Some(AccountId32(id.try_into().ok()?))
+}
+
+/// Parse a `0x`-prefixed extrinsic hash, as printed by `pool list`
+pub fn parse_extrinsic_hash(s: &str) -> Result<H256, String> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x"))
+        .map_err(|e| format!("invalid hash '{}': {}", s, e))?;
+    if bytes.len() != 32 {
+        return Err(format!("invalid hash '{}': expected 32 bytes", s));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Hash of an encoded extrinsic, as used by the pool and block explorers
+pub fn extrinsic_hash(bytes: &[u8]) -> H256 {
+    H256(sp_crypto_hashing::blake2_256(bytes))
+}
+
+/// Every extrinsic in the node's pool, ready and future (unsafe RPC)
+pub async fn pending_extrinsics(rpc: &RpcClient) -> Result<Vec<PendingExtrinsic>, PoolError> {
+    const METHOD: &str = "author_pendingExtrinsics";
+    let encoded: Vec<String> = rpc
+        .request(METHOD, rpc_params![])
+        .await
+        .map_err(rpc_error(METHOD))?;
+    encoded
+        .iter()
+        .map(|e| PendingExtrinsic::from_hex(e))
+        .collect()
+}
+
+/// Remove extrinsics from the pool by hash (unsafe RPC); returns the hashes
+/// actually removed, which includes extrinsics depending on them
+pub async fn remove_extrinsics(rpc: &RpcClient, hashes: &[H256]) -> Result<Vec<H256>, PoolError> {
+    const METHOD: &str = "author_removeExtrinsic";
+    // Each entry is an `ExtrinsicOrHash`: {"hash": "0x..."}
+    let targets: Vec<_> = hashes
+        .iter()
+        .map(|hash| serde_json::json!({ "hash": hash }))
+        .collect();
+    rpc.request(METHOD, rpc_params![targets])
+        .await
+        .map_err(rpc_error(METHOD))
+}
+
+/// Submit already-signed extrinsic bytes (e.g. to rebroadcast them through
+/// another node); returns the hash the pool accepted them under
+pub async fn submit(rpc: &RpcClient, bytes: &[u8]) -> Result<H256, PoolError> {
+    const METHOD: &str = "author_submitExtrinsic";
+    rpc.request(METHOD, rpc_params![format!("0x{}", hex::encode(bytes))])
+        .await
+        .map_err(rpc_error(METHOD))
+}
+
+/// Next nonce of `account` (SS58), counting its transactions in the pool
+pub async fn account_next_index(rpc: &RpcClient, account: &str) -> Result<u32, PoolError> {
+    const METHOD: &str = "system_accountNextIndex";
+    rpc.request(METHOD, rpc_params![account])
+        .await
+        .map_err(rpc_error(METHOD))
+}
+
+/// How [`wait_for_nonce_change`] ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceWait {
+    /// The nonce moved from `from` to `to`: the pending tx landed
+    Changed { from: u32, to: u32 },
+    /// The nonce did not move within the allotted polls
+    TimedOut,
+}
+
+/// Poll `nonce` every `interval` until it differs from `initial`, at most
+/// `max_polls` times
+///
+/// `nonce` is a closure so callers choose where the nonce is read (pool-aware
+/// [`account_next_index`], or finalized storage); failed reads are logged
+/// and polling continues.
+pub async fn wait_for_nonce_change<F, Fut, E>(
+    initial: u32,
+    interval: Duration,
+    max_polls: u32,
+    mut nonce: F,
+) -> NonceWait
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<u32, E>>,
+    E: std::fmt::Debug,
+{
+    for poll in 0..max_polls {
+        tokio::time::sleep(interval).await;
+        match nonce().await {
+            Ok(current) if current != initial => {
+                return NonceWait::Changed {
+                    from: initial,
+                    to: current,
+                }
+            }
+            Ok(_) => {
+                if poll % 5 == 4 {
+                    tracing::info!(
+                        "Still waiting for pending tx... ({}s)",
+                        (interval * (poll + 1)).as_secs()
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("Nonce query failed: {:?}", e),
+        }
+    }
+    NonceWait::TimedOut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_extrinsic_hash_is_blake2_256() {
+        // blake2b-256 of the empty input
+        assert_eq!(
+            format!("{:?}", extrinsic_hash(&[])),
+            "0x0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+    }
+
+    #[test]
+    fn test_pending_extrinsic_from_hex() {
+        let ext = PendingExtrinsic::from_hex("0x0102").unwrap();
+        assert_eq!(ext.bytes, vec![1, 2]);
+        assert_eq!(ext.preview(), "0x0102");
+        assert!(PendingExtrinsic::from_hex("0xzz").is_err());
+
+        let long = PendingExtrinsic {
+            bytes: vec![0xab; 32],
+        };
+        assert_eq!(
+            long.preview(),
+            format!("0x{}...{}", "ab".repeat(10), "ab".repeat(8))
+        );
+    }
+
+    #[test]
+    fn test_account_from_address() {
+        let mut multi_address = vec![0];
+        multi_address.extend([7; 32]);
+        assert_eq!(
+            account_from_address(&multi_address),
+            Some(AccountId32([7; 32]))
+        );
+        assert_eq!(account_from_address(&[7; 32]), Some(AccountId32([7; 32])));
+        // MultiAddress::Index and friends carry no account ID
+        assert_eq!(account_from_address(&[1, 4]), None);
+    }
+
+    #[test]
+    fn test_parse_extrinsic_hash() {
+        let hash = extrinsic_hash(&[]);
+        assert_eq!(parse_extrinsic_hash(&format!("{:?}", hash)), Ok(hash));
+        assert!(parse_extrinsic_hash("0x1234").is_err());
+        assert!(parse_extrinsic_hash("hash").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_nonce_change() {
+        let reads = Cell::new(0);
+        let outcome = wait_for_nonce_change(7, Duration::from_millis(1), 10, || {
+            reads.set(reads.get() + 1);
+            let nonce = if reads.get() < 3 { 7 } else { 8 };
+            async move { Ok::<_, ()>(nonce) }
+        })
+        .await;
+        assert_eq!(outcome, NonceWait::Changed { from: 7, to: 8 });
+        assert_eq!(reads.get(), 3);
+
+        let outcome = wait_for_nonce_change(7, Duration::from_millis(1), 3, || async {
+            Err::<u32, _>("down")
+        })
+        .await;
+        assert_eq!(outcome, NonceWait::TimedOut);
+    }
+}
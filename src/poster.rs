@@ -0,0 +1,288 @@
+//! Progress comments on a GitHub issue or forum thread (`--post-progress`)
+//!
+//! Every interval the bot posts a short markdown summary — percent complete,
+//! ETA and throughput since the previous post — as a comment on a GitHub
+//! issue (`github:owner/repo#123` or the issue URL) or a reply in a Discourse
+//! thread such as the Polkadot forum (the topic URL). The first post goes
+//! out one interval after the run loop's first status read.
+
+use crate::eta::{self, RemainingKeys};
+use crate::heartbeat::SessionProgress;
+use crate::utils::{format_duration, MigrationStatus};
+use anyhow::{Context, Result};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use westend_migration_bot::estimate::percent_complete;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Bound on each post
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where progress comments go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostTarget {
+    /// Issue (or pull request) `number` of `repo` (`owner/name`)
+    GitHub { repo: String, number: u64 },
+    /// Topic `topic` of the Discourse forum at `base`
+    Discourse { base: String, topic: u64 },
+}
+
+impl FromStr for PostTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid progress target '{}' (expected github:owner/repo#N, a GitHub issue \
+                 URL or a forum topic URL .../t/<slug>/<id>)",
+                s
+            )
+        };
+        if let Some(rest) = s.strip_prefix("github:") {
+            let (repo, number) = rest.split_once('#').ok_or_else(invalid)?;
+            if repo.split('/').filter(|part| !part.is_empty()).count() != 2 {
+                return Err(invalid());
+            }
+            return Ok(PostTarget::GitHub {
+                repo: repo.to_string(),
+                number: number.parse().map_err(|_| invalid())?,
+            });
+        }
+        let (scheme, rest) = s.split_once("://").ok_or_else(invalid)?;
+        let parts: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+        match parts.as_slice() {
+            ["github.com", owner, name, "issues" | "pull", number] => Ok(PostTarget::GitHub {
+                repo: format!("{}/{}", owner, name),
+                number: number.parse().map_err(|_| invalid())?,
+            }),
+            // .../t/<slug>/<id>, optionally followed by a post number
+            [host, "t", _slug, topic, ..] => Ok(PostTarget::Discourse {
+                base: format!("{}://{}", scheme, host),
+                topic: topic.parse().map_err(|_| invalid())?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for PostTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostTarget::GitHub { repo, number } => write!(f, "{}#{}", repo, number),
+            PostTarget::Discourse { base, topic } => write!(f, "{}/t/{}", base, topic),
+        }
+    }
+}
+
+/// Credentials for posting: a GitHub token, or a Discourse API key and the
+/// user it posts as
+pub struct PostAuth {
+    pub token: SecretString,
+    pub username: Option<String>,
+}
+
+/// Migrated counts at one point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub at: Instant,
+    pub items: u64,
+    pub bytes: u64,
+}
+
+/// Render the comment for `current`, with throughput since `previous`
+pub fn render(
+    previous: &Sample,
+    current: &Sample,
+    top_items: u64,
+    remaining_keys: Option<u64>,
+) -> String {
+    let hours = current.at.duration_since(previous.at).as_secs_f64() / 3600.0;
+    let items_per_hour = current.items.saturating_sub(previous.items) as f64 / hours.max(1e-9);
+    let bytes_per_hour = current.bytes.saturating_sub(previous.bytes) as f64 / hours.max(1e-9);
+
+    let mut lines = Vec::new();
+    lines.push(match remaining_keys {
+        Some(remaining) => format!(
+            "**Westend state trie migration: {:.1}% complete**",
+            percent_complete(top_items, remaining)
+        ),
+        None => "**Westend state trie migration progress**".to_string(),
+    });
+    lines.push(String::new());
+    lines.push(format!(
+        "- Migrated so far: {} top-trie items, {} total",
+        top_items, current.items
+    ));
+    if let Some(remaining) = remaining_keys {
+        lines.push(format!("- Remaining: ~{} keys", remaining));
+    }
+    lines.push(format!(
+        "- Throughput over the last {}: {:.0} items/h, {:.1} KB/h",
+        format_duration(current.at.duration_since(previous.at)),
+        items_per_hour,
+        bytes_per_hour / 1024.0
+    ));
+    let eta = remaining_keys.and_then(|remaining| eta::eta(remaining, items_per_hour / 60.0));
+    lines.push(match eta {
+        Some(eta) => format!("- ETA at this rate: ~{}", format_duration(eta)),
+        None => "- ETA: unknown (no progress in this period)".to_string(),
+    });
+    lines.push(String::new());
+    lines.push(format!(
+        "_Posted automatically by westend-migrate v{}_",
+        env!("CARGO_PKG_VERSION")
+    ));
+    lines.join("\n")
+}
+
+/// Post `body` to `target`
+async fn post(
+    http: &reqwest::Client,
+    target: &PostTarget,
+    auth: &PostAuth,
+    body: &str,
+) -> Result<()> {
+    let request = match target {
+        PostTarget::GitHub { repo, number } => http
+            .post(format!(
+                "{}/repos/{}/issues/{}/comments",
+                GITHUB_API, repo, number
+            ))
+            .bearer_auth(auth.token.expose_secret())
+            .header("Accept", "application/vnd.github+json")
+            .json(&json!({ "body": body })),
+        PostTarget::Discourse { base, topic } => http
+            .post(format!("{}/posts.json", base))
+            .header("Api-Key", auth.token.expose_secret())
+            .header(
+                "Api-Username",
+                auth.username
+                    .as_deref()
+                    .context("Forum posts need --post-progress-user")?,
+            )
+            .json(&json!({ "topic_id": topic, "raw": body })),
+    };
+    request
+        .header("User-Agent", "WestendMigrationBot/0.1")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to post progress to {}", target))?;
+    Ok(())
+}
+
+/// Spawn the poster; it stops when `shutdown` is cancelled
+pub fn spawn(
+    target: PostTarget,
+    auth: PostAuth,
+    mut progress: watch::Receiver<SessionProgress>,
+    remaining_keys: RemainingKeys,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let http = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    info!(
+        "Posting progress to {} every {}",
+        target,
+        format_duration(interval)
+    );
+    Ok(tokio::spawn(async move {
+        let sample = |status: &MigrationStatus| Sample {
+            at: Instant::now(),
+            items: status.top_items + status.child_items,
+            bytes: status.size,
+        };
+        // The baseline is the first status the run loop reads
+        let mut previous = tokio::select! {
+            _ = shutdown.cancelled() => return,
+            status = progress.wait_for(|p| p.last_status.is_some()) => match status {
+                Ok(p) => sample(p.last_status.as_ref().expect("waited for")),
+                Err(_) => return,
+            },
+        };
+        let mut interval = tokio::time::interval(interval);
+        interval.tick().await; // Skip first immediate tick
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    debug!("Progress poster shutting down");
+                    break;
+                }
+                _ = interval.tick() => {}
+            }
+
+            let Some(status) = progress.borrow().last_status.clone() else {
+                continue;
+            };
+            let current = sample(&status);
+            let body = render(&previous, &current, status.top_items, remaining_keys.get());
+            match post(&http, &target, &auth, &body).await {
+                Ok(()) => info!("Posted progress to {}", target),
+                Err(e) => warn!("{:#}", e),
+            }
+            previous = current;
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let issue = PostTarget::GitHub {
+            repo: "paritytech/polkadot-sdk".to_string(),
+            number: 1234,
+        };
+        assert_eq!(
+            "github:paritytech/polkadot-sdk#1234".parse::<PostTarget>(),
+            Ok(issue.clone())
+        );
+        assert_eq!(
+            "https://github.com/paritytech/polkadot-sdk/issues/1234".parse::<PostTarget>(),
+            Ok(issue)
+        );
+        assert_eq!(
+            "https://forum.polkadot.network/t/westend-trie-migration/5678/12".parse::<PostTarget>(),
+            Ok(PostTarget::Discourse {
+                base: "https://forum.polkadot.network".to_string(),
+                topic: 5678,
+            })
+        );
+        assert!("github:polkadot-sdk#1".parse::<PostTarget>().is_err());
+        assert!("https://example.com/issues".parse::<PostTarget>().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let start = Instant::now();
+        let previous = Sample {
+            at: start,
+            items: 1800,
+            bytes: 0,
+        };
+        let current = Sample {
+            at: start + Duration::from_secs(2 * 3600),
+            items: 3000,
+            bytes: 2 * 1024 * 1024,
+        };
+        let body = render(&previous, &current, 3000, Some(600));
+        assert!(body.starts_with("**Westend state trie migration: 83.3% complete**"));
+        assert!(body.contains("600 items/h, 1024.0 KB/h"));
+        assert!(body.contains("- Remaining: ~600 keys"));
+        assert!(body.contains("ETA at this rate: ~1h 00m 00s"));
+
+        let stalled = render(&current, &current, 3000, None);
+        assert!(stalled.contains("ETA: unknown"));
+    }
+}
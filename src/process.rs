@@ -0,0 +1,231 @@
+//! Decoding `StateTrieMigration::MigrationProcess` values
+//!
+//! The pallet's `MigrationTask` carries the counters shown as
+//! [`MigrationStatus`] and, in `progress_top` / `progress_child`, the last
+//! key migrated: since the pallet walks the trie in key order, that key is
+//! also where the migration stands in the keyspace.
+
+use crate::status::MigrationStatus;
+use scale_value::{At, Value, ValueDef};
+
+/// Parse migration status from a decoded `MigrationProcess` value
+/// Checks if progress variants are named "Complete"
+pub fn parse_migration_status<T: std::fmt::Debug>(decoded: &Value<T>) -> MigrationStatus {
+    let top_complete = decoded
+        .at("progress_top")
+        .map(|v| format!("{:?}", v).contains("Complete"))
+        .unwrap_or(false);
+
+    let child_complete = decoded
+        .at("progress_child")
+        .map(|v| format!("{:?}", v).contains("Complete"))
+        .unwrap_or(false);
+
+    let size = decoded.at("size").and_then(|v| v.as_u128()).unwrap_or(0) as u64;
+    let top_items = decoded
+        .at("top_items")
+        .and_then(|v| v.as_u128())
+        .unwrap_or(0) as u64;
+    let child_items = decoded
+        .at("child_items")
+        .and_then(|v| v.as_u128())
+        .unwrap_or(0) as u64;
+
+    let last_key = |field: &str| {
+        decoded
+            .at(field)
+            .and_then(|progress| progress_last_key(progress).ok().flatten())
+            .map(|key| format!("0x{}", hex::encode(key)))
+    };
+
+    MigrationStatus {
+        top_complete,
+        child_complete,
+        size,
+        top_items,
+        child_items,
+        top_last_key: last_key("progress_top"),
+        child_last_key: last_key("progress_child"),
+    }
+}
+
+/// Position of a key in the keyspace: its first 8 bytes, big-endian, zero-padded
+pub fn key_position(key: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    let len = key.len().min(8);
+    bytes[..len].copy_from_slice(&key[..len]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Last migrated top key from a `MigrationTask` value.
+/// `Ok(None)` when the top trie hasn't been started; `Err` once it is complete.
+pub fn last_top_key<T>(task: &Value<T>) -> Result<Option<Vec<u8>>, String> {
+    let progress = task
+        .at("progress_top")
+        .ok_or_else(|| "task has no `progress_top` field".to_string())?;
+    progress_last_key(progress)
+}
+
+/// Key of a `Progress` value: `Ok(None)` for `ToStart`, the bytes for
+/// `LastKey`, `Err` for `Complete` (or anything that isn't a `Progress`)
+pub fn progress_last_key<T>(progress: &Value<T>) -> Result<Option<Vec<u8>>, String> {
+    let ValueDef::Variant(variant) = &progress.value else {
+        return Err("progress is not a Progress variant".to_string());
+    };
+    match variant.name.as_str() {
+        "ToStart" => Ok(None),
+        "LastKey" => {
+            let mut key = Vec::new();
+            for value in variant.values.values() {
+                collect_bytes(value, &mut key)?;
+            }
+            Ok(Some(key))
+        }
+        other => Err(format!("progress is {}, no keys remain", other)),
+    }
+}
+
+/// Approximate top-trie percent complete from where `LastKey` sits in the
+/// keyspace. Pallet prefixes are hashed, so keys spread roughly evenly, but a
+/// large map skews it; sampling the remaining keys (the `remaining` command)
+/// is the better figure when it matters.
+/// `None` when the task has no usable `progress_top` (e.g. already complete).
+pub fn position_percent<T>(task: &Value<T>) -> Option<f64> {
+    let key = last_top_key(task).ok()?;
+    let position = key.as_deref().map_or(0, key_position);
+    Some(position as f64 / (1u128 << 64) as f64 * 100.0)
+}
+
+/// Flatten a (possibly nested) byte sequence such as `BoundedVec<u8>`
+pub(crate) fn collect_bytes<T>(value: &Value<T>, out: &mut Vec<u8>) -> Result<(), String> {
+    match &value.value {
+        ValueDef::Composite(composite) => {
+            for value in composite.values() {
+                collect_bytes(value, out)?;
+            }
+            Ok(())
+        }
+        ValueDef::Primitive(primitive) => {
+            let byte = primitive
+                .as_u128()
+                .and_then(|b| u8::try_from(b).ok())
+                .ok_or_else(|| "`LastKey` is not a byte sequence".to_string())?;
+            out.push(byte);
+            Ok(())
+        }
+        _ => Err("`LastKey` is not a byte sequence".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale_value::Composite;
+
+    #[test]
+    fn test_key_position() {
+        assert_eq!(key_position(&[]), 0);
+        assert_eq!(key_position(&[0x80]), 1 << 63);
+        assert_eq!(key_position(&[0, 0, 0, 0, 0, 0, 0, 1, 0xff]), 1);
+    }
+
+    #[test]
+    fn test_last_top_key() {
+        let task = |progress: Value<()>| Value::named_composite([("progress_top", progress)]);
+
+        let started = task(Value::unnamed_variant("ToStart", []));
+        assert_eq!(last_top_key(&started), Ok(None));
+
+        let bytes = Value::unnamed_composite([Value::from_bytes([0x26, 0xaa, 0x39])]);
+        let ongoing = task(Value::unnamed_variant("LastKey", [bytes]));
+        assert_eq!(last_top_key(&ongoing), Ok(Some(vec![0x26, 0xaa, 0x39])));
+
+        let done = task(Value::unnamed_variant("Complete", []));
+        assert!(last_top_key(&done).is_err());
+
+        let bad = task(Value::unnamed_variant("LastKey", [Value::string("x")]));
+        assert!(last_top_key(&bad).is_err());
+        assert!(last_top_key(&Value::named_composite(Vec::<(String, _)>::new())).is_err());
+    }
+
+    #[test]
+    fn test_position_percent() {
+        let task = |progress: Value<()>| Value::named_composite([("progress_top", progress)]);
+        let last_key =
+            |key: &[u8]| task(Value::unnamed_variant("LastKey", [Value::from_bytes(key)]));
+
+        assert_eq!(
+            position_percent(&task(Value::unnamed_variant("ToStart", []))),
+            Some(0.0)
+        );
+        assert_eq!(
+            position_percent(&last_key(&[0x40; 32])).map(|p| p.round()),
+            Some(25.0)
+        );
+        assert_eq!(position_percent(&last_key(&[0x80])), Some(50.0));
+        assert_eq!(
+            position_percent(&task(Value::unnamed_variant("Complete", []))),
+            None
+        );
+    }
+
+    // ==================== Parse Migration Status Tests ====================
+
+    #[test]
+    fn test_parse_migration_status_parsing() {
+        // Construct a Value mimicking the structure on chain
+        // MigrationProcess {
+        //   progress_top: Progress::Complete,
+        //   progress_child: Progress::ToStart,
+        //   size: 100,
+        //   top_items: 10,
+        //   child_items: 20
+        // }
+        let value = Value::named_composite([
+            (
+                "progress_top",
+                Value::variant("Complete", Composite::named::<&str, _>([])),
+            ),
+            (
+                "progress_child",
+                Value::variant("ToStart", Composite::named::<&str, _>([])),
+            ),
+            ("size", Value::u128(100)),
+            ("top_items", Value::u128(10)),
+            ("child_items", Value::u128(20)),
+        ]);
+
+        let status = parse_migration_status(&value);
+
+        assert!(status.top_complete);
+        assert!(!status.child_complete);
+        assert_eq!(status.size, 100);
+        assert_eq!(status.top_items, 10);
+        assert_eq!(status.child_items, 20);
+        assert_eq!(status.top_last_key, None);
+        assert_eq!(status.child_last_key, None);
+    }
+
+    #[test]
+    fn test_parse_migration_status_last_keys() {
+        let value = Value::named_composite([
+            (
+                "progress_top",
+                Value::unnamed_variant("LastKey", [Value::from_bytes([0x3a, 0x63, 0x68])]),
+            ),
+            (
+                "progress_child",
+                Value::unnamed_variant("LastKey", [Value::from_bytes([0xbe, 0xef])]),
+            ),
+            ("size", Value::u128(0)),
+            ("top_items", Value::u128(0)),
+            ("child_items", Value::u128(0)),
+        ]);
+
+        let status = parse_migration_status(&value);
+
+        assert!(!status.top_complete);
+        assert_eq!(status.top_last_key.as_deref(), Some("0x3a6368"));
+        assert_eq!(status.child_last_key.as_deref(), Some("0xbeef"));
+    }
+}
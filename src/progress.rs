@@ -0,0 +1,105 @@
+//! Progress bar for `--runs N`
+//!
+//! Shows finalized runs out of the target with elapsed time, the average
+//! time per tx and the projected completion time, updated after each
+//! finalization. It draws on stderr and hides itself when that isn't a
+//! terminal, so piped or remote logs are unaffected.
+
+use crate::utils::format_duration;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::{Duration, Instant};
+
+/// Average time per tx and projected remaining time of a `--runs` session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunEta {
+    pub per_tx: Duration,
+    pub remaining: Duration,
+}
+
+/// Project the remaining time from the runs finalized so far (`None` before the first)
+pub fn run_eta(done: u32, target: u32, elapsed: Duration) -> Option<RunEta> {
+    if done == 0 {
+        return None;
+    }
+    let per_tx = elapsed / done;
+    Some(RunEta {
+        per_tx,
+        remaining: per_tx * target.saturating_sub(done),
+    })
+}
+
+/// Progress bar over the `--runs` target
+pub struct RunProgress {
+    bar: ProgressBar,
+    target: u32,
+    /// Runs completed before this session (restored from --state-file)
+    resumed: u32,
+    started: Instant,
+}
+
+impl RunProgress {
+    pub fn new(target: u32, resumed: u32) -> Self {
+        let bar = ProgressBar::new(target as u64);
+        bar.set_position(resumed as u64);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{bar:30.green/white} {pos}/{len} runs | elapsed {elapsed_precise} | {msg}",
+            )
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        );
+        bar.set_message("waiting for first finalization");
+        Self {
+            bar,
+            target,
+            resumed,
+            started: Instant::now(),
+        }
+    }
+
+    /// Update after a finalized tx (`done` = successful runs so far)
+    pub fn record(&self, done: u32) {
+        self.bar.set_position(done as u64);
+        // Only this session's runs say anything about the current pace
+        if let Some(eta) = run_eta(
+            done.saturating_sub(self.resumed),
+            self.target.saturating_sub(self.resumed),
+            self.started.elapsed(),
+        ) {
+            let completion = chrono::Local::now()
+                + chrono::Duration::from_std(eta.remaining).unwrap_or_default();
+            self.bar.set_message(format!(
+                "avg {}/tx | done ~{} (in {})",
+                format_duration(eta.per_tx),
+                completion.format("%H:%M"),
+                format_duration(eta.remaining)
+            ));
+        }
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_eta_before_first_run() {
+        assert_eq!(run_eta(0, 10, Duration::from_secs(30)), None);
+    }
+
+    #[test]
+    fn test_eta_from_average() {
+        let eta = run_eta(4, 10, Duration::from_secs(120)).unwrap();
+        assert_eq!(eta.per_tx, Duration::from_secs(30));
+        assert_eq!(eta.remaining, Duration::from_secs(180));
+    }
+
+    #[test]
+    fn test_eta_when_done() {
+        let eta = run_eta(10, 10, Duration::from_secs(300)).unwrap();
+        assert_eq!(eta.remaining, Duration::ZERO);
+    }
+}
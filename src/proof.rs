@@ -0,0 +1,408 @@
+//! Storage proof verification (`--verify-proofs`)
+//!
+//! Without it the bot trusts whatever `MigrationProcess` its RPC endpoint
+//! returns. With it, the endpoint must also serve a `state_getReadProof` for
+//! the key at the block the state was read at; the proof's trie nodes are
+//! walked from the header's state root down to the value, hashing each one
+//! (Blake2-256, Substrate's node codec for both trie versions), and the value
+//! they prove has to be the one the bot is about to build its witness from.
+//!
+//! The header itself still comes from the endpoint, so this catches a node
+//! serving storage that is inconsistent with its own chain, not a node
+//! serving a forged chain.
+
+use anyhow::{Context, Result};
+use parity_scale_codec::{Compact, Decode};
+use sp_crypto_hashing::{blake2_256, twox_128};
+use std::collections::HashMap;
+use subxt::backend::rpc::RpcClient;
+use subxt::rpc_params;
+use subxt::utils::H256;
+
+/// Node header kinds (`sp_trie::trie_constants`)
+const EMPTY_TRIE: u8 = 0;
+const LEAF_PREFIX_MASK: u8 = 0b01 << 6;
+const BRANCH_WITHOUT_MASK: u8 = 0b10 << 6;
+const BRANCH_WITH_MASK: u8 = 0b11 << 6;
+const ALT_HASHING_LEAF_PREFIX_MASK: u8 = 0b001 << 5;
+const ALT_HASHING_BRANCH_WITH_MASK: u8 = 0b0001 << 4;
+
+/// Children of a branch node
+const NIBBLES: usize = 16;
+
+/// Values this long or longer are stored as a separate node under trie V1
+/// (`sp_core::storage::TRIE_VALUE_NODE_THRESHOLD`)
+const VALUE_NODE_THRESHOLD: usize = 33;
+
+/// Why a proof was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProofError {
+    #[error("proof is missing trie node {0:?}")]
+    MissingNode(H256),
+    #[error("proof is missing the value with hash {0:?}")]
+    MissingValue(H256),
+    #[error("invalid trie node: {0}")]
+    InvalidNode(String),
+}
+
+/// `twox128(pallet) ++ twox128(item)`: key of a plain storage value
+pub fn storage_key(pallet: &str, item: &str) -> Vec<u8> {
+    [twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat()
+}
+
+/// Reference to a child node or a value
+enum NodeRef<'a> {
+    Hash(H256),
+    Inline(&'a [u8]),
+}
+
+/// A decoded trie node
+enum Node<'a> {
+    Empty,
+    Leaf {
+        partial: Vec<u8>,
+        value: NodeRef<'a>,
+    },
+    Branch {
+        partial: Vec<u8>,
+        value: Option<NodeRef<'a>>,
+        children: Box<[Option<NodeRef<'a>>; NIBBLES]>,
+    },
+}
+
+fn invalid(what: &str) -> ProofError {
+    ProofError::InvalidNode(what.to_string())
+}
+
+/// Nibble count from the header byte's low bits and any continuation bytes
+fn decode_size(first: u8, input: &mut &[u8], prefix_bits: u32) -> Result<usize, ProofError> {
+    let max = 255u8 >> prefix_bits;
+    let mut size = (first & max) as usize;
+    if size < max as usize {
+        return Ok(size);
+    }
+    size -= 1;
+    loop {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| invalid("truncated size"))?;
+        *input = rest;
+        if byte < 255 {
+            return Ok(size + byte as usize + 1);
+        }
+        size += 255;
+    }
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProofError> {
+    if input.len() < len {
+        return Err(invalid("truncated node"));
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head)
+}
+
+fn take_hash(input: &mut &[u8]) -> Result<H256, ProofError> {
+    Ok(H256::from_slice(take(input, 32)?))
+}
+
+/// Length-prefixed bytes
+fn take_compact<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], ProofError> {
+    let len = Compact::<u32>::decode(input)
+        .map_err(|_| invalid("bad length prefix"))?
+        .0;
+    take(input, len as usize)
+}
+
+/// The node's key nibbles: packed two per byte, an odd count padded in front
+fn take_partial(input: &mut &[u8], nibbles: usize) -> Result<Vec<u8>, ProofError> {
+    let bytes = take(input, nibbles.div_ceil(2))?;
+    if nibbles % 2 == 1 && bytes[0] & 0xf0 != 0 {
+        return Err(invalid("bad partial key padding"));
+    }
+    let all: Vec<u8> = bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    Ok(all[all.len() - nibbles..].to_vec())
+}
+
+fn decode_node(data: &[u8]) -> Result<Node<'_>, ProofError> {
+    let input = &mut &data[..];
+    let (&first, rest) = input.split_first().ok_or_else(|| invalid("empty node"))?;
+    *input = rest;
+    if first == EMPTY_TRIE {
+        return Ok(Node::Empty);
+    }
+    // (is a branch, has an inline value, has a hashed value, nibble count)
+    let (branch, inline_value, hashed_value, nibbles) = match first & (0b11 << 6) {
+        LEAF_PREFIX_MASK => (false, true, false, decode_size(first, input, 2)?),
+        BRANCH_WITH_MASK => (true, true, false, decode_size(first, input, 2)?),
+        BRANCH_WITHOUT_MASK => (true, false, false, decode_size(first, input, 2)?),
+        _ if first & (0b111 << 5) == ALT_HASHING_LEAF_PREFIX_MASK => {
+            (false, false, true, decode_size(first, input, 3)?)
+        }
+        _ if first & (0b1111 << 4) == ALT_HASHING_BRANCH_WITH_MASK => {
+            (true, false, true, decode_size(first, input, 4)?)
+        }
+        _ => return Err(invalid("unknown node header")),
+    };
+    let partial = take_partial(input, nibbles)?;
+    let bitmap = if branch {
+        let bytes = take(input, 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    } else {
+        None
+    };
+    let value = if hashed_value {
+        Some(NodeRef::Hash(take_hash(input)?))
+    } else if inline_value {
+        Some(NodeRef::Inline(take_compact(input)?))
+    } else {
+        None
+    };
+    let Some(bitmap) = bitmap else {
+        let value = value.ok_or_else(|| invalid("leaf without value"))?;
+        return Ok(Node::Leaf { partial, value });
+    };
+    let mut children: Box<[Option<NodeRef<'_>>; NIBBLES]> = Default::default();
+    for (nibble, child) in children.iter_mut().enumerate() {
+        if bitmap & (1 << nibble) == 0 {
+            continue;
+        }
+        let encoded = take_compact(input)?;
+        *child = Some(match encoded.len() {
+            32 => NodeRef::Hash(H256::from_slice(encoded)),
+            _ => NodeRef::Inline(encoded),
+        });
+    }
+    Ok(Node::Branch {
+        partial,
+        value,
+        children,
+    })
+}
+
+/// A value read through a proof, and how the trie stores it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvenValue {
+    pub value: Vec<u8>,
+    /// Stored as a separate node referenced by hash (trie V1)
+    pub hashed: bool,
+}
+
+impl ProvenValue {
+    /// Stored the V0 way: a value long enough to be hashed under V1 held
+    /// inline, i.e. not (re)written since the trie moved to V1
+    pub fn is_v0(&self) -> bool {
+        !self.hashed && self.value.len() >= VALUE_NODE_THRESHOLD
+    }
+}
+
+/// Value of `key` under `root` as proven by `proof` (`None`: proven absent).
+/// Fails when the proof does not reach the key from the root.
+pub fn verify_read(
+    root: H256,
+    proof: &[Vec<u8>],
+    key: &[u8],
+) -> Result<Option<Vec<u8>>, ProofError> {
+    Ok(read_value(root, proof, key)?.map(|proven| proven.value))
+}
+
+/// Like [`verify_read`], also telling how the value is stored
+pub fn read_value(
+    root: H256,
+    proof: &[Vec<u8>],
+    key: &[u8],
+) -> Result<Option<ProvenValue>, ProofError> {
+    let nodes: HashMap<H256, &[u8]> = proof
+        .iter()
+        .map(|node| (H256(blake2_256(node)), node.as_slice()))
+        .collect();
+    let lookup = |hash: H256| nodes.get(&hash).copied();
+    let resolve = |value: NodeRef<'_>| match value {
+        NodeRef::Inline(bytes) => Ok(ProvenValue {
+            value: bytes.to_vec(),
+            hashed: false,
+        }),
+        NodeRef::Hash(hash) => lookup(hash)
+            .map(|bytes| ProvenValue {
+                value: bytes.to_vec(),
+                hashed: true,
+            })
+            .ok_or(ProofError::MissingValue(hash)),
+    };
+
+    let nibbles: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let mut rest = nibbles.as_slice();
+    let mut data = lookup(root).ok_or(ProofError::MissingNode(root))?;
+    loop {
+        match decode_node(data)? {
+            Node::Empty => return Ok(None),
+            Node::Leaf { partial, value } => {
+                if partial != rest {
+                    return Ok(None);
+                }
+                return resolve(value).map(Some);
+            }
+            Node::Branch {
+                partial,
+                value,
+                mut children,
+            } => {
+                let Some(after) = rest.strip_prefix(partial.as_slice()) else {
+                    return Ok(None);
+                };
+                let Some((&nibble, after)) = after.split_first() else {
+                    return value.map(resolve).transpose();
+                };
+                rest = after;
+                data = match children[nibble as usize].take() {
+                    None => return Ok(None),
+                    Some(NodeRef::Inline(node)) => node,
+                    Some(NodeRef::Hash(hash)) => {
+                        lookup(hash).ok_or(ProofError::MissingNode(hash))?
+                    }
+                };
+            }
+        }
+    }
+}
+
+/// Read `key` at block `at` through a proof checked against `state_root`
+pub async fn proven_storage(
+    rpc: &RpcClient,
+    at: H256,
+    state_root: H256,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    let proof = read_proof(rpc, at, &[key.to_vec()]).await?;
+    Ok(verify_read(state_root, &proof, key)?)
+}
+
+/// Trie nodes proving `keys` at block `at` (`state_getReadProof`), unchecked
+pub async fn read_proof(rpc: &RpcClient, at: H256, keys: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+    let keys: Vec<String> = keys
+        .iter()
+        .map(|key| format!("0x{}", hex::encode(key)))
+        .collect();
+    let response: serde_json::Value = rpc
+        .request("state_getReadProof", rpc_params![keys, at])
+        .await
+        .context("state_getReadProof failed")?;
+    response["proof"]
+        .as_array()
+        .context("state_getReadProof returned no proof")?
+        .iter()
+        .map(|node| {
+            let node = node.as_str().context("proof node is not a string")?;
+            hex::decode(node.trim_start_matches("0x")).context("proof node is not hex")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_of(node: &[u8]) -> H256 {
+        H256(blake2_256(node))
+    }
+
+    #[test]
+    fn test_leaf_root() {
+        // Leaf, 4 nibbles "1234", inline value 0xaa
+        let leaf = vec![0x44, 0x12, 0x34, 0x04, 0xaa];
+        let root = root_of(&leaf);
+        let proof = vec![leaf];
+        assert_eq!(
+            verify_read(root, &proof, &[0x12, 0x34]),
+            Ok(Some(vec![0xaa]))
+        );
+        // Another key diverges in the leaf: proven absent
+        assert_eq!(verify_read(root, &proof, &[0x12, 0x35]), Ok(None));
+        // A proof for another root proves nothing
+        assert_eq!(
+            verify_read(H256::zero(), &proof, &[0x12, 0x34]),
+            Err(ProofError::MissingNode(H256::zero()))
+        );
+    }
+
+    #[test]
+    fn test_branch_with_inline_and_hashed_children() {
+        // Leaf "4" with a small value: inlined in its parent
+        let small = vec![0x41, 0x04, 0x04, 0xaa];
+        // Leaf "6" with a 40-byte value: referenced by hash
+        let mut large = vec![0x41, 0x06, 40 << 2];
+        large.extend([0xbb; 40]);
+        // Branch "12", children at nibbles 3 and 5
+        let mut branch = vec![0x82, 0x12, 0x28, 0x00];
+        branch.push((small.len() as u8) << 2);
+        branch.extend(&small);
+        branch.push(32 << 2);
+        branch.extend(blake2_256(&large));
+        let root = root_of(&branch);
+        let proof = vec![branch.clone(), large];
+
+        assert_eq!(
+            verify_read(root, &proof, &[0x12, 0x34]),
+            Ok(Some(vec![0xaa]))
+        );
+        assert_eq!(
+            verify_read(root, &proof, &[0x12, 0x56]),
+            Ok(Some(vec![0xbb; 40]))
+        );
+        // No child at nibble 7
+        assert_eq!(verify_read(root, &proof, &[0x12, 0x78]), Ok(None));
+        // The hashed child is left out of the proof
+        let partial = vec![branch];
+        assert!(matches!(
+            verify_read(root, &partial, &[0x12, 0x56]),
+            Err(ProofError::MissingNode(_))
+        ));
+    }
+
+    #[test]
+    fn test_hashed_value_leaf() {
+        // Trie V1: leaf "1234" holding the hash of its value
+        let value = vec![0xcc; 64];
+        let mut leaf = vec![ALT_HASHING_LEAF_PREFIX_MASK | 4, 0x12, 0x34];
+        leaf.extend(blake2_256(&value));
+        let root = root_of(&leaf);
+        assert_eq!(
+            verify_read(root, &[leaf.clone(), value.clone()], &[0x12, 0x34]),
+            Ok(Some(value))
+        );
+        assert!(matches!(
+            verify_read(root, &[leaf], &[0x12, 0x34]),
+            Err(ProofError::MissingValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_value_layout() {
+        let value = vec![0xdd; 40];
+        // Trie V0: the 40-byte value inline in the leaf
+        let mut v0 = vec![0x44, 0x12, 0x34, 40 << 2];
+        v0.extend(&value);
+        let proven = read_value(root_of(&v0), &[v0.clone()], &[0x12, 0x34])
+            .unwrap()
+            .unwrap();
+        assert!(!proven.hashed);
+        assert!(proven.is_v0());
+
+        // Trie V1: hashed
+        let mut v1 = vec![ALT_HASHING_LEAF_PREFIX_MASK | 4, 0x12, 0x34];
+        v1.extend(blake2_256(&value));
+        let proven = read_value(root_of(&v1), &[v1, value], &[0x12, 0x34])
+            .unwrap()
+            .unwrap();
+        assert!(proven.hashed);
+        assert!(!proven.is_v0());
+
+        // Short values are inline under both versions
+        let short = ProvenValue {
+            value: vec![0; 32],
+            hashed: false,
+        };
+        assert!(!short.is_v0());
+    }
+}
@@ -0,0 +1,125 @@
+//! Migrating through a proxy account (`--proxy-for`)
+//!
+//! Each `continue_migrate` is wrapped in `Proxy.proxy(real, None, call)`
+//! (see [`crate::pipeline::MigrationCall::via_proxy`]) and signed by one of
+//! `real`'s proxies. Preflight checks that the signer is registered in
+//! `Proxy::Proxies` without an announcement delay. The proxy call itself
+//! succeeds whatever the inner call does, so its outcome is read from
+//! `Proxy.ProxyExecuted` instead.
+
+use anyhow::{Context, Result};
+use parity_scale_codec::Decode;
+use subxt::dynamic::At;
+use subxt::ext::scale_value::{Composite, ValueDef};
+use subxt::utils::AccountId32;
+
+/// One entry of `Proxy::Proxies`
+#[derive(Debug, Clone, PartialEq, Eq, Decode)]
+pub struct ProxyDefinition {
+    pub delegate: AccountId32,
+    /// `ProxyType` variant index
+    pub proxy_type: u8,
+    /// Blocks a call must be announced in advance
+    pub delay: u32,
+}
+
+/// Decode a `Proxy::Proxies` value: the definitions and their deposit
+pub fn decode_proxies(mut bytes: &[u8]) -> Result<Vec<ProxyDefinition>> {
+    let (proxies, _deposit) = <(Vec<ProxyDefinition>, u128)>::decode(&mut bytes)
+        .context("Invalid Proxy::Proxies value")?;
+    Ok(proxies)
+}
+
+/// Whether `delegate` may dispatch for the account right away; the error
+/// says why not
+pub fn check_delegate(
+    proxies: &[ProxyDefinition],
+    delegate: &AccountId32,
+    real: &AccountId32,
+) -> Result<(), String> {
+    let ours: Vec<_> = proxies.iter().filter(|p| &p.delegate == delegate).collect();
+    if ours.is_empty() {
+        return Err(format!("{} is not a proxy of {}", delegate, real));
+    }
+    if ours.iter().all(|p| p.delay > 0) {
+        return Err(format!(
+            "{} is a time-delayed proxy of {} (calls need announcing)",
+            delegate, real
+        ));
+    }
+    Ok(())
+}
+
+/// Error of the proxied call, from the fields of `Proxy.ProxyExecuted`
+pub fn executed_error<T>(fields: &Composite<T>) -> Option<String> {
+    let result = fields.at("result").or_else(|| fields.at(0))?;
+    match &result.value {
+        ValueDef::Variant(variant) if variant.name == "Err" => Some(variant.values.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parity_scale_codec::Encode;
+    use subxt::dynamic::Value;
+
+    fn definition(delegate: u8, delay: u32) -> ProxyDefinition {
+        ProxyDefinition {
+            delegate: AccountId32([delegate; 32]),
+            proxy_type: 0,
+            delay,
+        }
+    }
+
+    #[test]
+    fn test_decode_proxies() {
+        let mut bytes = vec![
+            (AccountId32([1; 32]), 1u8, 0u32),
+            (AccountId32([2; 32]), 0, 10),
+        ]
+        .encode();
+        bytes.extend_from_slice(&1_000_000_000_000u128.encode());
+        assert_eq!(
+            decode_proxies(&bytes).unwrap(),
+            vec![
+                ProxyDefinition {
+                    delegate: AccountId32([1; 32]),
+                    proxy_type: 1,
+                    delay: 0
+                },
+                definition(2, 10)
+            ]
+        );
+        assert!(decode_proxies(&bytes[..40]).is_err());
+    }
+
+    #[test]
+    fn test_check_delegate() {
+        let real = AccountId32([9; 32]);
+        let proxies = vec![definition(1, 0), definition(2, 10)];
+        assert!(check_delegate(&proxies, &AccountId32([1; 32]), &real).is_ok());
+        let delayed = check_delegate(&proxies, &AccountId32([2; 32]), &real).unwrap_err();
+        assert!(delayed.contains("time-delayed"));
+        let missing = check_delegate(&proxies, &AccountId32([3; 32]), &real).unwrap_err();
+        assert!(missing.contains("is not a proxy"));
+    }
+
+    #[test]
+    fn test_executed_error() {
+        let ok = Composite::named([(
+            "result",
+            Value::unnamed_variant("Ok", [Value::unnamed_composite(Vec::<Value>::new())]),
+        )]);
+        assert_eq!(executed_error(&ok), None);
+
+        let error = Value::named_variant(
+            "Module",
+            [("index", Value::u128(66)), ("error", Value::u128(3))],
+        );
+        let failed = Composite::named([("result", Value::unnamed_variant("Err", [error]))]);
+        let message = executed_error(&failed).unwrap();
+        assert!(message.contains("Module"));
+    }
+}
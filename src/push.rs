@@ -0,0 +1,160 @@
+//! WebSocket push of bot events
+//!
+//! With `--ws-addr`, every [`EventRecord`] the run loop emits (status, tx
+//! submitted / in block / finalized / failed, progress, stop) is sent to all
+//! connected WebSocket clients as the same JSON object written to the event
+//! log, so a dashboard can subscribe live instead of scraping logs.
+//!
+//! Clients only receive events; anything they send other than a close frame
+//! is ignored. A client that falls too far behind skips the missed events.
+
+use crate::events::EventRecord;
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+/// Events buffered per client before a slow one starts skipping
+const CLIENT_BUFFER: usize = 256;
+
+/// Fan-out of serialized events to connected clients
+#[derive(Clone)]
+pub struct EventHub {
+    tx: broadcast::Sender<String>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(CLIENT_BUFFER);
+        Self { tx }
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `record` to every connected client (a no-op without clients)
+    pub fn publish(&self, record: &EventRecord) {
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+        match serde_json::to_string(record) {
+            Ok(json) => {
+                let _ = self.tx.send(json);
+            }
+            Err(e) => debug!("Failed to serialize pushed event: {}", e),
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.tx.subscribe()
+    }
+}
+
+/// Bind `addr` and push events from `hub` to WebSocket clients until `shutdown`
+pub async fn serve(addr: SocketAddr, hub: EventHub, shutdown: CancellationToken) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind WebSocket server on {}", addr))?;
+    info!("Pushing events on ws://{}", addr);
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        debug!("WebSocket accept failed: {}", e);
+                        continue;
+                    }
+                },
+            };
+
+            let mut events = hub.subscribe();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let ws = match tokio_tungstenite::accept_async(stream).await {
+                    Ok(ws) => ws,
+                    Err(e) => {
+                        debug!("WebSocket handshake with {} failed: {}", peer, e);
+                        return;
+                    }
+                };
+                debug!("WebSocket client {} connected", peer);
+                let (mut write, mut read) = ws.split();
+
+                loop {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => {
+                            let _ = write.send(Message::Close(None)).await;
+                            break;
+                        }
+                        event = events.recv() => match event {
+                            Ok(json) => {
+                                if write.send(Message::Text(json)).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                debug!("WebSocket client {} skipped {} events", peer, skipped);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        },
+                        incoming = read.next() => match incoming {
+                            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                            Some(Ok(_)) => {}
+                        },
+                    }
+                }
+                debug!("WebSocket client {} disconnected", peer);
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::BotEvent;
+    use chrono::DateTime;
+
+    fn record() -> EventRecord {
+        EventRecord {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            event: BotEvent::TxInBlock {
+                tx_hash: "0xab".to_string(),
+                block_hash: "0xcd".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_publish_without_clients() {
+        EventHub::new().publish(&record());
+    }
+
+    #[test]
+    fn test_clients_receive_event_json() {
+        let hub = EventHub::new();
+        let mut first = hub.subscribe();
+        let mut second = hub.subscribe();
+        hub.publish(&record());
+
+        for rx in [&mut first, &mut second] {
+            let json = rx.try_recv().unwrap();
+            assert!(json.contains(r#""event":"tx_in_block""#));
+            assert_eq!(
+                serde_json::from_str::<EventRecord>(&json).unwrap(),
+                record()
+            );
+        }
+    }
+}
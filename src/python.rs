@@ -0,0 +1,124 @@
+//! Python bindings for [`MigrationClient`] (`--features python`)
+//!
+//! Built as an extension module with maturin (`maturin develop --release`,
+//! see `pyproject.toml`), so operational scripts can read the status and
+//! submit single migrations without shelling out to the binary:
+//!
+//! ```python
+//! import os
+//! from westend_migration_bot import MigrationClient
+//!
+//! client = MigrationClient("wss://westend-rpc.polkadot.io:443")
+//! print(client.status()["top_items"], client.estimate())
+//! block = client.submit_one(os.environ["SIGNER_SEED"], 51200, 1024)
+//! ```
+//!
+//! Calls block (with the GIL released) on a runtime owned by the client.
+//! Statuses come back as dicts with the same fields as the bot's JSON
+//! outputs; errors are raised as `RuntimeError`.
+
+use crate::client::{ClientError, MigrationClient};
+use crate::limits::MigrationLimits;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use secrecy::ExposeSecret;
+use std::str::FromStr;
+use subxt_signer::sr25519::Keypair;
+use subxt_signer::SecretUri;
+
+fn client_error(e: ClientError) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// Hex seed, mnemonic or SURI
+fn keypair(seed: &str) -> PyResult<Keypair> {
+    let uri = SecretUri::from_str(seed)
+        .map_err(|e| PyValueError::new_err(format!("Invalid seed: {:?}", e)))?;
+    if uri.phrase.expose_secret().is_empty() && !uri.junctions.is_empty() {
+        // `//Alice` would otherwise sign with the public dev phrase
+        return Err(PyValueError::new_err(
+            "Derivation path without a mnemonic or seed",
+        ));
+    }
+    Keypair::from_uri(&uri).map_err(|e| PyValueError::new_err(format!("Invalid seed: {:?}", e)))
+}
+
+#[pyclass(name = "MigrationClient", module = "westend_migration_bot")]
+struct PyMigrationClient {
+    client: MigrationClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[pymethods]
+impl PyMigrationClient {
+    #[new]
+    fn new(py: Python<'_>, url: &str) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let client = py
+            .allow_threads(|| runtime.block_on(MigrationClient::connect(url)))
+            .map_err(client_error)?;
+        Ok(Self { client, runtime })
+    }
+
+    /// `MigrationProcess` as a dict (`top_items`, `size`, `top_complete`, ...)
+    fn status(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let status = py
+            .allow_threads(|| self.runtime.block_on(self.client.status()))
+            .map_err(client_error)?;
+        let json =
+            serde_json::to_string(&status).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(py
+            .import_bound("json")?
+            .call_method1("loads", (json,))?
+            .unbind())
+    }
+
+    /// Rough percent of the top trie migrated
+    fn estimate(&self, py: Python<'_>) -> PyResult<f64> {
+        py.allow_threads(|| self.runtime.block_on(self.client.estimate()))
+            .map_err(client_error)
+    }
+
+    /// `SignedMigrationMaxLimits` as `(size, item)`, or `None`
+    fn max_limits(&self, py: Python<'_>) -> PyResult<Option<(u32, u32)>> {
+        let max = py
+            .allow_threads(|| self.runtime.block_on(self.client.max_limits()))
+            .map_err(client_error)?;
+        Ok(max.map(|max| (max.size, max.item)))
+    }
+
+    /// Submit one `continue_migrate` signed with `seed` and wait for it to
+    /// finalize; returns the block hash (0x hex). A zero limit means half
+    /// the chain maximum, as in the bot.
+    #[pyo3(signature = (seed, size_limit = 0, item_limit = 0))]
+    fn submit_one(
+        &self,
+        py: Python<'_>,
+        seed: &str,
+        size_limit: u32,
+        item_limit: u32,
+    ) -> PyResult<String> {
+        let signer = keypair(seed)?;
+        let block = py
+            .allow_threads(|| {
+                self.runtime.block_on(async {
+                    let limits = MigrationLimits::working(
+                        MigrationLimits::new(size_limit, item_limit),
+                        self.client.max_limits().await?,
+                    );
+                    self.client.submit_one(&signer, limits).await
+                })
+            })
+            .map_err(client_error)?;
+        Ok(format!("{:?}", block))
+    }
+}
+
+#[pymodule]
+fn westend_migration_bot(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyMigrationClient>()?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
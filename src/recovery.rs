@@ -0,0 +1,169 @@
+//! Recovery of transactions left in flight by a previous run
+//!
+//! Every tx is journaled (`--journal-db`) with its hash and nonce before it
+//! is broadcast. A row still `pending` at startup means the bot stopped
+//! between broadcast and outcome; submitting right away would race that tx
+//! for the same nonce and earn a pool conflict. Instead each one is looked up
+//! in recent finalized blocks and in the node's pool: an included tx is
+//! adopted (journaled as finalized), a pooled one is waited for, and only a
+//! dropped one leaves the nonce free for a new submission.
+
+use crate::journal::JournalEntry;
+use anyhow::Result;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use westend_migration_bot::pool;
+
+/// Finalized blocks searched for an in-flight tx (about 10 minutes)
+pub const LOOKBACK_BLOCKS: u32 = 100;
+
+/// Where a journaled in-flight tx turned out to be
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InFlight {
+    /// Found in a finalized block
+    Included {
+        block_hash: H256,
+        number: u32,
+        /// `StateTrieMigration` events of the extrinsic
+        events: Vec<String>,
+    },
+    /// Still in the node's pool
+    Pooled,
+    /// Not found, but its nonce is used: included before the searched blocks,
+    /// or replaced by another tx from the same account
+    NonceUsed,
+    /// Not found and its nonce is free: dropped, safe to submit anew
+    Dropped,
+}
+
+/// Decide from what was (not) found
+pub fn classify(
+    included: Option<(H256, u32, Vec<String>)>,
+    pooled: bool,
+    tx_nonce: u32,
+    finalized_nonce: u32,
+) -> InFlight {
+    match included {
+        Some((block_hash, number, events)) => InFlight::Included {
+            block_hash,
+            number,
+            events,
+        },
+        None if pooled => InFlight::Pooled,
+        None if finalized_nonce > tx_nonce => InFlight::NonceUsed,
+        None => InFlight::Dropped,
+    }
+}
+
+/// The newest in-flight tx per account and nonce, newest nonce first; older
+/// rows for the same nonce were superseded by a re-sign
+pub fn in_flight(entries: &[JournalEntry]) -> Vec<&JournalEntry> {
+    let mut pending: Vec<&JournalEntry> =
+        entries.iter().filter(|e| e.outcome == "pending").collect();
+    pending.sort_by(|a, b| {
+        (b.nonce, &b.account, b.submitted_at).cmp(&(a.nonce, &a.account, a.submitted_at))
+    });
+    pending.dedup_by(|a, b| (a.nonce, &a.account) == (b.nonce, &b.account));
+    pending
+}
+
+/// Search the last `lookback` finalized blocks for `tx_hash`
+pub async fn find_in_recent_blocks(
+    client: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    tx_hash: H256,
+    lookback: u32,
+) -> Result<Option<(H256, u32, Vec<String>)>> {
+    let mut hash = rpc.chain_get_finalized_head().await?;
+    for _ in 0..lookback {
+        let block = client.blocks().at(hash).await?;
+        for ext in block.extrinsics().await?.iter() {
+            let ext = ext?;
+            if pool::extrinsic_hash(ext.bytes()) != tx_hash {
+                continue;
+            }
+            let mut events = Vec::new();
+            for event in ext.events().await?.iter() {
+                let event = event?;
+                if event.pallet_name() == "StateTrieMigration" {
+                    events.push(event.variant_name().to_string());
+                }
+            }
+            return Ok(Some((hash, block.number(), events)));
+        }
+        if block.number() == 0 {
+            break;
+        }
+        hash = block.header().parent_hash;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tx_hash: &str, nonce: u32, submitted_at: i64, outcome: &str) -> JournalEntry {
+        JournalEntry {
+            tx_hash: tx_hash.to_string(),
+            submitted_at,
+            updated_at: submitted_at,
+            account: None,
+            nonce,
+            limits: None,
+            attempts: 1,
+            outcome: outcome.to_string(),
+            finished_at: None,
+            block_hash: None,
+            events: Vec::new(),
+            error: None,
+            fee: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let block = H256::repeat_byte(1);
+        assert_eq!(
+            classify(Some((block, 10, vec!["Migrated".into()])), true, 7, 8),
+            InFlight::Included {
+                block_hash: block,
+                number: 10,
+                events: vec!["Migrated".into()]
+            }
+        );
+        assert_eq!(classify(None, true, 7, 7), InFlight::Pooled);
+        assert_eq!(classify(None, false, 7, 8), InFlight::NonceUsed);
+        assert_eq!(classify(None, false, 7, 7), InFlight::Dropped);
+    }
+
+    #[test]
+    fn test_in_flight_keeps_newest_per_nonce() {
+        let entries = vec![
+            entry("0x01", 7, 100, "pending"),
+            entry("0x02", 7, 110, "pending"),
+            entry("0x03", 8, 120, "failed"),
+            entry("0x04", 9, 130, "pending"),
+            entry("0x05", 6, 90, "finalized"),
+        ];
+        let hashes: Vec<_> = in_flight(&entries)
+            .iter()
+            .map(|e| e.tx_hash.as_str())
+            .collect();
+        assert_eq!(hashes, vec!["0x04", "0x02"]);
+    }
+
+    #[test]
+    fn test_in_flight_per_account() {
+        let mut other = entry("0x02", 7, 90, "pending");
+        other.account = Some("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string());
+        let entries = vec![entry("0x01", 7, 100, "pending"), other];
+        let hashes: Vec<_> = in_flight(&entries)
+            .iter()
+            .map(|e| e.tx_hash.as_str())
+            .collect();
+        // Same nonce, different accounts: neither supersedes the other
+        assert_eq!(hashes, vec!["0x02", "0x01"]);
+    }
+}
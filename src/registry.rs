@@ -0,0 +1,189 @@
+//! Registry of the bot instances running on this host (`ps`)
+//!
+//! Every migrating or watching instance keeps a JSON entry in
+//! [`REGISTRY_DIR`], named after its PID: identity (instance ID, chain,
+//! account, endpoint) and its latest health snapshot, refreshed every
+//! [`REFRESH_INTERVAL`]. The entry is removed on shutdown; entries left by
+//! a process that no longer exists are pruned by [`list`].
+
+use crate::health::Health;
+use crate::lock::pid_alive;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+use westend_migration_bot::BotSnapshot;
+
+pub const REGISTRY_DIR: &str = "/tmp/westend-migrate.d";
+
+/// How often a running instance rewrites its entry
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// One running instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceEntry {
+    pub pid: u32,
+    pub instance_id: String,
+    /// `run`, `dry-run` or `watch`
+    pub mode: String,
+    pub chain: String,
+    /// Signer address (`None` for watchers)
+    pub account: Option<String>,
+    pub rpc_url: String,
+    pub health_addr: Option<SocketAddr>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub snapshot: Option<BotSnapshot>,
+}
+
+impl InstanceEntry {
+    /// Migration progress for `ps`
+    pub fn progress(&self) -> String {
+        match self.snapshot.as_ref().and_then(|s| s.status.as_ref()) {
+            Some(status) if status.is_complete() => "complete".to_string(),
+            Some(status) => format!(
+                "top {} / child {} items, {} bytes",
+                status.top_items, status.child_items, status.size
+            ),
+            None => "-".to_string(),
+        }
+    }
+}
+
+fn entry_path(dir: &Path, pid: u32) -> PathBuf {
+    dir.join(format!("{}.json", pid))
+}
+
+fn write(dir: &Path, entry: &InstanceEntry) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create registry {}", dir.display()))?;
+    // Write-then-rename, so `ps` never reads a half-written entry
+    let path = entry_path(dir, entry.pid);
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(entry)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Register `entry` and keep it current from `health` until `shutdown`,
+/// then remove it
+pub fn spawn(
+    dir: &Path,
+    mut entry: InstanceEntry,
+    health: Arc<Health>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    entry.snapshot = health.snapshot();
+    write(dir, &entry)?;
+    let dir = dir.to_path_buf();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(REFRESH_INTERVAL) => {}
+                _ = shutdown.cancelled() => break,
+            }
+            entry.snapshot = health.snapshot();
+            entry.updated_at = Utc::now();
+            if let Err(e) = write(&dir, &entry) {
+                debug!("Could not refresh the instance registry: {:#}", e);
+            }
+        }
+        let _ = std::fs::remove_file(entry_path(&dir, entry.pid));
+    });
+    Ok(())
+}
+
+/// Entries of live instances, oldest first; entries of processes that are
+/// gone are removed
+pub fn list(dir: &Path) -> Result<Vec<InstanceEntry>> {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut entries = Vec::new();
+    for file in read_dir {
+        let path = file?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let entry: InstanceEntry = match std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(entry) => entry,
+            None => {
+                debug!("Skipping unreadable registry entry {}", path.display());
+                continue;
+            }
+        };
+        if pid_alive(entry.pid) {
+            entries.push(entry);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    entries.sort_by_key(|entry| entry.started_at);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use westend_migration_bot::MigrationStatus;
+
+    fn entry(pid: u32) -> InstanceEntry {
+        InstanceEntry {
+            pid,
+            instance_id: "5Grwva@0x67f9".to_string(),
+            mode: "run".to_string(),
+            chain: "Westend Asset Hub".to_string(),
+            account: Some("5Grwva".to_string()),
+            rpc_url: "ws://127.0.0.1:9944".to_string(),
+            health_addr: None,
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            snapshot: None,
+        }
+    }
+
+    #[test]
+    fn test_list_prunes_dead_instances() {
+        let dir =
+            std::env::temp_dir().join(format!("westend-migrate-registry-{}", std::process::id()));
+        write(&dir, &entry(std::process::id())).unwrap();
+        #[cfg(target_os = "linux")]
+        write(&dir, &entry(u32::MAX)).unwrap();
+        std::fs::write(dir.join("garbage.json"), "{").unwrap();
+
+        let listed = list(&dir).unwrap();
+        let dead_left = entry_path(&dir, u32::MAX).exists();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].pid, std::process::id());
+        assert!(!dead_left);
+        assert!(list(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut e = entry(1);
+        assert_eq!(e.progress(), "-");
+        let health = Health::new(Duration::from_secs(60));
+        health.update(|h| {
+            h.status = Some(MigrationStatus {
+                top_items: 1024,
+                size: 2048,
+                ..Default::default()
+            })
+        });
+        e.snapshot = health.snapshot();
+        assert_eq!(e.progress(), "top 1024 / child 0 items, 2048 bytes");
+    }
+}
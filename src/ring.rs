@@ -0,0 +1,282 @@
+//! Rotating between several signer accounts (`--rotate-seed-file`)
+//!
+//! With one account, a banned tx, a tx stuck in the pool or a balance too
+//! low for the deposit stalls the bot until it clears. With more, the
+//! account is set aside ("benched") for a while and the next one that isn't
+//! signs from then on; only when every account is benched does the bot fall
+//! back to waiting. Nonces stay per account and are read from the chain as
+//! before: the ring only remembers the last nonce and balance seen for each
+//! account, for the log line listing them after each rotation.
+
+use crate::error::MigrationError;
+use crate::policy::{BANNED_TX_WAIT_SECS, BLOCK_TIME_SECS, PENDING_TX_TIMEOUT_ITERATIONS};
+use crate::signing::TxSigner;
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use subxt::utils::{AccountId32, MultiSignature};
+
+/// How long an account whose balance ran low is left alone (it needs topping up)
+const LOW_BALANCE_BENCH_SECS: u64 = 3600;
+
+/// Why an account was set aside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bench {
+    /// The pool temporarily banned its tx
+    Banned,
+    /// A tx of it is pending and won't clear (pool conflict, finalization timeout)
+    Stuck,
+    /// Its free balance no longer covers the deposit
+    LowBalance,
+}
+
+impl Bench {
+    /// Reason to switch accounts after a failed submission, if the failure
+    /// is tied to the signing account rather than the chain
+    pub fn of(err: &MigrationError) -> Option<Self> {
+        match err {
+            MigrationError::TxBanned => Some(Bench::Banned),
+            MigrationError::PoolConflict | MigrationError::NonceFuture => Some(Bench::Stuck),
+            MigrationError::SubmissionFailed(msg) if msg.contains("stuck") => Some(Bench::Stuck),
+            _ => None,
+        }
+    }
+
+    /// How long the account sits out
+    pub fn cooldown(self) -> Duration {
+        match self {
+            // Pool bans are a few minutes on default node settings; wait a
+            // good deal longer than the single-account retry
+            Bench::Banned => Duration::from_secs(BANNED_TX_WAIT_SECS * 10),
+            Bench::Stuck => {
+                Duration::from_secs(BLOCK_TIME_SECS * PENDING_TX_TIMEOUT_ITERATIONS as u64)
+            }
+            Bench::LowBalance => Duration::from_secs(LOW_BALANCE_BENCH_SECS),
+        }
+    }
+}
+
+impl fmt::Display for Bench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bench::Banned => "tx banned from the pool",
+            Bench::Stuck => "tx stuck in the pool",
+            Bench::LowBalance => "balance below the deposit",
+        })
+    }
+}
+
+/// Short form of an address for logs and notifications
+pub fn label(account: &AccountId32) -> String {
+    let address = account.to_string();
+    format!("{}…", &address[..8.min(address.len())])
+}
+
+#[derive(Debug, Default)]
+struct SlotState {
+    nonce: Option<u32>,
+    balance: Option<u128>,
+    benched: Option<(Bench, Instant)>,
+}
+
+impl SlotState {
+    fn available(&self, now: Instant) -> bool {
+        match self.benched {
+            Some((reason, since)) => now.duration_since(since) >= reason.cooldown(),
+            None => true,
+        }
+    }
+}
+
+struct Slot {
+    signer: Box<dyn TxSigner>,
+    account: AccountId32,
+    state: Mutex<SlotState>,
+}
+
+/// The bot's signer accounts, one of them active
+pub struct SignerRing {
+    slots: Vec<Slot>,
+    active: AtomicUsize,
+}
+
+impl SignerRing {
+    /// `signers[0]` starts out active
+    pub fn new(signers: Vec<Box<dyn TxSigner>>) -> Self {
+        assert!(!signers.is_empty(), "SignerRing needs a signer");
+        let slots = signers
+            .into_iter()
+            .map(|signer| Slot {
+                account: signer.account_id(),
+                signer,
+                state: Mutex::new(SlotState::default()),
+            })
+            .collect();
+        Self {
+            slots,
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether there is another account to rotate to
+    pub fn rotates(&self) -> bool {
+        self.slots.len() > 1
+    }
+
+    /// All accounts, in rotation order
+    pub fn accounts(&self) -> impl Iterator<Item = &AccountId32> {
+        self.slots.iter().map(|slot| &slot.account)
+    }
+
+    fn active(&self) -> &Slot {
+        &self.slots[self.active.load(Ordering::Relaxed)]
+    }
+
+    fn state(&self, index: usize) -> std::sync::MutexGuard<'_, SlotState> {
+        self.slots[index]
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Label of the active account
+    pub fn label(&self) -> String {
+        label(&self.active().account)
+    }
+
+    /// Remember the active account's nonce
+    pub fn record_nonce(&self, nonce: u32) {
+        self.state(self.active.load(Ordering::Relaxed)).nonce = Some(nonce);
+    }
+
+    /// Remember the active account's free balance
+    pub fn record_balance(&self, balance: u128) {
+        self.state(self.active.load(Ordering::Relaxed)).balance = Some(balance);
+    }
+
+    /// Set the active account aside and switch to the next one that isn't;
+    /// the account switched to, or `None` when all are benched (the active
+    /// one then stays active)
+    pub fn bench(&self, reason: Bench, now: Instant) -> Option<&AccountId32> {
+        let current = self.active.load(Ordering::Relaxed);
+        self.state(current).benched = Some((reason, now));
+        let next = (1..self.slots.len())
+            .map(|offset| (current + offset) % self.slots.len())
+            .find(|&index| self.state(index).available(now))?;
+        self.state(next).benched = None;
+        self.active.store(next, Ordering::Relaxed);
+        Some(&self.slots[next].account)
+    }
+
+    /// One line per account: address, last nonce and balance, and whether
+    /// it is active or benched
+    pub fn summary(&self, now: Instant) -> Vec<String> {
+        let active = self.active.load(Ordering::Relaxed);
+        self.slots
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| {
+                let state = self.state(index);
+                let mut line = slot.account.to_string();
+                if let Some(nonce) = state.nonce {
+                    line.push_str(&format!(" nonce {}", nonce));
+                }
+                if let Some(balance) = state.balance {
+                    line.push_str(&format!(" balance {}", balance));
+                }
+                if index == active {
+                    line.push_str(" [active]");
+                } else if let (false, Some((reason, _))) = (state.available(now), state.benched) {
+                    line.push_str(&format!(" [benched: {}]", reason));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// Signs as the active account
+impl TxSigner for SignerRing {
+    fn account_id(&self) -> AccountId32 {
+        self.active().account.clone()
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        self.active().signer.sign(payload)
+    }
+
+    fn confirms_on_device(&self) -> bool {
+        self.active().signer.confirms_on_device()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt_signer::sr25519::dev;
+
+    fn ring() -> SignerRing {
+        SignerRing::new(vec![
+            Box::new(dev::alice()),
+            Box::new(dev::bob()),
+            Box::new(dev::charlie()),
+        ])
+    }
+
+    #[test]
+    fn test_bench_of() {
+        assert_eq!(Bench::of(&MigrationError::TxBanned), Some(Bench::Banned));
+        assert_eq!(Bench::of(&MigrationError::PoolConflict), Some(Bench::Stuck));
+        assert_eq!(
+            Bench::of(&MigrationError::SubmissionFailed(
+                "Finalization timeout - TX may be stuck".to_string()
+            )),
+            Some(Bench::Stuck)
+        );
+        assert_eq!(Bench::of(&MigrationError::SizeExceeded), None);
+        assert_eq!(Bench::of(&MigrationError::NonceStale), None);
+    }
+
+    #[test]
+    fn test_rotates_past_benched_accounts() {
+        let ring = ring();
+        let alice = TxSigner::account_id(&dev::alice());
+        let bob = TxSigner::account_id(&dev::bob());
+        let charlie = TxSigner::account_id(&dev::charlie());
+        let now = Instant::now();
+        assert_eq!(ring.account_id(), alice);
+
+        assert_eq!(ring.bench(Bench::Banned, now), Some(&bob));
+        assert_eq!(ring.bench(Bench::LowBalance, now), Some(&charlie));
+        // Alice and Bob are still out: Charlie stays on
+        assert_eq!(ring.bench(Bench::Stuck, now), None);
+        assert_eq!(ring.account_id(), charlie);
+
+        // Alice's ban has expired by now, Bob's low balance hasn't
+        let later = now + Bench::Banned.cooldown();
+        assert_eq!(ring.bench(Bench::Stuck, later), Some(&alice));
+        let summary = ring.summary(later);
+        assert!(summary[0].ends_with("[active]"));
+        assert!(summary[1].ends_with("[benched: balance below the deposit]"));
+    }
+
+    #[test]
+    fn test_single_account_never_rotates() {
+        let ring = SignerRing::new(vec![Box::new(dev::alice())]);
+        assert!(!ring.rotates());
+        assert_eq!(ring.bench(Bench::Banned, Instant::now()), None);
+        ring.record_nonce(3);
+        ring.record_balance(10);
+        let summary = ring.summary(Instant::now());
+        assert!(summary[0].ends_with(" nonce 3 balance 10 [active]"));
+    }
+
+    #[test]
+    fn test_label() {
+        let alice = TxSigner::account_id(&dev::alice());
+        assert_eq!(label(&alice), "5GrwvaEF…");
+    }
+}
@@ -0,0 +1,194 @@
+//! Per-submission risk score
+//!
+//! A signed migration that fails dispatch slashes the deposit, so before each
+//! submission the bot scores how much could go wrong (0 = nothing we can see,
+//! 100 = as bad as it gets) from the factors below. With `--max-risk` set,
+//! submissions scoring above it are skipped and flagged instead of sent; this
+//! is a finer dial than turning the dry run on or off.
+//!
+//! | Factor | Points |
+//! |--------|--------|
+//! | No dry-run validation | 40 |
+//! | Witness older than one block | 5 per block, up to 20 |
+//! | Limits above half the chain max | up to 20 (at the max) |
+//! | Balance doesn't cover the deposit twice / once | 10 / 20 (10 if unknown) |
+
+use crate::limits::MigrationLimits;
+use std::fmt;
+use std::time::Duration;
+
+/// Block time used to express witness age in blocks
+const BLOCK_TIME: Duration = Duration::from_secs(6);
+
+/// Everything the score is computed from
+#[derive(Debug, Clone, Copy)]
+pub struct RiskInputs {
+    pub validated: bool,
+    /// Time between reading `MigrationProcess` and submitting
+    pub witness_age: Duration,
+    pub limits: MigrationLimits,
+    /// Allowed by `SignedMigrationMaxLimits`
+    pub max_limits: MigrationLimits,
+    /// Deposit at stake for this tx, if the pallet constants could be read
+    pub deposit: Option<u128>,
+    pub balance: u128,
+}
+
+/// A score with the factors that contributed to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskAssessment {
+    pub score: u32,
+    pub factors: Vec<(&'static str, u32)>,
+}
+
+impl fmt::Display for RiskAssessment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/100", self.score)?;
+        if !self.factors.is_empty() {
+            let factors: Vec<_> = self
+                .factors
+                .iter()
+                .map(|(name, points)| format!("{} +{}", name, points))
+                .collect();
+            write!(f, " ({})", factors.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Share of the limit above half the max, scaled to 0..=1
+fn limit_pressure(limit: u32, max: u32) -> f64 {
+    if max == 0 {
+        return 1.0;
+    }
+    ((limit as f64 / max as f64 - 0.5) * 2.0).clamp(0.0, 1.0)
+}
+
+/// Score a submission from its inputs
+pub fn assess(inputs: &RiskInputs) -> RiskAssessment {
+    let mut factors = Vec::new();
+
+    if !inputs.validated {
+        factors.push(("no dry run", 40));
+    }
+
+    let blocks_old = (inputs.witness_age.as_secs() / BLOCK_TIME.as_secs()) as u32;
+    if blocks_old > 0 {
+        factors.push(("stale witness", (blocks_old * 5).min(20)));
+    }
+
+    let (limits, max) = (inputs.limits, inputs.max_limits);
+    let pressure = limit_pressure(limits.item, max.item).max(limit_pressure(limits.size, max.size));
+    let points = (pressure * 20.0).round() as u32;
+    if points > 0 {
+        factors.push(("limits near max", points));
+    }
+
+    match inputs.deposit {
+        Some(deposit) if inputs.balance < deposit => factors.push(("deposit not covered", 20)),
+        Some(deposit) if inputs.balance < deposit.saturating_mul(2) => {
+            factors.push(("deposit barely covered", 10))
+        }
+        Some(_) => {}
+        None => factors.push(("deposit unknown", 10)),
+    }
+
+    RiskAssessment {
+        score: factors
+            .iter()
+            .map(|(_, points)| points)
+            .sum::<u32>()
+            .min(100),
+        factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn safe() -> RiskInputs {
+        RiskInputs {
+            validated: true,
+            witness_age: Duration::from_secs(2),
+            limits: MigrationLimits::new(51_200, 1024),
+            max_limits: MigrationLimits::new(102_400, 2048),
+            deposit: Some(1_000),
+            balance: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn test_safe_submission_scores_zero() {
+        let risk = assess(&safe());
+        assert_eq!(risk.score, 0);
+        assert_eq!(risk.to_string(), "0/100");
+    }
+
+    #[test]
+    fn test_missing_validation() {
+        let risk = assess(&RiskInputs {
+            validated: false,
+            ..safe()
+        });
+        assert_eq!(risk.score, 40);
+        assert_eq!(risk.to_string(), "40/100 (no dry run +40)");
+    }
+
+    #[test]
+    fn test_stale_witness_is_capped() {
+        let risk = assess(&RiskInputs {
+            witness_age: Duration::from_secs(13),
+            ..safe()
+        });
+        assert_eq!(risk.score, 10);
+        let risk = assess(&RiskInputs {
+            witness_age: Duration::from_secs(600),
+            ..safe()
+        });
+        assert_eq!(risk.score, 20);
+    }
+
+    #[test]
+    fn test_limits_at_max() {
+        let risk = assess(&RiskInputs {
+            limits: MigrationLimits::new(51_200, 2048),
+            ..safe()
+        });
+        assert_eq!(risk.score, 20);
+        let risk = assess(&RiskInputs {
+            limits: MigrationLimits::new(76_800, 1024),
+            ..safe()
+        });
+        assert_eq!(risk.score, 10);
+    }
+
+    #[test]
+    fn test_deposit_coverage() {
+        let score = |balance, deposit| {
+            assess(&RiskInputs {
+                balance,
+                deposit,
+                ..safe()
+            })
+            .score
+        };
+        assert_eq!(score(500, Some(1_000)), 20);
+        assert_eq!(score(1_500, Some(1_000)), 10);
+        assert_eq!(score(2_000, Some(1_000)), 0);
+        assert_eq!(score(2_000, None), 10);
+    }
+
+    #[test]
+    fn test_score_is_capped() {
+        let risk = assess(&RiskInputs {
+            validated: false,
+            witness_age: Duration::from_secs(600),
+            limits: MigrationLimits::new(204_800, 4096),
+            max_limits: MigrationLimits::new(102_400, 2048),
+            deposit: Some(1_000),
+            balance: 0,
+        });
+        assert_eq!(risk.score, 100);
+    }
+}
@@ -0,0 +1,69 @@
+//! Decoding raw storage and events with the runtime's metadata
+//!
+//! For consumers that read the chain themselves — a browser dashboard doing
+//! `state_getStorage` and `state_getMetadata` over its own connection — and
+//! only need the decoding: [`RuntimeDecoder`] turns the SCALE bytes of
+//! `MigrationProcess` and `System.Events` into the same [`MigrationStatus`],
+//! keyspace position and [`MigrationEvent`]s the bot works from. Builds for
+//! `wasm32-unknown-unknown`; `--features wasm` exports it to JavaScript.
+//!
+//! [`MigrationEvent`]: crate::chain_events::MigrationEvent
+
+use crate::chain_events::{migration_events, BlockEvent};
+use crate::process::{parse_migration_status, position_percent};
+use crate::status::MigrationStatus;
+use parity_scale_codec::Decode;
+use scale_value::Value;
+use subxt_metadata::Metadata;
+
+/// Runtime metadata, ready to decode storage values with
+pub struct RuntimeDecoder {
+    metadata: Metadata,
+}
+
+impl RuntimeDecoder {
+    /// From SCALE-encoded metadata, as returned by `state_getMetadata`
+    pub fn new(mut metadata: &[u8]) -> Result<Self, String> {
+        let metadata =
+            Metadata::decode(&mut metadata).map_err(|e| format!("Invalid metadata: {}", e))?;
+        Ok(Self { metadata })
+    }
+
+    /// Decode the value of storage item `pallet::entry`
+    pub fn storage_value(
+        &self,
+        pallet: &str,
+        entry: &str,
+        mut bytes: &[u8],
+    ) -> Result<Value<u32>, String> {
+        let ty = self
+            .metadata
+            .pallet_by_name(pallet)
+            .and_then(|pallet| pallet.storage())
+            .and_then(|storage| storage.entry_by_name(entry))
+            .ok_or_else(|| format!("{}::{} is not in the runtime metadata", pallet, entry))?
+            .entry_type()
+            .value_ty();
+        scale_value::scale::decode_as_type(&mut bytes, ty, self.metadata.types())
+            .map_err(|e| format!("Failed to decode {}::{}: {}", pallet, entry, e))
+    }
+
+    /// [`MigrationStatus`] from the bytes of `StateTrieMigration::MigrationProcess`
+    pub fn migration_status(&self, process: &[u8]) -> Result<MigrationStatus, String> {
+        let task = self.storage_value("StateTrieMigration", "MigrationProcess", process)?;
+        Ok(parse_migration_status(&task))
+    }
+
+    /// Keyspace position of the last migrated top key, in percent (see
+    /// [`position_percent`]); `None` once the top trie is done
+    pub fn position_percent(&self, process: &[u8]) -> Result<Option<f64>, String> {
+        let task = self.storage_value("StateTrieMigration", "MigrationProcess", process)?;
+        Ok(position_percent(&task))
+    }
+
+    /// Migration events in the bytes of `System::Events`
+    pub fn migration_events(&self, events: &[u8]) -> Result<Vec<BlockEvent>, String> {
+        let events = self.storage_value("System", "Events", events)?;
+        Ok(migration_events(&events))
+    }
+}
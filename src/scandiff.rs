@@ -0,0 +1,303 @@
+//! Key-level diff of two cached scans (`scan diff`)
+//!
+//! Two `remaining --full --key-cache` scans taken some time apart list the
+//! keys still to migrate at their blocks. A key of the older scan that the
+//! newer one no longer has, at or before the newer scan's starting key, was
+//! migrated in between; one after it was deleted. Keys only the newer scan has
+//! were created since. The migrated keys are then attributed to the blocks
+//! and accounts that migrated them: every block between the two scans with a
+//! `Migrated` event has its `progress_top` read, and since the pallet walks
+//! the trie in key order, the keys up to that block's `LastKey` (not taken by
+//! an earlier block) are that block's. Several migrations in one block split
+//! its keys in event order by their `top` counts.
+
+use crate::follower::{migrated_events, MigratedEvent};
+use crate::keycache::{CachedScan, KeyRecord};
+use crate::keyspace::last_top_key;
+use anyhow::{Context, Result};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Label for keys migrated by the pallet's automatic (unsigned) migration
+pub const AUTOMATIC: &str = "(automatic)";
+
+/// What changed between two scans
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanDiff {
+    /// Keys migrated in between, in key order
+    pub migrated: Vec<KeyRecord>,
+    /// Keys removed from the unmigrated part of the trie
+    pub deleted: u64,
+    /// Keys added to the unmigrated part of the trie
+    pub created: u64,
+}
+
+impl ScanDiff {
+    /// Value bytes of the migrated keys whose size the older scan read
+    pub fn migrated_bytes(&self) -> u64 {
+        self.migrated
+            .iter()
+            .filter_map(|record| record.size)
+            .map(u64::from)
+            .sum()
+    }
+}
+
+/// Merge the keys of an `old` and a `new` scan (both in key order); `boundary`
+/// is the key the new scan started after
+pub fn diff(
+    mut old: impl Iterator<Item = Result<KeyRecord>>,
+    mut new: impl Iterator<Item = Result<KeyRecord>>,
+    boundary: Option<&[u8]>,
+) -> Result<ScanDiff> {
+    let mut result = ScanDiff::default();
+    let mut a = old.next().transpose()?;
+    let mut b = new.next().transpose()?;
+    loop {
+        let order = match (&a, &b) {
+            (Some(a), Some(b)) => a.key.cmp(&b.key),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+        match order {
+            Ordering::Equal => {
+                a = old.next().transpose()?;
+                b = new.next().transpose()?;
+            }
+            Ordering::Less => {
+                let record = a.take().expect("compared above");
+                if boundary.is_some_and(|boundary| record.key.as_slice() <= boundary) {
+                    result.migrated.push(record);
+                } else {
+                    result.deleted += 1;
+                }
+                a = old.next().transpose()?;
+            }
+            Ordering::Greater => {
+                result.created += 1;
+                b = new.next().transpose()?;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Diff two cached scans, oldest first
+pub fn diff_scans(old: CachedScan, new: CachedScan) -> Result<ScanDiff> {
+    anyhow::ensure!(
+        old.block != new.block,
+        "Both scans are of block {:?}",
+        old.block
+    );
+    anyhow::ensure!(
+        new.from >= old.from,
+        "The second scan starts before the first: pass the older scan first"
+    );
+    let boundary = new.from.clone();
+    diff(old, new, boundary.as_deref())
+}
+
+/// A block that migrated top keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigratingBlock {
+    pub number: u32,
+    /// `progress_top`'s `LastKey` after the block; `None` once the top trie is complete
+    pub last_key: Option<Vec<u8>>,
+    pub events: Vec<MigratedEvent>,
+}
+
+/// Block a key was migrated in, and by whom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribution {
+    pub block: u32,
+    /// Signer, or [`AUTOMATIC`]
+    pub account: String,
+}
+
+/// Attribute each of `migrated` (in key order) to one of `blocks` (in block
+/// order); `None` for keys past the last block's `LastKey`
+pub fn attribute(migrated: &[KeyRecord], blocks: &[MigratingBlock]) -> Vec<Option<Attribution>> {
+    let mut attributions = Vec::with_capacity(migrated.len());
+    let mut next = 0;
+    for block in blocks {
+        let end = match &block.last_key {
+            Some(last_key) => {
+                next + migrated[next..].partition_point(|record| record.key <= *last_key)
+            }
+            None => migrated.len(),
+        };
+        let mut left = end - next;
+        for (index, event) in block.events.iter().enumerate() {
+            // The last migration of the block takes what the counts leave over
+            let count = if index + 1 == block.events.len() {
+                left
+            } else {
+                (event.top as usize).min(left)
+            };
+            let account = event.signer.as_deref().unwrap_or(AUTOMATIC);
+            for _ in 0..count {
+                attributions.push(Some(Attribution {
+                    block: block.number,
+                    account: account.to_string(),
+                }));
+            }
+            left -= count;
+        }
+        attributions.extend(std::iter::repeat_n(None, left));
+        next = end;
+    }
+    attributions.extend(std::iter::repeat_n(None, migrated.len() - next));
+    attributions
+}
+
+/// Keys and bytes migrated by one account
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountShare {
+    pub keys: u64,
+    pub bytes: u64,
+}
+
+/// Migrated keys and bytes per account (unattributed keys under `None`)
+pub fn by_account(
+    migrated: &[KeyRecord],
+    attributions: &[Option<Attribution>],
+) -> BTreeMap<Option<String>, AccountShare> {
+    let mut shares: BTreeMap<Option<String>, AccountShare> = BTreeMap::new();
+    for (record, attribution) in migrated.iter().zip(attributions) {
+        let share = shares
+            .entry(attribution.as_ref().map(|a| a.account.clone()))
+            .or_default();
+        share.keys += 1;
+        share.bytes += record.size.map_or(0, u64::from);
+    }
+    shares
+}
+
+/// Number of the block with `hash`
+pub async fn block_number(rpc: &LegacyRpcMethods<PolkadotConfig>, hash: H256) -> Result<u32> {
+    let header = rpc
+        .chain_get_header(Some(hash))
+        .await?
+        .with_context(|| format!("Block {:?} not found (pruned? use an archive node)", hash))?;
+    Ok(header.number)
+}
+
+/// Every block after `from` up to and including `to` that migrated keys
+pub async fn migrating_blocks(
+    client: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    from: u32,
+    to: u32,
+) -> Result<Vec<MigratingBlock>> {
+    let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+    let mut blocks = Vec::new();
+    for number in from + 1..=to {
+        let hash = rpc
+            .chain_get_block_hash(Some(number.into()))
+            .await?
+            .with_context(|| format!("Block #{} not found", number))?;
+        let block = client.blocks().at(hash).await?;
+        let events = migrated_events(&block).await?;
+        if events.iter().all(|event| event.top == 0) {
+            continue;
+        }
+        let task = client
+            .storage()
+            .at(hash)
+            .fetch(&query)
+            .await?
+            .with_context(|| format!("No MigrationProcess at block #{}", number))?
+            .to_value()?;
+        blocks.push(MigratingBlock {
+            number,
+            last_key: last_top_key(&task).unwrap_or(None),
+            events,
+        });
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: u8) -> KeyRecord {
+        KeyRecord {
+            key: vec![key],
+            size: Some(10),
+            version: 0,
+        }
+    }
+
+    fn records(keys: &[u8]) -> Vec<KeyRecord> {
+        keys.iter().copied().map(record).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = records(&[0x10, 0x20, 0x30, 0x40, 0x50]);
+        // 0x10..0x30 migrated (0x30 is the new LastKey), 0x40 deleted, 0x45 created
+        let new = records(&[0x45, 0x50]);
+        let diff = diff(
+            old.into_iter().map(Ok),
+            new.into_iter().map(Ok),
+            Some(&[0x30]),
+        )
+        .unwrap();
+        assert_eq!(diff.migrated, records(&[0x10, 0x20, 0x30]));
+        assert_eq!(diff.migrated_bytes(), 30);
+        assert_eq!((diff.deleted, diff.created), (1, 1));
+    }
+
+    fn event(top: u64, signer: Option<&str>) -> MigratedEvent {
+        MigratedEvent {
+            top,
+            child: 0,
+            signer: signer.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_attribute() {
+        let migrated = records(&[0x10, 0x20, 0x30, 0x40, 0x50]);
+        let blocks = vec![
+            MigratingBlock {
+                number: 7,
+                last_key: Some(vec![0x20]),
+                events: vec![event(1024, Some("alice"))],
+            },
+            MigratingBlock {
+                number: 9,
+                last_key: Some(vec![0x45]),
+                events: vec![event(1, Some("bob")), event(1024, None)],
+            },
+        ];
+        let accounts: Vec<_> = attribute(&migrated, &blocks)
+            .into_iter()
+            .map(|a| a.map(|a| (a.block, a.account)))
+            .collect();
+        let at = |block, account: &str| Some((block, account.to_string()));
+        assert_eq!(
+            accounts,
+            vec![
+                at(7, "alice"),
+                at(7, "alice"),
+                at(9, "bob"),
+                at(9, AUTOMATIC),
+                // Past the last migrating block's LastKey
+                None,
+            ]
+        );
+
+        let shares = by_account(&migrated, &attribute(&migrated, &blocks));
+        assert_eq!(
+            shares[&Some("alice".to_string())],
+            AccountShare { keys: 2, bytes: 20 }
+        );
+        assert_eq!(shares[&None].keys, 1);
+    }
+}
@@ -0,0 +1,437 @@
+//! Signer seed from a cloud secret manager (`--seed-source`)
+//!
+//! `aws:<secret ARN>` reads the secret's `SecretString` from AWS Secrets
+//! Manager, signing the request (SigV4) with credentials from the standard
+//! `AWS_*` environment variables, the ECS task role or the EC2 instance role
+//! (IMDSv2). `gcp:projects/<project>/secrets/<name>[/versions/<version>]`
+//! reads the latest (or given) version from GCP Secret Manager with the VM's
+//! service account token from the metadata server, or
+//! `GOOGLE_OAUTH_ACCESS_TOKEN`. The seed is resolved once at startup and only
+//! ever held in a `SecretString`; response bodies are zeroized once parsed.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// EC2 instance metadata service (IMDSv2)
+const IMDS_URL: &str = "http://169.254.169.254/latest";
+
+/// ECS task metadata endpoint, for `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI`
+const ECS_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+
+/// GCE metadata server token for the VM's default service account
+const GCP_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+const GCP_SECRET_MANAGER_URL: &str = "https://secretmanager.googleapis.com/v1";
+
+/// Bound on every request, so a missing metadata server fails startup quickly
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where `--seed-source` reads the seed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedSource {
+    /// AWS Secrets Manager secret, by ARN (or by name, region from `AWS_REGION`)
+    Aws { secret_id: String },
+    /// GCP Secret Manager secret version resource name
+    Gcp { version: String },
+}
+
+impl FromStr for SeedSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, id) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid seed source '{}' (expected aws:... or gcp:...)", s))?;
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(format!("seed source '{}' names no secret", s));
+        }
+        match scheme {
+            "aws" => Ok(SeedSource::Aws {
+                secret_id: id.to_string(),
+            }),
+            "gcp" => {
+                let parts: Vec<_> = id.split('/').collect();
+                let version = match parts.as_slice() {
+                    ["projects", _, "secrets", _] => format!("{}/versions/latest", id),
+                    ["projects", _, "secrets", _, "versions", _] => id.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "invalid GCP secret '{}' (expected \
+                             projects/<project>/secrets/<name>[/versions/<version>])",
+                            id
+                        ))
+                    }
+                };
+                Ok(SeedSource::Gcp { version })
+            }
+            other => Err(format!(
+                "unknown seed source '{}' (expected aws or gcp)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedSource::Aws { secret_id } => write!(f, "AWS Secrets Manager ({})", secret_id),
+            SeedSource::Gcp { version } => write!(f, "GCP Secret Manager ({})", version),
+        }
+    }
+}
+
+impl SeedSource {
+    /// Read the seed, trimmed of surrounding whitespace
+    pub async fn fetch(&self) -> Result<SecretString> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let secret = match self {
+            SeedSource::Aws { secret_id } => aws_secret(&http, secret_id).await?,
+            SeedSource::Gcp { version } => gcp_secret(&http, version).await?,
+        };
+        let seed = secret.expose_secret().trim();
+        anyhow::ensure!(!seed.is_empty(), "{} holds an empty secret", self);
+        Ok(SecretString::new(seed.to_string()))
+    }
+}
+
+/// Region of a secret ARN (`arn:aws:secretsmanager:<region>:<account>:secret:<name>`)
+fn arn_region(secret_id: &str) -> Option<&str> {
+    let mut parts = secret_id.strip_prefix("arn:")?.split(':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(_partition), Some("secretsmanager"), Some(region)) if !region.is_empty() => {
+            Some(region)
+        }
+        _ => None,
+    }
+}
+
+/// AWS credentials, the secret key held as a secret
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: SecretString,
+    session_token: Option<SecretString>,
+}
+
+/// Role credentials as returned by IMDS and the ECS endpoint
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RoleCredentials {
+    access_key_id: String,
+    secret_access_key: SecretString,
+    token: Option<SecretString>,
+}
+
+impl From<RoleCredentials> for AwsCredentials {
+    fn from(role: RoleCredentials) -> Self {
+        Self {
+            access_key_id: role.access_key_id,
+            secret_access_key: role.secret_access_key,
+            session_token: role.token,
+        }
+    }
+}
+
+/// Credentials from the environment, the ECS task role or the instance role
+async fn aws_credentials(http: &reqwest::Client) -> Result<AwsCredentials> {
+    if let (Ok(access_key_id), Ok(secret)) = (
+        std::env::var("AWS_ACCESS_KEY_ID"),
+        std::env::var("AWS_SECRET_ACCESS_KEY"),
+    ) {
+        return Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key: SecretString::new(secret),
+            session_token: std::env::var("AWS_SESSION_TOKEN")
+                .ok()
+                .map(SecretString::new),
+        });
+    }
+    if let Ok(uri) = std::env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+        let body = Zeroizing::new(
+            http.get(format!("{}{}", ECS_CREDENTIALS_HOST, uri))
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?,
+        );
+        let role: RoleCredentials =
+            serde_json::from_str(&body).context("Invalid ECS credentials response")?;
+        return Ok(role.into());
+    }
+
+    let token = http
+        .put(format!("{}/api/token", IMDS_URL))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("No AWS credentials in the environment and no instance metadata service")?
+        .text()
+        .await?;
+    let roles_url = format!("{}/meta-data/iam/security-credentials/", IMDS_URL);
+    let roles = http
+        .get(&roles_url)
+        .header("X-aws-ec2-metadata-token", &token)
+        .send()
+        .await?
+        .error_for_status()
+        .context("The instance has no IAM role")?
+        .text()
+        .await?;
+    let role = roles
+        .lines()
+        .next()
+        .context("The instance has no IAM role")?;
+    let body = Zeroizing::new(
+        http.get(format!("{}{}", roles_url, role))
+            .header("X-aws-ec2-metadata-token", &token)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?,
+    );
+    let role: RoleCredentials =
+        serde_json::from_str(&body).context("Invalid instance role credentials")?;
+    Ok(role.into())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 signing key for `date` (`YYYYMMDD`), region and service
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let secret = Zeroizing::new(format!("AWS4{}", secret_access_key));
+    let key = hmac_sha256(secret.as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// `SecretsManager.GetSecretValue`, signed with SigV4
+async fn aws_secret(http: &reqwest::Client, secret_id: &str) -> Result<SecretString> {
+    let region = match arn_region(secret_id) {
+        Some(region) => region.to_string(),
+        None => std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .context("Set AWS_REGION, or give the secret by ARN")?,
+    };
+    let credentials = aws_credentials(http).await?;
+
+    const SERVICE: &str = "secretsmanager";
+    const TARGET: &str = "secretsmanager.GetSecretValue";
+    const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+    let host = format!("secretsmanager.{}.amazonaws.com", region);
+    let body = serde_json::json!({ "SecretId": secret_id }).to_string();
+    let now = chrono::Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    // Canonical headers, sorted by name
+    let mut headers = vec![
+        ("content-type", CONTENT_TYPE.to_string()),
+        ("host", host.clone()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.expose_secret().clone()));
+    }
+    headers.push(("x-amz-target", TARGET.to_string()));
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body.as_bytes()))
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, SERVICE);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(
+        credentials.secret_access_key.expose_secret(),
+        &date,
+        &region,
+        SERVICE,
+    );
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    );
+
+    let mut request = http
+        .post(format!("https://{}/", host))
+        .header("Content-Type", CONTENT_TYPE)
+        .header("X-Amz-Date", &amz_date)
+        .header("X-Amz-Target", TARGET)
+        .header("Authorization", authorization);
+    if let Some(token) = &credentials.session_token {
+        request = request.header("X-Amz-Security-Token", token.expose_secret());
+    }
+    let response = request.body(body).send().await?;
+    let status = response.status();
+    let text = Zeroizing::new(response.text().await?);
+    if !status.is_success() {
+        // Error bodies carry a type and message, never the secret
+        anyhow::bail!("GetSecretValue failed ({}): {}", status, text.as_str());
+    }
+
+    #[derive(Deserialize)]
+    struct SecretValue {
+        #[serde(rename = "SecretString")]
+        secret_string: Option<SecretString>,
+    }
+    let value: SecretValue =
+        serde_json::from_str(&text).context("Invalid GetSecretValue response")?;
+    value
+        .secret_string
+        .context("The secret has no SecretString (binary secrets are not supported)")
+}
+
+/// OAuth token for Secret Manager: `GOOGLE_OAUTH_ACCESS_TOKEN` or the VM's
+/// service account
+async fn gcp_token(http: &reqwest::Client) -> Result<SecretString> {
+    if let Ok(token) = std::env::var("GOOGLE_OAUTH_ACCESS_TOKEN") {
+        return Ok(SecretString::new(token));
+    }
+
+    #[derive(Deserialize)]
+    struct Token {
+        access_token: SecretString,
+    }
+    let body = Zeroizing::new(
+        http.get(GCP_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("No GOOGLE_OAUTH_ACCESS_TOKEN and no GCE metadata server")?
+            .text()
+            .await?,
+    );
+    let token: Token = serde_json::from_str(&body).context("Invalid metadata token response")?;
+    Ok(token.access_token)
+}
+
+/// `versions.access` of a GCP secret version
+async fn gcp_secret(http: &reqwest::Client, version: &str) -> Result<SecretString> {
+    let token = gcp_token(http).await?;
+    let response = http
+        .get(format!("{}/{}:access", GCP_SECRET_MANAGER_URL, version))
+        .bearer_auth(token.expose_secret())
+        .send()
+        .await?;
+    let status = response.status();
+    let text = Zeroizing::new(response.text().await?);
+    if !status.is_success() {
+        anyhow::bail!(
+            "Secret Manager access failed ({}): {}",
+            status,
+            text.as_str()
+        );
+    }
+
+    #[derive(Deserialize)]
+    struct Access {
+        payload: Payload,
+    }
+    #[derive(Deserialize)]
+    struct Payload {
+        data: SecretString,
+    }
+    let access: Access = serde_json::from_str(&text).context("Invalid Secret Manager response")?;
+    let bytes = Zeroizing::new(
+        base64::engine::general_purpose::STANDARD
+            .decode(access.payload.data.expose_secret())
+            .context("Secret payload is not base64")?,
+    );
+    let seed = std::str::from_utf8(&bytes).context("Secret payload is not UTF-8")?;
+    Ok(SecretString::new(seed.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sources() {
+        let arn = "arn:aws:secretsmanager:eu-west-1:123456789012:secret:migrate-seed-AbCdEf";
+        assert_eq!(
+            format!("aws:{}", arn).parse::<SeedSource>(),
+            Ok(SeedSource::Aws {
+                secret_id: arn.to_string()
+            })
+        );
+        assert_eq!(
+            "gcp:projects/ops/secrets/seed".parse::<SeedSource>(),
+            Ok(SeedSource::Gcp {
+                version: "projects/ops/secrets/seed/versions/latest".to_string()
+            })
+        );
+        assert_eq!(
+            "gcp:projects/ops/secrets/seed/versions/3".parse::<SeedSource>(),
+            Ok(SeedSource::Gcp {
+                version: "projects/ops/secrets/seed/versions/3".to_string()
+            })
+        );
+        assert!("gcp:seed".parse::<SeedSource>().is_err());
+        assert!("aws:".parse::<SeedSource>().is_err());
+        assert!("vault:seed".parse::<SeedSource>().is_err());
+    }
+
+    #[test]
+    fn test_arn_region() {
+        assert_eq!(
+            arn_region("arn:aws:secretsmanager:eu-west-1:123456789012:secret:seed"),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            arn_region("arn:aws-cn:secretsmanager:cn-north-1:123456789012:secret:seed"),
+            Some("cn-north-1")
+        );
+        assert_eq!(arn_region("migrate-seed"), None);
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+}
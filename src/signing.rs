@@ -0,0 +1,204 @@
+//! Transaction signing backends
+//!
+//! `MigrationBot` holds its signer as a [`TxSigner`] trait object: a local
+//! sr25519 [`Keypair`] (seed, keystore, secret manager), a [`RemoteSigner`]
+//! (`--remote-signer`) that sends each signer payload to an external signing
+//! daemon, so the seed never lives on the bot host, a Ledger
+//! (`--ledger`, see [`crate::ledger`]) or a HashiCorp Vault transit key
+//! (`--vault-key`, see [`crate::vault`]). Transactions are built as
+//! partial extrinsics at a nonce from the bot's local counter (see
+//! [`crate::nonce`]), signed asynchronously and then assembled.
+//!
+//! The remote daemon speaks JSON-RPC 2.0 over HTTP(S), authenticated with a
+//! bearer token:
+//!
+//! - `signer_accountId` → the signing account's public key (`0x` hex)
+//! - `signer_signPayload {account, payload}` → the sr25519 signature (`0x` hex)
+//!
+//! Every signature is verified against the account before it is used, so a
+//! misbehaving daemon cannot get a malformed tx broadcast.
+
+use anyhow::{Context, Result};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+use std::time::Duration;
+use subxt::config::DefaultExtrinsicParamsBuilder;
+use subxt::tx::{DynamicPayload, Signer, SubmittableExtrinsic};
+use subxt::utils::{AccountId32, MultiSignature};
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519::{self, Keypair};
+
+/// Bound on each signing request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Signs the bot's transactions
+pub trait TxSigner: Send + Sync {
+    fn account_id(&self) -> AccountId32;
+
+    /// Signature of a signer payload
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>>;
+
+    /// Whether each signature waits for someone to confirm it on a device,
+    /// so the bot should sign as few times as it can
+    fn confirms_on_device(&self) -> bool {
+        false
+    }
+}
+
+impl TxSigner for Keypair {
+    fn account_id(&self) -> AccountId32 {
+        <Keypair as Signer<PolkadotConfig>>::account_id(self)
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        let signature = <Keypair as Signer<PolkadotConfig>>::sign(self, payload);
+        async move { Ok(signature) }.boxed()
+    }
+}
+
+/// Build `call` as a tx signed by `signer` at `nonce` (default params)
+pub async fn create_signed(
+    client: &OnlineClient<PolkadotConfig>,
+    call: &DynamicPayload,
+    signer: &dyn TxSigner,
+    nonce: u32,
+) -> Result<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+    let account_id = signer.account_id();
+    let params = DefaultExtrinsicParamsBuilder::<PolkadotConfig>::new()
+        .nonce(nonce.into())
+        .build();
+    let partial = client.tx().create_partial_signed_offline(call, params)?;
+    let signature = signer.sign(&partial.signer_payload()).await?;
+    Ok(partial.sign_with_address_and_signature(&account_id.into(), &signature))
+}
+
+/// External signing daemon (`--remote-signer`)
+pub struct RemoteSigner {
+    http: reqwest::Client,
+    url: String,
+    token: Option<SecretString>,
+    account: [u8; 32],
+}
+
+impl RemoteSigner {
+    /// Connect to the daemon at `url` and ask it which account it signs for
+    pub async fn connect(url: &str, token: Option<SecretString>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let mut signer = Self {
+            http,
+            url: url.to_string(),
+            token,
+            account: [0; 32],
+        };
+        let account = signer
+            .call("signer_accountId", json!([]))
+            .await
+            .with_context(|| format!("Remote signer {} did not name its account", url))?;
+        signer.account = decode_hex(&account, "account")?;
+        Ok(signer)
+    }
+
+    /// One JSON-RPC call, returning the string result
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<String> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut builder = self.http.post(&self.url).json(&request);
+        if let Some(token) = &self.token {
+            builder = builder.bearer_auth(token.expose_secret());
+        }
+        let response: serde_json::Value = builder
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("{} request failed", method))?
+            .json()
+            .await
+            .with_context(|| format!("{} returned invalid JSON", method))?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("{} failed: {}", method, error);
+        }
+        response["result"]
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("{} returned no result", method))
+    }
+}
+
+impl TxSigner for RemoteSigner {
+    fn account_id(&self) -> AccountId32 {
+        AccountId32(self.account)
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        async move {
+            let params = json!({
+                "account": format!("0x{}", hex::encode(self.account)),
+                "payload": format!("0x{}", hex::encode(payload)),
+            });
+            let signature = self.call("signer_signPayload", params).await?;
+            let signature: [u8; 64] = decode_hex(&signature, "signature")?;
+            anyhow::ensure!(
+                sr25519::verify(
+                    &sr25519::Signature(signature),
+                    payload,
+                    &sr25519::PublicKey(self.account)
+                ),
+                "Remote signer returned a signature that does not verify for {}",
+                self.account_id()
+            );
+            Ok(MultiSignature::Sr25519(signature))
+        }
+        .boxed()
+    }
+}
+
+/// Fixed-length `0x` hex from the daemon
+fn decode_hex<const N: usize>(hex_str: &str, what: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(hex_str.trim_start_matches("0x"))
+        .with_context(|| format!("Remote signer {} is not hex", what))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        anyhow::anyhow!(
+            "Remote signer {} is {} bytes, expected {}",
+            what,
+            bytes.len(),
+            N
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::signer_from_seed;
+
+    #[test]
+    fn test_decode_hex() {
+        let account: [u8; 32] = decode_hex(&format!("0x{}", "ab".repeat(32)), "account").unwrap();
+        assert_eq!(account, [0xab; 32]);
+        assert!(decode_hex::<32>("0xabcd", "account").is_err());
+        assert!(decode_hex::<32>("not hex", "account").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keypair_signer() {
+        let keypair = signer_from_seed(&format!("0x{}", "11".repeat(32))).unwrap();
+        let signer: &dyn TxSigner = &keypair;
+        let MultiSignature::Sr25519(signature) = signer.sign(b"payload").await.unwrap() else {
+            panic!("not an sr25519 signature");
+        };
+        assert!(sr25519::verify(
+            &sr25519::Signature(signature),
+            b"payload",
+            &keypair.public_key()
+        ));
+        assert_eq!(signer.account_id().0, keypair.public_key().0);
+    }
+}
@@ -0,0 +1,130 @@
+//! Run loop state machine
+//!
+//! The loop announces each phase with [`StateMachine::enter`]; the allowed
+//! transitions are listed in [`allowed`], so "what is the bot doing right
+//! now" has one answer, published in the health snapshot (`state`,
+//! `state_since`) and logged on every change. A transition outside the table
+//! is logged as a bug but still taken: the state is for observability and
+//! must never stop a migration.
+
+use std::time::{Duration, Instant};
+use westend_migration_bot::BotState;
+
+/// Whether the run loop may go from `from` to `to`
+pub fn allowed(from: BotState, to: BotState) -> bool {
+    use BotState::*;
+    match (from, to) {
+        (Complete, _) => false,
+        (_, Paused) | (_, Backoff) => true,
+        (Idle | Backoff | Paused, Fetching | Idle) => true,
+        (Fetching, Validating | Complete | Idle) => true,
+        (Validating, Submitting | Idle) => true,
+        (Submitting, AwaitingFinality) => true,
+        // Rebroadcasting the same tx via another endpoint
+        (AwaitingFinality, Submitting | Idle) => true,
+        _ => false,
+    }
+}
+
+/// A state change, as reported by [`StateMachine::enter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: BotState,
+    pub to: BotState,
+    /// Time spent in `from`
+    pub after: Duration,
+    /// Whether [`allowed`] permits it
+    pub valid: bool,
+}
+
+/// Current run loop state and when it was entered
+#[derive(Debug)]
+pub struct StateMachine {
+    state: BotState,
+    since: Instant,
+}
+
+impl StateMachine {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            state: BotState::Idle,
+            since: now,
+        }
+    }
+
+    /// Move to `to`; `None` if already there
+    pub fn enter(&mut self, to: BotState, now: Instant) -> Option<Transition> {
+        if to == self.state {
+            return None;
+        }
+        let transition = Transition {
+            from: self.state,
+            to,
+            after: now.duration_since(self.since),
+            valid: allowed(self.state, to),
+        };
+        self.state = to;
+        self.since = now;
+        Some(transition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BotState::*;
+
+    #[test]
+    fn test_submission_cycle_is_allowed() {
+        let cycle = [
+            Idle,
+            Fetching,
+            Validating,
+            Submitting,
+            AwaitingFinality,
+            Idle,
+        ];
+        for pair in cycle.windows(2) {
+            assert!(allowed(pair[0], pair[1]), "{} -> {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_failures_back_off_from_anywhere() {
+        for from in [Fetching, Validating, Submitting, AwaitingFinality] {
+            assert!(allowed(from, Backoff));
+            assert!(allowed(from, Paused));
+        }
+        assert!(allowed(Backoff, Fetching));
+        assert!(allowed(Paused, Fetching));
+    }
+
+    #[test]
+    fn test_invalid_transitions() {
+        assert!(!allowed(Idle, Submitting));
+        assert!(!allowed(Fetching, AwaitingFinality));
+        assert!(!allowed(AwaitingFinality, Validating));
+        assert!(allowed(AwaitingFinality, Submitting));
+        assert!(!allowed(Complete, Fetching));
+        assert!(!allowed(Complete, Backoff));
+    }
+
+    #[test]
+    fn test_enter_reports_changes_once() {
+        let start = Instant::now();
+        let mut machine = StateMachine::new(start);
+        let later = start + Duration::from_secs(5);
+
+        let transition = machine.enter(Fetching, later).unwrap();
+        assert_eq!(transition.from, Idle);
+        assert_eq!(transition.after, Duration::from_secs(5));
+        assert!(transition.valid);
+        assert_eq!(machine.enter(Fetching, later), None);
+
+        // Invalid transitions are still taken, but flagged
+        let transition = machine.enter(AwaitingFinality, later).unwrap();
+        assert!(!transition.valid);
+        // ... and the machine is in the new state
+        assert_eq!(machine.enter(AwaitingFinality, later), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! Session statistics and campaign caps
+//!
+//! Items and bytes are attributed from the cumulative `MigrationProcess`
+//! counters: the difference between the status before a finalized tx and the
+//! next status fetched is what that tx migrated.
+
+use crate::utils::{format_duration, MigrationStatus};
+use std::time::{Duration, Instant};
+
+/// Counters for the current bot session
+#[derive(Debug)]
+pub struct SessionStats {
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub items_migrated: u64,
+    pub bytes_migrated: u64,
+    started: Instant,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            successful_runs: 0,
+            failed_runs: 0,
+            items_migrated: 0,
+            bytes_migrated: 0,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue counting from earlier sessions' totals; elapsed time starts now
+    pub fn restored(
+        successful_runs: u32,
+        failed_runs: u32,
+        items_migrated: u64,
+        bytes_migrated: u64,
+    ) -> Self {
+        Self {
+            successful_runs,
+            failed_runs,
+            items_migrated,
+            bytes_migrated,
+            started: Instant::now(),
+        }
+    }
+
+    /// Attribute the progress between two statuses to this session.
+    /// Returns the (items, bytes) delta.
+    pub fn record_progress(
+        &mut self,
+        before: &MigrationStatus,
+        after: &MigrationStatus,
+    ) -> (u64, u64) {
+        let items = (after.top_items + after.child_items)
+            .saturating_sub(before.top_items + before.child_items);
+        let bytes = after.size.saturating_sub(before.size);
+        self.items_migrated += items;
+        self.bytes_migrated += bytes;
+        (items, bytes)
+    }
+
+    /// `runs` counted as successful at best-block inclusion (--pipeline)
+    /// never finalized: count them as failed instead
+    pub fn not_finalized(&mut self, runs: u32) {
+        let runs = runs.min(self.successful_runs);
+        self.successful_runs -= runs;
+        self.failed_runs += runs;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// One-line summary for logs and notifications
+    pub fn summary(&self) -> String {
+        format!(
+            "{} successful, {} failed, {} items / {} bytes in {}",
+            self.successful_runs,
+            self.failed_runs,
+            self.items_migrated,
+            self.bytes_migrated,
+            format_duration(self.elapsed())
+        )
+    }
+}
+
+/// Session-wide limits on migrated items and bytes (0 = unlimited)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CampaignCaps {
+    pub max_items: u64,
+    pub max_bytes: u64,
+}
+
+impl CampaignCaps {
+    /// Item limit to use for the next tx, or the reason the campaign is done.
+    ///
+    /// The item limit is clamped to the remaining item budget. The size limit
+    /// is never shrunk (a too-small `real_size_upper` risks a slash), so the
+    /// session stops before a tx whose size limit could exceed the byte budget.
+    pub fn next_item_limit(
+        &self,
+        stats: &SessionStats,
+        item_limit: u32,
+        size_limit: u32,
+    ) -> Result<u32, String> {
+        let mut limit = item_limit;
+
+        if self.max_items > 0 {
+            let remaining = self.max_items.saturating_sub(stats.items_migrated);
+            if remaining == 0 {
+                return Err(format!("item cap of {} reached", self.max_items));
+            }
+            limit = limit.min(remaining.min(u32::MAX as u64) as u32);
+        }
+
+        if self.max_bytes > 0 {
+            let remaining = self.max_bytes.saturating_sub(stats.bytes_migrated);
+            if remaining < size_limit as u64 {
+                return Err(format!(
+                    "byte cap of {} reached ({} remaining < size limit {})",
+                    self.max_bytes, remaining, size_limit
+                ));
+            }
+        }
+
+        Ok(limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(top_items: u64, child_items: u64, size: u64) -> MigrationStatus {
+        MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size,
+            top_items,
+            child_items,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_progress_accumulates() {
+        let mut stats = SessionStats::new();
+        assert_eq!(
+            stats.record_progress(&status(100, 0, 1000), &status(200, 5, 3000)),
+            (105, 2000)
+        );
+        stats.record_progress(&status(200, 5, 3000), &status(250, 5, 3500));
+        assert_eq!(stats.items_migrated, 155);
+        assert_eq!(stats.bytes_migrated, 2500);
+    }
+
+    #[test]
+    fn test_record_progress_never_negative() {
+        let mut stats = SessionStats::new();
+        assert_eq!(
+            stats.record_progress(&status(200, 0, 3000), &status(100, 0, 1000)),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_not_finalized_moves_runs_to_failed() {
+        let mut stats = SessionStats::restored(3, 1, 0, 0);
+        stats.not_finalized(2);
+        assert_eq!((stats.successful_runs, stats.failed_runs), (1, 3));
+        stats.not_finalized(5);
+        assert_eq!((stats.successful_runs, stats.failed_runs), (0, 4));
+    }
+
+    #[test]
+    fn test_unlimited_caps_pass_through() {
+        let caps = CampaignCaps::default();
+        let stats = SessionStats::new();
+        assert_eq!(caps.next_item_limit(&stats, 1024, 51200), Ok(1024));
+    }
+
+    #[test]
+    fn test_item_cap_clamps_last_tx() {
+        let caps = CampaignCaps {
+            max_items: 1500,
+            max_bytes: 0,
+        };
+        let mut stats = SessionStats::new();
+        stats.items_migrated = 1024;
+        assert_eq!(caps.next_item_limit(&stats, 1024, 51200), Ok(476));
+
+        stats.items_migrated = 1500;
+        assert!(caps.next_item_limit(&stats, 1024, 51200).is_err());
+    }
+
+    #[test]
+    fn test_byte_cap_stops_before_overshoot() {
+        let caps = CampaignCaps {
+            max_items: 0,
+            max_bytes: 100_000,
+        };
+        let mut stats = SessionStats::new();
+        stats.bytes_migrated = 40_000;
+        assert_eq!(caps.next_item_limit(&stats, 1024, 51200), Ok(1024));
+
+        stats.bytes_migrated = 60_000;
+        assert!(caps.next_item_limit(&stats, 1024, 51200).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Bot status types shared by the JSON outputs (health endpoints, event log,
+//! WebSocket push) and exposed to downstream tools through the library crate.
+//!
+//! These are a public schema: renaming or removing a field breaks consumers,
+//! and `tests/schema.rs` pins the serialized form against golden files.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Parsed migration status for display
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    pub top_complete: bool,
+    pub child_complete: bool,
+    pub size: u64,
+    pub top_items: u64,
+    pub child_items: u64,
+    /// `LastKey` of `progress_top` (0x hex), while the top trie is in progress
+    #[serde(default)]
+    pub top_last_key: Option<String>,
+    /// `LastKey` of `progress_child` within the child trie being migrated
+    #[serde(default)]
+    pub child_last_key: Option<String>,
+}
+
+impl MigrationStatus {
+    /// Migration is complete when top is complete (matches Parity's TypeScript bot)
+    /// Child migration happens inline with top, so progress_child may stay ToStart
+    pub fn is_complete(&self) -> bool {
+        self.top_complete
+    }
+}
+
+/// Structured validity error types for better matching
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidityError {
+    /// Nonce too low - transaction already applied or pending
+    Stale,
+    /// Nonce too high - previous transaction not yet applied
+    Future,
+    /// Transaction priority too low - pool conflict (used in from_validity_error matching)
+    Priority,
+    /// Unable to pay fees (insufficient balance)
+    Payment,
+    /// Bad signature or proof
+    BadProof,
+    /// Transaction from too old block (mortality expired)
+    AncientBirthBlock,
+    /// Would exhaust block resources
+    ExhaustsResources,
+    /// Other error with description
+    Other(String),
+}
+
+impl fmt::Display for ValidityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidityError::Stale => write!(
+                f,
+                "Stale - nonce too low (transaction already applied or pending)"
+            ),
+            ValidityError::Future => {
+                write!(f, "Future - nonce too high (previous tx not yet applied)")
+            }
+            ValidityError::Priority => write!(f, "Priority too low - pool conflict"),
+            ValidityError::Payment => {
+                write!(f, "Payment - unable to pay fees (insufficient balance)")
+            }
+            ValidityError::BadProof => write!(f, "BadProof - invalid signature"),
+            ValidityError::AncientBirthBlock => {
+                write!(f, "AncientBirthBlock - transaction mortality expired")
+            }
+            ValidityError::ExhaustsResources => {
+                write!(f, "ExhaustsResources - would exhaust block resources")
+            }
+            ValidityError::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Block the bot reads state and nonces at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadBasis {
+    /// Best (possibly unfinalized) block, nonce including the tx pool
+    #[default]
+    Best,
+    /// Finalized head only
+    Finalized,
+}
+
+impl fmt::Display for ReadBasis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadBasis::Best => write!(f, "best block"),
+            ReadBasis::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+/// What the run loop is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotState {
+    /// Between iterations (inter-tx delay, or not started yet)
+    #[default]
+    Idle,
+    /// Reading `MigrationProcess` and preflight checks
+    Fetching,
+    /// Building and dry-running the call
+    Validating,
+    /// Signing and broadcasting
+    Submitting,
+    /// Broadcast; waiting for the tx to be finalized
+    AwaitingFinality,
+    /// Waiting after a failure, a skipped submission or other migrators
+    Backoff,
+    /// Held: unacknowledged notification, unhealthy node, dashboard pause or
+    /// a tx awaiting approval
+    Paused,
+    /// The migration is complete
+    Complete,
+}
+
+impl fmt::Display for BotState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BotState::Idle => "idle",
+            BotState::Fetching => "fetching",
+            BotState::Validating => "validating",
+            BotState::Submitting => "submitting",
+            BotState::AwaitingFinality => "awaiting finality",
+            BotState::Backoff => "backoff",
+            BotState::Paused => "paused",
+            BotState::Complete => "complete",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Point-in-time state of a running bot, as served by `/healthz` and `/readyz`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotSnapshot {
+    pub connected: bool,
+    pub started_at: DateTime<Utc>,
+    /// Last time the run loop did anything (status fetch, tx result)
+    pub last_activity: DateTime<Utc>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub consecutive_errors: u32,
+    /// Submissions paused waiting for a critical notification to be acknowledged
+    pub paused: bool,
+    /// Block reads follow; `finalized` once persistent forks were detected
+    pub read_basis: ReadBasis,
+    pub status: Option<MigrationStatus>,
+    /// Current state of the run loop
+    #[serde(default)]
+    pub state: BotState,
+    /// When the run loop entered `state`
+    #[serde(default)]
+    pub state_since: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==================== ValidityError Tests ====================
+
+    #[test]
+    fn test_validity_error_display() {
+        assert_eq!(
+            ValidityError::Stale.to_string(),
+            "Stale - nonce too low (transaction already applied or pending)"
+        );
+        assert_eq!(
+            ValidityError::Future.to_string(),
+            "Future - nonce too high (previous tx not yet applied)"
+        );
+        assert_eq!(
+            ValidityError::Priority.to_string(),
+            "Priority too low - pool conflict"
+        );
+        assert_eq!(
+            ValidityError::Payment.to_string(),
+            "Payment - unable to pay fees (insufficient balance)"
+        );
+        assert_eq!(
+            ValidityError::BadProof.to_string(),
+            "BadProof - invalid signature"
+        );
+        assert_eq!(
+            ValidityError::AncientBirthBlock.to_string(),
+            "AncientBirthBlock - transaction mortality expired"
+        );
+        assert_eq!(
+            ValidityError::ExhaustsResources.to_string(),
+            "ExhaustsResources - would exhaust block resources"
+        );
+        assert_eq!(
+            ValidityError::Other("custom error".to_string()).to_string(),
+            "custom error"
+        );
+    }
+
+    #[test]
+    fn test_validity_error_equality() {
+        assert_eq!(ValidityError::Stale, ValidityError::Stale);
+        assert_ne!(ValidityError::Stale, ValidityError::Future);
+        assert_eq!(
+            ValidityError::Other("test".to_string()),
+            ValidityError::Other("test".to_string())
+        );
+    }
+
+    // ==================== MigrationStatus Tests ====================
+
+    #[test]
+    fn test_migration_status_incomplete() {
+        let status = MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size: 1000,
+            top_items: 500,
+            child_items: 0,
+            ..Default::default()
+        };
+        assert!(!status.is_complete());
+    }
+
+    #[test]
+    fn test_migration_status_top_complete() {
+        // When top is complete, migration is done (child migrates inline)
+        let status = MigrationStatus {
+            top_complete: true,
+            child_complete: false, // Can stay false/ToStart
+            size: 5000,
+            top_items: 1000,
+            child_items: 500,
+            ..Default::default()
+        };
+        assert!(status.is_complete()); // Now returns true!
+    }
+
+    #[test]
+    fn test_migration_status_fully_complete() {
+        let status = MigrationStatus {
+            top_complete: true,
+            child_complete: true,
+            size: 10000,
+            top_items: 2000,
+            child_items: 1000,
+            ..Default::default()
+        };
+        assert!(status.is_complete());
+    }
+}
@@ -0,0 +1,142 @@
+//! Signed maintenance tasks
+//!
+//! The run loop is an engine over a [`Task`]: fetch the on-chain state the
+//! task works from, stop once the task reports it complete, otherwise build
+//! its next call and hand it to the shared pipeline (dry run, signing,
+//! broadcast, confirmation, journaling) and the loop's resilience machinery
+//! (policy, pacing, endpoint failover, pauses). The state-trie migration is
+//! one task, [`TrieMigration`]; a sibling such as driving `pallet_migrations`
+//! implements the same trait instead of copying the loop. Monitor-only tasks
+//! ([`crate::mbm`]) implement just fetch and completion.
+
+use crate::limits::MigrationLimits;
+use crate::pipeline::MigrationCall;
+use crate::utils::{parse_migration_status, MigrationStatus};
+use anyhow::Result;
+use std::future::Future;
+use subxt::dynamic::Value;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Task selected with `--task`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TaskKind {
+    /// Sign `StateTrieMigration.continue_migrate` calls
+    Trie,
+    /// Monitor `pallet_migrations` multi-block migrations (read-only)
+    Mbm,
+}
+
+/// One kind of signed maintenance work
+pub trait Task {
+    /// What a fetch returns and a call is built from
+    type State: Clone;
+
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Read the task's state at block `at` (`None` if the chain has none)
+    fn fetch(
+        &self,
+        client: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> impl Future<Output = Result<Option<Self::State>>> + Send;
+
+    /// Whether there is nothing left to submit
+    fn is_complete(&self, state: &Self::State) -> bool;
+
+    /// Encode the next call from `state`, within `limits`
+    fn build(
+        &self,
+        _client: &OnlineClient<PolkadotConfig>,
+        _state: &Self::State,
+        _limits: MigrationLimits,
+    ) -> Result<MigrationCall> {
+        anyhow::bail!("{} is monitor-only and has no calls to submit", self.name())
+    }
+}
+
+/// `MigrationProcess` as read for one submission
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieState {
+    /// Passed back verbatim as `witness_task`
+    pub witness: Value<()>,
+    pub status: MigrationStatus,
+}
+
+/// `StateTrieMigration.continue_migrate`
+pub struct TrieMigration {
+    /// Witness from --witness-file, used instead of the live `MigrationProcess`
+    witness: Option<Value<()>>,
+}
+
+impl TrieMigration {
+    pub fn new(witness: Option<Value<()>>) -> Self {
+        Self { witness }
+    }
+}
+
+impl Task for TrieMigration {
+    type State = TrieState;
+
+    fn name(&self) -> &'static str {
+        "state-trie migration"
+    }
+
+    async fn fetch(
+        &self,
+        client: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> Result<Option<TrieState>> {
+        if let Some(witness) = &self.witness {
+            return Ok(Some(TrieState {
+                witness: witness.clone(),
+                status: parse_migration_status(witness),
+            }));
+        }
+
+        // Query MigrationProcess - this is what we pass to continue_migrate
+        let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+        let Some(thunk) = client.storage().at(at).fetch(&query).await? else {
+            return Ok(None);
+        };
+        let decoded = thunk.to_value()?;
+        Ok(Some(TrieState {
+            status: parse_migration_status(&decoded),
+            // Convert Value<TypeId> to Value<()> for use in the transaction;
+            // the queried value is passed back as-is, like the TypeScript bot does
+            witness: decoded.map_context(|_| ()),
+        }))
+    }
+
+    fn is_complete(&self, state: &TrieState) -> bool {
+        state.status.is_complete()
+    }
+
+    fn build(
+        &self,
+        client: &OnlineClient<PolkadotConfig>,
+        state: &TrieState,
+        limits: MigrationLimits,
+    ) -> Result<MigrationCall> {
+        MigrationCall::build(client, state.witness.clone(), limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_follows_status() {
+        let task = TrieMigration::new(None);
+        let mut state = TrieState {
+            witness: Value::unnamed_composite(vec![]),
+            status: MigrationStatus::default(),
+        };
+        assert!(!task.is_complete(&state));
+        state.status.top_complete = true;
+        state.status.child_complete = true;
+        assert!(task.is_complete(&state));
+    }
+}
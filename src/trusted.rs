@@ -0,0 +1,266 @@
+//! Trusted finality (`--trusted-finality`)
+//!
+//! Without it a tx counts as final once the RPC endpoint says its block is
+//! finalized. With it, an embedded light client (smoldot, started from the
+//! operator's chain specs) follows the chain as well, verifying each GRANDPA
+//! justification against the authority set from the spec's checkpoint, and
+//! the endpoint's block has to be the one the light client finalized at that
+//! height. For a parachain target the relay chain spec is needed too: the
+//! light client derives the para's finality from the relay chain's.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::backend::rpc::RpcClient;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Finalized blocks remembered by number; older ones are looked up by walking
+/// parent hashes back from the oldest one kept
+const KEPT_BLOCKS: usize = 4096;
+
+/// What the light client says about a block the endpoint calls finalized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finality {
+    /// The light client finalized the same block at that height
+    Final,
+    /// The light client has not finalized that height yet
+    Pending,
+    /// The light client finalized a different block at that height
+    Conflict { trusted_hash: H256 },
+}
+
+/// Finalized hashes by number, as seen by the light client
+#[derive(Debug, Default)]
+pub struct FinalizedChain {
+    hashes: BTreeMap<u32, H256>,
+}
+
+impl FinalizedChain {
+    pub fn insert(&mut self, number: u32, hash: H256) {
+        self.hashes.insert(number, hash);
+        while self.hashes.len() > KEPT_BLOCKS {
+            self.hashes.pop_first();
+        }
+    }
+
+    /// Highest finalized number seen
+    pub fn head(&self) -> Option<u32> {
+        self.hashes.keys().next_back().copied()
+    }
+
+    /// Compare block `number` (`hash`) with what was finalized; `None` when
+    /// that height is older than every block kept
+    pub fn check(&self, number: u32, hash: H256) -> Option<Finality> {
+        if self.head().is_none_or(|head| head < number) {
+            return Some(Finality::Pending);
+        }
+        let trusted_hash = *self.hashes.get(&number)?;
+        Some(if trusted_hash == hash {
+            Finality::Final
+        } else {
+            Finality::Conflict { trusted_hash }
+        })
+    }
+
+    /// Oldest block kept at or after `number`
+    fn oldest_from(&self, number: u32) -> Option<(u32, H256)> {
+        self.hashes
+            .range(number..)
+            .next()
+            .map(|(number, hash)| (*number, *hash))
+    }
+}
+
+/// Light client following the target chain's finality
+pub struct TrustedFinality {
+    rpc: LegacyRpcMethods<PolkadotConfig>,
+    chain: Arc<Mutex<FinalizedChain>>,
+    /// Latest finalized number; closed when the light client stops following
+    finalized: watch::Receiver<u32>,
+    _light_client: KeepAlive,
+}
+
+impl TrustedFinality {
+    /// Start the light client from `relay_spec` (and `para_spec` for a
+    /// parachain target) and follow its finalized blocks until `shutdown`
+    pub async fn start(
+        relay_spec: &Path,
+        para_spec: Option<&Path>,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read chain spec {}", path.display()))
+        };
+        let relay_spec = read(relay_spec)?;
+        let para_spec = para_spec.map(read).transpose()?;
+        let (rpc_client, light_client) = light_client(&relay_spec, para_spec.as_deref())?;
+        let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone())
+            .await
+            .context("Light client failed to sync the chain's metadata")?;
+        let rpc = LegacyRpcMethods::<PolkadotConfig>::new(rpc_client);
+        info!(
+            "Light client synced to #{}; tx finality is checked against it",
+            rpc.chain_get_header(None)
+                .await?
+                .map_or(0, |header| header.number)
+        );
+
+        let chain = Arc::new(Mutex::new(FinalizedChain::default()));
+        let (tx, finalized) = watch::channel(0);
+        let following = chain.clone();
+        tokio::spawn(async move {
+            let mut blocks = match client.blocks().subscribe_finalized().await {
+                Ok(blocks) => blocks,
+                Err(e) => {
+                    warn!("Light client failed to follow finality: {}", e);
+                    return;
+                }
+            };
+            loop {
+                let block = tokio::select! {
+                    _ = shutdown.cancelled() => return,
+                    block = blocks.next() => block,
+                };
+                match block {
+                    Some(Ok(block)) => {
+                        debug!("Light client finalized #{}", block.number());
+                        following
+                            .lock()
+                            .unwrap()
+                            .insert(block.number(), block.hash());
+                        tx.send_replace(block.number());
+                    }
+                    Some(Err(e)) => {
+                        warn!("Light client stopped following finality: {}", e);
+                        return;
+                    }
+                    None => {
+                        warn!("Light client finality subscription ended");
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            rpc,
+            chain,
+            finalized,
+            _light_client: light_client,
+        })
+    }
+
+    /// Wait up to `timeout` for the light client to finalize block `number`,
+    /// then compare its hash with `hash`
+    pub async fn wait_for_finality(
+        &self,
+        number: u32,
+        hash: H256,
+        timeout: Duration,
+    ) -> Result<Finality> {
+        let mut finalized = self.finalized.clone();
+        match tokio::time::timeout(timeout, finalized.wait_for(|head| *head >= number)).await {
+            Err(_) => return Ok(Finality::Pending),
+            Ok(Err(_)) => anyhow::bail!("Light client is no longer following finality"),
+            Ok(Ok(_)) => {}
+        }
+        let from = {
+            let chain = self.chain.lock().unwrap();
+            if let Some(finality) = chain.check(number, hash) {
+                return Ok(finality);
+            }
+            chain
+                .oldest_from(number)
+                .context("Light client has no finalized blocks")?
+        };
+        // Older than every block kept: walk back to it through the light
+        // client's (verified) headers
+        let (mut at_number, mut at) = from;
+        while at_number > number {
+            at = self
+                .rpc
+                .chain_get_header(Some(at))
+                .await?
+                .with_context(|| format!("Light client has no header for {:?}", at))?
+                .parent_hash;
+            at_number -= 1;
+        }
+        Ok(if at == hash {
+            Finality::Final
+        } else {
+            Finality::Conflict { trusted_hash: at }
+        })
+    }
+}
+
+/// Whatever has to outlive the light client's RPC connection
+#[cfg(feature = "light-client")]
+type KeepAlive = subxt::lightclient::LightClient;
+#[cfg(not(feature = "light-client"))]
+type KeepAlive = ();
+
+#[cfg(not(feature = "light-client"))]
+fn light_client(_relay_spec: &str, _para_spec: Option<&str>) -> Result<(RpcClient, KeepAlive)> {
+    anyhow::bail!("--trusted-finality requires building with `--features light-client`")
+}
+
+#[cfg(feature = "light-client")]
+fn light_client(relay_spec: &str, para_spec: Option<&str>) -> Result<(RpcClient, KeepAlive)> {
+    use subxt::lightclient::LightClient;
+    let (light, relay) =
+        LightClient::relay_chain(relay_spec).context("Failed to start the light client")?;
+    let rpc = match para_spec {
+        Some(spec) => RpcClient::new(
+            light
+                .parachain(spec)
+                .context("Failed to add the parachain to the light client")?,
+        ),
+        None => RpcClient::new(relay),
+    };
+    Ok((rpc, light))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let ours = H256::repeat_byte(1);
+        let other = H256::repeat_byte(2);
+        let mut chain = FinalizedChain::default();
+        assert_eq!(chain.check(100, ours), Some(Finality::Pending));
+
+        chain.insert(100, ours);
+        chain.insert(101, other);
+        assert_eq!(chain.check(100, ours), Some(Finality::Final));
+        assert_eq!(
+            chain.check(101, ours),
+            Some(Finality::Conflict {
+                trusted_hash: other
+            })
+        );
+        assert_eq!(chain.check(102, ours), Some(Finality::Pending));
+        // Before the light client started following
+        assert_eq!(chain.check(99, ours), None);
+        assert_eq!(chain.oldest_from(99), Some((100, ours)));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut chain = FinalizedChain::default();
+        for number in 0..KEPT_BLOCKS as u32 + 10 {
+            chain.insert(number, H256::zero());
+        }
+        assert_eq!(chain.hashes.len(), KEPT_BLOCKS);
+        assert_eq!(chain.oldest_from(0), Some((10, H256::zero())));
+        assert_eq!(chain.head(), Some(KEPT_BLOCKS as u32 + 9));
+    }
+}
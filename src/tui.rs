@@ -0,0 +1,400 @@
+//! Interactive terminal dashboard (`--tui`)
+//!
+//! The run loop feeds a shared [`Dashboard`]: every emitted [`BotEvent`]
+//! updates it, and balance/nonce/goal are set directly after each tx. A
+//! blocking task redraws it a few times per second with ratatui and handles
+//! the keybinds, which act back on the loop through the same `Dashboard`:
+//!
+//! - `p` / space: pause or resume submissions (the in-flight tx finishes)
+//! - `+` / `-`: raise or lower the item limit for the next tx
+//! - `q` / Ctrl-C: stop like SIGINT (drain the in-flight tx, then exit)
+//!
+//! The size limit is not adjustable: it also bounds `real_size_upper`, and
+//! getting that wrong risks a slash.
+
+use crate::events::{BotEvent, EventRecord};
+use crate::utils::{units_to_wnd, MigrationStatus};
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Redraw interval (also the key polling timeout)
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// Item limit change per `+`/`-` key press
+const ITEM_LIMIT_STEP: u32 = 64;
+
+/// Recent tx hashes and errors kept for display
+const RECENT_ENTRIES: usize = 8;
+
+/// What the dashboard shows
+#[derive(Debug, Clone, Default)]
+pub struct DashboardState {
+    pub status: Option<MigrationStatus>,
+    pub size_limit: u32,
+    pub balance: Option<u128>,
+    pub nonce: Option<u32>,
+    pub successful_runs: u32,
+    pub failed_runs: u32,
+    pub items_migrated: u64,
+    pub bytes_migrated: u64,
+    /// Fraction of the session goal (--runs / --max-items-total), if there is one
+    pub goal: Option<f64>,
+    pub recent_txs: VecDeque<(DateTime<Utc>, String)>,
+    pub errors: VecDeque<(DateTime<Utc>, String)>,
+    pub stopped: Option<String>,
+}
+
+fn push_recent(entries: &mut VecDeque<(DateTime<Utc>, String)>, at: DateTime<Utc>, text: String) {
+    if entries.len() == RECENT_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back((at, text));
+}
+
+impl DashboardState {
+    /// Fold an emitted event into the displayed state
+    pub fn apply(&mut self, record: &EventRecord) {
+        match &record.event {
+            BotEvent::Started { size_limit, .. } => self.size_limit = *size_limit,
+            BotEvent::Status(status) => self.status = Some(status.clone()),
+            BotEvent::TxFinalized { tx_hash, .. } => {
+                self.successful_runs += 1;
+                let hash = tx_hash.clone().unwrap_or_else(|| "(dry run)".to_string());
+                push_recent(&mut self.recent_txs, record.timestamp, hash);
+            }
+            BotEvent::Progress { items, bytes } => {
+                self.items_migrated += items;
+                self.bytes_migrated += bytes;
+            }
+            BotEvent::TxFailed { error, .. } => {
+                self.failed_runs += 1;
+                push_recent(&mut self.errors, record.timestamp, error.clone());
+            }
+            BotEvent::BalanceDecreased { .. } => {
+                push_recent(&mut self.errors, record.timestamp, record.event.describe());
+            }
+            BotEvent::Stopped { reason } => self.stopped = Some(reason.clone()),
+            BotEvent::TxSubmitted { .. } | BotEvent::TxInBlock { .. } => {}
+        }
+    }
+}
+
+/// Dashboard state plus the controls the keybinds act on
+#[derive(Default)]
+pub struct Dashboard {
+    state: Mutex<DashboardState>,
+    paused: AtomicBool,
+    item_limit: AtomicU32,
+    max_item_limit: AtomicU32,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut DashboardState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    pub fn apply(&self, record: &EventRecord) {
+        self.update(|state| state.apply(record));
+    }
+
+    /// Submissions paused from the keyboard
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn toggle_pause(&self) {
+        self.paused.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    /// Item limit for the next tx
+    pub fn item_limit(&self) -> u32 {
+        self.item_limit.load(Ordering::Relaxed)
+    }
+
+    /// Set the starting item limit and the chain maximum it may be raised to
+    pub fn set_item_limits(&self, item_limit: u32, max: u32) {
+        self.max_item_limit.store(max, Ordering::Relaxed);
+        self.item_limit
+            .store(item_limit.min(max), Ordering::Relaxed);
+    }
+
+    /// Move the item limit one step up or down, within `1..=max`
+    fn step_item_limit(&self, up: bool) {
+        let max = self.max_item_limit.load(Ordering::Relaxed);
+        let current = self.item_limit();
+        let next = if up {
+            current.saturating_add(ITEM_LIMIT_STEP).min(max)
+        } else {
+            current.saturating_sub(ITEM_LIMIT_STEP).max(1)
+        };
+        self.item_limit.store(next, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> DashboardState {
+        self.state.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// Run the dashboard until `shutdown` is cancelled; `q` cancels it
+pub fn spawn(
+    dashboard: Arc<Dashboard>,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = run(&dashboard, &shutdown) {
+            warn!("Dashboard failed: {}", e);
+        }
+    })
+}
+
+fn run(dashboard: &Dashboard, shutdown: &CancellationToken) -> std::io::Result<()> {
+    enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+
+    let result = (|| -> std::io::Result<()> {
+        while !shutdown.is_cancelled() {
+            let state = dashboard.snapshot();
+            terminal.draw(|frame| draw(frame, &state, dashboard))?;
+
+            if !event::poll(REFRESH)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') => shutdown.cancel(),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    shutdown.cancel()
+                }
+                KeyCode::Char('p') | KeyCode::Char(' ') => dashboard.toggle_pause(),
+                KeyCode::Char('+') | KeyCode::Char('=') => dashboard.step_item_limit(true),
+                KeyCode::Char('-') => dashboard.step_item_limit(false),
+                _ => {}
+            }
+        }
+        Ok(())
+    })();
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn draw(frame: &mut Frame, state: &DashboardState, dashboard: &Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Length(3),
+            Constraint::Min(4),
+            Constraint::Min(4),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    let trie = |complete: Option<bool>, items: Option<u64>| match (complete, items) {
+        (Some(true), Some(items)) => format!("COMPLETE ({} items)", items),
+        (Some(false), Some(items)) => format!("in progress ({} items)", items),
+        _ => "?".to_string(),
+    };
+    let status = state.status.as_ref();
+    let run_state = match (&state.stopped, dashboard.paused()) {
+        (Some(reason), _) => format!("stopped: {}", reason),
+        (None, true) => "PAUSED (p to resume)".to_string(),
+        (None, false) => "running".to_string(),
+    };
+    let overview = vec![
+        Line::from(format!(
+            "Top trie:   {}",
+            trie(status.map(|s| s.top_complete), status.map(|s| s.top_items))
+        )),
+        Line::from(format!(
+            "Child trie: {}",
+            trie(
+                status.map(|s| s.child_complete),
+                status.map(|s| s.child_items)
+            )
+        )),
+        Line::from(format!(
+            "Limits: {} items / {} bytes    Balance: {}    Nonce: {}",
+            dashboard.item_limit(),
+            state.size_limit,
+            state.balance.map_or_else(
+                || "?".to_string(),
+                |b| format!("{:.4} WND", units_to_wnd(b))
+            ),
+            state
+                .nonce
+                .map_or_else(|| "?".to_string(), |n| n.to_string())
+        )),
+        Line::from(format!(
+            "Session: {} ok / {} failed, {} items / {} bytes    [{}]",
+            state.successful_runs,
+            state.failed_runs,
+            state.items_migrated,
+            state.bytes_migrated,
+            run_state
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(overview).block(Block::default().borders(Borders::ALL).title("Migration")),
+        rows[0],
+    );
+
+    let goal = state.goal.unwrap_or(0.0).clamp(0.0, 1.0);
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Session goal"))
+            .gauge_style(Style::default().fg(Color::Green))
+            .ratio(goal)
+            .label(match state.goal {
+                Some(_) => format!("{:.0}%", goal * 100.0),
+                None => "no --runs / --max-items-total goal".to_string(),
+            }),
+        rows[1],
+    );
+
+    let entries = |entries: &VecDeque<(DateTime<Utc>, String)>| -> Vec<ListItem> {
+        entries
+            .iter()
+            .rev()
+            .map(|(at, text)| {
+                ListItem::new(format!(
+                    "{}  {}",
+                    at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                    text
+                ))
+            })
+            .collect()
+    };
+    frame.render_widget(
+        List::new(entries(&state.recent_txs))
+            .block(Block::default().borders(Borders::ALL).title("Recent txs")),
+        rows[2],
+    );
+    frame.render_widget(
+        List::new(entries(&state.errors))
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Last errors")),
+        rows[3],
+    );
+    frame.render_widget(
+        Paragraph::new("p: pause/resume   +/-: item limit   q: stop"),
+        rows[4],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64, event: BotEvent) -> EventRecord {
+        EventRecord {
+            timestamp: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            event,
+        }
+    }
+
+    #[test]
+    fn test_events_update_state() {
+        let mut state = DashboardState::default();
+        state.apply(&at(
+            0,
+            BotEvent::TxFinalized {
+                tx_hash: Some("0xab".to_string()),
+                elapsed_secs: 12.0,
+            },
+        ));
+        state.apply(&at(
+            1,
+            BotEvent::Progress {
+                items: 1024,
+                bytes: 4096,
+            },
+        ));
+        state.apply(&at(
+            2,
+            BotEvent::TxFailed {
+                error: "BadProof".to_string(),
+                recoverable: false,
+            },
+        ));
+        assert_eq!(state.successful_runs, 1);
+        assert_eq!(state.failed_runs, 1);
+        assert_eq!(state.items_migrated, 1024);
+        assert_eq!(state.recent_txs.back().unwrap().1, "0xab");
+        assert_eq!(state.errors.back().unwrap().1, "BadProof");
+    }
+
+    #[test]
+    fn test_recent_entries_are_bounded() {
+        let mut state = DashboardState::default();
+        for n in 0..(RECENT_ENTRIES as i64 + 3) {
+            state.apply(&at(
+                n,
+                BotEvent::TxFinalized {
+                    tx_hash: Some(format!("0x{:02x}", n)),
+                    elapsed_secs: 6.0,
+                },
+            ));
+        }
+        assert_eq!(state.recent_txs.len(), RECENT_ENTRIES);
+        assert_eq!(state.recent_txs.front().unwrap().1, "0x03");
+    }
+
+    #[test]
+    fn test_item_limit_steps_within_bounds() {
+        let dashboard = Dashboard::new();
+        dashboard.set_item_limits(2048, 4096);
+        assert_eq!(dashboard.item_limit(), 2048);
+
+        dashboard.step_item_limit(true);
+        assert_eq!(dashboard.item_limit(), 2048 + ITEM_LIMIT_STEP);
+
+        dashboard.set_item_limits(4090, 4096);
+        dashboard.step_item_limit(true);
+        assert_eq!(dashboard.item_limit(), 4096);
+
+        dashboard.set_item_limits(10, 4096);
+        dashboard.step_item_limit(false);
+        assert_eq!(dashboard.item_limit(), 1);
+    }
+
+    #[test]
+    fn test_pause_toggles() {
+        let dashboard = Dashboard::new();
+        assert!(!dashboard.paused());
+        dashboard.toggle_pause();
+        assert!(dashboard.paused());
+        dashboard.toggle_pause();
+        assert!(!dashboard.paused());
+    }
+}
@@ -1,113 +1,13 @@
-use std::fmt;
-use std::sync::atomic::{AtomicBool, Ordering};
-use subxt::dynamic::{At, Value};
-use tracing::debug;
+use crate::error::MigrationError;
+use chrono::{DateTime, Local};
+use secrecy::ExposeSecret;
+use std::str::FromStr;
+use std::time::Duration;
+use subxt::dynamic::Value;
+use subxt_signer::{bip39::Mnemonic, sr25519::Keypair, SecretUri, DEV_PHRASE};
 
-/// Global flag to disable desktop notifications
-static NOTIFICATIONS_DISABLED: AtomicBool = AtomicBool::new(false);
-
-/// Disable desktop notifications globally
-pub fn disable_notifications() {
-    NOTIFICATIONS_DISABLED.store(true, Ordering::Relaxed);
-}
-
-/// Structured validity error types for better matching
-#[derive(Debug, Clone, PartialEq)]
-pub enum ValidityError {
-    /// Nonce too low - transaction already applied or pending
-    Stale,
-    /// Nonce too high - previous transaction not yet applied
-    Future,
-    /// Transaction priority too low - pool conflict (used in from_validity_error matching)
-    #[allow(dead_code)]
-    Priority,
-    /// Unable to pay fees (insufficient balance)
-    Payment,
-    /// Bad signature or proof
-    BadProof,
-    /// Transaction from too old block (mortality expired)
-    AncientBirthBlock,
-    /// Would exhaust block resources
-    ExhaustsResources,
-    /// Other error with description
-    Other(String),
-}
-
-impl fmt::Display for ValidityError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ValidityError::Stale => write!(
-                f,
-                "Stale - nonce too low (transaction already applied or pending)"
-            ),
-            ValidityError::Future => {
-                write!(f, "Future - nonce too high (previous tx not yet applied)")
-            }
-            ValidityError::Priority => write!(f, "Priority too low - pool conflict"),
-            ValidityError::Payment => {
-                write!(f, "Payment - unable to pay fees (insufficient balance)")
-            }
-            ValidityError::BadProof => write!(f, "BadProof - invalid signature"),
-            ValidityError::AncientBirthBlock => {
-                write!(f, "AncientBirthBlock - transaction mortality expired")
-            }
-            ValidityError::ExhaustsResources => {
-                write!(f, "ExhaustsResources - would exhaust block resources")
-            }
-            ValidityError::Other(s) => write!(f, "{}", s),
-        }
-    }
-}
-
-/// Parsed migration status for display
-#[derive(Debug)]
-pub struct MigrationStatus {
-    pub top_complete: bool,
-    pub child_complete: bool,
-    pub size: u64,
-    pub top_items: u64,
-    pub child_items: u64,
-}
-
-impl MigrationStatus {
-    /// Migration is complete when top is complete (matches Parity's TypeScript bot)
-    /// Child migration happens inline with top, so progress_child may stay ToStart
-    pub fn is_complete(&self) -> bool {
-        self.top_complete
-    }
-}
-
-/// Parse migration status from a subxt Value
-/// Checks if progress variants are named "Complete"
-pub fn parse_migration_status<T: std::fmt::Debug>(decoded: &Value<T>) -> MigrationStatus {
-    let top_complete = decoded
-        .at("progress_top")
-        .map(|v| format!("{:?}", v).contains("Complete"))
-        .unwrap_or(false);
-
-    let child_complete = decoded
-        .at("progress_child")
-        .map(|v| format!("{:?}", v).contains("Complete"))
-        .unwrap_or(false);
-
-    let size = decoded.at("size").and_then(|v| v.as_u128()).unwrap_or(0) as u64;
-    let top_items = decoded
-        .at("top_items")
-        .and_then(|v| v.as_u128())
-        .unwrap_or(0) as u64;
-    let child_items = decoded
-        .at("child_items")
-        .and_then(|v| v.as_u128())
-        .unwrap_or(0) as u64;
-
-    MigrationStatus {
-        top_complete,
-        child_complete,
-        size,
-        top_items,
-        child_items,
-    }
-}
+pub use westend_migration_bot::process::parse_migration_status;
+pub use westend_migration_bot::{MigrationStatus, ValidityError};
 
 /// Decode TransactionValidityError from raw dry_run result bytes
 /// The dry_run result is: Result<Result<(), DispatchError>, TransactionValidityError>
@@ -191,128 +91,167 @@ pub fn decode_validity_error(raw_bytes: &[u8]) -> ValidityError {
     }
 }
 
-/// Fetch a random dad joke from icanhazdadjoke.com
-pub async fn fetch_dad_joke() -> Option<String> {
-    #[derive(serde::Deserialize)]
-    struct JokeResponse {
-        joke: String,
-    }
-
-    let client = reqwest::Client::new();
-    match client
-        .get("https://icanhazdadjoke.com/")
-        .header("Accept", "application/json")
-        .header("User-Agent", "WestendMigrationBot/0.1")
-        .send()
-        .await
-    {
-        Ok(resp) => match resp.json::<JokeResponse>().await {
-            Ok(j) => Some(j.joke),
-            Err(e) => {
-                debug!("Failed to parse dad joke: {:?}", e);
-                None
-            }
-        },
-        Err(e) => {
-            debug!("Failed to fetch dad joke: {:?}", e);
-            None
-        }
+/// Convert balance from units to WND (12 decimals)
+pub fn units_to_wnd(units: u128) -> f64 {
+    units as f64 / 1_000_000_000_000.0
+}
+
+/// Check if balance decreased (possible slashing)
+pub fn check_balance_decrease(before: u128, after: u128) -> Option<f64> {
+    if after < before {
+        Some(units_to_wnd(before - after))
+    } else {
+        None
     }
 }
 
-/// Send a desktop notification
-pub fn send_notification(summary: &str, body: &str, is_error: bool) {
-    // Skip if notifications are disabled (e.g., running on headless server)
-    if NOTIFICATIONS_DISABLED.load(Ordering::Relaxed) {
-        return;
+/// Human-readable form of a call, e.g. `Pallet.call(arg: value, ...)`,
+/// for comparison with the call data decoded in polkadot-js apps
+pub fn describe_call(pallet: &str, call: &str, args: &[(&str, &Value<()>)]) -> String {
+    let args: Vec<String> = args
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect();
+    format!("{}.{}({})", pallet, call, args.join(", "))
+}
+
+/// Parse a compact duration like `45s`, `90m`, `2h30m` or `1d`
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
     }
 
-    use notify_rust::{Notification, Timeout, Urgency};
+    let mut total_secs: u64 = 0;
+    let mut digits = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let value: u64 = digits.parse().ok()?;
+        digits.clear();
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return None,
+        };
+        total_secs = total_secs.checked_add(value.checked_mul(unit)?)?;
+    }
 
-    let (timeout, urgency) = if is_error {
-        (Timeout::Never, Urgency::Critical)
-    } else {
-        (Timeout::Milliseconds(5000), Urgency::Normal)
-    };
-
-    if let Err(e) = Notification::new()
-        .summary(summary)
-        .body(body)
-        .appname("Westend Migration Bot")
-        .timeout(timeout)
-        .urgency(urgency)
-        .show()
-    {
-        tracing::warn!("Failed to send notification: {:?}", e);
+    // Trailing digits without a unit are not accepted ("90" is ambiguous)
+    if !digits.is_empty() {
+        return None;
     }
+    Some(Duration::from_secs(total_secs))
 }
 
-/// Convert balance from units to WND (12 decimals)
-pub fn units_to_wnd(units: u128) -> f64 {
-    units as f64 / 1_000_000_000_000.0
+/// Parse a deadline as an RFC 3339 timestamp or a duration relative to `now`
+pub fn parse_deadline(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(input.trim()) {
+        return Ok(ts.with_timezone(&Local));
+    }
+
+    let duration = parse_duration(input).ok_or_else(|| {
+        format!(
+            "invalid deadline '{}' (expected RFC 3339 timestamp or duration like 90m, 2h30m)",
+            input
+        )
+    })?;
+    let duration = chrono::Duration::from_std(duration).map_err(|e| e.to_string())?;
+    Ok(now + duration)
 }
 
-/// Check if balance decreased (possible slashing)
-pub fn check_balance_decrease(before: u128, after: u128) -> Option<f64> {
-    if after < before {
-        Some(units_to_wnd(before - after))
+/// Clap value parser for `--deadline` (durations are relative to startup)
+pub fn parse_deadline_arg(input: &str) -> Result<DateTime<Local>, String> {
+    parse_deadline(input, Local::now())
+}
+
+/// `wait` cut short to end by `deadline`, if there is one
+pub fn until_deadline(
+    wait: Duration,
+    deadline: Option<DateTime<Local>>,
+    now: DateTime<Local>,
+) -> Duration {
+    match deadline {
+        Some(deadline) => wait.min((deadline - now).to_std().unwrap_or(Duration::ZERO)),
+        None => wait,
+    }
+}
+
+/// Why a node shouldn't be submitted to, given its `system_health`
+pub fn node_health_problem(peers: usize, is_syncing: bool, min_peers: usize) -> Option<String> {
+    if is_syncing {
+        Some(format!("node is syncing ({} peers)", peers))
+    } else if peers < min_peers {
+        Some(format!("node has {} peers (minimum {})", peers, min_peers))
     } else {
         None
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use subxt::ext::scale_value::Composite;
+/// Format a duration as `1h 02m 03s` for summaries
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {:02}m {:02}s", h, m, s)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
 
-    // ==================== ValidityError Tests ====================
+fn dev_phrase() -> MigrationError {
+    MigrationError::InvalidSeed("The public dev phrase cannot sign for the bot".to_string())
+}
 
-    #[test]
-    fn test_validity_error_display() {
-        assert_eq!(
-            ValidityError::Stale.to_string(),
-            "Stale - nonce too low (transaction already applied or pending)"
-        );
-        assert_eq!(
-            ValidityError::Future.to_string(),
-            "Future - nonce too high (previous tx not yet applied)"
-        );
-        assert_eq!(
-            ValidityError::Priority.to_string(),
-            "Priority too low - pool conflict"
-        );
-        assert_eq!(
-            ValidityError::Payment.to_string(),
-            "Payment - unable to pay fees (insufficient balance)"
-        );
-        assert_eq!(
-            ValidityError::BadProof.to_string(),
-            "BadProof - invalid signature"
-        );
-        assert_eq!(
-            ValidityError::AncientBirthBlock.to_string(),
-            "AncientBirthBlock - transaction mortality expired"
-        );
-        assert_eq!(
-            ValidityError::ExhaustsResources.to_string(),
-            "ExhaustsResources - would exhaust block resources"
-        );
-        assert_eq!(
-            ValidityError::Other("custom error".to_string()).to_string(),
-            "custom error"
-        );
+/// Signer keypair from a mnemonic or 0x hex seed, optionally followed by a
+/// derivation path (`//hard`, `/soft`) and `///password` as in polkadot-js
+pub fn signer_from_seed(seed: &str) -> Result<Keypair, MigrationError> {
+    // A bare path (`//Alice`) is parsed with the public dev phrase filled in
+    if seed.trim().starts_with('/') {
+        return Err(MigrationError::InvalidSeed(
+            "Derivation path without a mnemonic or seed".to_string(),
+        ));
+    }
+    if seed.contains('/') {
+        let uri = SecretUri::from_str(seed)
+            .map_err(|e| MigrationError::InvalidSeed(format!("Invalid SURI: {:?}", e)))?;
+        if uri.phrase.expose_secret() == DEV_PHRASE {
+            return Err(dev_phrase());
+        }
+        return Keypair::from_uri(&uri)
+            .map_err(|e| MigrationError::InvalidSeed(format!("Failed to derive: {:?}", e)));
+    }
+    if seed.starts_with("0x") {
+        // Hex seed - use zeroizing buffer
+        let seed_bytes = hex::decode(seed.trim_start_matches("0x"))
+            .map_err(|e| MigrationError::InvalidSeed(format!("Invalid hex: {}", e)))?;
+        let seed_array: [u8; 32] = seed_bytes
+            .try_into()
+            .map_err(|_| MigrationError::InvalidSeed("Seed must be 32 bytes".to_string()))?;
+        // Note: seed_array will be zeroized by scope exit
+        Keypair::from_secret_key(seed_array)
+            .map_err(|e| MigrationError::InvalidSeed(format!("Invalid seed: {:?}", e)))
+    } else {
+        // Mnemonic phrase
+        if seed.trim() == DEV_PHRASE {
+            return Err(dev_phrase());
+        }
+        let mnemonic = Mnemonic::parse(seed)
+            .map_err(|e| MigrationError::InvalidSeed(format!("Invalid mnemonic: {:?}", e)))?;
+        Keypair::from_phrase(&mnemonic, None)
+            .map_err(|e| MigrationError::InvalidSeed(format!("Failed to derive: {:?}", e)))
     }
+}
 
-    #[test]
-    fn test_validity_error_equality() {
-        assert_eq!(ValidityError::Stale, ValidityError::Stale);
-        assert_ne!(ValidityError::Stale, ValidityError::Future);
-        assert_eq!(
-            ValidityError::Other("test".to_string()),
-            ValidityError::Other("test".to_string())
-        );
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     // ==================== decode_validity_error Tests ====================
 
@@ -424,43 +363,22 @@ mod tests {
         assert!(matches!(result, ValidityError::Other(_)));
     }
 
-    // ==================== MigrationStatus Tests ====================
-
-    #[test]
-    fn test_migration_status_incomplete() {
-        let status = MigrationStatus {
-            top_complete: false,
-            child_complete: false,
-            size: 1000,
-            top_items: 500,
-            child_items: 0,
-        };
-        assert!(!status.is_complete());
-    }
-
-    #[test]
-    fn test_migration_status_top_complete() {
-        // When top is complete, migration is done (child migrates inline)
-        let status = MigrationStatus {
-            top_complete: true,
-            child_complete: false, // Can stay false/ToStart
-            size: 5000,
-            top_items: 1000,
-            child_items: 500,
-        };
-        assert!(status.is_complete()); // Now returns true!
-    }
+    // ==================== Call Preview Tests ====================
 
     #[test]
-    fn test_migration_status_fully_complete() {
-        let status = MigrationStatus {
-            top_complete: true,
-            child_complete: true,
-            size: 10000,
-            top_items: 2000,
-            child_items: 1000,
-        };
-        assert!(status.is_complete());
+    fn test_describe_call() {
+        let limits =
+            Value::named_composite([("size", Value::u128(51_200)), ("item", Value::u128(1024))]);
+        let upper = Value::u128(102_400);
+        let described = describe_call(
+            "StateTrieMigration",
+            "continue_migrate",
+            &[("limits", &limits), ("real_size_upper", &upper)],
+        );
+        assert!(described.starts_with("StateTrieMigration.continue_migrate(limits: "));
+        assert!(described.contains("size: 51200"));
+        assert!(described.contains("item: 1024"));
+        assert!(described.ends_with(", real_size_upper: 102400)"));
     }
 
     // ==================== Balance Utilities Tests ====================
@@ -503,38 +421,117 @@ mod tests {
         assert!((result.unwrap() - 0.001).abs() < 1e-10);
     }
 
-    // ==================== Parse Migration Status Tests ====================
+    // ==================== Duration / Deadline Tests ====================
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("45s"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2h30m"), Some(Duration::from_secs(9000)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("90"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("h"), None);
+    }
+
+    #[test]
+    fn test_parse_deadline_relative() {
+        let now = Local::now();
+        let deadline = parse_deadline("2h", now).unwrap();
+        assert_eq!((deadline - now).num_seconds(), 7200);
+    }
+
+    #[test]
+    fn test_parse_deadline_rfc3339() {
+        let deadline = parse_deadline("2030-01-02T03:04:05Z", Local::now()).unwrap();
+        assert_eq!(deadline.timestamp(), 1893553445);
+    }
+
+    #[test]
+    fn test_parse_deadline_invalid() {
+        assert!(parse_deadline("tomorrow", Local::now()).is_err());
+    }
+
+    #[test]
+    fn test_until_deadline() {
+        let now = Local::now();
+        let wait = Duration::from_secs(60);
+        assert_eq!(until_deadline(wait, None, now), wait);
+        let soon = now + chrono::Duration::seconds(10);
+        assert_eq!(
+            until_deadline(wait, Some(soon), now),
+            Duration::from_secs(10)
+        );
+        let later = now + chrono::Duration::hours(1);
+        assert_eq!(until_deadline(wait, Some(later), now), wait);
+        let past = now - chrono::Duration::seconds(10);
+        assert_eq!(until_deadline(wait, Some(past), now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+
+    #[test]
+    fn test_node_health_problem() {
+        assert_eq!(node_health_problem(8, false, 2), None);
+        assert_eq!(
+            node_health_problem(8, true, 2).as_deref(),
+            Some("node is syncing (8 peers)")
+        );
+        assert_eq!(
+            node_health_problem(1, false, 2).as_deref(),
+            Some("node has 1 peers (minimum 2)")
+        );
+        assert_eq!(node_health_problem(0, false, 0), None);
+    }
+
+    // ==================== signer_from_seed Tests ====================
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon about";
+
+    fn public(seed: &str) -> [u8; 32] {
+        signer_from_seed(seed).unwrap().public_key().0
+    }
+
+    #[test]
+    fn test_signer_from_seed_derivation() {
+        let expected = |suri: &str| {
+            Keypair::from_uri(&SecretUri::from_str(suri).unwrap())
+                .unwrap()
+                .public_key()
+                .0
+        };
+        let hard = format!("{}//Alice", PHRASE);
+        assert_eq!(public(&hard), expected(&hard));
+        // Hard, soft and password each give another account
+        assert_ne!(public(PHRASE), public(&hard));
+        assert_ne!(public(&format!("{}/Alice", PHRASE)), public(&hard));
+        assert_ne!(public(&format!("{}//Alice///pass", PHRASE)), public(&hard));
+        assert_eq!(
+            public(&format!("{}//westend//migrate", PHRASE)),
+            public(&format!("{}//westend//migrate", PHRASE))
+        );
+    }
 
     #[test]
-    fn test_parse_migration_status_parsing() {
-        // Construct a Value mimicking the structure on chain
-        // MigrationProcess {
-        //   progress_top: Progress::Complete,
-        //   progress_child: Progress::ToStart,
-        //   size: 100,
-        //   top_items: 10,
-        //   child_items: 20
-        // }
-        let value = Value::named_composite([
-            (
-                "progress_top",
-                Value::variant("Complete", Composite::named::<&str, _>([])),
-            ),
-            (
-                "progress_child",
-                Value::variant("ToStart", Composite::named::<&str, _>([])),
-            ),
-            ("size", Value::u128(100)),
-            ("top_items", Value::u128(10)),
-            ("child_items", Value::u128(20)),
-        ]);
-
-        let status = parse_migration_status(&value);
-
-        assert_eq!(status.top_complete, true);
-        assert_eq!(status.child_complete, false);
-        assert_eq!(status.size, 100);
-        assert_eq!(status.top_items, 10);
-        assert_eq!(status.child_items, 20);
+    fn test_signer_from_seed_rejects_dev_phrase_shorthand() {
+        assert!(matches!(
+            signer_from_seed("//Alice"),
+            Err(MigrationError::InvalidSeed(_))
+        ));
+        assert!(signer_from_seed(" //Alice").is_err());
+        assert!(signer_from_seed(&format!("{}//Alice", DEV_PHRASE)).is_err());
+        assert!(signer_from_seed(DEV_PHRASE).is_err());
+        assert!(signer_from_seed("0x1234").is_err());
     }
 }
@@ -0,0 +1,382 @@
+//! HashiCorp Vault signer (`--vault-addr`, `--vault-key`)
+//!
+//! `transit:[<mount>/]<key>` signs every tx with an ed25519 key held by
+//! Vault's transit engine: the account is the key's public key and the key
+//! never leaves Vault. The key version read at startup is pinned, so a
+//! rotation in Vault cannot silently switch the bot to another account.
+//! Transit has no sr25519 keys; an sr25519 account is read instead with
+//! `kv:<mount>/<path>[#<field>]`, which fetches the seed once at startup from
+//! a KV v2 secret (field `seed` by default) and signs locally. Either way the
+//! token comes from `--vault-token` / `VAULT_TOKEN`; for `kv:` a short-lived
+//! token is enough, since it is not used after startup.
+
+use crate::signing::TxSigner;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use subxt::utils::{AccountId32, MultiSignature};
+use zeroize::Zeroizing;
+
+/// Bound on each Vault request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEFAULT_TRANSIT_MOUNT: &str = "transit";
+
+const DEFAULT_SEED_FIELD: &str = "seed";
+
+/// What `--vault-key` names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultKey {
+    /// Transit key `name` under `mount`
+    Transit { mount: String, name: String },
+    /// Field `field` of the KV v2 secret `path` under `mount`
+    Kv {
+        mount: String,
+        path: String,
+        field: String,
+    },
+}
+
+impl FromStr for VaultKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid Vault key '{}' (expected transit:[<mount>/]<key> or \
+                 kv:<mount>/<path>[#<field>])",
+                s
+            )
+        };
+        let (scheme, rest) = s.split_once(':').ok_or_else(invalid)?;
+        let rest = rest.trim().trim_matches('/');
+        match scheme {
+            "transit" => {
+                let (mount, name) = rest
+                    .rsplit_once('/')
+                    .unwrap_or((DEFAULT_TRANSIT_MOUNT, rest));
+                if mount.is_empty() || name.is_empty() {
+                    return Err(invalid());
+                }
+                Ok(VaultKey::Transit {
+                    mount: mount.to_string(),
+                    name: name.to_string(),
+                })
+            }
+            "kv" => {
+                let (secret, field) = rest.split_once('#').unwrap_or((rest, DEFAULT_SEED_FIELD));
+                let (mount, path) = secret.split_once('/').ok_or_else(invalid)?;
+                if mount.is_empty() || path.is_empty() || field.is_empty() {
+                    return Err(invalid());
+                }
+                Ok(VaultKey::Kv {
+                    mount: mount.to_string(),
+                    path: path.to_string(),
+                    field: field.to_string(),
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for VaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultKey::Transit { mount, name } => write!(f, "transit key {}/{}", mount, name),
+            VaultKey::Kv { mount, path, field } => {
+                write!(f, "KV secret {}/{}#{}", mount, path, field)
+            }
+        }
+    }
+}
+
+/// An authenticated Vault client
+pub struct Vault {
+    http: reqwest::Client,
+    addr: String,
+    token: SecretString,
+}
+
+impl Vault {
+    pub fn new(addr: &str, token: SecretString) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            http,
+            addr: addr.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// `GET` (no body) or `POST` `/v1/<path>`, returning the response's `data`
+    async fn request(
+        &self,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/v1/{}", self.addr, path);
+        let request = match &body {
+            Some(body) => self.http.post(&url).json(body),
+            None => self.http.get(&url),
+        };
+        let response = request
+            .header("X-Vault-Token", self.token.expose_secret())
+            .header("X-Vault-Request", "true")
+            .send()
+            .await
+            .with_context(|| format!("Vault request to {} failed", url))?;
+        let status = response.status();
+        let text = Zeroizing::new(response.text().await?);
+        if !status.is_success() {
+            // Error bodies are `{"errors": [...]}`, never the secret
+            anyhow::bail!(
+                "Vault {} failed ({}): {}",
+                path,
+                status,
+                vault_errors(&text)
+            );
+        }
+        let mut response: serde_json::Value = serde_json::from_str(&text)
+            .with_context(|| format!("Vault {} returned invalid JSON", path))?;
+        Ok(response
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or_default())
+    }
+
+    /// The seed in field `field` of the KV v2 secret `path`
+    pub async fn read_seed(&self, mount: &str, path: &str, field: &str) -> Result<SecretString> {
+        let mut data = self
+            .request(&format!("{}/data/{}", mount, path), None)
+            .await?;
+        let field_value = data
+            .get_mut("data")
+            .and_then(|secret| secret.get_mut(field))
+            .map(serde_json::Value::take);
+        let seed = match field_value {
+            Some(serde_json::Value::String(seed)) => Zeroizing::new(seed),
+            _ => anyhow::bail!(
+                "Vault secret {}/{} has no string field `{}`",
+                mount,
+                path,
+                field
+            ),
+        };
+        let seed = seed.trim();
+        anyhow::ensure!(
+            !seed.is_empty(),
+            "Vault secret {}/{} holds an empty seed",
+            mount,
+            path
+        );
+        Ok(SecretString::new(seed.to_string()))
+    }
+}
+
+/// The `errors` of a Vault error body, or the body itself
+fn vault_errors(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|body| {
+            let errors: Vec<_> = body["errors"]
+                .as_array()?
+                .iter()
+                .filter_map(|error| error.as_str())
+                .collect();
+            Some(errors.join("; "))
+        })
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// A transit ed25519 key (`--vault-key transit:...`)
+pub struct VaultSigner {
+    vault: Vault,
+    mount: String,
+    name: String,
+    version: u64,
+    public: VerifyingKey,
+}
+
+impl VaultSigner {
+    /// Read the key's latest version and its public key
+    pub async fn connect(vault: Vault, mount: &str, name: &str) -> Result<Self> {
+        let key = vault
+            .request(&format!("{}/keys/{}", mount, name), None)
+            .await
+            .with_context(|| format!("Failed to read transit key {}/{}", mount, name))?;
+        let (version, public) = parse_key(&key)?;
+        Ok(Self {
+            vault,
+            mount: mount.to_string(),
+            name: name.to_string(),
+            version,
+            public,
+        })
+    }
+}
+
+/// Latest version and its public key, from `GET <mount>/keys/<name>`
+fn parse_key(key: &serde_json::Value) -> Result<(u64, VerifyingKey)> {
+    anyhow::ensure!(
+        key["type"] == "ed25519",
+        "Transit key is {}, not ed25519 (transit has no sr25519 keys; use kv: for an sr25519 seed)",
+        key["type"]
+    );
+    let version = key["latest_version"]
+        .as_u64()
+        .context("Transit key has no latest_version")?;
+    let public = key["keys"][version.to_string()]["public_key"]
+        .as_str()
+        .context("Transit key has no public key (is it exportable to this token?)")?;
+    let public: [u8; 32] = BASE64
+        .decode(public)
+        .context("Transit public key is not base64")?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| {
+            anyhow::anyhow!("Transit public key is {} bytes, expected 32", bytes.len())
+        })?;
+    let public = VerifyingKey::from_bytes(&public).context("Invalid transit public key")?;
+    Ok((version, public))
+}
+
+/// Signature bytes of a transit signature (`vault:v<N>:<base64>`)
+fn parse_signature(signature: &str) -> Result<[u8; 64]> {
+    let encoded = match signature.split(':').collect::<Vec<_>>().as_slice() {
+        ["vault", version, encoded] if version.starts_with('v') => *encoded,
+        _ => anyhow::bail!("Unexpected transit signature format: {}", signature),
+    };
+    BASE64
+        .decode(encoded)
+        .context("Transit signature is not base64")?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| {
+            anyhow::anyhow!("Transit signature is {} bytes, expected 64", bytes.len())
+        })
+}
+
+impl TxSigner for VaultSigner {
+    fn account_id(&self) -> AccountId32 {
+        AccountId32(self.public.to_bytes())
+    }
+
+    fn sign<'a>(&'a self, payload: &'a [u8]) -> BoxFuture<'a, Result<MultiSignature>> {
+        async move {
+            let body = json!({
+                "input": BASE64.encode(payload),
+                "key_version": self.version,
+            });
+            let response = self
+                .vault
+                .request(&format!("{}/sign/{}", self.mount, self.name), Some(body))
+                .await?;
+            let signature = parse_signature(
+                response["signature"]
+                    .as_str()
+                    .context("Vault returned no signature")?,
+            )?;
+            self.public
+                .verify_strict(payload, &Signature::from_bytes(&signature))
+                .with_context(|| {
+                    format!(
+                        "Vault returned a signature that does not verify for {}",
+                        self.account_id()
+                    )
+                })?;
+            Ok(MultiSignature::Ed25519(signature))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_spec() {
+        assert_eq!(
+            "transit:migrate".parse::<VaultKey>(),
+            Ok(VaultKey::Transit {
+                mount: "transit".to_string(),
+                name: "migrate".to_string()
+            })
+        );
+        assert_eq!(
+            "transit:ops/transit/migrate".parse::<VaultKey>(),
+            Ok(VaultKey::Transit {
+                mount: "ops/transit".to_string(),
+                name: "migrate".to_string()
+            })
+        );
+        assert_eq!(
+            "kv:secret/westend/bot".parse::<VaultKey>(),
+            Ok(VaultKey::Kv {
+                mount: "secret".to_string(),
+                path: "westend/bot".to_string(),
+                field: "seed".to_string()
+            })
+        );
+        assert_eq!(
+            "kv:secret/westend/bot#mnemonic".parse::<VaultKey>(),
+            Ok(VaultKey::Kv {
+                mount: "secret".to_string(),
+                path: "westend/bot".to_string(),
+                field: "mnemonic".to_string()
+            })
+        );
+        assert!("kv:secret".parse::<VaultKey>().is_err());
+        assert!("transit:".parse::<VaultKey>().is_err());
+        assert!("aws:seed".parse::<VaultKey>().is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let public = ed25519_dalek::SigningKey::from_bytes(&[1; 32])
+            .verifying_key()
+            .to_bytes();
+        let response = json!({
+            "type": "ed25519",
+            "latest_version": 2,
+            "keys": {
+                "1": { "public_key": BASE64.encode([0; 32]) },
+                "2": { "public_key": BASE64.encode(public) },
+            },
+        });
+        let (version, key) = parse_key(&response).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(key.to_bytes(), public);
+
+        let rsa = json!({ "type": "rsa-2048", "latest_version": 1 });
+        assert!(parse_key(&rsa).is_err());
+    }
+
+    #[test]
+    fn test_parse_signature() {
+        let signature = BASE64.encode([7; 64]);
+        assert_eq!(
+            parse_signature(&format!("vault:v3:{}", signature)).unwrap(),
+            [7; 64]
+        );
+        assert!(parse_signature(&signature).is_err());
+        assert!(parse_signature(&format!("vault:v1:{}", BASE64.encode([7; 32]))).is_err());
+    }
+
+    #[test]
+    fn test_vault_errors() {
+        assert_eq!(
+            vault_errors(r#"{"errors":["permission denied","1 error occurred"]}"#),
+            "permission denied; 1 error occurred"
+        );
+        assert_eq!(vault_errors("bad gateway\n"), "bad gateway");
+    }
+}
@@ -0,0 +1,70 @@
+//! JavaScript exports of the monitoring core (`--features wasm`)
+//!
+//! ```sh
+//! cargo build --lib --release --target wasm32-unknown-unknown --features wasm
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/westend_migration_bot.wasm
+//! ```
+//!
+//! The page fetches `state_getMetadata` and the raw storage itself and hands
+//! the bytes to a `Decoder`; statuses and events come back as JSON strings
+//! with the same fields as the bot's JSON outputs.
+
+use crate::estimate;
+use crate::runtime::RuntimeDecoder;
+use wasm_bindgen::prelude::*;
+
+/// [`RuntimeDecoder`] for JavaScript
+#[wasm_bindgen]
+pub struct Decoder(RuntimeDecoder);
+
+#[wasm_bindgen]
+impl Decoder {
+    /// From the bytes of `state_getMetadata`
+    #[wasm_bindgen(constructor)]
+    pub fn new(metadata: &[u8]) -> Result<Decoder, JsError> {
+        RuntimeDecoder::new(metadata)
+            .map(Decoder)
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// `MigrationStatus` JSON from the bytes of `MigrationProcess`
+    #[wasm_bindgen(js_name = migrationStatus)]
+    pub fn migration_status(&self, process: &[u8]) -> Result<String, JsError> {
+        let status = self
+            .0
+            .migration_status(process)
+            .map_err(|e| JsError::new(&e))?;
+        Ok(serde_json::to_string(&status)?)
+    }
+
+    /// Keyspace position of the last migrated top key, in percent
+    #[wasm_bindgen(js_name = positionPercent)]
+    pub fn position_percent(&self, process: &[u8]) -> Result<Option<f64>, JsError> {
+        self.0
+            .position_percent(process)
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// JSON list of the migration events in the bytes of `System.Events`
+    #[wasm_bindgen(js_name = migrationEvents)]
+    pub fn migration_events(&self, events: &[u8]) -> Result<String, JsError> {
+        let events = self
+            .0
+            .migration_events(events)
+            .map_err(|e| JsError::new(&e))?;
+        Ok(serde_json::to_string(&events)?)
+    }
+}
+
+/// Percent of the top trie migrated, given the keys still to migrate
+#[wasm_bindgen(js_name = percentComplete)]
+pub fn percent_complete(migrated: f64, remaining: f64) -> f64 {
+    estimate::percent_complete(migrated as u64, remaining as u64)
+}
+
+/// Seconds to migrate `remaining` keys at `items_per_min`
+#[wasm_bindgen(js_name = etaSeconds)]
+pub fn eta_seconds(remaining: f64, items_per_min: f64) -> Option<f64> {
+    estimate::eta(remaining as u64, items_per_min).map(|eta| eta.as_secs_f64())
+}
@@ -0,0 +1,404 @@
+//! Watch-only mode (`--watch`)
+//!
+//! Follows finalized blocks without loading a signer: logs `MigrationProcess`
+//! progress and every `StateTrieMigration` event, keeps the health endpoints
+//! up to date, and notifies on slashes, halts, completion and stalls. Meant
+//! for people who want visibility into the migration but must not hold keys;
+//! nothing in this module can build or sign an extrinsic.
+//!
+//! [`stay_alive`] is the same kind of watch for after completion
+//! (`--stay-alive`): it alerts if `MigrationProcess` stops reporting the
+//! migration complete, and periodically proves a sample of keys to catch
+//! values stored the V0 way again (e.g. written by a buggy runtime).
+
+use crate::health::Health;
+use crate::keyspace::{KeyScanner, StorageNames};
+use crate::notify::{send_notification, Severity};
+use crate::proof;
+use crate::utils::{format_duration, parse_migration_status, MigrationStatus};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use subxt::backend::rpc::RpcClient;
+use subxt::blocks::Block;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Notification for a `StateTrieMigration` event, if it warrants one
+pub fn event_alert(variant: &str) -> Option<(Severity, &'static str)> {
+    match variant {
+        "Slashed" => Some((Severity::Critical, "Migration Slash Observed")),
+        "Halted" => Some((Severity::Critical, "Migration Halted")),
+        "AutoMigrationFinished" => Some((Severity::Success, "Auto Migration Finished")),
+        _ => None,
+    }
+}
+
+/// How often `--stay-alive` proves a sample of keys
+const SAMPLE_EVERY: Duration = Duration::from_secs(10 * 60);
+
+/// Keys proven per sample
+const SAMPLE_KEYS: u64 = 64;
+
+/// V0 keys listed in one alert
+const LISTED_KEYS: usize = 5;
+
+/// What `--stay-alive` has already reported, so each regression alerts once
+#[derive(Debug, Default)]
+pub struct RegressionMonitor {
+    regressed: bool,
+    reported: HashSet<Vec<u8>>,
+}
+
+impl RegressionMonitor {
+    /// Take the latest completion flag; `Some(complete)` when it changed
+    pub fn record_status(&mut self, complete: bool) -> Option<bool> {
+        if self.regressed != complete {
+            return None;
+        }
+        self.regressed = !complete;
+        Some(complete)
+    }
+
+    /// The V0 keys among `keys` not reported before
+    pub fn new_v0_keys(&mut self, keys: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        keys.into_iter()
+            .filter(|key| self.reported.insert(key.clone()))
+            .collect()
+    }
+}
+
+/// Change in stall state after a progress sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallChange {
+    /// No progress for this long (reported once per stall)
+    Stalled(Duration),
+    /// Progress after a reported stall
+    Resumed,
+}
+
+/// Flags when the migrated item count stops moving
+#[derive(Debug)]
+pub struct StallDetector {
+    stall_after: Duration,
+    last_items: Option<u64>,
+    last_progress: Instant,
+    alerted: bool,
+}
+
+impl StallDetector {
+    pub fn new(stall_after: Duration, now: Instant) -> Self {
+        Self {
+            stall_after,
+            last_items: None,
+            last_progress: now,
+            alerted: false,
+        }
+    }
+
+    pub fn record(&mut self, items: u64, now: Instant) -> Option<StallChange> {
+        if self.last_items != Some(items) {
+            self.last_items = Some(items);
+            self.last_progress = now;
+            return std::mem::take(&mut self.alerted).then_some(StallChange::Resumed);
+        }
+        let idle = now.duration_since(self.last_progress);
+        if !self.alerted && idle >= self.stall_after {
+            self.alerted = true;
+            return Some(StallChange::Stalled(idle));
+        }
+        None
+    }
+}
+
+/// Follow finalized blocks until the migration completes or `shutdown` is cancelled
+pub async fn run(
+    client: OnlineClient<PolkadotConfig>,
+    health: Arc<Health>,
+    stall_after: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Watching migration (no signer loaded)");
+    send_notification(
+        Severity::Info,
+        "Westend Watcher Started",
+        "Watching migration progress (read-only).",
+    );
+
+    let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+    let mut blocks = client
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .context("Failed to subscribe to finalized blocks")?;
+    let mut stall = StallDetector::new(stall_after, Instant::now());
+    let mut last_status: Option<MigrationStatus> = None;
+
+    loop {
+        let block = tokio::select! {
+            _ = shutdown.cancelled() => {
+                info!("Watcher shutting down");
+                return Ok(());
+            }
+            next = blocks.next() => match next {
+                Some(block) => block?,
+                None => anyhow::bail!("Finalized block subscription ended"),
+            },
+        };
+        let number = block.number();
+
+        for event in block.events().await?.iter() {
+            let event = event?;
+            if event.pallet_name() != "StateTrieMigration" {
+                continue;
+            }
+            let fields = event
+                .field_values()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            info!(
+                "#{}: StateTrieMigration.{} {}",
+                number,
+                event.variant_name(),
+                fields
+            );
+            if let Some((severity, summary)) = event_alert(event.variant_name()) {
+                send_notification(
+                    severity,
+                    summary,
+                    &format!("Block #{}: {} {}", number, event.variant_name(), fields),
+                );
+            }
+        }
+
+        let Some(thunk) = client.storage().at(block.hash()).fetch(&query).await? else {
+            debug!("#{}: no MigrationProcess", number);
+            continue;
+        };
+        let status = parse_migration_status(&thunk.to_value()?);
+        health.update(|h| {
+            h.connected = true;
+            h.last_success = Some(chrono::Utc::now());
+            h.status = Some(status.clone());
+        });
+
+        if last_status.as_ref() != Some(&status) {
+            let delta = last_status
+                .as_ref()
+                .map(|last| status.top_items.saturating_sub(last.top_items))
+                .unwrap_or(0);
+            info!(
+                top_items = status.top_items,
+                child_items = status.child_items,
+                size = status.size,
+                "#{}: top={} child={} size={} (+{} top items)",
+                number,
+                status.top_items,
+                status.child_items,
+                status.size,
+                delta
+            );
+        }
+        if status.is_complete() {
+            info!("Migration complete");
+            send_notification(
+                Severity::Success,
+                "Migration Complete",
+                "The Westend state trie migration is complete!",
+            );
+            return Ok(());
+        }
+
+        match stall.record(status.top_items + status.child_items, Instant::now()) {
+            Some(StallChange::Stalled(idle)) => {
+                let msg = format!(
+                    "No migration progress for {} (top={} items)",
+                    format_duration(idle),
+                    status.top_items
+                );
+                warn!("{}", msg);
+                send_notification(Severity::Warning, "Migration Stalled", &msg);
+            }
+            Some(StallChange::Resumed) => {
+                info!("Migration progressing again");
+                send_notification(
+                    Severity::Info,
+                    "Migration Resumed",
+                    &format!("Progress resumed at block #{}", number),
+                );
+            }
+            None => {}
+        }
+        last_status = Some(status);
+    }
+}
+
+/// After completion: follow finalized blocks until `shutdown`, alerting when
+/// `MigrationProcess` is no longer complete or sampled keys are stored V0
+pub async fn stay_alive(
+    client: &OnlineClient<PolkadotConfig>,
+    rpc: &RpcClient,
+    health: &Health,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    info!("Migration complete; staying alive to watch for regressions");
+    let query = subxt::dynamic::storage("StateTrieMigration", "MigrationProcess", vec![]);
+    let names = StorageNames::from_metadata(&client.metadata());
+    let mut blocks = client
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .context("Failed to subscribe to finalized blocks")?;
+    let mut monitor = RegressionMonitor::default();
+    let mut last_sample: Option<Instant> = None;
+
+    loop {
+        let block = tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            next = blocks.next() => match next {
+                Some(block) => block?,
+                None => anyhow::bail!("Finalized block subscription ended"),
+            },
+        };
+        let number = block.number();
+
+        if let Some(thunk) = client.storage().at(block.hash()).fetch(&query).await? {
+            let status = parse_migration_status(&thunk.to_value()?);
+            health.update(|h| {
+                h.connected = true;
+                h.status = Some(status.clone());
+            });
+            match monitor.record_status(status.is_complete()) {
+                Some(false) => {
+                    let msg = format!(
+                        "MigrationProcess at #{} no longer reports the migration complete \
+                         (top={} child={} items)",
+                        number, status.top_items, status.child_items
+                    );
+                    warn!("{}", msg);
+                    send_notification(Severity::Critical, "Migration Regressed", &msg);
+                }
+                Some(true) => {
+                    info!("#{}: migration reported complete again", number);
+                    send_notification(
+                        Severity::Info,
+                        "Migration Complete Again",
+                        &format!("MigrationProcess reports completion again at #{}", number),
+                    );
+                }
+                None => {}
+            }
+        }
+
+        if last_sample.is_some_and(|at| at.elapsed() < SAMPLE_EVERY) {
+            continue;
+        }
+        last_sample = Some(Instant::now());
+        let v0_keys = match sample_v0_keys(rpc, &block).await {
+            Ok(keys) => monitor.new_v0_keys(keys),
+            Err(e) => {
+                warn!("Key sample at #{} failed: {:#}", number, e);
+                continue;
+            }
+        };
+        if v0_keys.is_empty() {
+            continue;
+        }
+        let listed: Vec<String> = v0_keys
+            .iter()
+            .take(LISTED_KEYS)
+            .map(|key| match names.resolve(key) {
+                Some(name) => format!("0x{} ({})", hex::encode(key), name),
+                None => format!("0x{}", hex::encode(key)),
+            })
+            .collect();
+        let msg = format!(
+            "{} sampled key(s) at #{} are stored the V0 way:\n{}",
+            v0_keys.len(),
+            number,
+            listed.join("\n")
+        );
+        warn!("{}", msg.replace('\n', ", "));
+        send_notification(Severity::Critical, "V0 Keys After Migration", &msg);
+    }
+}
+
+/// Prove up to [`SAMPLE_KEYS`] keys from a position picked by the block hash,
+/// returning those stored V0
+async fn sample_v0_keys(
+    rpc: &RpcClient,
+    block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<Vec<Vec<u8>>> {
+    let start = block.hash().0[..8].to_vec();
+    let mut keys = Vec::new();
+    KeyScanner::new(rpc, block.hash())
+        .scan(Some(&start), SAMPLE_KEYS, |key| {
+            keys.push(key.to_vec());
+            true
+        })
+        .await?;
+    let proof = proof::read_proof(rpc, block.hash(), &keys).await?;
+    let state_root = block.header().state_root;
+    let mut v0 = Vec::new();
+    for key in keys {
+        let proven = proof::read_value(state_root, &proof, &key)?;
+        if proven.is_some_and(|proven| proven.is_v0()) {
+            v0.push(key);
+        }
+    }
+    debug!("#{}: {} sampled key(s) stored V0", block.number(), v0.len());
+    Ok(v0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_alerts() {
+        assert!(matches!(
+            event_alert("Slashed"),
+            Some((Severity::Critical, _))
+        ));
+        assert!(matches!(
+            event_alert("Halted"),
+            Some((Severity::Critical, _))
+        ));
+        assert!(matches!(
+            event_alert("AutoMigrationFinished"),
+            Some((Severity::Success, _))
+        ));
+        assert!(event_alert("Migrated").is_none());
+    }
+
+    #[test]
+    fn test_stall_reported_once_then_resumed() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut stall = StallDetector::new(Duration::from_secs(30 * 60), start);
+
+        assert_eq!(stall.record(100, start), None);
+        assert_eq!(stall.record(100, minutes(29)), None);
+        assert_eq!(
+            stall.record(100, minutes(30)),
+            Some(StallChange::Stalled(Duration::from_secs(30 * 60)))
+        );
+        assert_eq!(stall.record(100, minutes(45)), None);
+        assert_eq!(stall.record(200, minutes(46)), Some(StallChange::Resumed));
+        assert_eq!(stall.record(300, minutes(47)), None);
+    }
+
+    #[test]
+    fn test_regression_reported_once() {
+        let mut monitor = RegressionMonitor::default();
+        assert_eq!(monitor.record_status(true), None);
+        assert_eq!(monitor.record_status(false), Some(false));
+        assert_eq!(monitor.record_status(false), None);
+        assert_eq!(monitor.record_status(true), Some(true));
+
+        assert_eq!(monitor.new_v0_keys(vec![vec![1], vec![2]]).len(), 2);
+        assert_eq!(monitor.new_v0_keys(vec![vec![2], vec![3]]), vec![vec![3]]);
+    }
+}
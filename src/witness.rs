@@ -0,0 +1,104 @@
+//! Captured `MigrationProcess` witnesses
+//!
+//! `witness export` saves the SCALE-encoded `MigrationProcess` value with the
+//! block and runtime it was read at; `--witness-file` feeds it back in place of
+//! the live storage read. That makes a witness-mismatch failure reproducible
+//! (dry-run the exact witness that failed) and lets call data be built
+//! without reading chain state at submission time. The encoded bytes are the
+//! source of truth; `decoded` is only for people reading the file.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use subxt::ext::scale_value::scale::decode_as_type;
+use subxt::{dynamic::Value, utils::H256, Metadata};
+
+/// A `MigrationProcess` value as read from chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WitnessSnapshot {
+    /// Block the value was read at
+    pub block: H256,
+    /// Runtime spec version at that block
+    pub spec_version: u32,
+    pub captured_at: chrono::DateTime<chrono::Utc>,
+    /// SCALE-encoded `MigrationTask`, 0x-prefixed hex
+    pub encoded: String,
+    /// Human-readable decoding (informational, ignored on load)
+    #[serde(default)]
+    pub decoded: String,
+}
+
+impl WitnessSnapshot {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write witness file {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read witness file {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid witness file {}", path.display()))
+    }
+
+    /// Decode against the `MigrationProcess` type of the current runtime
+    pub fn decode(&self, metadata: &Metadata) -> Result<Value<()>> {
+        let bytes = hex::decode(self.encoded.trim_start_matches("0x"))
+            .context("Witness `encoded` is not hex")?;
+        decode_task(&bytes, metadata)
+    }
+}
+
+/// Decode a SCALE-encoded `MigrationProcess` value, as the storage read would
+pub fn decode_task(bytes: &[u8], metadata: &Metadata) -> Result<Value<()>> {
+    let type_id = metadata
+        .pallet_by_name("StateTrieMigration")
+        .and_then(|p| p.storage())
+        .and_then(|s| s.entry_by_name("MigrationProcess"))
+        .context("Runtime has no StateTrieMigration::MigrationProcess")?
+        .entry_type()
+        .value_ty();
+    let cursor = &mut &bytes[..];
+    let value = decode_as_type(cursor, type_id, metadata.types())
+        .map_err(|e| anyhow::anyhow!("Witness does not decode as MigrationTask: {}", e))?;
+    anyhow::ensure!(
+        cursor.is_empty(),
+        "Witness has {} trailing bytes after the MigrationTask",
+        cursor.len()
+    );
+    Ok(value.map_context(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("witness-{}.json", std::process::id()));
+        let snapshot = WitnessSnapshot {
+            block: H256::repeat_byte(0xab),
+            spec_version: 1_016_000,
+            captured_at: chrono::Utc::now(),
+            encoded: "0x0100".to_string(),
+            decoded: "{ progress_top: ToStart }".to_string(),
+        };
+        snapshot.save(&path).unwrap();
+        assert_eq!(WitnessSnapshot::load(&path).unwrap(), snapshot);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_decoded_is_optional() {
+        let json = r#"{
+            "block": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "spec_version": 1,
+            "captured_at": "2026-01-01T00:00:00Z",
+            "encoded": "0x00"
+        }"#;
+        let snapshot: WitnessSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(snapshot.decoded, "");
+        assert_eq!(snapshot.block, H256::from_low_u64_be(1));
+    }
+}
@@ -0,0 +1,92 @@
+//! The serialized form of the public status types is a contract with
+//! downstream tools; these pin it against the golden files in `tests/golden`.
+//! If a change here is intentional, update the golden file in the same commit.
+
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use westend_migration_bot::{BotSnapshot, BotState, MigrationStatus, ReadBasis, ValidityError};
+
+fn golden(name: &str) -> Value {
+    let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    serde_json::from_str(&json).unwrap()
+}
+
+fn snapshot() -> BotSnapshot {
+    BotSnapshot {
+        connected: true,
+        started_at: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        last_activity: Utc.with_ymd_and_hms(2026, 1, 1, 1, 30, 0).unwrap(),
+        last_success: Some(Utc.with_ymd_and_hms(2026, 1, 1, 1, 29, 42).unwrap()),
+        consecutive_errors: 0,
+        paused: false,
+        read_basis: ReadBasis::Finalized,
+        status: Some(MigrationStatus {
+            top_complete: false,
+            child_complete: false,
+            size: 52_428_800,
+            top_items: 1_048_576,
+            child_items: 2048,
+            top_last_key: Some(
+                "0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9".to_string(),
+            ),
+            child_last_key: None,
+        }),
+        state: BotState::AwaitingFinality,
+        state_since: Some(Utc.with_ymd_and_hms(2026, 1, 1, 1, 29, 55).unwrap()),
+    }
+}
+
+#[test]
+fn bot_snapshot_matches_golden() {
+    assert_eq!(
+        serde_json::to_value(snapshot()).unwrap(),
+        golden("bot_snapshot.json")
+    );
+    let parsed: BotSnapshot = serde_json::from_value(golden("bot_snapshot.json")).unwrap();
+    assert_eq!(parsed, snapshot());
+}
+
+#[test]
+fn validity_errors_match_golden() {
+    let errors = vec![
+        ValidityError::Stale,
+        ValidityError::Future,
+        ValidityError::Priority,
+        ValidityError::Payment,
+        ValidityError::BadProof,
+        ValidityError::AncientBirthBlock,
+        ValidityError::ExhaustsResources,
+        ValidityError::Other("Custom(7)".to_string()),
+    ];
+    assert_eq!(
+        serde_json::to_value(&errors).unwrap(),
+        golden("validity_errors.json")
+    );
+    let parsed: Vec<ValidityError> =
+        serde_json::from_value(golden("validity_errors.json")).unwrap();
+    assert_eq!(parsed, errors);
+}
+
+#[test]
+fn snapshot_without_state_still_parses() {
+    // Snapshots written before the run loop state was added
+    let mut json = golden("bot_snapshot.json");
+    let object = json.as_object_mut().unwrap();
+    object.remove("state");
+    object.remove("state_since");
+    let parsed: BotSnapshot = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.state, BotState::Idle);
+    assert_eq!(parsed.state_since, None);
+}
+
+#[test]
+fn migration_status_without_last_keys_still_parses() {
+    // Event logs written before the last-key fields were added
+    let status: MigrationStatus = serde_json::from_str(
+        r#"{"top_complete":true,"child_complete":false,"size":1,"top_items":2,"child_items":3}"#,
+    )
+    .unwrap();
+    assert!(status.is_complete());
+    assert_eq!(status.top_last_key, None);
+}